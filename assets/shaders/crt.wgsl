@@ -0,0 +1,22 @@
+// crt.wgsl
+//
+// Retro CRT overlay: darkens every other band of device pixels into scanlines and shades the
+// corners. Drawn as a transparent UI node over the game, so it only darkens what's beneath.
+
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+// x: scanline period in device pixels, y: scanline darkness, z: vignette strength.
+@group(1) @binding(0) var<uniform> params: vec4<f32>;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    // `position` is in framebuffer pixels, so the lines keep their spacing at any window size.
+    let band = floor(in.position.y / params.x) % 2.0;
+    let scanline = band * params.y;
+
+    let centered = in.uv * 2.0 - 1.0;
+    let vignette = smoothstep(0.5, 1.5, length(centered)) * params.z;
+
+    let darkness = 1.0 - (1.0 - scanline) * (1.0 - vignette);
+    return vec4<f32>(0.0, 0.0, 0.0, darkness);
+}
@@ -0,0 +1,409 @@
+// achievements.rs
+
+//! Achievements: a fixed list of feats, each earned once and remembered in the save file.
+//!
+//! The checks are small systems watching the existing event streams and resources
+//! (`EnemyKilled`, `RunStats`, `SaveData`, round transitions), and each only writes an
+//! `AchievementEarned`. `record_achievements` is the one place that dedupes them, saves and
+//! queues the banner, so checks are free to fire repeatedly. The banner slides in from the top
+//! of the screen, one achievement at a time, and survives state transitions like toasts do.
+//! The attract mode demo never earns anything.
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::assets::GameAssets;
+use crate::components::{CurrentRound, EnemyKilled, GameMode, GameState, Health, KillSource};
+use crate::demo::in_demo;
+use crate::gems::GemCount;
+use crate::player::Player;
+use crate::round_timer::{record_round_time, RoundTimer};
+use crate::save::SaveData;
+use crate::score::RunStats;
+
+/// Bounces a shot needs before its kill counts as a trick shot.
+const TRICK_SHOT_BOUNCES: u32 = 3;
+
+/// Explosion kills needed within `CHAIN_REACTION_WINDOW` seconds for a chain reaction.
+const CHAIN_REACTION_KILLS: usize = 3;
+const CHAIN_REACTION_WINDOW: f32 = 1.0;
+
+const COMBO_MASTER_COMBO: u32 = 10;
+const CENTURION_KILLS: u64 = 100;
+const VETERAN_ROUND: u32 = 5;
+
+/// Seconds survived in a single endless run for the survivor achievement.
+const SURVIVOR_TIME: f32 = 300.0;
+
+/// A round cleared faster than this many seconds earns the speed run achievement.
+const SPEED_RUN_TIME: f32 = 30.0;
+
+/// Seconds a banner stays on screen, including its slides in and out.
+const BANNER_LIFETIME: f32 = 3.0;
+
+/// Seconds the banner takes to slide in, and again to slide out.
+const BANNER_SLIDE_TIME: f32 = 0.25;
+
+/// Distance of the banner from the top of the screen once it has slid in.
+const BANNER_TOP: f32 = 40.0;
+
+/// Where the banner slides in from, far enough up to be hidden.
+const BANNER_HIDDEN_TOP: f32 = -60.0;
+
+const BANNER_ICON_SIZE: f32 = 16.0;
+
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AchievementEarned>()
+            .init_resource::<BannerQueue>()
+            .init_resource::<RoundHurt>()
+            .add_systems(OnExit(GameState::Loading), spawn_achievement_banner)
+            .add_systems(OnEnter(GameState::Playing), reset_round_hurt)
+            .add_systems(
+                OnEnter(GameState::Victory),
+                check_round_clear
+                    .after(record_round_time)
+                    .run_if(not(in_demo)),
+            )
+            .add_systems(
+                Update,
+                (
+                    check_kills,
+                    check_player_hurt,
+                    check_run_stats.run_if(resource_changed::<RunStats>),
+                    check_lifetime_kills.run_if(resource_changed::<SaveData>),
+                    check_endless_survival.run_if(resource_equals(GameMode::Endless)),
+                )
+                    .run_if(in_state(GameState::Playing).and(not(in_demo))),
+            )
+            .add_systems(
+                Update,
+                (record_achievements, update_achievement_banner)
+                    .chain()
+                    .run_if(resource_exists::<GameAssets>),
+            );
+    }
+}
+
+/// Every achievement, in the order they are listed on the title screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    FirstBlood,
+    TrickShot,
+    HotFoot,
+    ChainReaction,
+    ComboMaster,
+    Centurion,
+    Untouchable,
+    SpeedRun,
+    Veteran,
+    Survivor,
+    Prospector,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 11] = [
+        Achievement::FirstBlood,
+        Achievement::TrickShot,
+        Achievement::HotFoot,
+        Achievement::ChainReaction,
+        Achievement::ComboMaster,
+        Achievement::Centurion,
+        Achievement::Untouchable,
+        Achievement::SpeedRun,
+        Achievement::Veteran,
+        Achievement::Survivor,
+        Achievement::Prospector,
+    ];
+
+    /// The name stored in the save file, so it must never change.
+    pub fn id(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "first_blood",
+            Achievement::TrickShot => "trick_shot",
+            Achievement::HotFoot => "hot_foot",
+            Achievement::ChainReaction => "chain_reaction",
+            Achievement::ComboMaster => "combo_master",
+            Achievement::Centurion => "centurion",
+            Achievement::Untouchable => "untouchable",
+            Achievement::SpeedRun => "speed_run",
+            Achievement::Veteran => "veteran",
+            Achievement::Survivor => "survivor",
+            Achievement::Prospector => "prospector",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Achievement::FirstBlood => "FIRST BLOOD",
+            Achievement::TrickShot => "TRICK SHOT",
+            Achievement::HotFoot => "HOT FOOT",
+            Achievement::ChainReaction => "CHAIN REACTION",
+            Achievement::ComboMaster => "COMBO MASTER",
+            Achievement::Centurion => "CENTURION",
+            Achievement::Untouchable => "UNTOUCHABLE",
+            Achievement::SpeedRun => "SPEED RUN",
+            Achievement::Veteran => "VETERAN",
+            Achievement::Survivor => "SURVIVOR",
+            Achievement::Prospector => "PROSPECTOR",
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Achievement::FirstBlood => "KILL AN ENEMY".to_string(),
+            Achievement::TrickShot => {
+                format!("KILL WITH A SHOT THAT BOUNCED {} TIMES", TRICK_SHOT_BOUNCES)
+            }
+            Achievement::HotFoot => "LURE AN ENEMY INTO LAVA".to_string(),
+            Achievement::ChainReaction => format!(
+                "KILL {} ENEMIES WITH EXPLOSIONS IN A SECOND",
+                CHAIN_REACTION_KILLS
+            ),
+            Achievement::ComboMaster => format!("REACH A {}X COMBO", COMBO_MASTER_COMBO),
+            Achievement::Centurion => format!("KILL {} ENEMIES IN ALL", CENTURION_KILLS),
+            Achievement::Untouchable => "CLEAR ROUND 1 WITHOUT LOSING A HEART".to_string(),
+            Achievement::SpeedRun => {
+                format!("CLEAR A ROUND IN UNDER {} SECONDS", SPEED_RUN_TIME)
+            }
+            Achievement::Veteran => format!("CLEAR ROUND {}", VETERAN_ROUND),
+            Achievement::Survivor => {
+                format!("SURVIVE {} MINUTES IN ENDLESS MODE", SURVIVOR_TIME / 60.0)
+            }
+            Achievement::Prospector => "COLLECT EVERY GEM ON A MAP".to_string(),
+        }
+    }
+}
+
+/// Sent by the checks whenever an achievement's condition is met, earned before or not.
+#[derive(Event)]
+pub struct AchievementEarned(pub Achievement);
+
+/// Banners waiting to be shown, and the one on screen with how long it has been up.
+#[derive(Resource, Default)]
+struct BannerQueue {
+    waiting: VecDeque<Achievement>,
+    showing: Option<(Achievement, f32)>,
+}
+
+/// Whether the player has lost a heart this round.
+#[derive(Resource, Default)]
+struct RoundHurt(bool);
+
+/// The sliding banner; its top edge is animated.
+#[derive(Component)]
+struct AchievementBanner;
+
+#[derive(Component)]
+struct AchievementBannerName;
+
+fn spawn_achievement_banner(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(BANNER_HIDDEN_TOP),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            GlobalZIndex(25),
+            Visibility::Hidden,
+            AchievementBanner,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(8.0),
+                        padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::BLACK.with_alpha(0.8)),
+                ))
+                .with_children(|banner| {
+                    banner.spawn((
+                        ImageNode::new(game_assets.enemy_texture.clone())
+                            .with_color(game_assets.palette.colors[4]),
+                        Node {
+                            width: Val::Px(BANNER_ICON_SIZE),
+                            height: Val::Px(BANNER_ICON_SIZE),
+                            ..default()
+                        },
+                    ));
+                    banner
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(4.0),
+                            ..default()
+                        })
+                        .with_children(|text| {
+                            text.spawn((
+                                Text::new("ACHIEVEMENT UNLOCKED"),
+                                TextFont {
+                                    font: game_assets.font.clone(),
+                                    font_size: 8.0,
+                                    ..default()
+                                },
+                                TextColor(game_assets.palette.colors[13]),
+                            ));
+                            text.spawn((
+                                Text::new(""),
+                                TextFont {
+                                    font: game_assets.font.clone(),
+                                    font_size: 12.0,
+                                    ..default()
+                                },
+                                TextColor(game_assets.palette.colors[4]),
+                                AchievementBannerName,
+                            ));
+                        });
+                });
+        });
+}
+
+fn reset_round_hurt(mut hurt: ResMut<RoundHurt>) {
+    hurt.0 = false;
+}
+
+fn check_player_hurt(
+    query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut hurt: ResMut<RoundHurt>,
+) {
+    if query.iter().any(|health| health.current < health.max) {
+        hurt.0 = true;
+    }
+}
+
+fn check_kills(
+    mut events: EventReader<EnemyKilled>,
+    mut earned: EventWriter<AchievementEarned>,
+    time: Res<Time>,
+    mut explosion_kills: Local<VecDeque<f32>>,
+) {
+    let now = time.elapsed_secs();
+    for event in events.read() {
+        earned.write(AchievementEarned(Achievement::FirstBlood));
+        match event.source {
+            KillSource::Ricochet { bounces } if bounces >= TRICK_SHOT_BOUNCES => {
+                earned.write(AchievementEarned(Achievement::TrickShot));
+            }
+            KillSource::Lava => {
+                earned.write(AchievementEarned(Achievement::HotFoot));
+            }
+            KillSource::Explosion => explosion_kills.push_back(now),
+            _ => {}
+        }
+    }
+    while explosion_kills
+        .front()
+        .is_some_and(|&time| now - time > CHAIN_REACTION_WINDOW)
+    {
+        explosion_kills.pop_front();
+    }
+    if explosion_kills.len() >= CHAIN_REACTION_KILLS {
+        earned.write(AchievementEarned(Achievement::ChainReaction));
+    }
+}
+
+fn check_run_stats(stats: Res<RunStats>, mut earned: EventWriter<AchievementEarned>) {
+    if stats.best_combo >= COMBO_MASTER_COMBO {
+        earned.write(AchievementEarned(Achievement::ComboMaster));
+    }
+}
+
+fn check_lifetime_kills(save: Res<SaveData>, mut earned: EventWriter<AchievementEarned>) {
+    if save.total_kills >= CENTURION_KILLS {
+        earned.write(AchievementEarned(Achievement::Centurion));
+    }
+}
+
+fn check_endless_survival(timer: Res<RoundTimer>, mut earned: EventWriter<AchievementEarned>) {
+    if timer.round >= SURVIVOR_TIME {
+        earned.write(AchievementEarned(Achievement::Survivor));
+    }
+}
+
+fn check_round_clear(
+    round: Res<CurrentRound>,
+    timer: Res<RoundTimer>,
+    hurt: Res<RoundHurt>,
+    gems: Res<GemCount>,
+    mut earned: EventWriter<AchievementEarned>,
+) {
+    if round.0 == 1 && !hurt.0 {
+        earned.write(AchievementEarned(Achievement::Untouchable));
+    }
+    if timer.round < SPEED_RUN_TIME {
+        earned.write(AchievementEarned(Achievement::SpeedRun));
+    }
+    if round.0 >= VETERAN_ROUND {
+        earned.write(AchievementEarned(Achievement::Veteran));
+    }
+    if gems.all_collected() {
+        earned.write(AchievementEarned(Achievement::Prospector));
+    }
+}
+
+/// Records achievements earned for the first time, writing the save at once, and queues their
+/// banners.
+fn record_achievements(
+    mut events: EventReader<AchievementEarned>,
+    mut save: ResMut<SaveData>,
+    mut queue: ResMut<BannerQueue>,
+) {
+    let mut new = false;
+    for AchievementEarned(achievement) in events.read() {
+        if save.has_achievement(*achievement) {
+            continue;
+        }
+        info!("Achievement earned: {}", achievement.name());
+        save.achievements.push(achievement.id().to_string());
+        queue.waiting.push_back(*achievement);
+        new = true;
+    }
+    if new {
+        save.save();
+    }
+}
+
+/// Shows the queued banners one after another, sliding each in and back out.
+fn update_achievement_banner(
+    mut queue: ResMut<BannerQueue>,
+    time: Res<Time<Real>>,
+    mut banner_query: Query<(&mut Node, &mut Visibility), With<AchievementBanner>>,
+    mut name_query: Query<&mut Text, With<AchievementBannerName>>,
+) {
+    let Ok((mut node, mut visibility)) = banner_query.single_mut() else {
+        return;
+    };
+    if let Some((_, age)) = &mut queue.showing {
+        *age += time.delta_secs();
+        if *age >= BANNER_LIFETIME {
+            queue.showing = None;
+        }
+    }
+    if queue.showing.is_none() {
+        let Some(next) = queue.waiting.pop_front() else {
+            visibility.set_if_neq(Visibility::Hidden);
+            return;
+        };
+        queue.showing = Some((next, 0.0));
+        if let Ok(mut text) = name_query.single_mut() {
+            text.0 = next.name().to_string();
+        }
+    }
+    let Some((_, age)) = queue.showing else {
+        return;
+    };
+    let slide = (age / BANNER_SLIDE_TIME)
+        .min((BANNER_LIFETIME - age) / BANNER_SLIDE_TIME)
+        .clamp(0.0, 1.0);
+    let eased = 1.0 - (1.0 - slide).powi(2);
+    node.top = Val::Px(BANNER_HIDDEN_TOP + (BANNER_TOP - BANNER_HIDDEN_TOP) * eased);
+    visibility.set_if_neq(Visibility::Inherited);
+}
@@ -0,0 +1,60 @@
+// arena.rs
+
+//! Persistent arena: an optional twist on the classic loop where the map is kept from one
+//! victorious round to the next instead of being generated afresh.
+//!
+//! A run's first round sets up as usual. Once it is won, `RunInProgress` marks the run as
+//! continuing, and with the setting on the next `OnEnter(Playing)` skips the systems that build
+//! the map (generation, floor colors, fog, the tilemap and backdrop, and the gems) while the
+//! player, enemies, spawners and HUD are spawned as every round. The victory cleanup leaves the
+//! entities marked `ArenaEntity` in place for the same reason, so the tiles and any gems not yet
+//! collected carry over with the map. Dying, restarting or quitting ends the run, and the next
+//! one starts from a fresh map.
+
+use bevy::prelude::*;
+
+use crate::components::{GameMode, GameState};
+
+pub struct ArenaPlugin;
+
+impl Plugin for ArenaPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PersistentArena(false))
+            .add_systems(OnEnter(GameState::Victory), continue_run)
+            .add_systems(OnEnter(GameState::GameOver), end_run)
+            .add_systems(OnEnter(GameState::Restarting), end_run)
+            .add_systems(OnEnter(GameState::Title), end_run);
+    }
+}
+
+/// Whether the map carries over between rounds, pushed from `Settings`.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PersistentArena(pub bool);
+
+/// Present from a run's first victory until the run ends, so the round setup can tell the
+/// first round of a run from the ones that follow it.
+#[derive(Resource)]
+pub struct RunInProgress;
+
+/// Marks the entities that make up the map itself, which survive the victory cleanup while
+/// the arena carries over. They are still `GameEntity`s, so ending the run removes them.
+#[derive(Component)]
+pub struct ArenaEntity;
+
+/// A run condition that passes when this round reuses the previous round's map. Only the
+/// classic mode has rounds to carry it between.
+pub fn carry_over_arena(
+    arena: Res<PersistentArena>,
+    run: Option<Res<RunInProgress>>,
+    mode: Res<GameMode>,
+) -> bool {
+    arena.0 && run.is_some() && *mode == GameMode::Classic
+}
+
+fn continue_run(mut commands: Commands) {
+    commands.insert_resource(RunInProgress);
+}
+
+fn end_run(mut commands: Commands) {
+    commands.remove_resource::<RunInProgress>();
+}
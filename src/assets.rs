@@ -1,21 +1,53 @@
 // assets.rs
 use crate::components::GameState;
+use crate::palette::{Palette, PaletteChoice, Palettes};
+use crate::tilemap::TILE_SIZE;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::{AssetPath, LoadState, RenderAssetUsages, UntypedAssetId};
 use bevy::audio::AudioSource;
 use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 
 pub struct AssetsPlugin;
 
+/// Number of frames in the explosion sprite sheet.
+pub const EXPLOSION_FRAMES: u32 = 6;
+
+/// Size of a single frame in the explosion sprite sheet, in pixels.
+const EXPLOSION_FRAME_SIZE: u32 = 32;
+
+/// The most alternate samples loaded for one sound effect (`name.wav`, `name_2.wav`, ...).
+const MAX_SFX_VARIANTS: usize = 4;
+
+/// Number of checks along each side of the placeholder texture.
+const PLACEHOLDER_CHECKS: u32 = 8;
+
+/// Width of the loading progress bar, in pixels.
+const LOADING_BAR_WIDTH: f32 = 200.0;
+
+/// With the `slow_load` feature, at most one asset counts as loaded per this many seconds, so
+/// the loading screen can be seen and checked.
+#[cfg(feature = "slow_load")]
+const SLOW_LOAD_STEP: f32 = 0.4;
+
 impl Plugin for AssetsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Loading), load_assets);
+        app.add_systems(
+            OnEnter(GameState::Loading),
+            (load_assets, spawn_loading_screen),
+        )
+        .add_systems(
+            OnExit(GameState::Loading),
+            (despawn_loading_screen, substitute_failed_assets),
+        )
+        .add_systems(
+            Update,
+            track_loading.run_if(in_state(GameState::Loading).and(resource_exists::<GameAssets>)),
+        );
     }
 }
 
-#[derive(Resource, Clone)]
-pub struct Palette {
-    pub colors: Vec<Color>,
-}
-
 #[derive(Resource)]
 pub struct GameAssets {
     pub wall_texture: Handle<Image>,
@@ -23,12 +55,67 @@ pub struct GameAssets {
     pub reservation_texture: Handle<Image>,
     pub enemy_texture: Handle<Image>,
     pub explosion_texture: Handle<Image>,
+    /// Sprite sheet of `EXPLOSION_FRAMES` frames laid out horizontally.
+    pub explosion_sheet: Handle<Image>,
+    pub explosion_layout: Handle<TextureAtlasLayout>,
     pub font: Handle<Font>,
-    pub shoot_sfx: Handle<AudioSource>,
-    pub explosion_sfx: Handle<AudioSource>,
+    /// Sound effects that play constantly have a few alternate samples, picked at random.
+    pub shoot_sfx: Vec<Handle<AudioSource>>,
+    pub explosion_sfx: Vec<Handle<AudioSource>>,
+    pub bounce_sfx: Vec<Handle<AudioSource>>,
+    pub pickup_sfx: Handle<AudioSource>,
+    pub tick_sfx: Handle<AudioSource>,
+    pub fanfare_sfx: Handle<AudioSource>,
+    pub death_sfx: Handle<AudioSource>,
+    pub heartbeat_sfx: Handle<AudioSource>,
+    /// Looping music; these files are optional and the game runs silently without them.
+    pub title_music: Handle<AudioSource>,
+    pub game_music: Handle<AudioSource>,
     pub palette: Palette,
 }
 
+impl GameAssets {
+    /// Every asset loading waits for; any that fail are replaced by `substitute_failed_assets`.
+    /// The explosion sheet and the music are optional (there are fallbacks for them), so they
+    /// don't hold up loading.
+    fn required_handles(&self) -> Vec<UntypedHandle> {
+        let mut handles = vec![
+            self.wall_texture.clone().untyped(),
+            self.player_texture.clone().untyped(),
+            self.reservation_texture.clone().untyped(),
+            self.enemy_texture.clone().untyped(),
+            self.explosion_texture.clone().untyped(),
+            self.font.clone().untyped(),
+            self.pickup_sfx.clone().untyped(),
+            self.tick_sfx.clone().untyped(),
+            self.fanfare_sfx.clone().untyped(),
+            self.death_sfx.clone().untyped(),
+            self.heartbeat_sfx.clone().untyped(),
+        ];
+        for sfx in self
+            .shoot_sfx
+            .iter()
+            .chain(&self.explosion_sfx)
+            .chain(&self.bounce_sfx)
+        {
+            handles.push(sfx.clone().untyped());
+        }
+        handles
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingText;
+
+#[derive(Component)]
+struct LoadingBar;
+
+#[derive(Component)]
+struct MissingAssetsBanner;
+
 //use bevy::prelude::Color;
 
 // Parses a hex color string (e.g., "#83769C" or "83769C") and returns a Color::Srgba
@@ -59,40 +146,296 @@ pub fn color_from_hex(hex: &str) -> Result<Color, &'static str> {
 fn load_assets(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    mut next_state: ResMut<NextState<GameState>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    palettes: Res<Palettes>,
+    palette_choice: Res<PaletteChoice>,
 ) {
-    let palette = Palette {
-        // https://lospec.com/palette-list/sweetie-16 by GrafxKid
-        colors: vec![
-            color_from_hex("#1a1c2c").unwrap(),
-            color_from_hex("#5d275d").unwrap(),
-            color_from_hex("#b13e53").unwrap(),
-            color_from_hex("#ef7d57").unwrap(),
-            color_from_hex("#ffcd75").unwrap(),
-            color_from_hex("#a7f070").unwrap(),
-            color_from_hex("#38b764").unwrap(),
-            color_from_hex("#257179").unwrap(),
-            color_from_hex("#29366f").unwrap(),
-            color_from_hex("#3b5dc9").unwrap(),
-            color_from_hex("#41a6f6").unwrap(),
-            color_from_hex("#73eff7").unwrap(),
-            color_from_hex("#f4f4f4").unwrap(),
-            color_from_hex("#94b0c2").unwrap(),
-            color_from_hex("#566c86").unwrap(),
-            color_from_hex("#333c57").unwrap(),
-        ],
-    };
-
     commands.insert_resource(GameAssets {
         wall_texture: asset_server.load("textures/wall.png"),
         player_texture: asset_server.load("textures/player.png"),
         reservation_texture: asset_server.load("textures/reservation.png"),
         enemy_texture: asset_server.load("textures/enemy.png"),
         explosion_texture: asset_server.load("textures/explosion.png"),
+        explosion_sheet: asset_server.load("textures/explosion_sheet.png"),
+        explosion_layout: layouts.add(TextureAtlasLayout::from_grid(
+            UVec2::splat(EXPLOSION_FRAME_SIZE),
+            EXPLOSION_FRAMES,
+            1,
+            None,
+            None,
+        )),
         font: asset_server.load("fonts/press_start_2p/PressStart2P-Regular.ttf"),
-        shoot_sfx: asset_server.load("sfx/shoot.wav"),
-        explosion_sfx: asset_server.load("sfx/explosion.wav"),
-        palette,
+        shoot_sfx: load_sfx_variants(&asset_server, "shoot"),
+        explosion_sfx: load_sfx_variants(&asset_server, "explosion"),
+        bounce_sfx: load_sfx_variants(&asset_server, "bounce"),
+        pickup_sfx: asset_server.load("sfx/pickup.wav"),
+        tick_sfx: asset_server.load("sfx/tick.wav"),
+        fanfare_sfx: asset_server.load("sfx/fanfare.wav"),
+        death_sfx: asset_server.load("sfx/death.wav"),
+        heartbeat_sfx: asset_server.load("sfx/heartbeat.wav"),
+        title_music: load_optional_music(&asset_server, "music/title.wav"),
+        game_music: load_optional_music(&asset_server, "music/game.wav"),
+        palette: palettes.get(&palette_choice.0).clone(),
     });
-    next_state.set(GameState::Title);
+}
+
+/// Shows loading progress. The game's own font isn't available yet, so this uses Bevy's
+/// built-in one.
+fn spawn_loading_screen(mut commands: Commands) {
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new("loading..."),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.9, 0.9, 0.9)),
+            TextLayout::new_with_justify(JustifyText::Center),
+            LoadingText,
+        ));
+
+        parent
+            .spawn((
+                Node {
+                    width: Val::Px(LOADING_BAR_WIDTH),
+                    height: Val::Px(6.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+            ))
+            .with_children(|bar| {
+                bar.spawn((
+                    Node {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.9, 0.9, 0.9)),
+                    LoadingBar,
+                ));
+            });
+    });
+}
+
+fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Moves on to the title once every required asset has either loaded or failed. Failures are
+/// swapped for placeholders on the way out by `substitute_failed_assets`.
+fn track_loading(
+    game_assets: Res<GameAssets>,
+    asset_server: Res<AssetServer>,
+    mut next_state: ResMut<NextState<GameState>>,
+    #[cfg(feature = "slow_load")] time: Res<Time<Real>>,
+    #[cfg(feature = "slow_load")] mut elapsed: Local<f32>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+    mut bar_query: Query<&mut Node, With<LoadingBar>>,
+) {
+    let handles = game_assets.required_handles();
+    let settled = handles
+        .iter()
+        .filter(|handle| {
+            matches!(
+                asset_server.get_load_state(handle.id()),
+                Some(LoadState::Loaded | LoadState::Failed(_))
+            )
+        })
+        .count();
+
+    #[cfg(feature = "slow_load")]
+    let settled = {
+        *elapsed += time.delta_secs();
+        settled.min((*elapsed / SLOW_LOAD_STEP) as usize)
+    };
+
+    if let Ok(mut text) = text_query.single_mut() {
+        text.0 = format!("loading... {}/{}", settled, handles.len());
+    }
+    if let Ok(mut bar) = bar_query.single_mut() {
+        bar.width = Val::Percent(100.0 * settled as f32 / handles.len() as f32);
+    }
+    if settled == handles.len() {
+        info!("Finished loading {} assets", handles.len());
+        next_state.set(GameState::Title);
+    }
+}
+
+/// Swaps every required asset that failed to load for a stand-in, so a missing or misnamed file
+/// doesn't stop the game: textures become a magenta checker, sounds go silent and the font falls
+/// back to Bevy's built-in one. A banner lists what's missing for the rest of the session.
+pub fn substitute_failed_assets(
+    mut commands: Commands,
+    mut game_assets: ResMut<GameAssets>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let failed =
+        |id: UntypedAssetId| matches!(asset_server.get_load_state(id), Some(LoadState::Failed(_)));
+    let mut missing = Vec::new();
+    let assets = &mut *game_assets;
+
+    let mut placeholder: Option<Handle<Image>> = None;
+    for texture in [
+        &mut assets.wall_texture,
+        &mut assets.player_texture,
+        &mut assets.reservation_texture,
+        &mut assets.enemy_texture,
+        &mut assets.explosion_texture,
+    ] {
+        if failed(texture.id().untyped()) {
+            record_substitution(&mut missing, texture.path(), "a placeholder texture");
+            *texture = placeholder
+                .get_or_insert_with(|| images.add(placeholder_image()))
+                .clone();
+        }
+    }
+
+    for sfx in [
+        &mut assets.pickup_sfx,
+        &mut assets.tick_sfx,
+        &mut assets.fanfare_sfx,
+        &mut assets.death_sfx,
+        &mut assets.heartbeat_sfx,
+    ] {
+        if failed(sfx.id().untyped()) {
+            record_substitution(&mut missing, sfx.path(), "silence");
+            *sfx = Handle::default();
+        }
+    }
+    for variants in [
+        &mut assets.shoot_sfx,
+        &mut assets.explosion_sfx,
+        &mut assets.bounce_sfx,
+    ] {
+        variants.retain(|sfx| {
+            let keep = !failed(sfx.id().untyped());
+            if !keep {
+                record_substitution(&mut missing, sfx.path(), "silence");
+            }
+            keep
+        });
+    }
+
+    if failed(assets.font.id().untyped()) {
+        record_substitution(&mut missing, assets.font.path(), "the default font");
+        assets.font = Handle::default();
+    }
+
+    if !missing.is_empty() {
+        spawn_missing_assets_banner(&mut commands, &missing);
+    }
+}
+
+fn record_substitution(missing: &mut Vec<String>, path: Option<&AssetPath>, substitute: &str) {
+    let path = path.map(|p| p.to_string()).unwrap_or_default();
+    error!("Failed to load {}; using {} instead", path, substitute);
+    missing.push(path);
+}
+
+/// An 8x8 magenta and black checkerboard, one tile across, that's hard to mistake for real art.
+fn placeholder_image() -> Image {
+    let size = TILE_SIZE as u32;
+    let cell = size / PLACEHOLDER_CHECKS;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let magenta = (x / cell + y / cell).is_multiple_of(2);
+            data.extend_from_slice(if magenta {
+                &[255, 0, 255, 255]
+            } else {
+                &[0, 0, 0, 255]
+            });
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Uses Bevy's built-in font, since the game's own may be one of the missing files.
+fn spawn_missing_assets_banner(commands: &mut Commands, missing: &[String]) {
+    commands.spawn((
+        Text::new(format!("missing assets:\n{}", missing.join("\n"))),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.0, 1.0)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.0),
+            left: Val::Px(4.0),
+            ..default()
+        },
+        GlobalZIndex(30),
+        MissingAssetsBanner,
+    ));
+}
+
+/// Loads `sfx/<name>.wav` along with any `sfx/<name>_2.wav`, `sfx/<name>_3.wav`, ... alternates
+/// that exist, stopping at the first missing one.
+fn load_sfx_variants(asset_server: &AssetServer, name: &str) -> Vec<Handle<AudioSource>> {
+    let mut variants = vec![asset_server.load(format!("sfx/{}.wav", name))];
+    for n in 2..=MAX_SFX_VARIANTS {
+        let path = format!("sfx/{}_{}.wav", name, n);
+        if !asset_file_exists(&path) {
+            break;
+        }
+        variants.push(asset_server.load(path));
+    }
+    variants
+}
+
+/// Loads a music track if its file is there, or returns the silent default handle without
+/// asking the asset server for it, so a game shipped without music doesn't log load errors.
+/// On the web, where files can't be looked for, it is always requested.
+fn load_optional_music(asset_server: &AssetServer, path: &str) -> Handle<AudioSource> {
+    if cfg!(target_arch = "wasm32") || asset_file_exists(path) {
+        asset_server.load(path.to_string())
+    } else {
+        info!("No {} found; playing without it", path);
+        Handle::default()
+    }
+}
+
+/// Whether a file exists in the assets folder. Always false on the web, where alternates can't
+/// be discovered.
+#[cfg(not(target_arch = "wasm32"))]
+fn asset_file_exists(path: &str) -> bool {
+    FileAssetReader::get_base_path()
+        .join("assets")
+        .join(path)
+        .exists()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn asset_file_exists(_path: &str) -> bool {
+    false
 }
@@ -0,0 +1,181 @@
+// atlas.rs
+
+//! Packs the game's sprites into a single texture once loading finishes, so the tile chunks and
+//! the enemy and player sprites share one texture instead of binding a handful each frame.
+//! Animation frames become adjacent atlas indices.
+//!
+//! If the atlas can't be built, `GameAtlas` is never inserted and `atlas_sprite` falls back to
+//! the standalone images in `GameAssets`.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::assets::{substitute_failed_assets, GameAssets, EXPLOSION_FRAMES};
+use crate::autotile::WallVariants;
+use crate::components::GameState;
+
+/// Empty pixels between packed images, so neighbours never bleed into each other.
+const ATLAS_PADDING: u32 = 2;
+
+pub struct AtlasPlugin;
+
+impl Plugin for AtlasPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Loading),
+            build_atlas.after(substitute_failed_assets),
+        );
+    }
+}
+
+/// The logical names of the images packed into the atlas.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AtlasSprite {
+    Wall,
+    Player,
+    Reservation,
+    Enemy,
+    /// The single-frame explosion, only packed when the sprite sheet is missing.
+    Explosion,
+    /// The first of `EXPLOSION_FRAMES` adjacent explosion frames.
+    ExplosionFrames,
+    /// The generated wall for a wall mask; see `autotile`.
+    WallVariant(u8),
+}
+
+#[derive(Resource)]
+pub struct GameAtlas {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    indices: HashMap<AtlasSprite, usize>,
+}
+
+impl GameAtlas {
+    /// The atlas index of `key`, if that image was packed.
+    pub fn index(&self, key: AtlasSprite) -> Option<usize> {
+        self.indices.get(&key).copied()
+    }
+
+    /// A sprite showing `key`, if that image was packed.
+    pub fn sprite(&self, key: AtlasSprite) -> Option<Sprite> {
+        let index = self.index(key)?;
+        Some(Sprite::from_atlas_image(
+            self.image.clone(),
+            TextureAtlas {
+                layout: self.layout.clone(),
+                index,
+            },
+        ))
+    }
+}
+
+/// Builds the sprite for `key` from the atlas, or from its standalone image when there's no
+/// atlas.
+pub fn atlas_sprite(
+    atlas: Option<&GameAtlas>,
+    game_assets: &GameAssets,
+    key: AtlasSprite,
+) -> Sprite {
+    if let Some(sprite) = atlas.and_then(|a| a.sprite(key)) {
+        return sprite;
+    }
+    match key {
+        // Without the atlas the tilemap draws every wall with the plain image; see `TileTextures`.
+        AtlasSprite::Wall | AtlasSprite::WallVariant(_) => {
+            Sprite::from_image(game_assets.wall_texture.clone())
+        }
+        AtlasSprite::Player => Sprite::from_image(game_assets.player_texture.clone()),
+        AtlasSprite::Reservation => Sprite::from_image(game_assets.reservation_texture.clone()),
+        AtlasSprite::Enemy => Sprite::from_image(game_assets.enemy_texture.clone()),
+        AtlasSprite::Explosion => Sprite::from_image(game_assets.explosion_texture.clone()),
+        AtlasSprite::ExplosionFrames => Sprite::from_atlas_image(
+            game_assets.explosion_sheet.clone(),
+            TextureAtlas {
+                layout: game_assets.explosion_layout.clone(),
+                index: 0,
+            },
+        ),
+    }
+}
+
+pub fn build_atlas(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    wall_variants: Option<Res<WallVariants>>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    // The explosion sheet is optional; without it the single-frame texture is packed instead.
+    let has_sheet = images.contains(&game_assets.explosion_sheet);
+    let explosion = if has_sheet {
+        (AtlasSprite::ExplosionFrames, &game_assets.explosion_sheet)
+    } else {
+        (AtlasSprite::Explosion, &game_assets.explosion_texture)
+    };
+    let mut entries = vec![
+        (AtlasSprite::Wall, &game_assets.wall_texture),
+        (AtlasSprite::Player, &game_assets.player_texture),
+        (AtlasSprite::Reservation, &game_assets.reservation_texture),
+        (AtlasSprite::Enemy, &game_assets.enemy_texture),
+        explosion,
+    ];
+    if let Some(variants) = &wall_variants {
+        for (mask, handle) in variants.0.iter().enumerate() {
+            entries.push((AtlasSprite::WallVariant(mask as u8), handle));
+        }
+    }
+
+    let mut builder = TextureAtlasBuilder::default();
+    builder.padding(UVec2::splat(ATLAS_PADDING));
+    for &(key, handle) in &entries {
+        let Some(image) = images.get(handle) else {
+            warn!("{:?} image missing; not building the sprite atlas", key);
+            return;
+        };
+        builder.add_texture(Some(handle.id()), image);
+    }
+    let (mut layout, sources, image) = match builder.build() {
+        Ok(built) => built,
+        Err(err) => {
+            warn!(
+                "Couldn't build the sprite atlas, using separate images: {}",
+                err
+            );
+            return;
+        }
+    };
+
+    let mut indices = HashMap::new();
+    for &(key, handle) in &entries {
+        if let Some(index) = sources.texture_index(handle) {
+            indices.insert(key, index);
+        }
+    }
+
+    // Split the packed sheet into its frames, appended so they sit at adjacent indices.
+    if let Some(sheet) = indices.get(&AtlasSprite::ExplosionFrames).copied() {
+        let rect = layout.textures[sheet];
+        let frame_width = rect.width() / EXPLOSION_FRAMES;
+        let first = layout.len();
+        for frame in 0..EXPLOSION_FRAMES {
+            let min = rect.min + UVec2::new(frame * frame_width, 0);
+            layout.add_texture(URect::from_corners(
+                min,
+                min + UVec2::new(frame_width, rect.height()),
+            ));
+        }
+        indices.insert(AtlasSprite::ExplosionFrames, first);
+    }
+
+    info!(
+        "Packed {} sprites into a {}x{} atlas",
+        entries.len(),
+        image.width(),
+        image.height()
+    );
+    commands.insert_resource(GameAtlas {
+        image: images.add(image),
+        layout: layouts.add(layout),
+        indices,
+    });
+}
@@ -1,27 +1,330 @@
 // audio.rs
-use bevy::audio::{AudioPlayer, PlaybackSettings, Volume};
+use bevy::audio::{AudioPlayer, AudioSinkPlayback, GlobalVolume, PlaybackSettings, Volume};
 use bevy::prelude::*;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use std::collections::HashMap;
+
+use crate::assets::GameAssets;
+use crate::components::GameState;
+use crate::input::InputMap;
+use crate::random::{random_float, random_pick};
+use crate::settings::Settings;
+
+/// Extra volume given to a sound for each identical request merged into it in the same frame.
+const COALESCE_BOOST: f32 = 0.15;
+
+/// The loudest a merged sound can get, relative to its requested volume.
+const MAX_COALESCE_BOOST: f32 = 1.6;
+
+/// Default playback speed variation for sounds that repeat a lot: each play is sped up or slowed
+/// down by up to this fraction.
+pub const PITCH_VARIATION: f32 = 0.1;
 
 pub struct AudioPlugin;
 
 impl Plugin for AudioPlugin {
-    fn build(&self, _app: &mut App) {
-        // No additional setup needed, as AudioPlugin is included in DefaultPlugins
+    fn build(&self, app: &mut App) {
+        // Bevy's own AudioPlugin is included in DefaultPlugins; this only manages volume levels.
+        app.init_resource::<AudioSettings>()
+            .init_resource::<SfxBudget>()
+            // Budgeting must happen before Bevy creates sinks for the newly spawned sounds. Its
+            // audio set is private, but runs after transform propagation.
+            .add_systems(
+                PostUpdate,
+                budget_sfx.before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(OnExit(GameState::Loading), spawn_mute_indicator)
+            .add_systems(
+                Update,
+                (
+                    toggle_mute,
+                    (apply_audio_settings, update_mute_indicator)
+                        .run_if(resource_changed::<AudioSettings>),
+                ),
+            );
+    }
+}
+
+/// Player-adjustable volume levels, each from 0.0 (silent) to 1.0 (full).
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AudioSettings {
+    pub master: f32,
+    pub sfx: f32,
+    pub music: f32,
+    /// Silences everything without losing the levels above.
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            master: 1.0,
+            sfx: 1.0,
+            music: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// The overall multiplier for sound effects.
+    pub fn sfx_level(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master * self.sfx
+        }
+    }
+
+    /// The overall multiplier for music.
+    pub fn music_level(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master * self.music
+        }
+    }
+}
+
+/// A playing sound effect and the volume it was requested at, so its sink can be adjusted when
+/// the volume settings change mid-playback.
+#[derive(Component)]
+pub struct Sfx {
+    pub volume: f32,
+}
+
+/// Groups sound effects so each group can be capped separately.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SfxCategory {
+    Shot,
+    Bounce,
+    Explosion,
+    Other,
+}
+
+impl SfxCategory {
+    pub const ALL: [SfxCategory; 4] = [
+        SfxCategory::Shot,
+        SfxCategory::Bounce,
+        SfxCategory::Explosion,
+        SfxCategory::Other,
+    ];
+
+    /// The most sounds of this category allowed to play at once.
+    pub fn cap(self) -> usize {
+        match self {
+            SfxCategory::Shot => 4,
+            SfxCategory::Bounce => 3,
+            SfxCategory::Explosion => 6,
+            SfxCategory::Other => 8,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SfxCategory::Shot => "shot",
+            SfxCategory::Bounce => "bounce",
+            SfxCategory::Explosion => "expl",
+            SfxCategory::Other => "other",
+        }
+    }
+}
+
+/// How many sound effects of each category are currently playing.
+#[derive(Resource, Default, Debug)]
+pub struct SfxBudget {
+    pub live: HashMap<SfxCategory, usize>,
+}
+
+impl SfxBudget {
+    pub fn live(&self, category: SfxCategory) -> usize {
+        self.live.get(&category).copied().unwrap_or(0)
+    }
+}
+
+#[derive(Component)]
+struct MuteIndicator;
+
+/// Applies the sound effect volume to every sound started from now on, and to those already
+/// playing. Music sets its own sink volume each frame (see `music.rs`), so this doesn't affect it.
+fn apply_audio_settings(
+    settings: Res<AudioSettings>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut playing: Query<(&Sfx, &mut AudioSink)>,
+) {
+    global_volume.volume = Volume::Linear(settings.sfx_level());
+    for (sfx, mut sink) in &mut playing {
+        sink.set_volume(Volume::Linear(sfx.volume * settings.sfx_level()));
     }
 }
 
+/// Toggles mute through the settings, so it is remembered between sessions.
+fn toggle_mute(
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut settings: ResMut<Settings>,
+) {
+    if keys.just_pressed(input_map.mute) {
+        settings.muted = !settings.muted;
+    }
+}
+
+fn spawn_mute_indicator(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    settings: Res<AudioSettings>,
+) {
+    commands.spawn((
+        Text::new("MUTED"),
+        TextFont {
+            font: game_assets.font.clone(),
+            font_size: 8.0,
+            ..default()
+        },
+        TextColor(game_assets.palette.colors[13]),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            right: Val::Px(8.0),
+            display: if settings.muted {
+                Display::Flex
+            } else {
+                Display::None
+            },
+            ..default()
+        },
+        GlobalZIndex(20),
+        MuteIndicator,
+    ));
+}
+
+fn update_mute_indicator(
+    settings: Res<AudioSettings>,
+    mut query: Query<&mut Node, With<MuteIndicator>>,
+) {
+    for mut node in &mut query {
+        node.display = if settings.muted {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+/// Keeps each category under its cap. Identical sounds requested in the same frame are merged
+/// into one slightly louder sound, and new sounds over the cap are dropped before they start.
+fn budget_sfx(
+    mut commands: Commands,
+    mut budget: ResMut<SfxBudget>,
+    playing: Query<&SfxCategory, With<AudioSink>>,
+    mut requested: Query<
+        (
+            Entity,
+            &SfxCategory,
+            &AudioPlayer,
+            &mut PlaybackSettings,
+            &mut Sfx,
+        ),
+        Added<SfxCategory>,
+    >,
+) {
+    budget.live.clear();
+    for category in &playing {
+        *budget.live.entry(*category).or_default() += 1;
+    }
+
+    let mut merged: HashMap<(SfxCategory, AssetId<AudioSource>), Entity> = HashMap::new();
+    let mut extra: HashMap<Entity, u32> = HashMap::new();
+    for (entity, category, player, _, _) in &requested {
+        match merged.get(&(*category, player.0.id())) {
+            Some(&kept) => {
+                *extra.entry(kept).or_default() += 1;
+                commands.entity(entity).despawn();
+            }
+            None => {
+                let live = budget.live.entry(*category).or_default();
+                if *live >= category.cap() {
+                    commands.entity(entity).despawn();
+                    continue;
+                }
+                *live += 1;
+                merged.insert((*category, player.0.id()), entity);
+            }
+        }
+    }
+
+    for (kept, count) in extra {
+        if let Ok((_, _, _, mut settings, mut sfx)) = requested.get_mut(kept) {
+            let boost = (1.0 + COALESCE_BOOST * count as f32).min(MAX_COALESCE_BOOST);
+            sfx.volume *= boost;
+            settings.volume = Volume::Linear(sfx.volume);
+        }
+    }
+}
+
+/// Whether `handle` is the stand-in for a sound that wasn't found or failed to load, which plays
+/// nothing.
+pub fn is_silent(handle: &Handle<AudioSource>) -> bool {
+    handle.id() == AssetId::default()
+}
+
 /// Plays a sound effect by spawning an entity that will despawn automatically after playback.
 /// This is efficient for one-shot SFX and handles cleanup to avoid entity buildup.
 pub fn play(commands: &mut Commands, audio: Handle<AudioSource>) {
-    commands.spawn((AudioPlayer::new(audio), PlaybackSettings::DESPAWN));
+    play_with_volume(commands, audio, 1.0);
 }
 
+/// Plays a sound effect at `volume`, which is further scaled by the master and SFX levels
+/// through `GlobalVolume`.
 pub fn play_with_volume(commands: &mut Commands, audio: Handle<AudioSource>, volume: f32) {
+    play_with_pitch(commands, audio, volume, 1.0);
+}
+
+/// Plays a sound effect at `volume` and playback `speed`, which raises or lowers its pitch.
+pub fn play_with_pitch(
+    commands: &mut Commands,
+    audio: Handle<AudioSource>,
+    volume: f32,
+    speed: f32,
+) {
+    if is_silent(&audio) {
+        return;
+    }
     commands.spawn((
         AudioPlayer::new(audio),
         PlaybackSettings {
             volume: Volume::Linear(volume),
+            speed,
+            ..PlaybackSettings::DESPAWN
+        },
+        Sfx { volume },
+        SfxCategory::Other,
+    ));
+}
+
+/// Plays one of `variants` at random, at `volume`, with its playback speed randomly varied by up
+/// to `pitch_variation` either way, so repeated effects don't sound identical.
+pub fn play_varied(
+    commands: &mut Commands,
+    category: SfxCategory,
+    variants: &[Handle<AudioSource>],
+    volume: f32,
+    pitch_variation: f32,
+    rng: &mut GlobalEntropy<WyRand>,
+) {
+    if variants.is_empty() {
+        return;
+    }
+    let variant = random_pick(rng, variants).clone();
+    let speed = 1.0 + (random_float(rng) * 2.0 - 1.0) * pitch_variation;
+    commands.spawn((
+        AudioPlayer::new(variant),
+        PlaybackSettings {
+            volume: Volume::Linear(volume),
+            speed,
             ..PlaybackSettings::DESPAWN
         },
+        Sfx { volume },
+        category,
     ));
 }
@@ -0,0 +1,158 @@
+// autotile.rs
+
+//! Picks a wall sprite for each wall tile from which of its neighbours are walls, so the edges
+//! of corridors read clearly.
+//!
+//! There is no wall tile-sheet yet, so the sixteen variants are generated from `wall.png` once
+//! loading finishes: the wall is shaded down, except for a bright strip along each side that
+//! faces open floor. They're packed into the sprite atlas alongside everything else.
+
+use bevy::prelude::*;
+
+use crate::assets::{substitute_failed_assets, GameAssets};
+use crate::atlas::{build_atlas, AtlasSprite, GameAtlas};
+use crate::components::GameState;
+use crate::grid_movement::is_wall;
+use crate::map::MapData;
+
+/// Bits of a wall mask, set when the neighbour on that side is also a wall.
+pub const NORTH: u8 = 1;
+pub const EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const WEST: u8 = 8;
+
+/// One variant for every combination of the four sides.
+pub const WALL_VARIANTS: usize = 16;
+
+/// How much darker a wall is away from its highlighted edges.
+const INTERIOR_SHADE: f32 = 0.7;
+
+/// Width of the highlight along an edge facing floor, as a fraction of the tile.
+const HIGHLIGHT_WIDTH: f32 = 0.125;
+
+pub struct AutotilePlugin;
+
+impl Plugin for AutotilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Loading),
+            generate_wall_variants
+                .after(substitute_failed_assets)
+                .before(build_atlas),
+        );
+    }
+}
+
+/// The generated wall images, indexed by wall mask.
+#[derive(Resource)]
+pub struct WallVariants(pub Vec<Handle<Image>>);
+
+/// Which of the four neighbours of `pos` are walls, as `NORTH | EAST | SOUTH | WEST` bits.
+/// Anything off the map counts as a wall, so the border doesn't get highlighted edges.
+pub fn wall_mask(map_data: &MapData, pos: IVec2) -> u8 {
+    [
+        (IVec2::Y, NORTH),
+        (IVec2::X, EAST),
+        (IVec2::NEG_Y, SOUTH),
+        (IVec2::NEG_X, WEST),
+    ]
+    .into_iter()
+    .filter(|&(dir, _)| is_wall(pos + dir, map_data))
+    .fold(0, |mask, (_, bit)| mask | bit)
+}
+
+/// Where the tilemap finds the plain wall image, which floor tiles use, and the wall for each
+/// wall mask, as texture coordinates into `image`.
+#[derive(Resource)]
+pub struct TileTextures {
+    pub image: Handle<Image>,
+    pub plain: Rect,
+    pub walls: Vec<Rect>,
+}
+
+impl TileTextures {
+    /// Takes the rectangles from the atlas where the walls were packed. Without an atlas every
+    /// tile shows the whole plain wall image: a chunk mesh has only the one texture, and the
+    /// generated variants are separate images.
+    pub fn new(
+        atlas: Option<&GameAtlas>,
+        layouts: &Assets<TextureAtlasLayout>,
+        game_assets: &GameAssets,
+    ) -> Self {
+        let whole = Rect::new(0.0, 0.0, 1.0, 1.0);
+        let Some((atlas, layout)) = atlas.and_then(|a| Some((a, layouts.get(&a.layout)?))) else {
+            return TileTextures {
+                image: game_assets.wall_texture.clone(),
+                plain: whole,
+                walls: vec![whole; WALL_VARIANTS],
+            };
+        };
+        let atlas_size = layout.size.as_vec2();
+        let uv_rect = |key| {
+            let rect = layout.textures.get(atlas.index(key)?)?.as_rect();
+            Some(Rect::from_corners(
+                rect.min / atlas_size,
+                rect.max / atlas_size,
+            ))
+        };
+        let plain = uv_rect(AtlasSprite::Wall).unwrap_or(whole);
+        let walls = (0..WALL_VARIANTS as u8)
+            .map(|mask| uv_rect(AtlasSprite::WallVariant(mask)).unwrap_or(plain))
+            .collect();
+        TileTextures {
+            image: atlas.image.clone(),
+            plain,
+            walls,
+        }
+    }
+
+    /// The texture rectangle for a wall with `mask`, or the plain one for floor.
+    pub fn get(&self, wall_mask: Option<u8>) -> Rect {
+        wall_mask
+            .and_then(|mask| self.walls.get(mask as usize))
+            .copied()
+            .unwrap_or(self.plain)
+    }
+}
+
+fn generate_wall_variants(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let Some(wall) = images.get(&game_assets.wall_texture).cloned() else {
+        warn!("Wall image missing; not generating wall variants");
+        return;
+    };
+    let handles = (0..WALL_VARIANTS as u8)
+        .map(|mask| images.add(wall_variant(&wall, mask)))
+        .collect();
+    commands.insert_resource(WallVariants(handles));
+}
+
+/// Shades `wall` down except along the sides `mask` says face floor.
+fn wall_variant(wall: &Image, mask: u8) -> Image {
+    let mut image = wall.clone();
+    let (width, height) = (image.width(), image.height());
+    let strip_x = ((width as f32 * HIGHLIGHT_WIDTH).round() as u32).max(1);
+    let strip_y = ((height as f32 * HIGHLIGHT_WIDTH).round() as u32).max(1);
+    for y in 0..height {
+        for x in 0..width {
+            // Image rows count down from the top, so row 0 is the north edge.
+            let highlighted = (mask & NORTH == 0 && y < strip_y)
+                || (mask & SOUTH == 0 && y >= height - strip_y)
+                || (mask & WEST == 0 && x < strip_x)
+                || (mask & EAST == 0 && x >= width - strip_x);
+            if highlighted {
+                continue;
+            }
+            let Ok(color) = image.get_color_at(x, y) else {
+                // A format without per-pixel access; leave the variant as the plain wall.
+                return wall.clone();
+            };
+            let shaded = color.to_srgba() * INTERIOR_SHADE;
+            let _ = image.set_color_at(x, y, shaded.with_alpha(color.alpha()).into());
+        }
+    }
+    image
+}
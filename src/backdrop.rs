@@ -0,0 +1,192 @@
+// backdrop.rs
+
+//! A faint star field behind the tilemap, showing wherever the view runs off the edge of the
+//! map. It scrolls at a fraction of the map's speed, so it reads as far away.
+//!
+//! It's one tiled sprite a little larger than the view, nudged by less than one repeat of its
+//! pattern as the map scrolls, so no edge is ever on screen and it costs a single batch. It's
+//! tinted from the floor palette and hidden under fog of war, where it would show through the
+//! black of unexplored tiles.
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::sprite::SpriteImageMode;
+
+use crate::arena::{carry_over_arena, ArenaEntity};
+use crate::components::{GameEntity, GameState};
+use crate::fog::FogOfWar;
+use crate::grid_movement::MovementSystems;
+use crate::tilemap::{
+    spawn_tilemap, FloorPalette, MapOffset, TileOffset, ViewportTiles, TILE_SIZE,
+};
+
+/// How fast the backdrop scrolls compared with the map.
+const PARALLAX_FACTOR: f32 = 0.3;
+
+/// Side of the generated star image, in pixels.
+const PATTERN_PIXELS: u32 = 128;
+/// How many world units each image pixel covers.
+const PATTERN_SCALE: f32 = 2.0;
+/// Side of one repeat of the pattern, in world units.
+const PATTERN_SIZE: f32 = PATTERN_PIXELS as f32 * PATTERN_SCALE;
+
+/// Roughly one pixel in this many is a star.
+const STAR_RARITY: u32 = 90;
+/// Brightness of the space between the stars, before tinting.
+const SKY_LEVEL: f32 = 0.2;
+
+/// Behind the tiles, which sit at z = 0.
+const BACKDROP_Z: f32 = -1.0;
+
+pub struct BackdropPlugin;
+
+impl Plugin for BackdropPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, create_backdrop_image)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (spawn_backdrop, scroll_backdrop)
+                    .chain()
+                    .after(spawn_tilemap)
+                    .run_if(not(carry_over_arena)),
+            )
+            .add_systems(
+                Update,
+                (
+                    scroll_backdrop
+                        .run_if(resource_changed::<MapOffset>.or(resource_changed::<TileOffset>))
+                        .in_set(MovementSystems::ApplyOffsetChanges),
+                    resize_backdrop.run_if(resource_changed::<ViewportTiles>),
+                    tint_backdrop.run_if(resource_exists_and_changed::<FloorPalette>),
+                    hide_backdrop_under_fog.run_if(resource_changed::<FogOfWar>),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct Backdrop;
+
+/// The star image the backdrop repeats.
+#[derive(Resource)]
+struct BackdropImage(Handle<Image>);
+
+/// Scatters stars over a dim grey square. The stars come from a fixed hash of each pixel
+/// rather than the game's generator, so seeded runs don't depend on whether this ran.
+fn create_backdrop_image(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let sky = (SKY_LEVEL * 255.0) as u8;
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: PATTERN_PIXELS,
+            height: PATTERN_PIXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[sky, sky, sky, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    for y in 0..PATTERN_PIXELS {
+        for x in 0..PATTERN_PIXELS {
+            let hash = pixel_hash(x, y);
+            if hash.is_multiple_of(STAR_RARITY) {
+                // Vary the brightness a little so the field has some depth of its own.
+                let level = 0.6 + 0.4 * ((hash >> 8) % 256) as f32 / 255.0;
+                let _ = image.set_color_at(x, y, Color::srgb(level, level, level));
+            }
+        }
+    }
+    commands.insert_resource(BackdropImage(images.add(image)));
+}
+
+/// A cheap integer hash, mixing the coordinates well enough that the stars don't line up.
+fn pixel_hash(x: u32, y: u32) -> u32 {
+    let mut h = x.wrapping_mul(0x9E37_79B9) ^ y.wrapping_mul(0x85EB_CA6B);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    h
+}
+
+fn spawn_backdrop(
+    mut commands: Commands,
+    image: Res<BackdropImage>,
+    floor_palette: Res<FloorPalette>,
+    fog: Res<FogOfWar>,
+    viewport: Res<ViewportTiles>,
+) {
+    commands.spawn((
+        Sprite {
+            image: image.0.clone(),
+            color: backdrop_tint(&floor_palette),
+            custom_size: Some(backdrop_size(&viewport)),
+            image_mode: SpriteImageMode::Tiled {
+                tile_x: true,
+                tile_y: true,
+                stretch_value: PATTERN_SCALE,
+            },
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, BACKDROP_Z),
+        backdrop_visibility(&fog),
+        Backdrop,
+        ArenaEntity,
+        GameEntity,
+    ));
+}
+
+/// Moves the backdrop by a fraction of how far the map has scrolled, wrapped to within one
+/// repeat of the pattern.
+fn scroll_backdrop(
+    map_offset: Res<MapOffset>,
+    tile_offset: Res<TileOffset>,
+    mut query: Query<&mut Transform, With<Backdrop>>,
+) {
+    let scrolled = map_offset.0.as_vec2() * TILE_SIZE - tile_offset.0;
+    let shift = -(scrolled * PARALLAX_FACTOR).rem_euclid(Vec2::splat(PATTERN_SIZE));
+    for mut transform in &mut query {
+        transform.translation = shift.extend(BACKDROP_Z);
+    }
+}
+
+fn resize_backdrop(viewport: Res<ViewportTiles>, mut query: Query<&mut Sprite, With<Backdrop>>) {
+    for mut sprite in &mut query {
+        sprite.custom_size = Some(backdrop_size(&viewport));
+    }
+}
+
+/// The rendered area with one full repeat of slack on every side, so shifting by up to a
+/// repeat never shows an edge.
+fn backdrop_size(viewport: &ViewportTiles) -> Vec2 {
+    viewport.size().as_vec2() * TILE_SIZE + Vec2::splat(2.0 * PATTERN_SIZE)
+}
+
+fn tint_backdrop(floor_palette: Res<FloorPalette>, mut query: Query<&mut Sprite, With<Backdrop>>) {
+    for mut sprite in &mut query {
+        sprite.color = backdrop_tint(&floor_palette);
+    }
+}
+
+fn hide_backdrop_under_fog(fog: Res<FogOfWar>, mut query: Query<&mut Visibility, With<Backdrop>>) {
+    for mut visibility in &mut query {
+        visibility.set_if_neq(backdrop_visibility(&fog));
+    }
+}
+
+/// The first floor color, so the backdrop always sits with the floor around it.
+fn backdrop_tint(floor_palette: &FloorPalette) -> Color {
+    floor_palette
+        .pairs
+        .first()
+        .map_or(Color::BLACK, |&(color, _)| color)
+}
+
+fn backdrop_visibility(fog: &FogOfWar) -> Visibility {
+    if fog.0 {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
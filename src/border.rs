@@ -1,10 +1,39 @@
 // border.rs
-use crate::components::{GameEntity, GameState};
-use crate::resolution::Resolution;
-use crate::tilemap::{RENDERED_HEIGHT, RENDERED_WIDTH, TILE_SIZE};
+
+//! The blocks that frame the tilemap. Their geometry follows the camera, and their colour
+//! follows a `BorderMood`: red pulses while the player is hurt or an enemy is close, gold pulses
+//! when a round is nearly cleared, and a white flash marks a victory.
+
+use crate::components::{GameEntity, GameMode, GameState, Health};
+use crate::enemy::Enemy;
+use crate::grid_movement::GridMover;
+use crate::player::Player;
+use crate::score::EnemyCount;
+use crate::screen_flash::FlashSettings;
+use crate::tilemap::{ViewportTiles, TILE_SIZE};
 use bevy::prelude::*;
 use bevy::window::PrimaryWindow;
 
+/// The resting border colour.
+const BORDER_COLOR: Srgba = Srgba::rgb(0.1, 0.1, 0.1);
+const DANGER_COLOR: Srgba = Srgba::rgb(0.7, 0.05, 0.05);
+const NEARLY_CLEAR_COLOR: Srgba = Srgba::rgb(0.85, 0.65, 0.1);
+
+/// An enemy within this many tiles of the player puts the border on alert.
+const DANGER_RADIUS: f32 = 3.0;
+
+/// Rounds with this many enemies left, or fewer, pulse gold.
+const NEARLY_CLEAR_ENEMIES: u32 = 5;
+
+/// Pulses per second.
+const PULSE_RATE: f32 = 1.5;
+
+/// Seconds for the victory flash to fade.
+const VICTORY_FLASH_TIME: f32 = 0.5;
+
+/// How quickly the border colour catches up with its target; higher is snappier.
+const COLOR_SMOOTHING: f32 = 10.0;
+
 #[derive(Component)]
 enum BorderSide {
     Left,
@@ -17,14 +46,95 @@ pub struct BorderPlugin;
 
 impl Plugin for BorderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::Playing),
-            (spawn_borders, update_borders).chain(), // Chain update_borders after spawn_borders
-        )
-        .add_systems(
-            Update,
-            update_borders.run_if(in_state(GameState::Playing).and(resource_changed::<Resolution>)),
-        );
+        app.init_resource::<BorderMood>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (spawn_borders, update_borders).chain(), // Chain update_borders after spawn_borders
+            )
+            .add_systems(OnEnter(GameState::Victory), flash_border_on_victory)
+            .add_systems(
+                Update,
+                (
+                    // Every frame, since the camera eases between zoom levels and its view is
+                    // only recomputed after this runs.
+                    update_borders.run_if(in_state(GameState::Playing)),
+                    update_border_mood.run_if(in_state(GameState::Playing)),
+                    // The borders stay up through the victory screen, so this runs in any state.
+                    update_border_colors.after(update_border_mood),
+                ),
+            );
+    }
+}
+
+/// What the border colour should be saying. Written by `update_border_mood` and the victory
+/// flash, read by `update_border_colors`.
+#[derive(Resource, Default)]
+pub struct BorderMood {
+    /// The player is hurt or an enemy is within `DANGER_RADIUS`.
+    pub danger: bool,
+    /// A winnable round is down to its last few enemies.
+    pub nearly_clear: bool,
+    /// Strength of the white victory flash, from 1.0 down to 0.0.
+    pub flash: f32,
+}
+
+fn update_border_mood(
+    mut mood: ResMut<BorderMood>,
+    mode: Res<GameMode>,
+    enemy_count: Res<EnemyCount>,
+    player: Query<(&GridMover, &Health), With<Player>>,
+    enemies: Query<&GridMover, With<Enemy>>,
+) {
+    let danger = player.single().is_ok_and(|(player_mover, health)| {
+        health.current < health.max
+            || enemies.iter().any(|enemy| {
+                (enemy.grid_pos - player_mover.grid_pos).as_vec2().length() <= DANGER_RADIUS
+            })
+    });
+    let nearly_clear = *mode != GameMode::Endless
+        && enemy_count.value > 0
+        && enemy_count.value <= NEARLY_CLEAR_ENEMIES;
+    // Only written on change, so nothing downstream sees spurious change detection.
+    if mood.danger != danger || mood.nearly_clear != nearly_clear {
+        mood.danger = danger;
+        mood.nearly_clear = nearly_clear;
+    }
+}
+
+fn flash_border_on_victory(mut mood: ResMut<BorderMood>) {
+    mood.danger = false;
+    mood.nearly_clear = false;
+    mood.flash = 1.0;
+}
+
+/// Eases the border towards the colour its mood calls for. Pulses and flashes are capped by the
+/// flash accessibility setting.
+fn update_border_colors(
+    mut mood: ResMut<BorderMood>,
+    settings: Res<FlashSettings>,
+    time: Res<Time<Real>>,
+    mut borders: Query<&mut Sprite, With<BorderSide>>,
+) {
+    if mood.flash > 0.0 {
+        mood.flash = (mood.flash - time.delta_secs() / VICTORY_FLASH_TIME).max(0.0);
+    }
+    let cap = settings.max_opacity;
+    let pulse = 0.5 + 0.5 * (time.elapsed_secs() * PULSE_RATE * std::f32::consts::TAU).sin();
+    let mut target = if mood.danger {
+        BORDER_COLOR.mix(&DANGER_COLOR, pulse * cap)
+    } else if mood.nearly_clear {
+        BORDER_COLOR.mix(&NEARLY_CLEAR_COLOR, pulse * cap)
+    } else {
+        BORDER_COLOR
+    };
+    if mood.flash > 0.0 {
+        target = target.mix(&Srgba::WHITE, mood.flash * cap);
+    }
+
+    let blend = 1.0 - (-time.delta_secs() * COLOR_SMOOTHING).exp();
+    for mut sprite in &mut borders {
+        let current = sprite.color.to_srgba();
+        sprite.color = current.mix(&target, blend).into();
     }
 }
 
@@ -34,7 +144,7 @@ fn spawn_borders(mut commands: Commands) {
 
     commands.spawn((
         Sprite {
-            color: Color::srgb(0.1, 0.1, 0.1),
+            color: BORDER_COLOR.into(),
             custom_size: Some(Vec2::ZERO),
             ..default()
         },
@@ -44,7 +154,7 @@ fn spawn_borders(mut commands: Commands) {
     ));
     commands.spawn((
         Sprite {
-            color: Color::srgb(0.1, 0.1, 0.1),
+            color: BORDER_COLOR.into(),
             custom_size: Some(Vec2::ZERO),
             ..default()
         },
@@ -54,7 +164,7 @@ fn spawn_borders(mut commands: Commands) {
     ));
     commands.spawn((
         Sprite {
-            color: Color::srgb(0.1, 0.1, 0.1),
+            color: BORDER_COLOR.into(),
             custom_size: Some(Vec2::ZERO),
             ..default()
         },
@@ -64,7 +174,7 @@ fn spawn_borders(mut commands: Commands) {
     ));
     commands.spawn((
         Sprite {
-            color: Color::srgb(0.1, 0.1, 0.1),
+            color: BORDER_COLOR.into(),
             custom_size: Some(Vec2::ZERO),
             ..default()
         },
@@ -77,6 +187,7 @@ fn spawn_borders(mut commands: Commands) {
 fn update_borders(
     windows: Query<&Window, With<PrimaryWindow>>,
     cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    viewport: Res<ViewportTiles>,
     mut borders: Query<(&BorderSide, &mut Transform, &mut Sprite)>,
 ) {
     let Ok(_window) = windows.single() else {
@@ -99,8 +210,10 @@ fn update_borders(
     let world_bottom = ndc_to_world(Vec3::new(0.0, -1.0, 0.0)).y;
     let world_top = ndc_to_world(Vec3::new(0.0, 1.0, 0.0)).y;
 
-    let tilemap_half_w = (RENDERED_WIDTH as f32 / 2.0) * TILE_SIZE;
-    let tilemap_half_h = (RENDERED_HEIGHT as f32 / 2.0) * TILE_SIZE;
+    let Vec2 {
+        x: tilemap_half_w,
+        y: tilemap_half_h,
+    } = viewport.half_world_size();
 
     let tilemap_left = -tilemap_half_w;
     let tilemap_right = tilemap_half_w - TILE_SIZE;
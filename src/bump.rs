@@ -0,0 +1,128 @@
+// bump.rs
+
+//! Bump-and-stun contact, the gentler alternative to contact killing both parties.
+//!
+//! With `ContactMode::Bump`, `resolve_damage` turns a melee hit on an enemy into a `Bumped`
+//! event instead of a kill, and the player loses a heart as usual and is bumped too. Each bumped
+//! entity is knocked one tile away from the other, onto a free tile if there is one, and both
+//! are stunned for `STUN_TIME`. A stunned enemy can't be bumped again and the player is
+//! invulnerable after the hit, so the two can't keep colliding while they recover.
+
+use bevy::prelude::*;
+
+use crate::collider::resolve_damage;
+use crate::components::GameState;
+use crate::enemy::EnemyMovementAI;
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{is_wall, GridMover, IntendedDirection, MovementSystems};
+use crate::grid_reservation::GridReservations;
+use crate::map::MapData;
+
+/// Seconds both parties are stunned after a bump.
+const STUN_TIME: f32 = 1.0;
+
+pub struct BumpPlugin;
+
+impl Plugin for BumpPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Bumped>().add_systems(
+            Update,
+            (
+                hold_stunned
+                    .after(MovementSystems::Input)
+                    .after(EnemyMovementAI)
+                    .before(MovementSystems::UpdateMover),
+                knock_back_bumped.after(resolve_damage),
+            )
+                .run_if(in_state(GameState::Playing).and(simulation_running)),
+        );
+    }
+}
+
+/// Sent by `resolve_damage` when a melee hit bumps `entity` away from `from`, in world space.
+#[derive(Event)]
+pub struct Bumped {
+    pub entity: Entity,
+    pub from: Vec3,
+}
+
+/// Keeps an entity from moving (or, for the player, shooting) until the timer finishes.
+#[derive(Component)]
+pub struct Stunned(pub Timer);
+
+/// Cancels the stunned entities' movement requests, and lets them go once the stun is over.
+fn hold_stunned(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Stunned, &mut IntendedDirection)>,
+) {
+    for (entity, mut stunned, mut intended) in &mut query {
+        if stunned.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Stunned>();
+        } else {
+            intended.0 = IVec2::ZERO;
+        }
+    }
+}
+
+/// Knocks each bumped entity one tile away and stuns it.
+///
+/// The tile straight away from the other party is tried first, then the others that don't
+/// lead back towards it. Walls and tiles reserved by anyone else are skipped; when all of them
+/// are blocked, the entity is only stunned.
+fn knock_back_bumped(
+    mut commands: Commands,
+    mut events: EventReader<Bumped>,
+    mut query: Query<(&mut GridMover, &mut IntendedDirection, &Transform)>,
+    mut reservations: ResMut<GridReservations>,
+    map_data: Res<MapData>,
+) {
+    for bump in events.read() {
+        let Ok((mut mover, mut intended, transform)) = query.get_mut(bump.entity) else {
+            continue;
+        };
+        commands
+            .entity(bump.entity)
+            .insert(Stunned(Timer::from_seconds(STUN_TIME, TimerMode::Once)));
+        intended.0 = IVec2::ZERO;
+
+        // Settle on whichever tile the entity is closer to, giving up the other one.
+        if mover.direction != IVec2::ZERO {
+            let (kept, released) = if mover.progress >= 0.5 {
+                (mover.grid_pos + mover.direction, mover.grid_pos)
+            } else {
+                (mover.grid_pos, mover.grid_pos + mover.direction)
+            };
+            if reservations.0.get(&released) == Some(&bump.entity) {
+                reservations.0.remove(&released);
+            }
+            mover.grid_pos = kept;
+            mover.direction = IVec2::ZERO;
+            mover.progress = 0.0;
+        }
+
+        let away = (transform.translation - bump.from).truncate();
+        let mut candidates: Vec<IVec2> = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| IVec2::new(x, y)))
+            .filter(|dir| *dir != IVec2::ZERO && dir.as_vec2().dot(away) > 0.0)
+            .collect();
+        candidates.sort_by(|a, b| {
+            let score = |dir: &IVec2| dir.as_vec2().normalize().dot(away);
+            score(b).total_cmp(&score(a))
+        });
+        let free = candidates.into_iter().find(|dir| {
+            let tile = mover.grid_pos + *dir;
+            !is_wall(tile, &map_data)
+                && reservations
+                    .0
+                    .get(&tile)
+                    .is_none_or(|&occupant| occupant == bump.entity)
+        });
+        // Slides over like a normal move; the stun stops it there.
+        if let Some(dir) = free {
+            mover.direction = dir;
+            mover.progress = 0.0;
+            reservations.0.insert(mover.grid_pos + dir, bump.entity);
+        }
+    }
+}
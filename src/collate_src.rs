@@ -1,20 +1,54 @@
 // src/collate_src.rs
 use bevy::prelude::*;
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::Path;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
-/// Plugin for collating all .rs files in src/ into a single assets/collated_src.txt file
+/// Environment variable that turns collation on in release builds.
+const COLLATE_ENV: &str = "COLLATE_SRC";
+
+/// Plugin for collating all .rs files under src/ (plus Cargo.toml) into a single text file,
+/// for pasting into an LLM as context.
+///
+/// Only registered in debug builds or when `COLLATE_SRC=1` is set; see `collation_enabled`.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CollateSrcPlugin;
 
 impl Plugin for CollateSrcPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CustomPrompt>()
+            .init_resource::<CollateConfig>()
             .add_systems(PreStartup, collate_source_files);
     }
 }
 
+/// Whether `CollateSrcPlugin` should be added: always in debug builds, and in release builds
+/// only when `COLLATE_SRC=1`. Never on the web, where there are no source files to read.
+pub fn collation_enabled() -> bool {
+    if cfg!(target_arch = "wasm32") {
+        return false;
+    }
+    cfg!(debug_assertions) || std::env::var(COLLATE_ENV).is_ok_and(|value| value == "1")
+}
+
+/// Where the collated file is written and what goes into it.
+#[derive(Resource)]
+pub struct CollateConfig {
+    /// The collated file, relative to the working directory.
+    pub output_path: PathBuf,
+    /// Source files to leave out, as paths relative to `src/`.
+    pub exclude: Vec<String>,
+}
+
+impl Default for CollateConfig {
+    fn default() -> Self {
+        CollateConfig {
+            output_path: PathBuf::from("assets/collated_src.txt"),
+            exclude: vec!["collate_src.rs".to_string()],
+        }
+    }
+}
+
 /// Resource to hold the custom instruction prompt
 #[derive(Resource)]
 pub struct CustomPrompt(String);
@@ -41,50 +75,90 @@ After outputting all files, provide a concise summary (outside of codeboxes) tha
     }
 }
 
-/// System to read .rs files from src/ and write them to assets/collated_src.txt
-fn collate_source_files(prompt: Res<CustomPrompt>) {
-    // Ensure the assets directory exists
-    let assets_dir = Path::new("assets");
-    if !assets_dir.exists() {
-        fs::create_dir_all(assets_dir).expect("Failed to create assets directory");
+/// Writes the collated file at startup. Failures are logged rather than fatal, so a read-only
+/// working directory doesn't stop the game.
+fn collate_source_files(prompt: Res<CustomPrompt>, config: Res<CollateConfig>) {
+    match write_collation(&prompt.0, &config) {
+        Ok(files) => info!(
+            "Collated {} files into {}",
+            files,
+            config.output_path.display()
+        ),
+        Err(err) => warn!(
+            "Couldn't collate source into {}: {}",
+            config.output_path.display(),
+            err
+        ),
     }
+}
 
-    // Open the output file
-    let mut output_file =
-        File::create("assets/collated_src.txt").expect("Failed to create collated_src.txt");
+/// Collects every .rs file under `dir`, recursively, as paths relative to `root`.
+fn collect_sources(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_sources(root, &path, files)?;
+        } else if path.extension().and_then(|s| s.to_str()) == Some("rs") {
+            if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_path_buf());
+            }
+        }
+    }
+    Ok(())
+}
 
-    // Read all files in src/
+/// Builds the collated text and writes it out, returning the number of files included.
+fn write_collation(prompt: &str, config: &CollateConfig) -> io::Result<usize> {
     let src_dir = Path::new("src");
+    let mut sources = Vec::new();
     if src_dir.is_dir() {
-        for entry in fs::read_dir(src_dir).expect("Failed to read src directory") {
-            let entry = entry.expect("Failed to read directory entry");
-            let path = entry.path();
-
-            // Process only .rs files, excluding collate_src.rs
-            if path.extension().and_then(|s| s.to_str()) == Some("rs") {
-                if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                    if file_name != "collate_src.rs" {
-                        // Read file contents
-                        let contents = fs::read_to_string(&path)
-                            .expect(&format!("Failed to read file: {}", file_name));
-
-                        // Write tagged contents to output file
-                        writeln!(output_file, "<{}>", file_name)
-                            .expect("Failed to write file name tag");
-                        write!(output_file, "{}", contents).expect("Failed to write file contents");
-                        writeln!(output_file, "</{}>\n", file_name)
-                            .expect("Failed to write closing tag");
-                    }
-                }
+        collect_sources(src_dir, src_dir, &mut sources)?;
+    }
+    // Sorted so the output is the same on every platform.
+    sources.sort();
+
+    // Tagged with forward slashes whatever the platform.
+    let tag = |path: &Path| -> String {
+        path.components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    let mut files: Vec<(String, PathBuf)> = sources
+        .into_iter()
+        .map(|path| (tag(&path), src_dir.join(path)))
+        .filter(|(name, _)| !config.exclude.contains(name))
+        .collect();
+    files.push(("Cargo.toml".to_string(), PathBuf::from("Cargo.toml")));
+
+    let mut output = String::new();
+    let mut included = 0;
+    for (name, path) in &files {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                output.push_str(&format!("<{}>\n{}</{}>\n\n", name, contents, name));
+                included += 1;
             }
+            Err(err) => warn!(
+                "Skipping {} in the collated source: {}",
+                path.display(),
+                err
+            ),
         }
     }
 
     // Append the custom prompt
-    writeln!(output_file, "<task rules>").expect("Failed to write prompt opening tag");
-    write!(output_file, "{}", prompt.0).expect("Failed to write prompt contents");
-    writeln!(output_file, "\n</task rules>").expect("Failed to write prompt closing tag");
-    writeln!(output_file, "<task>").expect("Failed to write prompt opening task tag");
-    writeln!(output_file, " ").expect("Failed to write instructions tag");
-    writeln!(output_file, "</task>").expect("Failed to write closing task tag");
+    output.push_str(&format!("<task rules>\n{}\n</task rules>\n", prompt));
+    output.push_str("<task>\n \n</task>\n");
+    // A rough guide for context packing: about four characters per token.
+    output.push_str(&format!(
+        "<!-- roughly {} tokens -->\n",
+        output.chars().count() / 4
+    ));
+
+    if let Some(parent) = config.output_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::File::create(&config.output_path)?.write_all(output.as_bytes())?;
+    Ok(included)
 }
@@ -1,16 +1,111 @@
 // collider.rs
-use crate::components::{EnemyDied, GameState, PlayerDied};
+use crate::bump::{Bumped, Stunned};
+use crate::components::{
+    Dying, EnemyDied, EnemyKilled, Faction, FactionRules, GameState, Health, KillSource, PlayerDied,
+};
+use crate::difficulty::ContactMode;
 use crate::enemy::Enemy;
-use crate::grid_movement::GridMover;
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{GridMover, MovementSystems, PreviousTranslation};
 use crate::grid_reservation::GridReservations;
-use crate::player::Player;
-use crate::projectile::{Bouncable, Projectile};
+use crate::player::{Invulnerable, Player};
+use crate::profiler::SystemTimings;
+use crate::projectile::{handle_projectile_collisions, Bouncable, Projectile};
+use crate::spawner::{Spawner, SpawnerDestroyed};
 use bevy::prelude::*;
+use std::collections::HashMap;
 
-/// Component representing a collider with a size for AABB collision detection.
-#[derive(Component)]
+/// The geometric shape used by a `Collider` for overlap tests.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColliderShape {
+    /// An axis-aligned box spanning the collider's `size`.
+    Aabb,
+    /// A circle of the given radius, for visually round entities.
+    Circle { radius: f32 },
+}
+
+/// Component representing a collider for collision detection.
+#[derive(Component, Clone, Copy)]
 pub struct Collider {
+    /// The bounding size of the collider. Used directly by `Aabb` shapes.
     pub size: Vec2,
+    /// Offset of the collider's center from the entity's transform.
+    pub offset: Vec2,
+    /// Multiplier applied to the shape for player-enemy adjacency (hurtbox) checks.
+    pub hurtbox_scale: f32,
+    pub shape: ColliderShape,
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Collider {
+            size: Vec2::ZERO,
+            offset: Vec2::ZERO,
+            hurtbox_scale: 1.0,
+            shape: ColliderShape::Aabb,
+        }
+    }
+}
+
+impl Collider {
+    /// Returns the world-space center of the collider for an entity at `translation`.
+    pub fn center(&self, translation: Vec3) -> Vec2 {
+        translation.xy() + self.offset
+    }
+
+    /// Returns a copy of this collider scaled up to its hurtbox.
+    pub fn hurtbox(&self) -> Collider {
+        let scale = self.hurtbox_scale;
+        Collider {
+            size: self.size * scale,
+            hurtbox_scale: 1.0,
+            shape: match self.shape {
+                ColliderShape::Circle { radius } => ColliderShape::Circle {
+                    radius: radius * scale,
+                },
+                ColliderShape::Aabb => ColliderShape::Aabb,
+            },
+            ..*self
+        }
+    }
+
+    /// Checks whether this collider, centered at `pos`, overlaps `other` centered at `other_pos`.
+    pub fn overlaps(&self, pos: Vec2, other: &Collider, other_pos: Vec2) -> bool {
+        match (self.shape, other.shape) {
+            (ColliderShape::Aabb, ColliderShape::Aabb) => {
+                aabb_overlap(pos, self.size, other_pos, other.size)
+            }
+            (ColliderShape::Circle { radius: r1 }, ColliderShape::Circle { radius: r2 }) => {
+                circle_overlap(pos, r1, other_pos, r2)
+            }
+            (ColliderShape::Circle { radius }, ColliderShape::Aabb) => {
+                circle_aabb_overlap(pos, radius, other_pos, other.size)
+            }
+            (ColliderShape::Aabb, ColliderShape::Circle { radius }) => {
+                circle_aabb_overlap(other_pos, radius, pos, self.size)
+            }
+        }
+    }
+
+    /// Swept version of `overlaps`. `start` is this collider's center relative to `other` at
+    /// the beginning of the frame, and `delta` is the relative movement over the frame.
+    pub fn swept_overlaps(&self, other: &Collider, start: Vec2, delta: Vec2) -> bool {
+        match (self.shape, other.shape) {
+            (ColliderShape::Aabb, ColliderShape::Aabb) => {
+                swept_aabb_overlap(start, delta, (self.size + other.size) / 2.0)
+            }
+            (ColliderShape::Circle { radius: r1 }, ColliderShape::Circle { radius: r2 }) => {
+                segment_point_distance(start, delta) < r1 + r2
+            }
+            // The box is symmetric about its center, so which of the two is moving doesn't matter.
+            (ColliderShape::Circle { radius }, ColliderShape::Aabb) => {
+                segment_aabb_distance(start, delta, other.size / 2.0) < radius
+            }
+            (ColliderShape::Aabb, ColliderShape::Circle { radius }) => {
+                segment_aabb_distance(start, delta, self.size / 2.0) < radius
+            }
+        }
+    }
 }
 
 /// Event triggered when a projectile collides with another entity.
@@ -20,6 +115,30 @@ pub struct ProjectileCollision {
     pub victim: Entity,
 }
 
+/// Event describing damage dealt to `victim`.
+///
+/// Every damage path (projectiles, melee contact, explosions) writes these instead of
+/// despawning directly; `resolve_damage` adds up each victim's hits so it dies at most once per
+/// frame. Any nonzero amount is lethal to victims without `Health`.
+#[derive(Event)]
+pub struct DamageEvent {
+    pub victim: Entity,
+    pub amount: u32,
+    pub source: KillSource,
+    /// Where the damage came from, in world space.
+    pub position: Vec3,
+}
+
+/// Tints a sprite white for a moment after it survives a hit, then puts its colour back.
+#[derive(Component)]
+pub struct HitFlash {
+    timer: Timer,
+    color: Color,
+}
+
+/// How long a hit flash lasts, in seconds.
+const HIT_FLASH_TIME: f32 = 0.1;
+
 /// The eight adjacent directions (cardinal and diagonal) for adjacency checks.
 const DIRECTIONS: [IVec2; 8] = [
     IVec2::new(0, 1),   // Up
@@ -32,35 +151,79 @@ const DIRECTIONS: [IVec2; 8] = [
     IVec2::new(1, -1),  // Down-Right
 ];
 
-/// Expansion factor for player and enemy colliders during AABB checks.
-const COLLIDER_EXPANSION_FACTOR: f32 = 2.25;
-
 pub struct ColliderPlugin;
 
 impl Plugin for ColliderPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ProjectileCollision>().add_systems(
-            Update,
-            (
-                check_projectile_collisions,
-                check_player_enemy_adjacency
-                    .after(crate::grid_movement::MovementSystems::UpdateMover),
+        app.add_event::<ProjectileCollision>()
+            .add_event::<DamageEvent>()
+            .add_systems(
+                Update,
+                (
+                    // Swept checks need this frame's positions, so run after they are written.
+                    check_projectile_collisions.after(MovementSystems::UpdatePosition),
+                    check_player_enemy_adjacency.after(MovementSystems::UpdateMover),
+                    // All damage for the frame is in by now.
+                    resolve_damage
+                        .after(check_player_enemy_adjacency)
+                        .after(handle_projectile_collisions),
+                )
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
             )
-                .run_if(in_state(GameState::Playing)),
-        );
+            .add_systems(
+                Update,
+                update_hit_flashes.run_if(in_state(GameState::Playing)),
+            )
+            // Entities marked as dying are removed once every system has seen the marker.
+            .add_systems(PostUpdate, despawn_dying);
     }
 }
 
 /// Checks for collisions between projectiles and other entities using the grid reservation system.
 /// This is a highly efficient, targeted collision detection method.
-fn check_projectile_collisions(
+///
+/// The narrow phase is swept: both entities' movement over the frame is taken into account,
+/// so a fast projectile and a fast enemy crossing head-on cannot pass through each other
+/// between frames.
+#[allow(clippy::type_complexity)]
+pub fn check_projectile_collisions(
     mut events: EventWriter<ProjectileCollision>,
     reservations: Res<GridReservations>,
-    projectiles: Query<(Entity, &Transform, &Collider, &GridMover, &Bouncable), With<Projectile>>,
-    collidables: Query<(&Transform, &Collider)>,
-    player_query: Query<(), With<Player>>,
+    projectiles: Query<
+        (
+            Entity,
+            &Transform,
+            &Collider,
+            &GridMover,
+            &Bouncable,
+            &Faction,
+            Option<Ref<PreviousTranslation>>,
+        ),
+        (With<Projectile>, Without<Dying>),
+    >,
+    collidables: Query<
+        (
+            &Transform,
+            &Collider,
+            &Faction,
+            Option<Ref<PreviousTranslation>>,
+        ),
+        (Without<Projectile>, Without<Dying>),
+    >,
+    rules: Res<FactionRules>,
+    timings: Res<SystemTimings>,
 ) {
-    for (proj_entity, proj_transform, proj_collider, proj_mover, bouncable) in &projectiles {
+    let _span = timings.span("check_projectile_collisions");
+    for (
+        proj_entity,
+        proj_transform,
+        proj_collider,
+        proj_mover,
+        bouncable,
+        &proj_faction,
+        proj_prev,
+    ) in &projectiles
+    {
         // A projectile is only a threat if it's actively moving towards a new tile.
         if proj_mover.direction == IVec2::ZERO {
             continue;
@@ -75,20 +238,37 @@ fn check_projectile_collisions(
             // --- Narrow Phase ---
             // We have a potential collision. Get the victim's components.
             // The .get() method on a Query is highly optimized.
-            if let Ok((victim_transform, victim_collider)) = collidables.get(victim_entity) {
-                // Check if the victim is the player and if the projectile hasn't bounced at least once.
-                let is_player = player_query.get(victim_entity).is_ok();
+            if let Ok((victim_transform, victim_collider, &victim_faction, victim_prev)) =
+                collidables.get(victim_entity)
+            {
+                // A fresh shot only hits its faction's enemies. Once it has bounced it can hit
+                // its own side too, its shooter included. Shots never hit each other.
                 let bounced = bouncable.initial.saturating_sub(bouncable.remaining);
-                if is_player && bounced < 1 {
-                    continue; // Skip collision with player if projectile hasn't bounced.
+                let valid_target = if bounced < 1 {
+                    rules.hostile(proj_faction, victim_faction)
+                } else {
+                    rules.ricochet_hostile(proj_faction, victim_faction)
+                };
+                if !valid_target {
+                    continue;
                 }
 
-                // Perform the precise AABB check.
-                if aabb_overlap(
-                    proj_transform.translation.xy(),
-                    proj_collider.size,
-                    victim_transform.translation.xy(),
-                    victim_collider.size,
+                let proj_now = proj_collider.center(proj_transform.translation);
+                let victim_now = victim_collider.center(victim_transform.translation);
+
+                // Where each entity was at the start of the frame. Newly spawned entities have no
+                // meaningful history yet, so they are treated as stationary.
+                let previous = |prev: Option<Ref<PreviousTranslation>>, now: Vec2, offset: Vec2| {
+                    prev.filter(|p| !p.is_added()).map_or(now, |p| p.0 + offset)
+                };
+                let proj_start = previous(proj_prev, proj_now, proj_collider.offset);
+                let victim_start = previous(victim_prev, victim_now, victim_collider.offset);
+
+                // Perform the swept check in the victim's frame of reference.
+                if proj_collider.swept_overlaps(
+                    victim_collider,
+                    proj_start - victim_start,
+                    (proj_now - proj_start) - (victim_now - victim_start),
                 ) {
                     // Collision confirmed. Write the event.
                     events.write(ProjectileCollision {
@@ -101,43 +281,53 @@ fn check_projectile_collisions(
     }
 }
 
-/// Checks for AABB overlap between the player and enemies in adjacent grid cells with expanded collider sizes.
-/// Triggers player and enemy death if an overlap is detected.
+/// Checks for AABB overlap between the player and hostile entities in adjacent grid cells using their hurtboxes.
+/// Reports melee damage to both the player and the enemy if an overlap is detected. A player
+/// with i-frames passes through enemies unharmed, and harms none of them either.
+#[allow(clippy::type_complexity)]
 fn check_player_enemy_adjacency(
-    mut commands: Commands,
-    mut player_died_events: EventWriter<PlayerDied>,
-    mut enemy_died_events: EventWriter<EnemyDied>,
-    player_query: Query<(Entity, &GridMover, &Transform, &Collider), With<Player>>,
-    enemy_query: Query<(Entity, &Transform, &Collider), With<Enemy>>,
+    mut damage_events: EventWriter<DamageEvent>,
+    player_query: Query<
+        (Entity, &GridMover, &Transform, &Collider, &Faction),
+        (With<Player>, Without<Invulnerable>, Without<Dying>),
+    >,
+    other_query: Query<(Entity, &Transform, &Collider, &Faction), Without<Dying>>,
     reservations: Res<GridReservations>,
+    rules: Res<FactionRules>,
 ) {
-    if let Ok((player_entity, player_mover, player_transform, player_collider)) =
+    if let Ok((player_entity, player_mover, player_transform, player_collider, &player_faction)) =
         player_query.single()
     {
         // Check each adjacent cell using the constant DIRECTIONS array.
         for &dir in DIRECTIONS.iter() {
             let adjacent_pos = player_mover.grid_pos + dir;
             if let Some(&enemy_entity) = reservations.0.get(&adjacent_pos) {
-                // Confirm the entity is an enemy.
-                if let Ok((enemy_entity, enemy_transform, enemy_collider)) =
-                    enemy_query.get(enemy_entity)
+                // Confirm the entity is hostile to the player.
+                if let Ok((enemy_entity, enemy_transform, enemy_collider, &enemy_faction)) =
+                    other_query.get(enemy_entity)
                 {
-                    // Perform AABB overlap check with expanded collider sizes.
-                    if aabb_overlap(
-                        player_transform.translation.xy(),
-                        player_collider.size * COLLIDER_EXPANSION_FACTOR,
-                        enemy_transform.translation.xy(),
-                        enemy_collider.size * COLLIDER_EXPANSION_FACTOR,
+                    if !rules.hostile(player_faction, enemy_faction) {
+                        continue;
+                    }
+                    // Perform the overlap check with each entity's hurtbox.
+                    if player_collider.hurtbox().overlaps(
+                        player_collider.center(player_transform.translation),
+                        &enemy_collider.hurtbox(),
+                        enemy_collider.center(enemy_transform.translation),
                     ) {
-                        // Collision detected; despawn both and trigger death events.
-                        commands.entity(player_entity).despawn();
-                        commands.entity(enemy_entity).despawn();
-                        player_died_events.write(PlayerDied(player_transform.translation));
-                        enemy_died_events.write(EnemyDied(enemy_transform.translation));
-                        info!(
-                            "Player died due to AABB overlap with enemy at {:?}",
-                            adjacent_pos
-                        );
+                        // Collision detected; both entities die.
+                        damage_events.write(DamageEvent {
+                            victim: player_entity,
+                            amount: 1,
+                            source: KillSource::Melee,
+                            position: enemy_transform.translation,
+                        });
+                        damage_events.write(DamageEvent {
+                            victim: enemy_entity,
+                            amount: 1,
+                            source: KillSource::Melee,
+                            position: player_transform.translation,
+                        });
                         // Break after first collision to avoid multiple death events in one frame.
                         break;
                     }
@@ -147,6 +337,191 @@ fn check_player_enemy_adjacency(
     }
 }
 
+/// Every hit one victim took this frame, added up so they are applied together.
+#[derive(Debug, PartialEq)]
+struct FrameDamage {
+    victim: Entity,
+    /// Damage from every hit except melee hits that bump.
+    amount: u32,
+    /// Damage from melee hits under `ContactMode::Bump`, which bump enemies instead of hurting
+    /// them but still hurt anything else.
+    bump_amount: u32,
+    /// Where the first bumping hit came from.
+    bump_from: Option<Vec3>,
+    /// Source and position of the first hit that did damage, credited with the kill.
+    source: KillSource,
+    position: Vec3,
+}
+
+/// Adds up this frame's hits per victim, in the order the victims were first hit.
+fn sum_damage<'a>(
+    hits: impl IntoIterator<Item = &'a DamageEvent>,
+    melee_bumps: bool,
+) -> Vec<FrameDamage> {
+    let mut totals: Vec<FrameDamage> = Vec::new();
+    let mut index: HashMap<Entity, usize> = HashMap::new();
+    for hit in hits {
+        if hit.amount == 0 {
+            continue;
+        }
+        let i = *index.entry(hit.victim).or_insert_with(|| {
+            totals.push(FrameDamage {
+                victim: hit.victim,
+                amount: 0,
+                bump_amount: 0,
+                bump_from: None,
+                source: hit.source,
+                position: hit.position,
+            });
+            totals.len() - 1
+        });
+        let total = &mut totals[i];
+        if melee_bumps && hit.source == KillSource::Melee {
+            total.bump_amount += hit.amount;
+            total.bump_from.get_or_insert(hit.position);
+        } else {
+            if total.amount == 0 {
+                // A bump alone doesn't get the credit over a hit that does damage.
+                total.source = hit.source;
+                total.position = hit.position;
+            }
+            total.amount += hit.amount;
+        }
+    }
+    totals
+}
+
+/// Applies all damage reported this frame, killing each victim exactly once.
+///
+/// Every hit on a victim is added up first. A victim with `Health` then loses the total at once
+/// and only dies if that takes it to zero; surviving flashes it, and a player is made briefly
+/// `Invulnerable` as well. Any damage is lethal to victims without `Health`.
+///
+/// Victims are marked `Dying` rather than despawned immediately, so any system that runs later
+/// in the frame can tell they are already dead. The matching `PlayerDied`/`EnemyDied` or
+/// `SpawnerDestroyed` event is written here and nowhere else.
+///
+/// With `ContactMode::Bump`, melee hits don't hurt enemies: they are bumped away instead, unless
+/// already stunned, and a player who survives the hit is bumped too.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn resolve_damage(
+    mut commands: Commands,
+    mut damage_events: EventReader<DamageEvent>,
+    mut player_died_events: EventWriter<PlayerDied>,
+    mut enemy_died_events: EventWriter<EnemyDied>,
+    mut enemy_killed_events: EventWriter<EnemyKilled>,
+    mut bumped_events: EventWriter<Bumped>,
+    mut spawner_destroyed_events: EventWriter<SpawnerDestroyed>,
+    contact: Res<ContactMode>,
+    mut victim_query: Query<
+        (
+            Has<Player>,
+            Has<Enemy>,
+            Has<Spawner>,
+            Has<Stunned>,
+            &Transform,
+            Option<&mut Health>,
+            Option<&Sprite>,
+            Option<&HitFlash>,
+        ),
+        (Without<Dying>, Without<Invulnerable>),
+    >,
+) {
+    for damage in sum_damage(damage_events.read(), *contact == ContactMode::Bump) {
+        let Ok((is_player, is_enemy, is_spawner, is_stunned, transform, health, sprite, flash)) =
+            victim_query.get_mut(damage.victim)
+        else {
+            continue; // Already dying, invulnerable or gone.
+        };
+        let bump_from = damage.bump_from.filter(|_| !(is_enemy && is_stunned));
+        let amount = if is_enemy {
+            damage.amount
+        } else {
+            damage.amount + damage.bump_amount
+        };
+        if amount == 0 {
+            // Only bumped.
+            if let Some(from) = bump_from {
+                bumped_events.write(Bumped {
+                    entity: damage.victim,
+                    from,
+                });
+            }
+            continue;
+        }
+        if let Some(mut health) = health {
+            health.current = health.current.saturating_sub(amount);
+            if health.current > 0 {
+                debug!(
+                    "{:?} took {} damage from {:?} at {}, {} left",
+                    damage.victim, amount, damage.source, damage.position, health.current
+                );
+                let mut victim = commands.entity(damage.victim);
+                if is_player {
+                    victim.insert(Invulnerable::after_hit());
+                }
+                if let Some(sprite) = sprite {
+                    // A sprite still flashing from an earlier hit is white, so keep the colour
+                    // that flash saved and just start it over.
+                    let color = flash.map_or(sprite.color, |flash| flash.color);
+                    victim.insert(HitFlash {
+                        timer: Timer::from_seconds(HIT_FLASH_TIME, TimerMode::Once),
+                        color,
+                    });
+                }
+                if let Some(from) = bump_from {
+                    bumped_events.write(Bumped {
+                        entity: damage.victim,
+                        from,
+                    });
+                }
+                continue;
+            }
+        }
+        let pos = transform.translation;
+        commands.entity(damage.victim).insert(Dying);
+        if is_player {
+            info!("Player died from {:?} at {}", damage.source, pos);
+            player_died_events.write(PlayerDied(pos));
+        } else if is_enemy {
+            enemy_died_events.write(EnemyDied(pos));
+            enemy_killed_events.write(EnemyKilled {
+                position: pos,
+                source: damage.source,
+            });
+        } else if is_spawner {
+            spawner_destroyed_events.write(SpawnerDestroyed {
+                spawner: damage.victim,
+                position: pos,
+            });
+        }
+    }
+}
+
+/// Holds flashing sprites white, restoring their colour once the flash is over.
+fn update_hit_flashes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash, mut sprite) in &mut query {
+        if flash.timer.tick(time.delta()).finished() {
+            sprite.color = flash.color;
+            commands.entity(entity).remove::<HitFlash>();
+        } else {
+            sprite.color = Color::WHITE;
+        }
+    }
+}
+
+/// Despawns all entities whose death was resolved this frame.
+fn despawn_dying(mut commands: Commands, query: Query<Entity, With<Dying>>) {
+    for entity in &query {
+        // Other paths (e.g. a projectile stopping at a wall) may have removed it already.
+        commands.entity(entity).try_despawn();
+    }
+}
+
 /// Checks for overlap between two Axis-Aligned Bounding Boxes.
 pub fn aabb_overlap(pos1: Vec2, size1: Vec2, pos2: Vec2, size2: Vec2) -> bool {
     let half1 = size1 / 2.0;
@@ -158,3 +533,402 @@ pub fn aabb_overlap(pos1: Vec2, size1: Vec2, pos2: Vec2, size2: Vec2) -> bool {
 
     min1.x < max2.x && max1.x > min2.x && min1.y < max2.y && max1.y > min2.y
 }
+
+/// Checks for overlap between two circles.
+pub fn circle_overlap(pos1: Vec2, radius1: f32, pos2: Vec2, radius2: f32) -> bool {
+    let reach = radius1 + radius2;
+    pos1.distance_squared(pos2) < reach * reach
+}
+
+/// Checks for overlap between a circle and an Axis-Aligned Bounding Box.
+/// A circle whose center lies inside the box always overlaps it.
+pub fn circle_aabb_overlap(circle_pos: Vec2, radius: f32, box_pos: Vec2, box_size: Vec2) -> bool {
+    let half = box_size / 2.0;
+    let closest = circle_pos.clamp(box_pos - half, box_pos + half);
+    circle_pos.distance_squared(closest) < radius * radius
+}
+
+/// Returns the shortest distance from the origin to the segment `start..start + delta`.
+pub fn segment_point_distance(start: Vec2, delta: Vec2) -> f32 {
+    let len_sq = delta.length_squared();
+    if len_sq < f32::EPSILON {
+        return start.length();
+    }
+    let t = (-start.dot(delta) / len_sq).clamp(0.0, 1.0);
+    (start + delta * t).length()
+}
+
+/// Returns the shortest distance from the segment `start..start + delta` to an AABB centered on
+/// the origin with the given half extents.
+///
+/// The distance to a convex shape is convex along a line, so a ternary search over the segment
+/// converges on the minimum without needing the full analytic case split.
+pub fn segment_aabb_distance(start: Vec2, delta: Vec2, half_extents: Vec2) -> f32 {
+    let distance_at = |t: f32| {
+        let p = start + delta * t;
+        (p.abs() - half_extents).max(Vec2::ZERO).length()
+    };
+
+    let (mut lo, mut hi) = (0.0_f32, 1.0_f32);
+    for _ in 0..24 {
+        let m1 = lo + (hi - lo) / 3.0;
+        let m2 = hi - (hi - lo) / 3.0;
+        if distance_at(m1) <= distance_at(m2) {
+            hi = m2;
+        } else {
+            lo = m1;
+        }
+    }
+    distance_at((lo + hi) / 2.0)
+}
+
+/// Checks whether a point moving from `start` by `delta` passes through an AABB centered on the
+/// origin with the given half extents.
+///
+/// Used for swept collision: positions are expressed relative to the target, and the half
+/// extents are the Minkowski sum of both colliders, so a segment test is equivalent to testing
+/// the two moving boxes against each other over the whole frame.
+pub fn swept_aabb_overlap(start: Vec2, delta: Vec2, half_extents: Vec2) -> bool {
+    let mut t_min: f32 = 0.0;
+    let mut t_max: f32 = 1.0;
+
+    // Slab test: clip the segment's parameter range against each axis in turn.
+    for axis in 0..2 {
+        let origin = start[axis];
+        let dir = delta[axis];
+        let half = half_extents[axis];
+
+        if dir.abs() < f32::EPSILON {
+            // Not moving on this axis; the segment must already lie inside the slab.
+            if origin <= -half || origin >= half {
+                return false;
+            }
+        } else {
+            let t1 = (-half - origin) / dir;
+            let t2 = (half - origin) / dir;
+            t_min = t_min.max(t1.min(t2));
+            t_max = t_max.min(t1.max(t2));
+            if t_min >= t_max {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn center_adds_the_offset() {
+        let collider = Collider {
+            size: Vec2::splat(10.0),
+            offset: Vec2::new(2.0, -3.0),
+            ..default()
+        };
+        assert_eq!(
+            collider.center(Vec3::new(100.0, 50.0, 7.0)),
+            Vec2::new(102.0, 47.0)
+        );
+    }
+
+    #[test]
+    fn hurtbox_scales_the_shape_but_not_the_offset() {
+        let collider = Collider {
+            size: Vec2::new(10.0, 20.0),
+            offset: Vec2::new(1.0, 2.0),
+            hurtbox_scale: 1.5,
+            ..default()
+        };
+        let hurtbox = collider.hurtbox();
+        assert_eq!(hurtbox.size, Vec2::new(15.0, 30.0));
+        assert_eq!(hurtbox.offset, collider.offset);
+        // Scaling is applied once, not again when the hurtbox is itself scaled.
+        assert_eq!(hurtbox.hurtbox_scale, 1.0);
+    }
+
+    #[test]
+    fn hurtboxes_reach_further_than_the_colliders() {
+        let collider = Collider {
+            size: Vec2::splat(8.0),
+            hurtbox_scale: 1.5,
+            ..default()
+        };
+        let (a, b) = (Vec2::ZERO, Vec2::new(10.0, 0.0));
+        assert!(!collider.overlaps(a, &collider, b));
+        assert!(collider.hurtbox().overlaps(a, &collider.hurtbox(), b));
+    }
+
+    #[test]
+    fn swept_aabb_catches_a_pass_through() {
+        let half = Vec2::splat(5.0);
+        // Starts and ends clear of the box on either side, but crosses it during the frame.
+        assert!(swept_aabb_overlap(
+            Vec2::new(-20.0, 0.0),
+            Vec2::new(40.0, 0.0),
+            half
+        ));
+        assert!(swept_aabb_overlap(
+            Vec2::new(3.0, 20.0),
+            Vec2::new(0.0, -40.0),
+            half
+        ));
+    }
+
+    #[test]
+    fn swept_aabb_misses_a_path_beside_the_box() {
+        let half = Vec2::splat(5.0);
+        assert!(!swept_aabb_overlap(
+            Vec2::new(-20.0, 6.0),
+            Vec2::new(40.0, 0.0),
+            half
+        ));
+        // Stops short of the box.
+        assert!(!swept_aabb_overlap(
+            Vec2::new(-20.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            half
+        ));
+        // Moving away from it.
+        assert!(!swept_aabb_overlap(
+            Vec2::new(-20.0, 0.0),
+            Vec2::new(-10.0, 0.0),
+            half
+        ));
+    }
+
+    #[test]
+    fn swept_aabb_without_movement_is_a_plain_overlap_test() {
+        let half = Vec2::splat(5.0);
+        assert!(swept_aabb_overlap(Vec2::new(4.0, -4.0), Vec2::ZERO, half));
+        assert!(!swept_aabb_overlap(Vec2::new(6.0, 0.0), Vec2::ZERO, half));
+        // Touching edges don't count, as with `aabb_overlap`.
+        assert!(!swept_aabb_overlap(Vec2::new(5.0, 0.0), Vec2::ZERO, half));
+    }
+
+    #[test]
+    fn swept_overlaps_catches_a_head_on_crossing() {
+        let collider = Collider {
+            size: Vec2::splat(8.0),
+            ..default()
+        };
+        // Both moving 30 units towards each other from 40 apart: they swap sides in one frame.
+        let (a_start, a_end) = (Vec2::new(-20.0, 0.0), Vec2::new(10.0, 0.0));
+        let (b_start, b_end) = (Vec2::new(20.0, 0.0), Vec2::new(-10.0, 0.0));
+        assert!(!collider.overlaps(a_end, &collider, b_end));
+        assert!(collider.swept_overlaps(
+            &collider,
+            a_start - b_start,
+            (a_end - a_start) - (b_end - b_start),
+        ));
+    }
+
+    #[test]
+    fn circles_overlap_by_distance() {
+        assert!(circle_overlap(Vec2::ZERO, 3.0, Vec2::new(5.0, 0.0), 3.0));
+        assert!(!circle_overlap(Vec2::ZERO, 3.0, Vec2::new(6.0, 0.0), 3.0));
+        // Diagonal: 4.3 along each axis is over 6 away, though each axis alone is within reach.
+        assert!(!circle_overlap(Vec2::ZERO, 3.0, Vec2::splat(4.3), 3.0));
+    }
+
+    #[test]
+    fn circle_misses_a_box_corner_its_bounds_would_hit() {
+        let box_size = Vec2::splat(10.0);
+        let circle_pos = Vec2::splat(8.0);
+        // The circle's bounding box reaches the box's corner at (5, 5), but the circle doesn't.
+        assert!(aabb_overlap(
+            circle_pos,
+            Vec2::splat(8.0),
+            Vec2::ZERO,
+            box_size
+        ));
+        assert!(!circle_aabb_overlap(circle_pos, 4.0, Vec2::ZERO, box_size));
+        // Straight above the box's edge it does.
+        assert!(circle_aabb_overlap(
+            Vec2::new(0.0, 8.0),
+            4.0,
+            Vec2::ZERO,
+            box_size
+        ));
+        // A centre inside the box always overlaps.
+        assert!(circle_aabb_overlap(Vec2::ONE, 0.1, Vec2::ZERO, box_size));
+    }
+
+    #[test]
+    fn overlaps_is_symmetric_across_shapes() {
+        let circle = Collider {
+            shape: ColliderShape::Circle { radius: 4.0 },
+            ..default()
+        };
+        let square = Collider {
+            size: Vec2::splat(10.0),
+            ..default()
+        };
+        for pos in [Vec2::splat(8.0), Vec2::new(0.0, 8.0), Vec2::new(9.5, 0.0)] {
+            assert_eq!(
+                circle.overlaps(pos, &square, Vec2::ZERO),
+                square.overlaps(Vec2::ZERO, &circle, pos),
+            );
+        }
+    }
+
+    #[test]
+    fn segment_distances() {
+        // A segment passing 3 units from the origin.
+        let (start, delta) = (Vec2::new(-10.0, 3.0), Vec2::new(20.0, 0.0));
+        assert!((segment_point_distance(start, delta) - 3.0).abs() < 1e-4);
+        // A segment that stops short measures from its nearest end.
+        assert!(
+            (segment_point_distance(Vec2::new(-10.0, 0.0), Vec2::new(6.0, 0.0)) - 4.0).abs() < 1e-4
+        );
+        // Degenerate segments are points.
+        assert!((segment_point_distance(Vec2::new(3.0, 4.0), Vec2::ZERO) - 5.0).abs() < 1e-4);
+
+        let half = Vec2::splat(1.0);
+        assert!((segment_aabb_distance(start, delta, half) - 2.0).abs() < 1e-3);
+        assert!(segment_aabb_distance(Vec2::new(-10.0, 0.0), delta, half) < 1e-3);
+    }
+
+    #[test]
+    fn swept_circle_catches_a_pass_through() {
+        let circle = Collider {
+            shape: ColliderShape::Circle { radius: 2.0 },
+            ..default()
+        };
+        let square = Collider {
+            size: Vec2::splat(4.0),
+            ..default()
+        };
+        let delta = Vec2::new(40.0, 0.0);
+        assert!(circle.swept_overlaps(&circle, Vec2::new(-20.0, 3.0), delta));
+        assert!(!circle.swept_overlaps(&circle, Vec2::new(-20.0, 5.0), delta));
+        assert!(circle.swept_overlaps(&square, Vec2::new(-20.0, 3.0), delta));
+        assert!(square.swept_overlaps(&circle, Vec2::new(-20.0, 3.0), delta));
+        assert!(!circle.swept_overlaps(&square, Vec2::new(-20.0, 4.5), delta));
+    }
+
+    /// An app that runs only `resolve_damage`, with the events it reads and writes.
+    fn damage_app() -> App {
+        let mut app = App::new();
+        app.add_event::<DamageEvent>()
+            .add_event::<PlayerDied>()
+            .add_event::<EnemyDied>()
+            .add_event::<EnemyKilled>()
+            .add_event::<Bumped>()
+            .add_event::<SpawnerDestroyed>()
+            .init_resource::<ContactMode>()
+            .add_systems(Update, resolve_damage);
+        app
+    }
+
+    fn hit(victim: Entity, source: KillSource) -> DamageEvent {
+        DamageEvent {
+            victim,
+            amount: 1,
+            source,
+            position: Vec3::ZERO,
+        }
+    }
+
+    fn event_count<E: Event>(app: &App) -> usize {
+        app.world().resource::<Events<E>>().len()
+    }
+
+    #[test]
+    fn a_victim_hit_twice_in_a_frame_dies_once() {
+        let mut app = damage_app();
+        let enemy = app.world_mut().spawn((Enemy, Transform::default())).id();
+        app.world_mut().send_event(hit(enemy, KillSource::Shot));
+        app.world_mut()
+            .send_event(hit(enemy, KillSource::Explosion));
+        app.update();
+        assert!(app.world().get::<Dying>(enemy).is_some());
+        assert_eq!(event_count::<EnemyDied>(&app), 1);
+        assert_eq!(event_count::<EnemyKilled>(&app), 1);
+
+        // Already dying, so a late hit next frame doesn't kill it again.
+        app.world_mut().send_event(hit(enemy, KillSource::Shot));
+        app.update();
+        assert_eq!(event_count::<EnemyDied>(&app), 1);
+    }
+
+    #[test]
+    fn hits_on_different_victims_are_kept_apart() {
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+        let hits = [
+            hit(a, KillSource::Shot),
+            hit(b, KillSource::Melee),
+            hit(a, KillSource::Shot),
+        ];
+        let totals = sum_damage(&hits, false);
+        assert_eq!(totals.len(), 2);
+        // In the order the victims were first hit.
+        assert_eq!((totals[0].victim, totals[0].amount), (a, 2));
+        assert_eq!((totals[1].victim, totals[1].amount), (b, 1));
+    }
+
+    #[test]
+    fn sum_damage_adds_up_and_credits_the_first_damaging_hit() {
+        let victim = Entity::from_raw(1);
+        let mut first = hit(victim, KillSource::Explosion);
+        first.amount = 2;
+        first.position = Vec3::X;
+        let hits = [first, hit(victim, KillSource::Shot)];
+        let totals = sum_damage(&hits, false);
+        assert_eq!(totals.len(), 1);
+        assert_eq!(totals[0].amount, 3);
+        assert_eq!(totals[0].source, KillSource::Explosion);
+        assert_eq!(totals[0].position, Vec3::X);
+    }
+
+    #[test]
+    fn sum_damage_keeps_bumps_apart_and_skips_empty_hits() {
+        let victim = Entity::from_raw(1);
+        let mut nothing = hit(victim, KillSource::Shot);
+        nothing.amount = 0;
+        let mut bump = hit(victim, KillSource::Melee);
+        bump.position = Vec3::Y;
+        let hits = [nothing, bump, hit(victim, KillSource::Shot)];
+
+        let totals = sum_damage(&hits, true);
+        assert_eq!(totals[0].amount, 1);
+        assert_eq!(totals[0].bump_amount, 1);
+        assert_eq!(totals[0].bump_from, Some(Vec3::Y));
+        // The shot did the damage, so it gets the credit over the bump that came first.
+        assert_eq!(totals[0].source, KillSource::Shot);
+
+        // Without bumping, melee is ordinary damage.
+        let totals = sum_damage(&hits, false);
+        assert_eq!((totals[0].amount, totals[0].bump_amount), (2, 0));
+        assert_eq!(totals[0].source, KillSource::Melee);
+    }
+
+    #[test]
+    fn health_takes_the_frame_total_at_once() {
+        let mut app = damage_app();
+        let tough = app
+            .world_mut()
+            .spawn((Enemy, Transform::default(), Health::new(3)))
+            .id();
+        let sprite = Sprite::from_color(Color::BLACK, Vec2::ONE);
+        let weak = app
+            .world_mut()
+            .spawn((Enemy, Transform::default(), Health::new(2), sprite))
+            .id();
+        for _ in 0..3 {
+            app.world_mut().send_event(hit(tough, KillSource::Shot));
+        }
+        app.world_mut().send_event(hit(weak, KillSource::Shot));
+        app.update();
+
+        assert!(app.world().get::<Dying>(tough).is_some());
+        assert_eq!(event_count::<EnemyDied>(&app), 1);
+        assert!(app.world().get::<Dying>(weak).is_none());
+        assert_eq!(app.world().get::<Health>(weak).unwrap().current, 1);
+        // The survivor flashes, and remembers its own colour to go back to.
+        let flash = app.world().get::<HitFlash>(weak).unwrap();
+        assert_eq!(flash.color, Color::BLACK);
+    }
+}
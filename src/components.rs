@@ -1,5 +1,10 @@
 // components.rs
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::frame_step::simulation_running;
+use crate::grid_movement::MovementSystems;
+use crate::tilemap::GridSpace;
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Hash, States, Debug)]
 pub enum GameState {
@@ -8,27 +13,155 @@ pub enum GameState {
     Title,
     Playing,
     Victory,
+    GameOver,
+    /// A brief pause while the current run is torn down for a quick restart.
+    Restarting,
 }
 
 #[derive(Component)]
 pub struct GameEntity;
 
+/// Hit points for entities that can survive a hit. Entities without it die to any damage.
+#[derive(Component)]
+pub struct Health {
+    pub current: u32,
+    pub max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Self {
+        Health { current: max, max }
+    }
+}
+
+/// Marks an entity whose death has been resolved this frame.
+///
+/// Inserted by the death resolution pass; the entity is despawned at the end of the frame.
+/// Collision and damage systems skip entities carrying this marker so a single victim can
+/// never die twice.
+#[derive(Component)]
+pub struct Dying;
+
+/// Despawns the entity when the timer runs out. This is the standard way to make an ephemeral
+/// entity in the game world (particles, score popups, explosions, telegraphs): give it a
+/// `Lifetime` rather than ticking a timer of its own. It ticks in virtual time, so it follows
+/// `GameSpeed` and stops outside `Playing` and while frame stepping holds the simulation. UI
+/// that must keep time regardless, such as toasts, keeps its own real-time timer.
+#[derive(Component)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    pub fn from_seconds(seconds: f32) -> Self {
+        Lifetime(Timer::from_seconds(seconds, TimerMode::Once))
+    }
+}
+
+/// Fades a `Lifetime` entity's sprite to transparent over the last `FADE_OUT_FRACTION` of its
+/// lifetime.
+#[derive(Component)]
+pub struct FadeOut;
+
+/// The share of a lifetime that `FadeOut` spends fading.
+const FADE_OUT_FRACTION: f32 = 0.3;
+
+/// Which side an entity is on, for deciding who can hurt whom. The `Player`/`Enemy` markers
+/// stay for identity-specific logic; targeting questions go through `FactionRules`.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Faction {
+    Players,
+    Enemies,
+    /// Pickups and scenery: nothing targets them and they target nothing.
+    Neutral,
+}
+
+/// Decides which factions are hostile to each other.
+#[derive(Resource, Default)]
+pub struct FactionRules {
+    /// Whether members of the same faction can hurt each other.
+    pub friendly_fire: bool,
+}
+
+impl FactionRules {
+    /// Whether `a` can damage `b`. Neutral entities are never hostile either way.
+    pub fn hostile(&self, a: Faction, b: Faction) -> bool {
+        match (a, b) {
+            (Faction::Neutral, _) | (_, Faction::Neutral) => false,
+            _ if a == b => self.friendly_fire,
+            _ => true,
+        }
+    }
+
+    /// Whether a shot fired by `owner` that has bounced off a wall can damage `b`. A ricochet
+    /// also turns on its own side, so the shooter has to dodge it even without friendly fire.
+    pub fn ricochet_hostile(&self, owner: Faction, b: Faction) -> bool {
+        self.hostile(owner, b) || (owner == b && owner != Faction::Neutral)
+    }
+}
+
+/// Free motion in world space, in pixels per second, for entities that aren't on the grid
+/// (debris, thrown objects). `update_velocity` is the one system that moves these, and it
+/// carries them along when the map scrolls so they stay over the same spot; grid entities move
+/// through `GridMover` instead.
 #[derive(Component)]
 pub struct Velocity {
     pub velocity: Vec2,
 }
 
+/// Slows a `Velocity` down exponentially; the value is the decay rate per second.
 #[derive(Component)]
-pub struct Speed {
-    pub value: f32,
-}
+pub struct Damping(pub f32);
+
+/// Pulls a `Velocity` downwards, in pixels per second squared.
+#[derive(Component)]
+pub struct Gravity(pub f32);
 
 #[derive(Event)]
 pub struct PlayerDied(pub Vec3);
 
+/// Written exactly once per enemy removed by death, by `resolve_damage`.
 #[derive(Event)]
 pub struct EnemyDied(pub Vec3);
 
+/// Written once for every enemy spawned, so the enemy count can track additions.
+#[derive(Event)]
+pub struct EnemySpawned(pub Entity);
+
+/// What dealt a killing blow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KillSource {
+    /// A projectile that hadn't bounced yet.
+    Shot,
+    /// A projectile that had bounced at least once.
+    Ricochet { bounces: u32 },
+    /// Contact between the player and an enemy.
+    Melee,
+    /// A chain explosion from a nearby kill.
+    Explosion,
+    /// Stepping onto a lava tile.
+    Lava,
+}
+
+impl KillSource {
+    /// Returns the source for a projectile that has bounced `bounces` times.
+    pub fn projectile(bounces: u32) -> Self {
+        if bounces == 0 {
+            KillSource::Shot
+        } else {
+            KillSource::Ricochet { bounces }
+        }
+    }
+}
+
+/// Details of how an enemy was killed, written alongside `EnemyDied` for scoring.
+#[derive(Event)]
+pub struct EnemyKilled {
+    pub position: Vec3,
+    pub source: KillSource,
+}
+
+/// How fast gameplay runs, as a multiple of real time. While playing, `apply_game_speed`
+/// copies it onto `Time<Virtual>`, so every system reading `Res<Time>` slows down together.
+/// UI, audio fades and anything else that must stay responsive reads `Time<Real>` instead.
 #[derive(Resource)]
 pub struct GameSpeed {
     pub value: f32,
@@ -37,6 +170,32 @@ pub struct GameSpeed {
 #[derive(Resource)]
 pub struct EnemyGroupSize(pub u32);
 
+/// Which set of rules a run is played under, chosen on the title screen.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Clear every enemy to win the round, then move on to a bigger round.
+    #[default]
+    Classic,
+    /// Enemies keep coming; survive as long as possible.
+    Endless,
+    /// A single round on a fixed course, cleared against the clock for a medal.
+    TimeAttack,
+}
+
+impl GameMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            GameMode::Classic => "CLASSIC",
+            GameMode::Endless => "ENDLESS",
+            GameMode::TimeAttack => "TIME ATTACK",
+        }
+    }
+}
+
+/// The current round number within a run, starting at 1.
+#[derive(Resource)]
+pub struct CurrentRound(pub u32);
+
 pub struct ComponentsPlugin;
 
 impl Plugin for ComponentsPlugin {
@@ -44,24 +203,130 @@ impl Plugin for ComponentsPlugin {
         // Register the PlayerDied and EnemyDied events here.
         app.add_event::<PlayerDied>()
             .add_event::<EnemyDied>()
+            .add_event::<EnemySpawned>()
+            .add_event::<EnemyKilled>()
             .insert_resource(GameSpeed { value: 1.0 })
+            .init_resource::<GameMode>()
+            .init_resource::<FactionRules>()
+            .add_systems(First, apply_game_speed)
             .add_systems(
                 Update,
-                (update_velocity)
-                    .chain()
-                    .run_if(in_state(GameState::Playing)),
+                (
+                    // Once this frame's scroll is known.
+                    update_velocity.after(MovementSystems::ApplyOffsetChanges),
+                    update_lifetimes,
+                )
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
             );
     }
 }
 
+/// Scales virtual time by `GameSpeed` while playing, and runs it at full speed everywhere else
+/// so menus never crawl.
+fn apply_game_speed(
+    game_speed: Res<GameSpeed>,
+    state: Res<State<GameState>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let speed = if *state.get() == GameState::Playing {
+        game_speed.value
+    } else {
+        1.0
+    };
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+}
+
+/// Applies gravity and damping to every `Velocity`, then moves the entity by it, in game time,
+/// and by however far the map has scrolled since the last time it ran.
 pub fn update_velocity(
-    mut query: Query<(&Velocity, &mut Transform, Option<&Speed>)>,
+    mut query: Query<(
+        &mut Velocity,
+        &mut Transform,
+        Option<&Damping>,
+        Option<&Gravity>,
+    )>,
     time: Res<Time>,
-    game_speed: Res<GameSpeed>,
+    grid_space: GridSpace,
+    mut last_origin: Local<Option<Vec2>>,
 ) {
-    for (velocity, mut transform, speed) in query.iter_mut() {
-        let speed_modifier = speed.map_or(1.0, |s| s.value) * game_speed.value;
-        transform.translation.x += velocity.velocity.x * time.delta_secs() * speed_modifier;
-        transform.translation.y += velocity.velocity.y * time.delta_secs() * speed_modifier;
+    let dt = time.delta_secs();
+    let origin = grid_space.grid_to_world(Vec2::ZERO);
+    let scroll = last_origin.map_or(Vec2::ZERO, |last| origin - last);
+    *last_origin = Some(origin);
+    for (mut velocity, mut transform, damping, gravity) in query.iter_mut() {
+        if let Some(Gravity(gravity)) = gravity {
+            velocity.velocity.y -= gravity * dt;
+        }
+        if let Some(Damping(rate)) = damping {
+            velocity.velocity *= (-rate * dt).exp();
+        }
+        transform.translation += (velocity.velocity * dt + scroll).extend(0.0);
+    }
+}
+
+/// Ticks every `Lifetime`, fading `FadeOut` sprites and despawning entities whose time is up.
+pub fn update_lifetimes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Lifetime, Option<&mut Sprite>, Has<FadeOut>)>,
+    time: Res<Time>,
+) {
+    for (entity, mut lifetime, sprite, fades) in &mut query {
+        if lifetime.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let (true, Some(mut sprite)) = (fades, sprite) {
+            let remaining = lifetime.0.fraction_remaining();
+            if remaining < FADE_OUT_FRACTION {
+                sprite.color = sprite.color.with_alpha(remaining / FADE_OUT_FRACTION);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn factions_are_hostile_to_each_other_but_not_themselves() {
+        let rules = FactionRules::default();
+        assert!(rules.hostile(Faction::Players, Faction::Enemies));
+        assert!(rules.hostile(Faction::Enemies, Faction::Players));
+        assert!(!rules.hostile(Faction::Players, Faction::Players));
+        assert!(!rules.hostile(Faction::Enemies, Faction::Enemies));
+    }
+
+    #[test]
+    fn friendly_fire_makes_a_faction_hostile_to_itself() {
+        let rules = FactionRules {
+            friendly_fire: true,
+        };
+        assert!(rules.hostile(Faction::Players, Faction::Players));
+        assert!(rules.hostile(Faction::Enemies, Faction::Enemies));
+        assert!(rules.hostile(Faction::Players, Faction::Enemies));
+    }
+
+    #[test]
+    fn neutral_is_never_hostile() {
+        for friendly_fire in [false, true] {
+            let rules = FactionRules { friendly_fire };
+            for other in [Faction::Players, Faction::Enemies, Faction::Neutral] {
+                assert!(!rules.hostile(Faction::Neutral, other));
+                assert!(!rules.hostile(other, Faction::Neutral));
+                assert!(!rules.ricochet_hostile(Faction::Neutral, other));
+                assert!(!rules.ricochet_hostile(other, Faction::Neutral));
+            }
+        }
+    }
+
+    #[test]
+    fn ricochets_turn_on_their_own_side() {
+        let rules = FactionRules::default();
+        assert!(rules.ricochet_hostile(Faction::Players, Faction::Players));
+        assert!(rules.ricochet_hostile(Faction::Players, Faction::Enemies));
+        assert!(rules.ricochet_hostile(Faction::Enemies, Faction::Enemies));
     }
 }
@@ -0,0 +1,90 @@
+// config.rs
+
+//! Loads designer-tunable values from a central `config.ron` file.
+//!
+//! Every section of the file is optional: anything missing falls back to the defaults
+//! compiled into the game, and a missing or malformed file is logged rather than fatal.
+//! Each section is inserted as its own resource so systems only depend on what they use.
+
+use bevy::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::explosion::ExplosionConfig;
+use crate::platform_io::{read_config, write_config};
+use crate::player::CameraConfig;
+
+/// Path of the central config file, relative to the working directory.
+pub const CONFIG_PATH: &str = "config.ron";
+
+/// Re-reads `config.ron` while the game runs, so values can be tuned without restarting.
+const RELOAD_KEY: KeyCode = KeyCode::F9;
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        let config: GameConfig = load_ron(CONFIG_PATH).unwrap_or_default();
+        app.insert_resource(config.explosion)
+            .insert_resource(config.camera)
+            .add_systems(Update, reload_config);
+    }
+}
+
+fn reload_config(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut explosion: ResMut<ExplosionConfig>,
+    mut camera: ResMut<CameraConfig>,
+) {
+    if !keys.just_pressed(RELOAD_KEY) {
+        return;
+    }
+    let config: GameConfig = load_ron(CONFIG_PATH).unwrap_or_default();
+    *explosion = config.explosion;
+    *camera = config.camera;
+    info!("Reloaded {}", CONFIG_PATH);
+}
+
+/// The full contents of `config.ron`.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+pub struct GameConfig {
+    pub explosion: ExplosionConfig,
+    pub camera: CameraConfig,
+}
+
+/// Reads and parses a RON file.
+///
+/// Returns `None` if the file doesn't exist or can't be parsed; parse failures are logged
+/// with a warning so a typo in a config file doesn't go unnoticed.
+pub fn load_ron<T: DeserializeOwned>(path: &str) -> Option<T> {
+    let contents = read_config(path)?;
+    parse_ron(path, &contents)
+}
+
+/// Parses `contents`, read from the RON file at `path`. Like `load_ron`, returns `None` and logs
+/// a warning if they are malformed.
+pub fn parse_ron<T: DeserializeOwned>(path: &str, contents: &str) -> Option<T> {
+    match ron::from_str(contents) {
+        Ok(value) => {
+            info!("Loaded {}", path);
+            Some(value)
+        }
+        Err(err) => {
+            warn!("Failed to parse {}: {}; using defaults", path, err);
+            None
+        }
+    }
+}
+
+/// Serializes `value` to RON and writes it to `path` atomically; see `write_config`.
+/// Failures are logged and otherwise ignored.
+pub fn save_ron<T: Serialize>(path: &str, value: &T) {
+    let result = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| write_config(path, contents.as_bytes()));
+
+    if let Err(err) = result {
+        warn!("Failed to save {}: {}", path, err);
+    }
+}
@@ -0,0 +1,569 @@
+// console.rs
+
+//! A drop-down debug console, opened with the backtick key.
+//!
+//! While it's open, typed keys go to the console's input line and the keyboard and mouse state
+//! is cleared before `Update`, so gameplay and menus don't react to them. Each command is a
+//! plain function with exclusive `World` access, registered by name in `ConsoleCommands`;
+//! other plugins can add their own with `ConsoleCommands::register`.
+
+use bevy::ecs::system::SystemState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+
+use crate::assets::GameAssets;
+use crate::atlas::GameAtlas;
+use crate::collider::DamageEvent;
+use crate::components::{Dying, GameSpeed, GameState, KillSource};
+use crate::enemy::{Enemy, EnemyKind, EnemySpawner};
+use crate::grid_movement::{is_wall, GridMover, PreviousTranslation};
+use crate::grid_reservation::GridReservations;
+use crate::map::MapData;
+use crate::pickup::{spawn_pickup, PickupKind};
+use crate::player::Player;
+use crate::seed::{format_seed, MapSeed};
+
+/// Lines kept in the scrollback; older ones are dropped.
+const SCROLLBACK_LINES: usize = 200;
+
+/// Lines of scrollback shown above the input line.
+const VISIBLE_LINES: usize = 14;
+
+/// The most enemies or pickups a single command will spawn.
+const MAX_SPAWN_COUNT: u32 = 50;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .init_resource::<ConsoleCommands>()
+            .add_systems(Startup, spawn_console)
+            .add_systems(PreUpdate, handle_console_input.after(InputSystem))
+            .add_systems(
+                Update,
+                (
+                    run_console_commands.run_if(|console: Res<Console>| console.has_pending()),
+                    update_console_ui.run_if(resource_changed::<Console>),
+                )
+                    .chain(),
+            );
+
+        let mut commands = app.world_mut().resource_mut::<ConsoleCommands>();
+        commands.register("help", "help", "lists the commands", help_command);
+        commands.register("clear", "clear", "clears the scrollback", clear_command);
+        commands.register(
+            "spawn",
+            "spawn <left|right|chaser> [n]",
+            "spawns enemies away from the player",
+            spawn_command,
+        );
+        commands.register("tp", "tp <x> <y>", "teleports the player", tp_command);
+        commands.register(
+            "kill_all",
+            "kill_all",
+            "kills every enemy",
+            kill_all_command,
+        );
+        commands.register(
+            "give",
+            "give <gem> [n]",
+            "drops pickups on the player",
+            give_command,
+        );
+        commands.register("set", "set speed <f32>", "sets the game speed", set_command);
+        commands.register("seed", "seed", "prints the map seed", seed_command);
+        commands.register(
+            "wall",
+            "wall <x> <y> <0|1>",
+            "removes or places a wall",
+            wall_command,
+        );
+        commands.register(
+            "state",
+            "state <title|playing|victory>",
+            "switches the game state",
+            state_command,
+        );
+    }
+}
+
+/// The result of a command: a line to print, or an error explaining what was wrong. The
+/// command's usage is printed after any error.
+pub type CommandResult = Result<String, String>;
+
+/// A console command. It gets the arguments after the command name, split on whitespace.
+pub type CommandHandler = fn(&mut World, &[&str]) -> CommandResult;
+
+struct ConsoleCommand {
+    usage: &'static str,
+    description: &'static str,
+    handler: CommandHandler,
+}
+
+/// Every command the console understands, by name.
+#[derive(Resource, Default)]
+pub struct ConsoleCommands(BTreeMap<&'static str, ConsoleCommand>);
+
+impl ConsoleCommands {
+    /// Adds a command, replacing any existing command with the same name.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        description: &'static str,
+        handler: CommandHandler,
+    ) {
+        self.0.insert(
+            name,
+            ConsoleCommand {
+                usage,
+                description,
+                handler,
+            },
+        );
+    }
+}
+
+/// The console's open state, input line, scrollback and command history.
+#[derive(Resource)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    scrollback: VecDeque<String>,
+    history: Vec<String>,
+    /// Which history entry the up and down keys have recalled, if any.
+    history_cursor: Option<usize>,
+    /// Submitted lines waiting for `run_console_commands`.
+    pending: Vec<String>,
+}
+
+impl Default for Console {
+    fn default() -> Self {
+        let mut console = Console {
+            open: false,
+            input: String::new(),
+            scrollback: VecDeque::new(),
+            history: Vec::new(),
+            history_cursor: None,
+            pending: Vec::new(),
+        };
+        console.print("Type help for a list of commands.");
+        console
+    }
+}
+
+impl Console {
+    /// Adds text to the scrollback, one entry per line.
+    pub fn print(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        self.scrollback.extend(text.lines().map(str::to_string));
+        while self.scrollback.len() > SCROLLBACK_LINES {
+            self.scrollback.pop_front();
+        }
+    }
+
+    fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        self.history_cursor = None;
+        if line.trim().is_empty() {
+            return;
+        }
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        self.pending.push(line);
+    }
+
+    /// Moves through the history: `step` of -1 recalls an older line, 1 a newer one.
+    fn recall(&mut self, step: i32) {
+        if self.history.is_empty() || (self.history_cursor.is_none() && step > 0) {
+            return;
+        }
+        let last = self.history.len() - 1;
+        self.history_cursor = match (self.history_cursor, step < 0) {
+            (None, _) => Some(last),
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i < last => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.input = self
+            .history_cursor
+            .map_or_else(String::new, |i| self.history[i].clone());
+    }
+}
+
+#[derive(Component)]
+struct ConsoleRoot;
+
+#[derive(Component)]
+struct ConsoleScrollback;
+
+#[derive(Component)]
+struct ConsoleInputLine;
+
+/// Builds the console panel across the top of the screen, hidden until opened. Uses Bevy's
+/// built-in font so it works in any state.
+fn spawn_console(mut commands: Commands) {
+    let font = TextFont {
+        font_size: 10.0,
+        ..default()
+    };
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(40.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::FlexEnd,
+                padding: UiRect::all(Val::Px(4.0)),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            GlobalZIndex(40),
+            Visibility::Hidden,
+            ConsoleRoot,
+        ))
+        .id();
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new(""),
+            font.clone(),
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ConsoleScrollback,
+        ));
+        parent.spawn((
+            Text::new(""),
+            font,
+            TextColor(Color::srgb(1.0, 1.0, 0.6)),
+            ConsoleInputLine,
+        ));
+    });
+}
+
+/// Toggles the console on backtick and, while it's open, feeds typed keys into it. Runs right
+/// after Bevy updates the input state, and clears that state while the console is open so no
+/// later system sees the keys.
+pub fn handle_console_input(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    mut keys: ResMut<ButtonInput<KeyCode>>,
+    mut mouse: ResMut<ButtonInput<MouseButton>>,
+) {
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        if event.key_code == KeyCode::Backquote {
+            if !event.repeat {
+                console.open = !console.open;
+            }
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => console.submit(),
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::ArrowUp => console.recall(-1),
+            Key::ArrowDown => console.recall(1),
+            Key::Escape => console.open = false,
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => {
+                console
+                    .input
+                    .extend(text.chars().filter(|c| !c.is_control()));
+            }
+            _ => {}
+        }
+    }
+
+    // Also swallows the key that closed the console, so Escape doesn't reach the window.
+    if console.open || console.is_changed() {
+        keys.reset_all();
+        mouse.reset_all();
+    }
+}
+
+/// Runs every submitted line, printing each command's output to the scrollback.
+fn run_console_commands(world: &mut World) {
+    let lines = std::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in lines {
+        let mut output = vec![format!("> {}", line)];
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = words
+            .split_first()
+            .expect("blank lines are never submitted");
+        let command = world
+            .resource::<ConsoleCommands>()
+            .0
+            .get(name)
+            .map(|c| (c.usage, c.handler));
+        match command {
+            Some((usage, handler)) => match handler(world, args) {
+                Ok(result) => output.push(result),
+                Err(err) => {
+                    output.push(format!("error: {}", err));
+                    output.push(format!("usage: {}", usage));
+                }
+            },
+            None => output.push(format!("unknown command '{}', try help", name)),
+        }
+        let mut console = world.resource_mut::<Console>();
+        for text in output {
+            console.print(text);
+        }
+    }
+}
+
+fn update_console_ui(
+    console: Res<Console>,
+    mut root: Query<&mut Visibility, With<ConsoleRoot>>,
+    mut scrollback: Query<&mut Text, (With<ConsoleScrollback>, Without<ConsoleInputLine>)>,
+    mut input_line: Query<&mut Text, With<ConsoleInputLine>>,
+) {
+    if let Ok(mut visibility) = root.single_mut() {
+        *visibility = if console.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if let Ok(mut text) = scrollback.single_mut() {
+        let skip = console.scrollback.len().saturating_sub(VISIBLE_LINES);
+        text.0 = console
+            .scrollback
+            .iter()
+            .skip(skip)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+    if let Ok(mut text) = input_line.single_mut() {
+        text.0 = format!("> {}_", console.input);
+    }
+}
+
+/// Parses `arg`, describing it as `what` if it doesn't parse.
+fn parse_arg<T: FromStr>(arg: &str, what: &str) -> Result<T, String> {
+    arg.parse()
+        .map_err(|_| format!("'{}' isn't a valid {}", arg, what))
+}
+
+/// Parses an optional count argument, defaulting to one.
+fn parse_count(arg: Option<&&str>) -> Result<u32, String> {
+    let count = arg.map_or(Ok(1), |a| parse_arg(a, "count"))?;
+    if !(1..=MAX_SPAWN_COUNT).contains(&count) {
+        return Err(format!("count must be 1 to {}", MAX_SPAWN_COUNT));
+    }
+    Ok(count)
+}
+
+/// Commands that touch the map or its entities only make sense mid-run.
+fn require_playing(world: &World) -> Result<(), String> {
+    if *world.resource::<State<GameState>>().get() != GameState::Playing
+        || !world.contains_resource::<MapData>()
+    {
+        return Err("only available while playing".to_string());
+    }
+    Ok(())
+}
+
+fn find_player(world: &mut World) -> Result<(Entity, IVec2), String> {
+    world
+        .query_filtered::<(Entity, &GridMover), With<Player>>()
+        .single(world)
+        .map(|(entity, mover)| (entity, mover.grid_pos))
+        .map_err(|_| "there's no player".to_string())
+}
+
+fn help_command(world: &mut World, _args: &[&str]) -> CommandResult {
+    let commands = world.resource::<ConsoleCommands>();
+    Ok(commands
+        .0
+        .values()
+        .map(|c| format!("{} - {}", c.usage, c.description))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn clear_command(world: &mut World, _args: &[&str]) -> CommandResult {
+    world.resource_mut::<Console>().scrollback.clear();
+    Ok(String::new())
+}
+
+fn spawn_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let (kind, count) = match args {
+        [kind] | [kind, _] => (*kind, parse_count(args.get(1))?),
+        _ => return Err("expected an enemy type".to_string()),
+    };
+    let kind = match kind {
+        "left" => EnemyKind::LeftTurner,
+        "right" => EnemyKind::RightTurner,
+        "chaser" => EnemyKind::Chaser,
+        _ => return Err(format!("unknown enemy type '{}'", kind)),
+    };
+    require_playing(world)?;
+    let (_, player_pos) = find_player(world)?;
+
+    let mut state = SystemState::<EnemySpawner>::new(world);
+    let mut spawner = state.get_mut(world);
+    let spawned = spawner
+        .spawn_many(&vec![kind; count as usize], player_pos)
+        .len();
+    state.apply(world);
+    if spawned == 0 {
+        return Err("no free cell to spawn on".to_string());
+    }
+    Ok(format!("Spawned {} {:?}", spawned, kind))
+}
+
+fn tp_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let [x, y] = args else {
+        return Err("expected two coordinates".to_string());
+    };
+    let pos = IVec2::new(parse_arg(x, "x")?, parse_arg(y, "y")?);
+    require_playing(world)?;
+    let (player, _) = find_player(world)?;
+    if is_wall(pos, world.resource::<MapData>()) {
+        return Err(format!("{} is a wall or off the map", pos));
+    }
+
+    let mut reservations = world.resource_mut::<GridReservations>();
+    if reservations.0.get(&pos).is_some_and(|&e| e != player) {
+        return Err(format!("{} is occupied", pos));
+    }
+    // Drop the cells the player held (two while between tiles) and hold the new one.
+    reservations.0.retain(|_, e| *e != player);
+    reservations.0.insert(pos, player);
+
+    let mut entity = world.entity_mut(player);
+    if let Some(mut mover) = entity.get_mut::<GridMover>() {
+        mover.grid_pos = pos;
+        mover.direction = IVec2::ZERO;
+        mover.progress = 0.0;
+    }
+    // Re-adding the component marks it as new, so swept collisions don't treat the jump as
+    // movement across the map.
+    entity
+        .remove::<PreviousTranslation>()
+        .insert(PreviousTranslation::default());
+    Ok(format!("Teleported to {}", pos))
+}
+
+fn kill_all_command(world: &mut World, _args: &[&str]) -> CommandResult {
+    require_playing(world)?;
+    let enemies: Vec<(Entity, Vec3)> = world
+        .query_filtered::<(Entity, &Transform), (With<Enemy>, Without<Dying>)>()
+        .iter(world)
+        .map(|(entity, transform)| (entity, transform.translation))
+        .collect();
+    for &(victim, position) in &enemies {
+        world.send_event(DamageEvent {
+            victim,
+            amount: u32::MAX,
+            source: KillSource::Explosion,
+            position,
+        });
+    }
+    Ok(format!("Killed {} enemies", enemies.len()))
+}
+
+fn give_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let (kind, count) = match args {
+        [kind] | [kind, _] => (*kind, parse_count(args.get(1))?),
+        _ => return Err("expected a pickup".to_string()),
+    };
+    let kind = match kind {
+        "gem" => PickupKind::Gem,
+        _ => return Err(format!("unknown pickup '{}'", kind)),
+    };
+    require_playing(world)?;
+    let (_, player_pos) = find_player(world)?;
+
+    let mut state = SystemState::<(Commands, Res<GameAssets>, Option<Res<GameAtlas>>)>::new(world);
+    let (mut commands, game_assets, atlas) = state.get_mut(world);
+    for _ in 0..count {
+        spawn_pickup(
+            &mut commands,
+            &game_assets,
+            atlas.as_deref(),
+            kind,
+            player_pos,
+        );
+    }
+    state.apply(world);
+    Ok(format!("Gave {} {:?}", count, kind))
+}
+
+fn set_command(world: &mut World, args: &[&str]) -> CommandResult {
+    match args {
+        ["speed", value] => {
+            let speed: f32 = parse_arg(value, "speed")?;
+            if !speed.is_finite() || speed <= 0.0 {
+                return Err("speed must be above zero".to_string());
+            }
+            world.resource_mut::<GameSpeed>().value = speed;
+            Ok(format!("Game speed set to {}", speed))
+        }
+        _ => Err("expected a setting and a value".to_string()),
+    }
+}
+
+fn seed_command(world: &mut World, _args: &[&str]) -> CommandResult {
+    let seed = world.resource::<MapSeed>();
+    Ok(format!("Map seed {}", format_seed(seed.current)))
+}
+
+fn wall_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let [x, y, flag] = args else {
+        return Err("expected two coordinates and 0 or 1".to_string());
+    };
+    let pos = IVec2::new(parse_arg(x, "x")?, parse_arg(y, "y")?);
+    let wall = match *flag {
+        "0" => false,
+        "1" => true,
+        _ => return Err(format!("'{}' isn't 0 or 1", flag)),
+    };
+    require_playing(world)?;
+    if wall && world.resource::<GridReservations>().0.contains_key(&pos) {
+        return Err(format!("{} is occupied", pos));
+    }
+    if !world.resource_mut::<MapData>().set_wall(pos, wall) {
+        return Err(format!("{} is off the map", pos));
+    }
+    Ok(format!(
+        "{} is now {}",
+        pos,
+        if wall { "a wall" } else { "floor" }
+    ))
+}
+
+fn state_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let state = match args {
+        ["title"] => GameState::Title,
+        ["playing"] => GameState::Playing,
+        ["victory"] => GameState::Victory,
+        [other] => return Err(format!("unknown state '{}'", other)),
+        _ => return Err("expected a state".to_string()),
+    };
+    if *world.resource::<State<GameState>>().get() == GameState::Loading {
+        return Err("still loading".to_string());
+    }
+    world.resource_mut::<NextState<GameState>>().set(state);
+    Ok(format!("Switching to {:?}", state))
+}
@@ -0,0 +1,87 @@
+// crt.rs
+
+//! An optional retro CRT look: scanlines and a soft vignette, drawn by a small WGSL UI material
+//! stretched over the window. Toggled from the settings page and off by default.
+//!
+//! The overlay sits in the UI pass, so it covers the game, and below every other UI root, so
+//! text stays crisp. The scanlines are measured in framebuffer pixels, which keeps their spacing
+//! the same whatever the window size or zoom. While the effect is off the overlay entity doesn't
+//! exist, so it costs nothing.
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef};
+
+pub struct CrtPlugin;
+
+impl Plugin for CrtPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(UiMaterialPlugin::<CrtMaterial>::default())
+            .insert_resource(CrtEffect(false))
+            .add_systems(
+                Update,
+                sync_crt_overlay.run_if(resource_changed::<CrtEffect>),
+            );
+    }
+}
+
+/// Whether the CRT overlay is shown, pushed from `Settings`.
+#[derive(Resource, PartialEq)]
+pub struct CrtEffect(pub bool);
+
+const CRT_SHADER_PATH: &str = "shaders/crt.wgsl";
+
+/// Height of one scanline band, in device pixels.
+const SCANLINE_PERIOD: f32 = 2.0;
+
+/// How much the dark scanline bands darken what's beneath, from 0.0 to 1.0.
+const SCANLINE_DARKNESS: f32 = 0.25;
+
+const VIGNETTE_STRENGTH: f32 = 0.5;
+
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct CrtMaterial {
+    /// Scanline period, scanline darkness and vignette strength, packed for the shader.
+    #[uniform(0)]
+    params: Vec4,
+}
+
+impl UiMaterial for CrtMaterial {
+    fn fragment_shader() -> ShaderRef {
+        CRT_SHADER_PATH.into()
+    }
+}
+
+#[derive(Component)]
+struct CrtOverlay;
+
+/// Spawns the overlay when the effect is turned on and removes it when it's turned off.
+fn sync_crt_overlay(
+    mut commands: Commands,
+    effect: Res<CrtEffect>,
+    mut materials: ResMut<Assets<CrtMaterial>>,
+    overlays: Query<Entity, With<CrtOverlay>>,
+) {
+    if !effect.0 {
+        for entity in &overlays {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+    if !overlays.is_empty() {
+        return;
+    }
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        MaterialNode(materials.add(CrtMaterial {
+            params: Vec4::new(SCANLINE_PERIOD, SCANLINE_DARKNESS, VIGNETTE_STRENGTH, 0.0),
+        })),
+        // Under every other UI root, so menus and the HUD draw over it.
+        GlobalZIndex(-1),
+        CrtOverlay,
+    ));
+}
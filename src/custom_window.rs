@@ -1,30 +1,360 @@
 // custom_window_plugin.rs -> assistant: leave this comment in place, it orients the AI
-use bevy::{prelude::*, window::PresentMode, window::WindowMode, window::WindowResolution};
+
+//! Creates the primary window from `save/window.ron`, and writes its size and position back
+//! there when they change so the game reopens where it was left.
+//!
+//! The file is read before `DefaultPlugins` are added, so it decides how the window is first
+//! created. A missing or malformed file falls back to the defaults below.
+//!
+//! The plugin also sets the window icon, and pauses the game while the window is unfocused or
+//! minimized unless `AutoPause` is turned off in the settings.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::{
+    Monitor, PresentMode, PrimaryWindow, WindowFocused, WindowMode, WindowMoved, WindowOccluded,
+    WindowResized, WindowResolution,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::components::{GameMode, GameState};
+use crate::config::{load_ron, save_ron};
 
 const GAME_TITLE: &str = "Gridman ECS";
 const BACKGROUND_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
 
+/// Where the window configuration is kept, relative to the working directory.
+pub const WINDOW_CONFIG_PATH: &str = "save/window.ron";
+
+/// How long the window has to stay put before a new size or position is saved.
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(1);
+
+const MIN_WINDOW_SIZE: f32 = 320.0;
+const MAX_WINDOW_SIZE: f32 = 7680.0;
+
+/// The window icon, embedded so it's there before the asset server has loaded anything.
+#[cfg(not(target_arch = "wasm32"))]
+const ICON_PNG: &[u8] = include_bytes!("../assets/textures/player.png");
+
 pub struct CustomWindowPlugin;
 
 impl Plugin for CustomWindowPlugin {
     fn build(&self, app: &mut App) {
+        let config: WindowConfig = load_ron::<WindowConfig>(WINDOW_CONFIG_PATH)
+            .unwrap_or_default()
+            .sanitized();
         app.insert_resource(ClearColor(BACKGROUND_COLOR))
             .add_plugins(
                 DefaultPlugins
                     .set(bevy::window::WindowPlugin {
-                        primary_window: Some(Window {
-                            title: GAME_TITLE.to_string(),
-                            present_mode: PresentMode::AutoNoVsync,
-                            mode: WindowMode::Windowed,
-                            position: WindowPosition::Centered(MonitorSelection::Primary),
-                            resolution: WindowResolution::new(800.0, 600.0),
-                            ..default()
-                        }),
+                        primary_window: Some(config.window()),
                         ..default()
                     })
                     .set(ImagePlugin::default_nearest()),
             )
-            .add_systems(Update, close_on_esc);
+            .insert_resource(config)
+            .init_resource::<WindowSaveTimer>()
+            .insert_resource(AutoPause(true))
+            .init_resource::<FocusPause>()
+            .add_systems(Startup, log_window_config)
+            // After `First`, where virtual time is advanced, so the frame that unpauses still
+            // has a zero delta rather than the whole time the window was away.
+            .add_systems(PreUpdate, pause_when_unfocused)
+            .add_systems(
+                Update,
+                (
+                    close_on_esc.run_if(escape_quits),
+                    keep_window_on_screen,
+                    track_window_changes,
+                    save_window_config,
+                ),
+            );
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Update, set_window_icon);
+    }
+}
+
+/// How the window is shown.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    /// Borderless fullscreen on the primary monitor.
+    Borderless,
+}
+
+/// Vertical sync.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VsyncSetting {
+    #[default]
+    Off,
+    On,
+    /// Vsync without blocking; falls back to `On` where unsupported.
+    Mailbox,
+}
+
+/// The contents of `window.ron`.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub width: f32,
+    pub height: f32,
+    pub mode: WindowModeSetting,
+    pub vsync: VsyncSetting,
+    /// Saves the window's size and position whenever they change.
+    pub remember: bool,
+    /// The last known top-left corner in physical pixels; centered on the primary monitor
+    /// if unset. Kept as a tuple since glam's serde support isn't enabled.
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            width: 800.0,
+            height: 600.0,
+            mode: WindowModeSetting::Windowed,
+            vsync: VsyncSetting::Off,
+            remember: true,
+            position: None,
+        }
+    }
+}
+
+impl WindowConfig {
+    /// Clamps the size into a sensible range, in case the file was edited by hand.
+    fn sanitized(mut self) -> Self {
+        if !self.width.is_finite() || !self.height.is_finite() {
+            let defaults = WindowConfig::default();
+            self.width = defaults.width;
+            self.height = defaults.height;
+        }
+        self.width = self.width.clamp(MIN_WINDOW_SIZE, MAX_WINDOW_SIZE);
+        self.height = self.height.clamp(MIN_WINDOW_SIZE, MAX_WINDOW_SIZE);
+        self
+    }
+
+    /// The primary window this configuration describes.
+    fn window(&self) -> Window {
+        Window {
+            title: GAME_TITLE.to_string(),
+            present_mode: match self.vsync {
+                VsyncSetting::Off => PresentMode::AutoNoVsync,
+                VsyncSetting::On => PresentMode::AutoVsync,
+                VsyncSetting::Mailbox => PresentMode::Mailbox,
+            },
+            mode: match self.mode {
+                WindowModeSetting::Windowed => WindowMode::Windowed,
+                WindowModeSetting::Borderless => {
+                    WindowMode::BorderlessFullscreen(MonitorSelection::Primary)
+                }
+            },
+            position: match self.position {
+                Some((x, y)) => WindowPosition::At(IVec2::new(x, y)),
+                None => WindowPosition::Centered(MonitorSelection::Primary),
+            },
+            resolution: WindowResolution::new(self.width, self.height),
+            ..default()
+        }
+    }
+}
+
+/// Counts down to saving the window configuration after the last resize or move.
+#[derive(Resource, Default)]
+struct WindowSaveTimer(Option<Timer>);
+
+/// Logged at startup rather than when the file is read, since logging isn't set up until the
+/// default plugins are.
+fn log_window_config(config: Res<WindowConfig>) {
+    info!(
+        "Window: {}x{} {:?}, vsync {:?}, position {:?}, remember {}",
+        config.width, config.height, config.mode, config.vsync, config.position, config.remember
+    );
+}
+
+/// Recenters the window once the monitors are known if its saved position isn't on any of
+/// them, e.g. because it was last on a monitor that has since been unplugged.
+fn keep_window_on_screen(
+    mut checked: Local<bool>,
+    monitors: Query<&Monitor>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if *checked || monitors.is_empty() {
+        return;
+    }
+    *checked = true;
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+    let WindowPosition::At(position) = window.position else {
+        return;
+    };
+    let on_screen = monitors.iter().any(|monitor| {
+        let min = monitor.physical_position;
+        let max = min + UVec2::new(monitor.physical_width, monitor.physical_height).as_ivec2();
+        position.cmpge(min).all() && position.cmplt(max).all()
+    });
+    if !on_screen {
+        warn!(
+            "Saved window position {} is off screen; centering",
+            position
+        );
+        window.position = WindowPosition::Centered(MonitorSelection::Primary);
+    }
+}
+
+/// Records the window's new size and position, and restarts the save countdown.
+fn track_window_changes(
+    mut resized: EventReader<WindowResized>,
+    mut moved: EventReader<WindowMoved>,
+    mut config: ResMut<WindowConfig>,
+    mut timer: ResMut<WindowSaveTimer>,
+    primary: Query<(), With<PrimaryWindow>>,
+) {
+    let mut changed = false;
+    // A borderless window is the size of the monitor, which isn't worth remembering.
+    let windowed = config.mode == WindowModeSetting::Windowed;
+    for event in resized.read() {
+        if windowed && primary.contains(event.window) {
+            config.width = event.width;
+            config.height = event.height;
+            changed = true;
+        }
+    }
+    for event in moved.read() {
+        if windowed && primary.contains(event.window) {
+            config.position = Some((event.position.x, event.position.y));
+            changed = true;
+        }
+    }
+    if changed && config.remember {
+        timer.0 = Some(Timer::new(SAVE_DEBOUNCE, TimerMode::Once));
+    }
+}
+
+/// Saves the window configuration once it has settled, and on exit if a save is pending.
+fn save_window_config(
+    config: Res<WindowConfig>,
+    mut timer: ResMut<WindowSaveTimer>,
+    time: Res<Time<Real>>,
+    mut exit_events: EventReader<AppExit>,
+) {
+    let exiting = exit_events.read().next().is_some();
+    let Some(pending) = timer.0.as_mut() else {
+        return;
+    };
+    if exiting || pending.tick(time.delta()).finished() {
+        timer.0 = None;
+        save_ron(WINDOW_CONFIG_PATH, &*config);
+    }
+}
+
+/// Whether to pause while the window is unfocused or minimized; set from `Settings`.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct AutoPause(pub bool);
+
+/// Whether the window is away, and whether this plugin paused virtual time because of it.
+#[derive(Resource, Default)]
+struct FocusPause {
+    unfocused: bool,
+    occluded: bool,
+    paused: bool,
+}
+
+/// Pauses virtual time while the window is unfocused or minimized during play, which freezes
+/// every timer and, through `simulation_running`, the gameplay systems. Only unpauses a pause
+/// it started.
+fn pause_when_unfocused(
+    mut focused_events: EventReader<WindowFocused>,
+    mut occluded_events: EventReader<WindowOccluded>,
+    primary: Query<(), With<PrimaryWindow>>,
+    auto_pause: Res<AutoPause>,
+    state: Res<State<GameState>>,
+    mut focus: ResMut<FocusPause>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    for event in focused_events.read() {
+        if primary.contains(event.window) {
+            focus.unfocused = !event.focused;
+        }
+    }
+    for event in occluded_events.read() {
+        if primary.contains(event.window) {
+            focus.occluded = event.occluded;
+        }
+    }
+    let away = focus.unfocused || focus.occluded;
+    let pause = auto_pause.0 && away && *state.get() == GameState::Playing;
+    if pause && !focus.paused {
+        info!("Window lost focus; pausing");
+        time.pause();
+        focus.paused = true;
+    } else if !pause && focus.paused {
+        info!("Resuming");
+        time.unpause();
+        focus.paused = false;
+    }
+}
+
+/// Sets the window icon once winit has created the window, which can be a few frames after
+/// startup.
+#[cfg(not(target_arch = "wasm32"))]
+fn set_window_icon(
+    mut done: Local<bool>,
+    windows: NonSend<bevy::winit::WinitWindows>,
+    primary: Query<Entity, With<PrimaryWindow>>,
+) {
+    if *done {
+        return;
+    }
+    let Some(window) = primary
+        .single()
+        .ok()
+        .and_then(|entity| windows.get_window(entity))
+    else {
+        return;
+    };
+    *done = true;
+    match window_icon() {
+        Ok(icon) => window.set_window_icon(Some(icon)),
+        Err(err) => warn!("Couldn't set the window icon: {}", err),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn window_icon() -> Result<winit::window::Icon, String> {
+    use bevy::asset::RenderAssetUsages;
+    use bevy::image::{CompressedImageFormats, ImageSampler, ImageType};
+    use bevy::render::render_resource::TextureFormat;
+
+    let image = Image::from_buffer(
+        ICON_PNG,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )
+    .map_err(|err| err.to_string())?;
+    if image.texture_descriptor.format != TextureFormat::Rgba8UnormSrgb {
+        return Err(format!(
+            "expected 8-bit RGBA, got {:?}",
+            image.texture_descriptor.format
+        ));
+    }
+    let (width, height) = (image.width(), image.height());
+    let rgba = image.data.ok_or("the image has no pixel data")?;
+    winit::window::Icon::from_rgba(rgba, width, height).map_err(|err| err.to_string())
+}
+
+/// Escape means "back" on the title screen's pages and on a time attack course result, so it
+/// only quits the game everywhere else.
+fn escape_quits(state: Res<State<GameState>>, mode: Res<GameMode>) -> bool {
+    match state.get() {
+        GameState::Title => false,
+        GameState::Victory => *mode != GameMode::TimeAttack,
+        _ => true,
     }
 }
 
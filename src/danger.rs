@@ -0,0 +1,204 @@
+// danger.rs
+
+//! The danger map: which tiles a live projectile is about to pass through, for enemy AI that
+//! wants to keep out of the line of fire.
+//!
+//! Every frame each moving projectile's path is traced `DANGER_LOOKAHEAD` steps ahead from the
+//! tile it occupies, stopping at the first wall it can't bounce off. Where it has a bounce left,
+//! the trace branches into every way it could reflect rather than only the one
+//! `calculate_reflection` would pick, so the map errs on the side of too much danger. Each tile
+//! on the way is marked until `DANGER_DECAY` seconds from now, so a tile stays threatened
+//! briefly after a projectile turns away from it or is spent. The branching is bounded by the
+//! lookahead, so the work is proportional to the projectiles in flight, never to the size of
+//! the map.
+//!
+//! Chasers ask `DangerMap::is_threatened` before picking their next tile. The turners
+//! deliberately ignore it, so the classic enemies stay predictable. The AI debug layer (F8)
+//! tints every threatened tile.
+
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+use crate::assets::GameAssets;
+use crate::components::{GameEntity, GameState};
+use crate::debug::debug_layer;
+use crate::enemy::EnemyMovementAI;
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{is_wall, GridMover, MovementSystems};
+use crate::map::MapData;
+use crate::projectile::{Bouncable, Projectile};
+use crate::tilemap::{GridSpace, TILE_SIZE};
+
+/// How many tiles ahead of each projectile are marked, counting the one it is moving into.
+const DANGER_LOOKAHEAD: usize = 6;
+
+/// Seconds a tile stays threatened after it was last marked.
+const DANGER_DECAY: f32 = 0.25;
+
+/// Opacity of the debug tint on a threatened tile.
+const DANGER_TINT_ALPHA: f32 = 0.3;
+
+pub struct DangerPlugin;
+
+impl Plugin for DangerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DangerMap>()
+            .add_systems(OnEnter(GameState::Playing), clear_danger_map)
+            .add_systems(
+                Update,
+                // After the player's shots are fired, and before the AI decides where to go.
+                update_danger_map
+                    .after(MovementSystems::Input)
+                    .before(EnemyMovementAI)
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
+            )
+            .add_systems(
+                Update,
+                (
+                    sync_danger_visuals
+                        .after(MovementSystems::ApplyOffsetChanges)
+                        .run_if(debug_layer(|f| f.ai)),
+                    clear_danger_visuals.run_if(not(debug_layer(|f| f.ai))),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Tiles threatened by projectiles in flight, each with the elapsed game time at which the
+/// threat lapses. Expired tiles are dropped every frame, so any tile present is threatened.
+#[derive(Resource, Default)]
+pub struct DangerMap(pub HashMap<IVec2, f32>);
+
+impl DangerMap {
+    /// Whether a projectile may pass through `tile` soon.
+    pub fn is_threatened(&self, tile: IVec2) -> bool {
+        self.0.contains_key(&tile)
+    }
+}
+
+/// A debug tint on one threatened tile.
+#[derive(Component)]
+struct DangerVisualizer(IVec2);
+
+fn clear_danger_map(mut danger: ResMut<DangerMap>) {
+    danger.0.clear();
+}
+
+/// Drops the lapsed threats and marks the path ahead of every moving projectile.
+fn update_danger_map(
+    time: Res<Time>,
+    mut danger: ResMut<DangerMap>,
+    map_data: Res<MapData>,
+    projectiles: Query<(&GridMover, Option<&Bouncable>), With<Projectile>>,
+) {
+    let now = time.elapsed_secs();
+    danger.0.retain(|_, expires| *expires > now);
+    let expires = now + DANGER_DECAY;
+    for (mover, bouncable) in &projectiles {
+        if mover.direction == IVec2::ZERO {
+            continue;
+        }
+        let bounces = bouncable.map_or(0, |b| b.remaining);
+        danger.0.insert(mover.grid_pos, expires);
+        mark_path(
+            &mut danger,
+            &map_data,
+            mover.grid_pos,
+            mover.direction,
+            bounces,
+            DANGER_LOOKAHEAD,
+            expires,
+        );
+    }
+}
+
+/// Marks the tiles a projectile on `pos` heading in `dir` passes through in its next `steps`
+/// steps, branching at every wall it can bounce off.
+fn mark_path(
+    danger: &mut DangerMap,
+    map_data: &MapData,
+    mut pos: IVec2,
+    dir: IVec2,
+    bounces: u32,
+    mut steps: usize,
+    expires: f32,
+) {
+    while steps > 0 {
+        steps -= 1;
+        if is_wall(pos + dir, map_data) {
+            if bounces == 0 {
+                return;
+            }
+            // The bounce happens in place, so it uses up a step without a new tile.
+            for reflected in reflections(dir) {
+                mark_path(
+                    danger,
+                    map_data,
+                    pos,
+                    reflected,
+                    bounces - 1,
+                    steps,
+                    expires,
+                );
+            }
+            return;
+        }
+        pos += dir;
+        danger.0.insert(pos, expires);
+    }
+}
+
+/// Every direction a projectile heading in `dir` could leave a wall in: off either axis, or
+/// straight back out of a corner. A straight shot can only come straight back.
+fn reflections(dir: IVec2) -> Vec<IVec2> {
+    let mut candidates = Vec::new();
+    for reflected in [IVec2::new(dir.x, -dir.y), IVec2::new(-dir.x, dir.y), -dir] {
+        if reflected != dir && !candidates.contains(&reflected) {
+            candidates.push(reflected);
+        }
+    }
+    candidates
+}
+
+/// Spawns and despawns tint sprites to match the danger map, and keeps them on their tiles.
+fn sync_danger_visuals(
+    mut commands: Commands,
+    danger: Res<DangerMap>,
+    game_assets: Res<GameAssets>,
+    grid_space: GridSpace,
+    mut visuals: Query<(Entity, &DangerVisualizer, &mut Transform)>,
+) {
+    let mut shown = HashSet::new();
+    for (entity, visualizer, mut transform) in &mut visuals {
+        if !danger.0.contains_key(&visualizer.0) {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let world = grid_space.tile_to_world(visualizer.0);
+        transform.translation.x = world.x;
+        transform.translation.y = world.y;
+        shown.insert(visualizer.0);
+    }
+    let color = game_assets.palette.colors[2].with_alpha(DANGER_TINT_ALPHA);
+    for &tile in danger.0.keys() {
+        if shown.contains(&tile) {
+            continue;
+        }
+        let world = grid_space.tile_to_world(tile);
+        commands.spawn((
+            Sprite::from_color(color, Vec2::splat(TILE_SIZE)),
+            // Over the floor, under the reservation sprites.
+            Transform::from_xyz(world.x, world.y, 1.4),
+            DangerVisualizer(tile),
+            GameEntity,
+        ));
+    }
+}
+
+/// Removes the tints once the layer is turned off.
+fn clear_danger_visuals(mut commands: Commands, visuals: Query<Entity, With<DangerVisualizer>>) {
+    for entity in &visuals {
+        commands.entity(entity).despawn();
+    }
+}
@@ -1,51 +1,529 @@
 use crate::assets::GameAssets;
-use crate::components::{GameEntity, GameState};
+use crate::audio::{SfxBudget, SfxCategory};
+use crate::collider::{Collider, ColliderShape};
+use crate::components::{GameEntity, GameSpeed, GameState};
+use crate::enemy::{AiDebugInfo, Enemy, EnemyMovementAI, Turner};
+use crate::explosion::ExplosionConfig;
+use crate::grid_movement::{GridMover, IntendedDirection, MovementSystems};
+use crate::grid_reservation::GridReservations;
+use crate::map::MapData;
+use crate::particle::Particle;
+use crate::player::{CameraConfig, CameraDebug, Player};
+use crate::profiler::{format_timings, SystemTimings};
+use crate::projectile::Projectile;
+use crate::tilemap::{GridSpace, MapOffset, TILE_SIZE};
 use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use std::time::Duration;
 
 pub struct DebugPlugin;
 
 impl Plugin for DebugPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), setup_fps_display)
+        app.insert_resource(DebugFlags::from_environment())
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (setup_fps_display, setup_config_panel, setup_camera_text),
+            )
+            .add_systems(OnExit(GameState::Loading), spawn_debug_legend)
+            .add_systems(Update, (toggle_debug_flags, update_debug_legend).chain())
+            .add_systems(
+                Update,
+                set_game_speed_from_keys.run_if(|flags: Res<DebugFlags>| flags.master),
+            )
             .add_systems(
                 Update,
-                (update_fps_display, test_clear).run_if(in_state(GameState::Playing)),
+                (
+                    update_panel_visibility,
+                    update_fps_display.run_if(debug_layer(|f| f.panel)),
+                    test_clear,
+                    update_config_panel,
+                    update_camera_text,
+                    draw_camera_gizmos
+                        .after(MovementSystems::AdjustScroll)
+                        .run_if(debug_layer(|f| f.camera)),
+                    // Gizmos are immediate-mode, so there is nothing to clean up on state exit.
+                    draw_collider_gizmos
+                        .after(MovementSystems::ApplyOffsetChanges)
+                        .run_if(debug_layer(|f| f.colliders)),
+                    // The AI only fills in `AiDebugInfo` on enemies that have it, so it's
+                    // attached while the layer is on and stripped when it goes off.
+                    attach_ai_debug_info
+                        .before(EnemyMovementAI)
+                        .run_if(debug_layer(|f| f.ai)),
+                    detach_ai_debug_info
+                        .run_if(resource_changed::<DebugFlags>.and(not(debug_layer(|f| f.ai)))),
+                    draw_ai_gizmos
+                        .after(MovementSystems::ApplyOffsetChanges)
+                        .run_if(debug_layer(|f| f.ai)),
+                    (update_coord_labels, inspect_tile_under_cursor)
+                        .after(MovementSystems::ApplyOffsetChanges)
+                        .run_if(debug_layer(|f| f.coords)),
+                    despawn_coord_labels
+                        .run_if(resource_changed::<DebugFlags>.and(not(debug_layer(|f| f.coords)))),
+                )
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
 
+/// Command-line flag that turns the debug panel on at startup.
+const DEBUG_ARG: &str = "--debug";
+
+/// Environment variable that does the same as `DEBUG_ARG`.
+const DEBUG_ENV: &str = "GRIDMAN_DEBUG";
+
+/// Which debug visualizations are on. Every debug system should key its run condition off
+/// these (see `debug_layer`) rather than adding a toggle of its own.
+///
+/// F3 turns debugging on and off as a whole; the layer keys only work while it's on.
+#[derive(Resource, Default)]
+pub struct DebugFlags {
+    /// The master toggle (F3). Layers are only drawn while this is on.
+    pub master: bool,
+    /// Map coordinates on every fourth tile, and the tile under the cursor, which a click
+    /// logs (F2).
+    pub coords: bool,
+    /// Frame rate and entity counters (F4).
+    pub panel: bool,
+    /// The loaded config values, shown under the panel (F4 again).
+    pub config: bool,
+    /// Draws colliders, grid cells and projectile target tiles with gizmos (F5).
+    pub colliders: bool,
+    /// A sprite on every reserved grid cell (F6).
+    pub reservations: bool,
+    /// Draws the camera follow state: buffer zone, view center, player and target (F7).
+    pub camera: bool,
+    /// Enemy AI decisions: intended and last direction, the last rejected tile, and the tiles
+    /// projectiles threaten (F8).
+    pub ai: bool,
+}
+
+impl DebugFlags {
+    /// Everything starts off unless `--debug` or `GRIDMAN_DEBUG` asks for the panel; debug
+    /// builds show it by default.
+    fn from_environment() -> Self {
+        let requested =
+            std::env::args().any(|arg| arg == DEBUG_ARG) || std::env::var_os(DEBUG_ENV).is_some();
+        let on = requested || cfg!(debug_assertions);
+        DebugFlags {
+            master: on,
+            panel: on,
+            ..default()
+        }
+    }
+
+    /// The layers in legend order, with their keys and current state.
+    fn layers(&self) -> [(&'static str, &'static str, bool); 6] {
+        [
+            ("F2", "coords", self.coords),
+            ("F4", "panel", self.panel),
+            ("F5", "colliders", self.colliders),
+            ("F6", "reservations", self.reservations),
+            ("F7", "camera", self.camera),
+            ("F8", "ai", self.ai),
+        ]
+    }
+}
+
+/// A run condition that passes while debugging is on and `layer` picks out an enabled layer,
+/// e.g. `run_if(debug_layer(|f| f.colliders))`.
+pub fn debug_layer(layer: fn(&DebugFlags) -> bool) -> impl Fn(Res<DebugFlags>) -> bool + Clone {
+    move |flags: Res<DebugFlags>| flags.master && layer(&flags)
+}
+
+/// Flips the debug flags in response to their hotkeys.
+fn toggle_debug_flags(keys: Res<ButtonInput<KeyCode>>, mut flags: ResMut<DebugFlags>) {
+    if keys.just_pressed(KeyCode::F3) {
+        flags.master = !flags.master;
+        info!("Debug visualizations: {}", flags.master);
+    }
+    if !flags.master {
+        return;
+    }
+    if keys.just_pressed(KeyCode::F2) {
+        flags.coords = !flags.coords;
+    }
+    if keys.just_pressed(KeyCode::F4) {
+        // Cycles off -> panel -> panel with config -> off.
+        (flags.panel, flags.config) = match (flags.panel, flags.config) {
+            (false, _) => (true, false),
+            (true, false) => (true, true),
+            (true, true) => (false, false),
+        };
+    }
+    if keys.just_pressed(KeyCode::F5) {
+        flags.colliders = !flags.colliders;
+    }
+    if keys.just_pressed(KeyCode::F6) {
+        flags.reservations = !flags.reservations;
+    }
+    if keys.just_pressed(KeyCode::F7) {
+        flags.camera = !flags.camera;
+    }
+    if keys.just_pressed(KeyCode::F8) {
+        flags.ai = !flags.ai;
+    }
+}
+
+/// Game speeds for Ctrl+1 to Ctrl+4 while debugging.
+const SPEED_KEYS: [(KeyCode, f32); 4] = [
+    (KeyCode::Digit1, 0.25),
+    (KeyCode::Digit2, 0.5),
+    (KeyCode::Digit3, 1.0),
+    (KeyCode::Digit4, 2.0),
+];
+
+fn set_game_speed_from_keys(keys: Res<ButtonInput<KeyCode>>, mut game_speed: ResMut<GameSpeed>) {
+    if !keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    for (key, speed) in SPEED_KEYS {
+        if keys.just_pressed(key) {
+            game_speed.value = speed;
+            info!("Game speed: {}x", speed);
+        }
+    }
+}
+
+#[derive(Component)]
+struct DebugLegend;
+
+/// Lists the debug layers and which are on, in the top-right corner. Uses Bevy's built-in font
+/// so it works in any state.
+fn spawn_debug_legend(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 10.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        TextLayout::new_with_justify(JustifyText::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.0),
+            right: Val::Px(4.0),
+            ..default()
+        },
+        GlobalZIndex(20),
+        Visibility::Hidden,
+        DebugLegend,
+    ));
+}
+
+fn update_debug_legend(
+    flags: Res<DebugFlags>,
+    mut query: Query<(&mut Text, &mut Visibility), With<DebugLegend>>,
+    added: Query<(), Added<DebugLegend>>,
+) {
+    if !flags.is_changed() && added.is_empty() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+    if !flags.master {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Inherited;
+    let mut lines = vec!["F3 debug".to_string()];
+    for (key, name, on) in flags.layers() {
+        lines.push(format!(
+            "{} {} {}",
+            key,
+            name,
+            if on { "ON" } else { "off" }
+        ));
+    }
+    text.0 = lines.join("\n");
+}
+
+/// Shows the panel only while its layer is on.
+fn update_panel_visibility(
+    flags: Res<DebugFlags>,
+    mut query: Query<&mut Visibility, With<FpsText>>,
+) {
+    let shown = flags.master && flags.panel;
+    for mut visibility in &mut query {
+        visibility.set_if_neq(if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+/// Draws every `Collider` as a rectangle at its transform, the hurtbox used for adjacency by
+/// players and enemies, the grid cell each `GridMover` currently occupies, and the tile
+/// each moving projectile is heading into.
+///
+/// Runs after the movement and scroll sets so the boxes line up with the rendered sprites.
+fn draw_collider_gizmos(
+    mut gizmos: Gizmos,
+    game_assets: Res<GameAssets>,
+    grid_space: GridSpace,
+    colliders: Query<(&Transform, &Collider, Has<Player>, Has<Enemy>)>,
+    movers: Query<(&Transform, &GridMover, Has<Projectile>)>,
+) {
+    let palette = &game_assets.palette.colors;
+    let collider_color = palette[5];
+    let expanded_color = palette[2];
+    let cell_color = palette[13];
+    let target_color = palette[4];
+
+    // Same grid-to-world conversion used by `update_grid_positions`.
+    let cell_to_world = |cell: IVec2| grid_space.tile_to_world(cell);
+
+    for (transform, collider, is_player, is_enemy) in &colliders {
+        let pos = collider.center(transform.translation);
+        if !grid_space.viewport.is_in_view(pos) {
+            continue;
+        }
+        draw_collider(&mut gizmos, pos, collider, collider_color);
+        if is_player || is_enemy {
+            draw_collider(&mut gizmos, pos, &collider.hurtbox(), expanded_color);
+        }
+    }
+
+    for (transform, mover, is_projectile) in &movers {
+        if !grid_space.viewport.is_in_view(transform.translation.xy()) {
+            continue;
+        }
+        gizmos.rect_2d(
+            cell_to_world(mover.grid_pos),
+            Vec2::splat(TILE_SIZE),
+            cell_color,
+        );
+        if is_projectile && mover.direction != IVec2::ZERO {
+            gizmos.rect_2d(
+                cell_to_world(mover.grid_pos + mover.direction),
+                Vec2::splat(TILE_SIZE * 0.9),
+                target_color,
+            );
+        }
+    }
+}
+
+/// One line of the debug panel, each its own text span so it can be colored on its own.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum DebugStat {
+    Fps,
+    /// The `GameSpeed` multiplier, highlighted when it isn't 1x.
+    Speed,
+    Entities,
+    Enemies,
+    Projectiles,
+    Reservations,
+    Particles,
+    PlayerCell,
+    MapOffset,
+    Sfx,
+    /// Average and worst frame time of each instrumented system, slowest first.
+    Timings,
+}
+
+impl DebugStat {
+    const ALL: [DebugStat; 11] = [
+        DebugStat::Fps,
+        DebugStat::Speed,
+        DebugStat::Entities,
+        DebugStat::Enemies,
+        DebugStat::Projectiles,
+        DebugStat::Reservations,
+        DebugStat::Particles,
+        DebugStat::PlayerCell,
+        DebugStat::MapOffset,
+        DebugStat::Sfx,
+        DebugStat::Timings,
+    ];
+}
+
+/// Below this frame rate the FPS line turns red.
+const LOW_FPS: f64 = 30.0;
+
+const DEBUG_TEXT_COLOR: Color = Color::srgb(0.9, 0.9, 0.9); // Light gray for minimalist look
+const DEBUG_WARN_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+
 #[derive(Component)]
 struct FpsText;
 
 fn setup_fps_display(mut commands: Commands, game_assets: Res<GameAssets>) {
     info!("Setting up FPS display");
+    let font = TextFont {
+        font: game_assets.font.clone(),
+        font_size: 8.0,
+        ..default()
+    };
+    let root = commands
+        .spawn((
+            Text::new(""),
+            font.clone(),
+            TextColor(DEBUG_TEXT_COLOR),
+            TextLayout::new_with_justify(JustifyText::Left),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            FpsText,
+            GameEntity, // Ensures cleanup when exiting GameState::Playing
+        ))
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        for stat in DebugStat::ALL {
+            parent.spawn((
+                TextSpan::new(""),
+                font.clone(),
+                TextColor(DEBUG_TEXT_COLOR),
+                stat,
+            ));
+        }
+    });
+}
+
+#[derive(Component)]
+struct ConfigPanelText;
+
+fn setup_config_panel(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: game_assets.font.clone(),
+            font_size: 8.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
+        TextLayout::new_with_justify(JustifyText::Left),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(30.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+        ConfigPanelText,
+        GameEntity,
+    ));
+}
+
+/// Shows or hides the config panel and fills it with the currently loaded values.
+fn update_config_panel(
+    flags: Res<DebugFlags>,
+    config: Res<ExplosionConfig>,
+    camera: Res<CameraConfig>,
+    mut query: Query<(&mut Text, &mut Visibility), With<ConfigPanelText>>,
+    added: Query<(), Added<ConfigPanelText>>,
+) {
+    if !flags.is_changed() && !config.is_changed() && !camera.is_changed() && added.is_empty() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+    if flags.master && flags.panel && flags.config {
+        *visibility = Visibility::Inherited;
+        text.0 = format!("[explosion]\n{:#?}\n[camera]\n{:#?}", *config, *camera);
+    } else {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+/// Draws the camera buffer zone around the view center, with markers for the view center
+/// (white), the player (green) and the lookahead target (yellow).
+fn draw_camera_gizmos(
+    mut gizmos: Gizmos,
+    game_assets: Res<GameAssets>,
+    config: Res<CameraConfig>,
+    camera: Res<CameraDebug>,
+    grid_space: GridSpace,
+) {
+    let palette = &game_assets.palette.colors;
+    let to_world = |map_pos: Vec2| grid_space.grid_to_world(map_pos);
+    let buffer = Vec2::new(config.buffer_width, config.buffer_height) * TILE_SIZE;
+    gizmos.rect_2d(to_world(camera.view_center), buffer, palette[13]);
+    gizmos.cross_2d(to_world(camera.view_center), TILE_SIZE * 0.5, palette[12]);
+    gizmos.cross_2d(to_world(camera.player), TILE_SIZE * 0.3, palette[5]);
+    gizmos.circle_2d(to_world(camera.target), TILE_SIZE * 0.25, palette[4]);
+}
+
+#[derive(Component)]
+struct CameraDebugText;
+
+fn setup_camera_text(mut commands: Commands, game_assets: Res<GameAssets>) {
     commands.spawn((
-        Text::new("FPS: --"),
+        Text::new(""),
         TextFont {
             font: game_assets.font.clone(),
             font_size: 8.0,
             ..default()
         },
-        TextColor(Color::srgb(0.9, 0.9, 0.9)), // Light gray for minimalist look
+        TextColor(Color::srgb(0.9, 0.9, 0.9)),
         TextLayout::new_with_justify(JustifyText::Left),
         Node {
             position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
+            bottom: Val::Px(10.0),
             left: Val::Px(10.0),
             ..default()
         },
-        BackgroundColor(Color::NONE),
-        FpsText,
-        GameEntity, // Ensures cleanup when exiting GameState::Playing
+        Visibility::Hidden,
+        CameraDebugText,
+        GameEntity,
     ));
 }
 
+/// Shows the live camera follow values while the camera overlay is on.
+fn update_camera_text(
+    flags: Res<DebugFlags>,
+    camera: Res<CameraDebug>,
+    mut query: Query<(&mut Text, &mut Visibility), With<CameraDebugText>>,
+) {
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+    if !(flags.master && flags.camera) {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Inherited;
+    let tau = camera
+        .tau
+        .map_or("-- (in buffer)".to_string(), |tau| format!("{:.2}s", tau));
+    text.0 = format!(
+        "view {:.1},{:.1}\nplayer {:.1},{:.1}\ntarget {:.1},{:.1}\ntau {}",
+        camera.view_center.x,
+        camera.view_center.y,
+        camera.player.x,
+        camera.player.y,
+        camera.target.x,
+        camera.target.y,
+        tau
+    );
+}
+
+/// Refreshes the debug panel. Lines that point at a problem turn red: a low frame rate, or
+/// more grid reservations than enemies plus the player, which means a reservation has leaked.
+#[allow(clippy::too_many_arguments)]
 fn update_fps_display(
     diagnostics: Res<DiagnosticsStore>,
-    mut query: Query<&mut Text, With<FpsText>>,
-    time: Res<Time>,
+    sfx_budget: Res<SfxBudget>,
+    timings: Res<SystemTimings>,
+    game_speed: Res<GameSpeed>,
+    reservations: Res<GridReservations>,
+    map_offset: Res<MapOffset>,
+    entities: Query<()>,
+    enemies: Query<(), With<Enemy>>,
+    projectiles: Query<(), With<Projectile>>,
+    particles: Query<(), With<Particle>>,
+    player: Query<&GridMover, With<Player>>,
+    mut spans: Query<(&mut TextSpan, &mut TextColor, &DebugStat)>,
+    time: Res<Time<Real>>,
     mut timer: Local<Timer>, // Local timer to track update interval
 ) {
     // Update every 0.5 seconds
@@ -56,16 +534,261 @@ fn update_fps_display(
     timer.set_duration(Duration::from_secs_f32(0.5));
     timer.reset();
 
-    if let Ok(mut text) = query.single_mut() {
-        // Get FPS from diagnostics
-        if let Some(fps) = diagnostics
-            .get(&FrameTimeDiagnosticsPlugin::FPS)
-            .and_then(|fps| fps.smoothed())
-        {
-            text.0 = format!("FPS: {:.0}", fps);
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed());
+    let enemy_count = enemies.iter().len();
+
+    for (mut span, mut color, stat) in &mut spans {
+        let (line, warn) = match stat {
+            DebugStat::Fps => match fps {
+                Some(fps) => (format!("FPS: {:.0}", fps), fps < LOW_FPS),
+                None => ("FPS: --".to_string(), false),
+            },
+            DebugStat::Speed => (
+                format!("speed: {}x", game_speed.value),
+                game_speed.value != 1.0,
+            ),
+            DebugStat::Entities => (format!("entities: {}", entities.iter().len()), false),
+            DebugStat::Enemies => (format!("enemies: {}", enemy_count), false),
+            DebugStat::Projectiles => (format!("projectiles: {}", projectiles.iter().len()), false),
+            DebugStat::Reservations => (
+                format!("reservations: {}", reservations.0.len()),
+                reservations.0.len() > enemy_count + 1,
+            ),
+            DebugStat::Particles => (format!("particles: {}", particles.iter().len()), false),
+            DebugStat::PlayerCell => match player.single() {
+                Ok(mover) => (
+                    format!("player: {},{}", mover.grid_pos.x, mover.grid_pos.y),
+                    false,
+                ),
+                Err(_) => ("player: --".to_string(), false),
+            },
+            DebugStat::MapOffset => (
+                format!("map offset: {},{}", map_offset.0.x, map_offset.0.y),
+                false,
+            ),
+            // Live sound effects per category, against their caps.
+            DebugStat::Sfx => {
+                let lines: Vec<String> = SfxCategory::ALL
+                    .into_iter()
+                    .map(|category| {
+                        format!(
+                            "{}: {}/{}",
+                            category.label(),
+                            sfx_budget.live(category),
+                            category.cap()
+                        )
+                    })
+                    .collect();
+                (lines.join("\n"), false)
+            }
+            DebugStat::Timings => {
+                let rows = timings.summary();
+                if rows.is_empty() {
+                    ("timings: --".to_string(), false)
+                } else {
+                    (
+                        format!("timings avg/max ms:\n{}", format_timings(&rows)),
+                        false,
+                    )
+                }
+            }
+        };
+        // Every line but the last ends in a newline, since spans are laid out inline.
+        span.0 = if *stat == DebugStat::Timings {
+            line
         } else {
-            info!("FPS diagnostic not available");
-            text.0 = "FPS: --".to_string();
+            format!("{}\n", line)
+        };
+        color.0 = if warn {
+            DEBUG_WARN_COLOR
+        } else {
+            DEBUG_TEXT_COLOR
+        };
+    }
+}
+
+fn attach_ai_debug_info(
+    mut commands: Commands,
+    enemies: Query<Entity, (With<Enemy>, Without<AiDebugInfo>)>,
+) {
+    for entity in &enemies {
+        commands.entity(entity).insert(AiDebugInfo::default());
+    }
+}
+
+fn detach_ai_debug_info(mut commands: Commands, enemies: Query<Entity, With<AiDebugInfo>>) {
+    for entity in &enemies {
+        commands.entity(entity).remove::<AiDebugInfo>();
+    }
+}
+
+/// Draws each visible enemy's intended direction (yellow arrow), the direction it last moved
+/// in (blue arrow, shorter), and a cross on the last tile its AI rejected (red).
+fn draw_ai_gizmos(
+    mut gizmos: Gizmos,
+    game_assets: Res<GameAssets>,
+    grid_space: GridSpace,
+    enemies: Query<(
+        &Transform,
+        &IntendedDirection,
+        &AiDebugInfo,
+        Option<&Turner>,
+    )>,
+) {
+    let palette = &game_assets.palette.colors;
+    let intended_color = palette[4];
+    let last_known_color = palette[10];
+    let rejected_color = palette[2];
+
+    for (transform, intended, info, turner) in &enemies {
+        let pos = transform.translation.xy();
+        if !grid_space.viewport.is_in_view(pos) {
+            continue;
+        }
+        if intended.0 != IVec2::ZERO {
+            let end = pos + intended.0.as_vec2() * TILE_SIZE * 0.8;
+            gizmos.arrow_2d(pos, end, intended_color);
+        }
+        let last_known = turner.map(|t| t.last_known_direction);
+        if let Some(dir) = last_known.filter(|d| *d != IVec2::ZERO) {
+            let end = pos + dir.as_vec2() * TILE_SIZE * 0.5;
+            gizmos.arrow_2d(pos, end, last_known_color);
+        }
+        if let Some(cell) = info.rejected {
+            let cell_pos = grid_space.tile_to_world(cell);
+            gizmos.cross_2d(cell_pos, TILE_SIZE * 0.3, rejected_color);
+        }
+    }
+}
+
+/// How far apart the coordinate labels are, in tiles along each axis.
+const COORD_LABEL_SPACING: i32 = 4;
+
+/// Above the tiles and the things on them.
+const COORD_LABEL_Z: f32 = 5.0;
+
+/// A label showing the map coordinates of the tile it sits on.
+#[derive(Component)]
+struct CoordLabel(IVec2);
+
+/// Keeps a label on every tile in view whose coordinates are both multiples of
+/// `COORD_LABEL_SPACING`. The labels are respawned when the view scrolls a whole tile or
+/// resizes, and otherwise just follow the sub-tile scroll.
+fn update_coord_labels(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    grid_space: GridSpace,
+    mut labels: Query<(Entity, &CoordLabel, &mut Transform)>,
+) {
+    let to_world = |pos: IVec2| grid_space.tile_to_world(pos).extend(COORD_LABEL_Z);
+    let scrolled = grid_space.map_offset.is_changed() || grid_space.viewport.is_changed();
+    if !labels.is_empty() && !scrolled {
+        for (_, label, mut transform) in &mut labels {
+            transform.translation = to_world(label.0);
+        }
+        return;
+    }
+    for (entity, _, _) in &labels {
+        commands.entity(entity).despawn();
+    }
+    let view = grid_space.viewport_rect_in_map();
+    for y in view.min.y..=view.max.y {
+        for x in view.min.x..=view.max.x {
+            let pos = IVec2::new(x, y);
+            if pos.x.rem_euclid(COORD_LABEL_SPACING) != 0
+                || pos.y.rem_euclid(COORD_LABEL_SPACING) != 0
+            {
+                continue;
+            }
+            commands.spawn((
+                Text2d::new(format!("{},{}", pos.x, pos.y)),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 8.0,
+                    ..default()
+                },
+                TextColor(DEBUG_TEXT_COLOR),
+                Transform::from_translation(to_world(pos)),
+                CoordLabel(pos),
+                GameEntity,
+            ));
+        }
+    }
+}
+
+fn despawn_coord_labels(mut commands: Commands, labels: Query<Entity, With<CoordLabel>>) {
+    for entity in &labels {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Outlines the tile under the mouse cursor and, when it's clicked, logs the tile's
+/// coordinates, what kind of tile it is and who has it reserved. The click also fires, as it
+/// always does.
+#[allow(clippy::too_many_arguments)]
+fn inspect_tile_under_cursor(
+    mut gizmos: Gizmos,
+    game_assets: Res<GameAssets>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    grid_space: GridSpace,
+    map_data: Res<MapData>,
+    reservations: Res<GridReservations>,
+    occupants: Query<(Has<Player>, Has<Enemy>)>,
+) {
+    let Some(cursor) = windows.single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+    let Ok((camera, camera_transform)) = cameras.single() else {
+        return;
+    };
+    let Ok(world) = camera.viewport_to_world_2d(camera_transform, cursor) else {
+        return;
+    };
+    let pos = grid_space.world_to_tile(world);
+    gizmos.rect_2d(
+        grid_space.tile_to_world(pos),
+        Vec2::splat(TILE_SIZE),
+        game_assets.palette.colors[4],
+    );
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let kind = match map_data.index(pos) {
+        None => "off the map",
+        Some(idx) if map_data.is_wall[idx] => "wall",
+        Some(_) if map_data.is_lava(pos) => "lava",
+        Some(_) => "floor",
+    };
+    let occupant = match reservations.0.get(&pos) {
+        None => "nobody".to_string(),
+        Some(&entity) => match occupants.get(entity) {
+            Ok((true, _)) => format!("the player ({})", entity),
+            Ok((_, true)) => format!("an enemy ({})", entity),
+            _ => entity.to_string(),
+        },
+    };
+    info!(
+        "Tile {},{}: {}, wall: {}, reserved by {}",
+        pos.x,
+        pos.y,
+        kind,
+        map_data.index(pos).is_some_and(|idx| map_data.is_wall[idx]),
+        occupant
+    );
+}
+
+/// Draws a single collider outline in its own shape.
+fn draw_collider(gizmos: &mut Gizmos, pos: Vec2, collider: &Collider, color: Color) {
+    match collider.shape {
+        ColliderShape::Aabb => {
+            gizmos.rect_2d(pos, collider.size, color);
+        }
+        ColliderShape::Circle { radius } => {
+            gizmos.circle_2d(pos, radius, color);
         }
     }
 }
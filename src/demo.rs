@@ -0,0 +1,260 @@
+// demo.rs
+
+//! Attract mode: a bot-driven demo that plays behind the title screen when it is left idle.
+//!
+//! The demo is an ordinary run with the `Demo` flag set. The player is spawned with
+//! `ControlSource::Bot`, so the keyboard systems ignore it and `drive_bot` steers it instead.
+//! Dying restarts the demo rather than showing the game over screen, nothing is recorded to the
+//! save file or high score table, and any key returns to the title.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+
+use crate::assets::GameAssets;
+use crate::atlas::GameAtlas;
+use crate::components::{Dying, GameMode, GameState};
+use crate::difficulty::DifficultySetting;
+use crate::enemy::Enemy;
+use crate::grid_movement::{is_wall, GridMover, IntendedDirection, MovementSystems};
+use crate::map::MapData;
+use crate::player::{fire_projectile, ControlSource, Player};
+use crate::restart::teardown_run;
+use crate::title::TitlePage;
+
+/// Seconds the title screen must sit untouched before the demo starts.
+const DEMO_IDLE_TIME: f32 = 15.0;
+
+/// How far, in tiles, the bot looks along each axis for an enemy to shoot.
+const BOT_SIGHT_RANGE: i32 = 8;
+
+/// Seconds between the bot's shots.
+const BOT_FIRE_INTERVAL: f32 = 0.35;
+
+/// Enemy distance (in tiles) beyond which the bot stops caring how far away they are.
+const BOT_SAFE_DISTANCE: i32 = 6;
+
+const DIRECTIONS: [IVec2; 4] = [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y];
+
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Demo>()
+            .insert_resource(IdleTimer(Timer::from_seconds(
+                DEMO_IDLE_TIME,
+                TimerMode::Once,
+            )))
+            .add_systems(
+                OnEnter(GameState::Title),
+                (
+                    reset_idle_timer,
+                    despawn_demo_overlay,
+                    (teardown_run, end_demo).chain().run_if(in_demo),
+                ),
+            )
+            .add_systems(OnExit(GameState::Title), spawn_demo_overlay.run_if(in_demo))
+            .add_systems(
+                Update,
+                (
+                    start_demo_when_idle.run_if(in_state(GameState::Title)),
+                    leave_demo_on_input.run_if(in_demo.and(not(in_state(GameState::Title)))),
+                    drive_bot
+                        .in_set(MovementSystems::Input)
+                        .run_if(in_state(GameState::Playing)),
+                ),
+            );
+    }
+}
+
+/// Set while the attract mode demo is running.
+#[derive(Resource, Default)]
+pub struct Demo(pub bool);
+
+/// Run condition that is true while the demo is running.
+pub fn in_demo(demo: Res<Demo>) -> bool {
+    demo.0
+}
+
+/// Time the title screen has been left idle.
+#[derive(Resource)]
+struct IdleTimer(Timer);
+
+#[derive(Component)]
+struct DemoOverlay;
+
+/// Returns true if any key, mouse button or gamepad button was pressed this frame.
+fn any_input_pressed(
+    keys: &ButtonInput<KeyCode>,
+    mouse: &ButtonInput<MouseButton>,
+    gamepads: &Query<&Gamepad>,
+) -> bool {
+    keys.get_just_pressed().next().is_some()
+        || mouse.get_just_pressed().next().is_some()
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.get_just_pressed().next().is_some())
+}
+
+fn reset_idle_timer(mut timer: ResMut<IdleTimer>) {
+    timer.0.reset();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_demo_when_idle(
+    mut timer: ResMut<IdleTimer>,
+    time: Res<Time<Real>>,
+    page: Res<TitlePage>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut demo: ResMut<Demo>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Only the main page counts as idle; someone reading the settings or help is still here.
+    if *page != TitlePage::Main || any_input_pressed(&keys, &mouse, &gamepads) {
+        timer.0.reset();
+        return;
+    }
+    timer.0.tick(time.delta());
+    if timer.0.finished() {
+        info!("Title idle, starting demo");
+        demo.0 = true;
+        *mode = GameMode::Classic;
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn leave_demo_on_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if any_input_pressed(&keys, &mouse, &gamepads) {
+        next_state.set(GameState::Title);
+    }
+}
+
+fn end_demo(mut demo: ResMut<Demo>) {
+    info!("Demo finished");
+    demo.0 = false;
+}
+
+/// Dims the demo behind the game's name and a prompt to press any key.
+fn spawn_demo_overlay(mut commands: Commands, game_assets: Res<GameAssets>) {
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(24.0),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.5)),
+            GlobalZIndex(5),
+            DemoOverlay,
+        ))
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new("GRIDMAN"),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[3].with_alpha(0.8)),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+
+        parent.spawn((
+            Text::new("DEMO - PRESS ANY KEY"),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[4]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+    });
+}
+
+fn despawn_demo_overlay(mut commands: Commands, query: Query<Entity, With<DemoOverlay>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Steers a bot-controlled player: shoots any enemy in a clear line, otherwise steps to the open
+/// neighbouring cell that is farthest from the nearest enemy.
+#[allow(clippy::too_many_arguments)]
+fn drive_bot(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    map_data: Res<MapData>,
+    difficulty: Res<DifficultySetting>,
+    time: Res<Time>,
+    mut rng: GlobalEntropy<WyRand>,
+    mut fire_cooldown: Local<f32>,
+    mut player_query: Query<(&GridMover, &mut IntendedDirection, &ControlSource), With<Player>>,
+    enemy_query: Query<&GridMover, (With<Enemy>, Without<Dying>)>,
+    atlas: Option<Res<GameAtlas>>,
+) {
+    let Ok((mover, mut intended, source)) = player_query.single_mut() else {
+        return;
+    };
+    if *source != ControlSource::Bot {
+        return;
+    }
+    *fire_cooldown -= time.delta_secs();
+
+    let enemies: Vec<IVec2> = enemy_query.iter().map(|enemy| enemy.grid_pos).collect();
+    let pos = mover.grid_pos;
+
+    if *fire_cooldown <= 0.0 {
+        let target = DIRECTIONS.into_iter().find(|&dir| {
+            (1..=BOT_SIGHT_RANGE)
+                .map(|step| pos + dir * step)
+                .take_while(|&cell| !is_wall(cell, &map_data))
+                .any(|cell| enemies.contains(&cell))
+        });
+        if let Some(dir) = target {
+            intended.0 = dir;
+            if fire_projectile(
+                &mut commands,
+                &game_assets,
+                atlas.as_deref(),
+                mover,
+                dir,
+                &map_data,
+                difficulty.0.projectile_bounces(),
+                &mut rng,
+            ) {
+                *fire_cooldown = BOT_FIRE_INTERVAL;
+                return;
+            }
+        }
+    }
+
+    let nearest_enemy = |cell: IVec2| {
+        enemies
+            .iter()
+            .map(|&enemy| (enemy - cell).abs().element_sum())
+            .min()
+            .unwrap_or(BOT_SAFE_DISTANCE)
+            .min(BOT_SAFE_DISTANCE)
+    };
+    let best = DIRECTIONS
+        .into_iter()
+        .filter(|&dir| !is_wall(pos + dir, &map_data))
+        // Prefer to keep going the same way, so the bot doesn't dither between equally safe cells.
+        .max_by_key(|&dir| nearest_enemy(pos + dir) * 2 + i32::from(dir == intended.0));
+    intended.0 = best.unwrap_or(IVec2::ZERO);
+}
@@ -1,5 +1,36 @@
 // diagnostics.rs
-use bevy::{diagnostic::FrameTimeDiagnosticsPlugin, prelude::*};
+
+//! Frame time diagnostics, plus an optional performance log for comparing play sessions.
+//!
+//! Started with `--perf-log`, the `GRIDMAN_PERF_LOG` environment variable or the `perf_log`
+//! console command, the log appends one row per second to `perf_log.csv`. Rows are buffered and
+//! flushed every ten seconds, when logging stops, and when the game exits.
+
+use bevy::app::AppExit;
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use std::time::Duration;
+
+use crate::console::{CommandResult, ConsoleCommands};
+use crate::enemy::Enemy;
+use crate::grid_reservation::GridReservations;
+use crate::platform_io::{append_log, config_exists, since_epoch};
+use crate::projectile::Projectile;
+
+/// Command-line flag that starts the performance log at startup.
+const PERF_LOG_ARG: &str = "--perf-log";
+
+/// Environment variable that does the same as `PERF_LOG_ARG`.
+const PERF_LOG_ENV: &str = "GRIDMAN_PERF_LOG";
+
+/// The performance log, relative to the working directory.
+const PERF_LOG_PATH: &str = "perf_log.csv";
+
+const PERF_LOG_HEADER: &str =
+    "timestamp,fps,frame_time_p95_ms,entities,enemies,projectiles,reservations";
+
+const ROW_INTERVAL: Duration = Duration::from_secs(1);
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
 
 pub struct DiagnosticsPlugin;
 
@@ -7,7 +38,14 @@ impl Plugin for DiagnosticsPlugin {
     fn build(&self, app: &mut App) {
         app
             // Adds frame time diagnostics (FPS, frame time, etc.)
-            .add_plugins(FrameTimeDiagnosticsPlugin::default());
+            .add_plugins(FrameTimeDiagnosticsPlugin::default())
+            .insert_resource(PerfLog::from_environment())
+            .init_resource::<ConsoleCommands>()
+            .add_systems(
+                Update,
+                write_perf_log.run_if(|log: Res<PerfLog>| log.is_running()),
+            )
+            .add_systems(Last, flush_perf_log_on_exit);
         // Logs diagnostics to the console at regular intervals
         //.add_plugins(LogDiagnosticsPlugin::default())
         // Optional diagnostic plugins (uncomment to enable)
@@ -15,5 +53,157 @@ impl Plugin for DiagnosticsPlugin {
         // .add_plugins(bevy::asset::diagnostic::AssetCountDiagnosticsPlugin::<Texture>::default())
         // .add_plugins(bevy::diagnostic::SystemInformationDiagnosticsPlugin::default());
         //
+
+        app.world_mut().resource_mut::<ConsoleCommands>().register(
+            "perf_log",
+            "perf_log [on|off]",
+            "logs performance to perf_log.csv",
+            perf_log_command,
+        );
+    }
+}
+
+/// The CSV performance log. Rows are kept in `pending` until the next flush.
+#[derive(Resource)]
+pub struct PerfLog {
+    running: bool,
+    pending: String,
+    row_timer: Timer,
+    flush_timer: Timer,
+}
+
+impl PerfLog {
+    fn from_environment() -> Self {
+        let mut log = PerfLog {
+            running: false,
+            pending: String::new(),
+            row_timer: Timer::new(ROW_INTERVAL, TimerMode::Repeating),
+            flush_timer: Timer::new(FLUSH_INTERVAL, TimerMode::Repeating),
+        };
+        if std::env::args().any(|arg| arg == PERF_LOG_ARG)
+            || std::env::var_os(PERF_LOG_ENV).is_some()
+        {
+            log.start();
+        }
+        log
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Starts appending to the log, writing the header if the file is new. Logs and leaves the
+    /// log stopped if the file can't be written.
+    fn start(&mut self) -> bool {
+        if self.is_running() {
+            return true;
+        }
+        let header = if config_exists(PERF_LOG_PATH) {
+            String::new()
+        } else {
+            format!("{}\n", PERF_LOG_HEADER)
+        };
+        match append_log(PERF_LOG_PATH, &header) {
+            Ok(()) => {
+                info!("Logging performance to {}", PERF_LOG_PATH);
+                self.running = true;
+                self.row_timer.reset();
+                self.flush_timer.reset();
+                true
+            }
+            Err(err) => {
+                error!("Couldn't open {}: {}", PERF_LOG_PATH, err);
+                false
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        self.flush();
+        self.running = false;
+    }
+
+    /// Appends the pending rows to the file, stopping the log if that fails.
+    fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        if let Err(err) = append_log(PERF_LOG_PATH, &self.pending) {
+            error!("Couldn't write to {}, stopping: {}", PERF_LOG_PATH, err);
+            self.running = false;
+        }
+        self.pending.clear();
+    }
+}
+
+/// The 95th percentile of the recorded frame times, in milliseconds.
+fn frame_time_p95(diagnostics: &DiagnosticsStore) -> Option<f64> {
+    let mut times: Vec<f64> = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)?
+        .values()
+        .copied()
+        .collect();
+    if times.is_empty() {
+        return None;
+    }
+    times.sort_by(f64::total_cmp);
+    let index = ((times.len() as f64 * 0.95).ceil() as usize).clamp(1, times.len()) - 1;
+    Some(times[index])
+}
+
+fn write_perf_log(
+    mut log: ResMut<PerfLog>,
+    time: Res<Time<Real>>,
+    diagnostics: Res<DiagnosticsStore>,
+    reservations: Res<GridReservations>,
+    entities: Query<()>,
+    enemies: Query<(), With<Enemy>>,
+    projectiles: Query<(), With<Projectile>>,
+) {
+    log.flush_timer.tick(time.delta());
+    if log.row_timer.tick(time.delta()).just_finished() {
+        let timestamp = since_epoch().as_secs_f64();
+        let fps = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed());
+        let optional = |value: Option<f64>| value.map_or(String::new(), |v| format!("{:.2}", v));
+        let row = format!(
+            "{:.3},{},{},{},{},{},{}\n",
+            timestamp,
+            optional(fps),
+            optional(frame_time_p95(&diagnostics)),
+            entities.iter().len(),
+            enemies.iter().len(),
+            projectiles.iter().len(),
+            reservations.0.len()
+        );
+        log.pending.push_str(&row);
+    }
+    if log.flush_timer.just_finished() {
+        log.flush();
+    }
+}
+
+fn flush_perf_log_on_exit(mut exit_events: EventReader<AppExit>, mut log: ResMut<PerfLog>) {
+    if exit_events.read().next().is_some() {
+        log.stop();
+    }
+}
+
+fn perf_log_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let mut log = world.resource_mut::<PerfLog>();
+    let on = match args {
+        [] => !log.is_running(),
+        ["on"] => true,
+        ["off"] => false,
+        _ => return Err("expected on or off".to_string()),
+    };
+    if !on {
+        log.stop();
+        return Ok("Performance log stopped".to_string());
+    }
+    if !log.start() {
+        return Err(format!("couldn't open {}", PERF_LOG_PATH));
     }
+    Ok(format!("Logging performance to {}", PERF_LOG_PATH))
 }
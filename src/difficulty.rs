@@ -0,0 +1,161 @@
+// difficulty.rs
+
+//! The Easy/Normal/Hard difficulty setting and the gameplay values it controls, and the
+//! contact mode that sits alongside it.
+//!
+//! The setting is chosen on the title screen and read by the spawn and shooting systems at the
+//! start of each round. It is persisted through the settings file.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assets::GameAssets;
+use crate::components::{GameEntity, GameState};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DifficultySetting>()
+            .insert_resource(ContactMode::default())
+            .add_systems(OnEnter(GameState::Playing), setup_difficulty_display);
+    }
+}
+
+/// What touching an enemy does, pushed from `Settings`.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContactMode {
+    /// Both the enemy and one of the player's hearts are lost.
+    #[default]
+    Deadly,
+    /// The player loses a heart, and both are knocked apart and briefly stunned.
+    Bump,
+}
+
+impl ContactMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            ContactMode::Deadly => "DEADLY",
+            ContactMode::Bump => "BUMP",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            ContactMode::Deadly => ContactMode::Bump,
+            ContactMode::Bump => ContactMode::Deadly,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn label(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "EASY",
+            Difficulty::Normal => "NORMAL",
+            Difficulty::Hard => "HARD",
+        }
+    }
+
+    /// The next harder difficulty, staying at Hard.
+    pub fn harder(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Normal,
+            _ => Difficulty::Hard,
+        }
+    }
+
+    /// The next easier difficulty, staying at Easy.
+    pub fn easier(self) -> Self {
+        match self {
+            Difficulty::Hard => Difficulty::Normal,
+            _ => Difficulty::Easy,
+        }
+    }
+
+    /// Number of enemies of each type to spawn, given the round's base group size.
+    pub fn enemy_group_size(self, base: u32) -> u32 {
+        match self {
+            Difficulty::Easy => (base / 2).max(1),
+            _ => base,
+        }
+    }
+
+    /// Enemy speed as a fraction of the player's speed.
+    pub fn enemy_speed_factor(self) -> f32 {
+        match self {
+            Difficulty::Hard => 0.6,
+            _ => 0.5,
+        }
+    }
+
+    /// Minimum distance in tiles between the player and a newly spawned enemy.
+    pub fn enemy_spawn_distance(self) -> i64 {
+        match self {
+            Difficulty::Hard => 24,
+            _ => 32,
+        }
+    }
+
+    /// Number of enemy spawners placed on each map.
+    pub fn spawner_count(self) -> usize {
+        match self {
+            Difficulty::Easy => 2,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 5,
+        }
+    }
+
+    /// How many times a projectile can bounce off walls.
+    pub fn projectile_bounces(self) -> u32 {
+        match self {
+            Difficulty::Easy => 5,
+            Difficulty::Normal => 3,
+            Difficulty::Hard => 2,
+        }
+    }
+
+    /// Hits the player can take before dying.
+    pub fn player_hearts(self) -> u32 {
+        match self {
+            Difficulty::Easy => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// The currently selected difficulty.
+#[derive(Resource, Default, Clone, Copy, PartialEq)]
+pub struct DifficultySetting(pub Difficulty);
+
+fn setup_difficulty_display(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    difficulty: Res<DifficultySetting>,
+) {
+    commands.spawn((
+        Text::new(difficulty.0.label()),
+        TextFont {
+            font: game_assets.font.clone(),
+            font_size: 8.0,
+            ..default()
+        },
+        TextColor(game_assets.palette.colors[13]),
+        TextLayout::new_with_justify(JustifyText::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(54.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        GameEntity,
+    ));
+}
@@ -0,0 +1,82 @@
+// endless.rs
+
+//! Endless mode: enemies keep trickling in and the goal is to survive as long as possible.
+//!
+//! The spawn interval shrinks the longer the player survives, and spawning pauses whenever the
+//! living population reaches `ENDLESS_ENEMY_CAP`. There is no victory in this mode.
+
+use bevy::prelude::*;
+
+use crate::components::{GameMode, GameState};
+use crate::enemy::EnemySpawner;
+use crate::grid_movement::GridMover;
+use crate::player::Player;
+use crate::round_intro::round_in_progress;
+use crate::round_timer::RoundTimer;
+use crate::score::EnemyCount;
+
+/// Most enemies allowed alive at once; the spawner waits while the population is at the cap.
+const ENDLESS_ENEMY_CAP: u32 = 400;
+
+/// Seconds between spawns at the start of a run.
+const START_SPAWN_INTERVAL: f32 = 2.0;
+
+/// The shortest the spawn interval can get.
+const MIN_SPAWN_INTERVAL: f32 = 0.15;
+
+/// Seconds of survival over which the interval shrinks from the start value to the minimum.
+const SPAWN_RAMP_TIME: f32 = 240.0;
+
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnCountdown>()
+            .add_systems(OnEnter(GameState::Playing), reset_spawn_countdown)
+            .add_systems(
+                Update,
+                spawn_endless_enemies.run_if(
+                    in_state(GameState::Playing)
+                        .and(resource_equals(GameMode::Endless))
+                        .and(round_in_progress),
+                ),
+            );
+    }
+}
+
+/// Seconds until the next enemy spawns.
+#[derive(Resource, Default)]
+struct SpawnCountdown(f32);
+
+/// Returns the spawn interval after surviving `seconds`.
+fn spawn_interval(seconds: f32) -> f32 {
+    let t = (seconds / SPAWN_RAMP_TIME).clamp(0.0, 1.0);
+    START_SPAWN_INTERVAL + (MIN_SPAWN_INTERVAL - START_SPAWN_INTERVAL) * t
+}
+
+fn reset_spawn_countdown(mut countdown: ResMut<SpawnCountdown>) {
+    countdown.0 = START_SPAWN_INTERVAL;
+}
+
+fn spawn_endless_enemies(
+    mut spawner: EnemySpawner,
+    mut countdown: ResMut<SpawnCountdown>,
+    time: Res<Time>,
+    round_timer: Res<RoundTimer>,
+    enemy_count: Res<EnemyCount>,
+    player_query: Query<&GridMover, With<Player>>,
+) {
+    countdown.0 -= time.delta_secs();
+    if countdown.0 > 0.0 {
+        return;
+    }
+    if enemy_count.value >= ENDLESS_ENEMY_CAP {
+        return; // Try again as soon as the population drops.
+    }
+    let Ok(player) = player_query.single() else {
+        return;
+    };
+
+    spawner.spawn_random(player.grid_pos);
+    countdown.0 = spawn_interval(round_timer.round);
+}
@@ -2,41 +2,89 @@
 
 //! Manages enemy spawning, AI, and behavior.
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use bevy::transform::TransformSystem;
+use bevy_rand::prelude::{ForkableRng, GlobalEntropy, WyRand};
+use serde::{Deserialize, Serialize};
 
 use crate::assets::GameAssets;
+use crate::atlas::{atlas_sprite, AtlasSprite, GameAtlas};
 use crate::collider::Collider;
-use crate::components::{EnemyGroupSize, GameEntity, GameState};
-use crate::grid_movement::{self, GridMover, IntendedDirection, MovementSystems};
+use crate::components::{EnemyGroupSize, EnemySpawned, Faction, GameEntity, GameState};
+use crate::danger::DangerMap;
+use crate::difficulty::DifficultySetting;
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{
+    self, GridMover, IntendedDirection, MovementSystems, PreviousTranslation,
+};
 use crate::grid_reservation::{GridReservations, GridReserver};
 use crate::map::MapData;
+use crate::palette::{recolor_entities, PaletteChanged};
+use crate::pixel_snap::{restore_translations, snap_translations};
 use crate::player::{spawn_player, Player, DEFAULT_PLAYER_SPEED};
-use crate::random::{random_colour, random_float};
-use crate::tilemap::TILE_SIZE;
+use crate::profiler::SystemTimings;
+use crate::quicksave::PendingRestore;
+use crate::random::{random_colour, random_pick, sample_k, shuffle, WeightedTable};
+use crate::tilemap::{ViewportTiles, TILE_SIZE};
 
-const DEFAULT_ENEMY_SPEED: f32 = 0.5 * DEFAULT_PLAYER_SPEED;
+/// Hurtbox multiplier for enemies, tighter than the player's.
+const ENEMY_HURTBOX_SCALE: f32 = 2.0;
+
+/// The directions an enemy can set off in when it spawns.
+const SPAWN_DIRECTIONS: [IVec2; 4] = [IVec2::Y, IVec2::NEG_Y, IVec2::X, IVec2::NEG_X];
+
+/// How many tiles further from the player a chaser will go to stay out of the line of fire.
+const CHASER_DANGER_SLACK: i32 = 1;
 
 /// A plugin for all enemy-related logic.
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Title), setup_enemy_colors)
+        app.init_resource::<EnemyKindTable>()
+            .add_systems(OnEnter(GameState::Title), setup_enemy_colors)
+            .add_systems(
+                Update,
+                // Runs after the generic recolor so the re-rolled colors win.
+                refresh_enemy_colors
+                    .after(recolor_entities)
+                    .run_if(resource_exists::<EnemyColors>.and(on_event::<PaletteChanged>)),
+            )
             .add_systems(
                 OnEnter(GameState::Playing),
-                spawn_enemies.after(spawn_player),
+                // A resumed quicksave brings its own enemies.
+                spawn_enemies
+                    .after(spawn_player)
+                    .run_if(not(resource_exists::<PendingRestore>)),
             )
             .configure_sets(
                 Update,
                 // The AI systems must run before the movement system to avoid a 1-frame delay.
-                EnemyMovementAI.before(MovementSystems::UpdateMover),
+                EnemyMovementAI
+                    .before(MovementSystems::UpdateMover)
+                    .run_if(simulation_running),
             )
             .add_systems(
                 Update,
-                (update_left_turners, update_right_turners)
+                (update_turners, update_chasers)
                     .in_set(EnemyMovementAI)
                     .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                // Runs once all transforms, including scroll adjustments, are final for the frame.
+                separate_overlapping_enemies
+                    .after(MovementSystems::ApplyOffsetChanges)
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
+            )
+            // The snapping restores the offset translation, which then has the offset taken off.
+            .add_systems(First, remove_separation_offsets.after(restore_translations))
+            .add_systems(
+                PostUpdate,
+                apply_separation_offsets
+                    .before(snap_translations)
+                    .before(TransformSystem::TransformPropagate),
             );
     }
 }
@@ -49,26 +97,86 @@ pub struct EnemyMovementAI;
 #[derive(Component)]
 pub struct Enemy;
 
-/// A stateful component for enemies that prefer turning left.
+/// A stateful component for enemies that pick a new direction whenever they are stopped.
 #[derive(Component)]
-pub struct LeftTurner {
+pub struct Turner {
     /// The last direction the enemy was intentionally moving.
     /// This is crucial for making turn decisions after being stopped by a collision.
     pub last_known_direction: IVec2,
 }
 
-/// A stateful component for enemies that prefer turning right.
+/// A marker for enemies that head for the player, sidestepping tiles in the line of fire.
 #[derive(Component)]
-pub struct RightTurner {
-    /// The last direction the enemy was intentionally moving.
-    pub last_known_direction: IVec2,
+pub struct Chaser;
+
+/// One way a stopped enemy can turn, relative to the direction it was moving.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TurnChoice {
+    Left,
+    Right,
+    Back,
 }
 
+impl TurnChoice {
+    /// The direction this turn leads in from `forward`.
+    pub fn apply(self, forward: IVec2) -> IVec2 {
+        match self {
+            TurnChoice::Left => IVec2::new(forward.y, -forward.x),
+            TurnChoice::Right => IVec2::new(-forward.y, forward.x),
+            TurnChoice::Back => -forward,
+        }
+    }
+}
+
+/// The order in which a `Turner` tries its turns. The first open one is taken, and the last
+/// one is taken regardless, so a boxed-in enemy still picks a direction and waits.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TurnPreference {
+    pub order: [TurnChoice; 3],
+}
+
+impl TurnPreference {
+    /// Left, then right, then back.
+    pub const LEFT: Self = Self {
+        order: [TurnChoice::Left, TurnChoice::Right, TurnChoice::Back],
+    };
+    /// Right, then left, then back.
+    pub const RIGHT: Self = Self {
+        order: [TurnChoice::Right, TurnChoice::Left, TurnChoice::Back],
+    };
+}
+
+/// The most recent turn decision of an enemy, for the AI debug layer. Only present while that
+/// layer is on, so the AI systems skip the bookkeeping otherwise.
+#[derive(Component, Default)]
+pub struct AiDebugInfo {
+    /// The last tile the AI considered and found blocked.
+    pub rejected: Option<IVec2>,
+}
+
+/// A purely visual offset that nudges overlapping enemies apart mid-transition.
+///
+/// Like pixel snapping, it is only on the `Transform` while the frame is drawn: it is added in
+/// `PostUpdate` and taken off again at the start of the next frame. Movement, collisions and
+/// the previous translations the swept checks use never see it.
+#[derive(Component, Default)]
+pub struct SeparationOffset(pub Vec2);
+
+/// Fraction of a collider's area two enemies must overlap by before they are pushed apart.
+const SEPARATION_OVERLAP_THRESHOLD: f32 = 0.3;
+
+/// Maximum visual offset, kept small enough that an enemy never leaves its logical tile.
+const MAX_SEPARATION_OFFSET: f32 = TILE_SIZE * 0.25;
+
+/// Time constant (in seconds) for easing the offset towards its target.
+const SEPARATION_TAU: f32 = 0.08;
+
 /// A resource to store the globally chosen colors for each enemy type.
 #[derive(Resource)]
 pub struct EnemyColors {
     pub left_turner: Color,
     pub right_turner: Color,
+    pub chaser: Color,
 }
 
 /// Runs once to select and store the colors for enemies.
@@ -77,131 +185,287 @@ fn setup_enemy_colors(
     game_assets: Res<GameAssets>,
     mut rng: GlobalEntropy<WyRand>,
 ) {
-    let color_a = random_colour(&mut rng, &game_assets);
-    let mut color_b = random_colour(&mut rng, &game_assets);
-    // Ensure the two colors are different.
+    commands.insert_resource(roll_enemy_colors(&mut rng, &game_assets));
+}
+
+/// Re-rolls the enemy colors from the new palette and repaints any enemies already spawned.
+fn refresh_enemy_colors(
+    mut enemy_colors: ResMut<EnemyColors>,
+    game_assets: Res<GameAssets>,
+    mut rng: GlobalEntropy<WyRand>,
+    mut enemies: Query<(&mut Sprite, Option<&TurnPreference>), With<Enemy>>,
+) {
+    *enemy_colors = roll_enemy_colors(&mut rng, &game_assets);
+    for (mut sprite, preference) in &mut enemies {
+        sprite.color = enemy_colors.of(EnemyKind::of(preference));
+    }
+}
+
+fn roll_enemy_colors(
+    rng: &mut GlobalEntropy<WyRand>,
+    game_assets: &Res<GameAssets>,
+) -> EnemyColors {
+    let color_a = random_colour(rng, game_assets);
+    let mut color_b = random_colour(rng, game_assets);
+    // Ensure the colors are all different.
     while color_a == color_b {
-        color_b = random_colour(&mut rng, &game_assets);
+        color_b = random_colour(rng, game_assets);
+    }
+    let mut color_c = random_colour(rng, game_assets);
+    while color_c == color_a || color_c == color_b {
+        color_c = random_colour(rng, game_assets);
     }
-    commands.insert_resource(EnemyColors {
+    EnemyColors {
         left_turner: color_a,
         right_turner: color_b,
-    });
+        chaser: color_c,
+    }
 }
 
-/// Spawns all initial enemies in random, valid locations.
-pub fn spawn_enemies(
-    mut commands: Commands,
-    game_assets: Res<GameAssets>,
-    mut rng: GlobalEntropy<WyRand>,
-    map_data: Res<MapData>,
-    mut reservations: ResMut<GridReservations>,
-    enemy_colors: Res<EnemyColors>,
-    player_query: Query<&GridMover, With<Player>>,
-    enemy_group_size: Res<EnemyGroupSize>,
-) {
-    let player_pos = player_query.single().unwrap().grid_pos;
-    info!("Spawning enemies, player position: {:?}", player_pos);
-    let valid_directions = [
-        IVec2::new(0, 1),
-        IVec2::new(0, -1),
-        IVec2::new(1, 0),
-        IVec2::new(-1, 0),
-    ];
-
-    let num_left_turners = enemy_group_size.0;
-    let num_right_turners = num_left_turners;
-
-    // Spawn LeftTurners
-    for _ in 0..num_left_turners {
-        let (spawn_pos, start_dir) = find_valid_spawn(
-            &mut rng,
-            &map_data,
-            &reservations,
-            &valid_directions,
-            player_pos,
-        );
+impl EnemyColors {
+    /// The color enemies of `kind` are drawn in.
+    pub fn of(&self, kind: EnemyKind) -> Color {
+        match kind {
+            EnemyKind::LeftTurner => self.left_turner,
+            EnemyKind::RightTurner => self.right_turner,
+            EnemyKind::Chaser => self.chaser,
+        }
+    }
+}
 
-        let entity = commands
-            .spawn((
-                Sprite {
-                    color: enemy_colors.left_turner,
-                    image: game_assets.enemy_texture.clone(),
-                    ..default()
-                },
-                Transform::from_xyz(0.0, 0.0, 0.9),
-                Enemy,
-                GridMover {
-                    grid_pos: spawn_pos,
-                    direction: IVec2::ZERO,
-                    progress: 0.0,
-                    speed: DEFAULT_ENEMY_SPEED,
-                },
-                IntendedDirection(start_dir),
-                LeftTurner {
-                    last_known_direction: start_dir,
-                },
-                GridReserver,
-                Collider {
-                    size: Vec2::splat(TILE_SIZE * 0.5),
-                },
-                GameEntity,
-            ))
-            .id();
-        reservations.0.insert(spawn_pos, entity);
+/// Which AI a spawned enemy has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnemyKind {
+    LeftTurner,
+    RightTurner,
+    /// Heads for the player; only spawned from the console so far.
+    Chaser,
+}
+
+impl EnemyKind {
+    /// The order a turner of this kind tries its turns in, or `None` if it isn't a turner.
+    pub fn turn_preference(self) -> Option<TurnPreference> {
+        match self {
+            EnemyKind::LeftTurner => Some(TurnPreference::LEFT),
+            EnemyKind::RightTurner => Some(TurnPreference::RIGHT),
+            EnemyKind::Chaser => None,
+        }
+    }
+
+    /// The kind an enemy with `preference` was spawned as. Only turners have one.
+    pub fn of(preference: Option<&TurnPreference>) -> Self {
+        match preference {
+            Some(&TurnPreference::LEFT) => EnemyKind::LeftTurner,
+            Some(_) => EnemyKind::RightTurner,
+            None => EnemyKind::Chaser,
+        }
+    }
+}
+
+/// Everything needed to spawn enemies, bundled so any system can spawn them the same way.
+#[derive(SystemParam)]
+pub struct EnemySpawner<'w, 's> {
+    commands: Commands<'w, 's>,
+    game_assets: Res<'w, GameAssets>,
+    atlas: Option<Res<'w, GameAtlas>>,
+    rng: GlobalEntropy<'w, WyRand>,
+    map_data: Res<'w, MapData>,
+    reservations: ResMut<'w, GridReservations>,
+    enemy_colors: Res<'w, EnemyColors>,
+    difficulty: Res<'w, DifficultySetting>,
+    kind_table: Res<'w, EnemyKindTable>,
+    spawned_events: EventWriter<'w, EnemySpawned>,
+}
+
+/// How likely each kind of enemy is to be picked for a random spawn, built once.
+#[derive(Resource)]
+pub struct EnemyKindTable(WeightedTable<EnemyKind>);
+
+impl Default for EnemyKindTable {
+    fn default() -> Self {
+        EnemyKindTable(
+            WeightedTable::new([(EnemyKind::LeftTurner, 1.0), (EnemyKind::RightTurner, 1.0)])
+                .expect("enemy kind weights are valid"),
+        )
+    }
+}
+
+impl EnemySpawner<'_, '_> {
+    /// The reservations the spawner claims cells in, for callers that place enemies themselves.
+    pub fn reservations_mut(&mut self) -> &mut GridReservations {
+        &mut self.reservations
     }
 
-    // Spawn RightTurners
-    for _ in 0..num_right_turners {
-        let (spawn_pos, start_dir) = find_valid_spawn(
-            &mut rng,
-            &map_data,
-            &reservations,
-            &valid_directions,
+    /// Spawns one enemy of `kind` at a random valid location away from `player_pos`,
+    /// reserving its cell and reporting it with an `EnemySpawned` event. Returns `None` if no
+    /// cell is free.
+    pub fn spawn(&mut self, kind: EnemyKind, player_pos: IVec2) -> Option<Entity> {
+        let tiles = spawn_tiles(
+            &self.map_data,
+            &self.reservations,
             player_pos,
+            self.difficulty.0.enemy_spawn_distance(),
         );
+        let spawn_pos = sample_k(&mut self.rng, tiles, 1).pop()?;
+        Some(self.spawn_at(kind, spawn_pos))
+    }
 
-        let entity = commands
-            .spawn((
-                Sprite {
-                    color: enemy_colors.right_turner,
-                    image: game_assets.enemy_texture.clone(),
-                    ..default()
-                },
-                Transform::from_xyz(0.0, 0.0, 0.9),
-                Enemy,
-                GridMover {
-                    grid_pos: spawn_pos,
-                    direction: IVec2::ZERO,
-                    progress: 0.0,
-                    speed: DEFAULT_ENEMY_SPEED,
-                },
-                IntendedDirection(start_dir),
-                RightTurner {
+    /// Spawns one enemy per entry in `kinds`, each on a different cell. The eligible cells are
+    /// gathered and shuffled once, so a crowded map runs out of cells rather than looping;
+    /// any enemies that don't fit are skipped with a warning.
+    pub fn spawn_many(&mut self, kinds: &[EnemyKind], player_pos: IVec2) -> Vec<Entity> {
+        let mut tiles: Vec<IVec2> = spawn_tiles(
+            &self.map_data,
+            &self.reservations,
+            player_pos,
+            self.difficulty.0.enemy_spawn_distance(),
+        )
+        .collect();
+        shuffle(&mut self.rng, &mut tiles);
+        if tiles.len() < kinds.len() {
+            warn!("Only room for {} of {} enemies", tiles.len(), kinds.len());
+        }
+        kinds
+            .iter()
+            .zip(tiles)
+            .map(|(&kind, spawn_pos)| self.spawn_at(kind, spawn_pos))
+            .collect()
+    }
+
+    /// Spawns one enemy of a randomly chosen kind.
+    pub fn spawn_random(&mut self, player_pos: IVec2) -> Option<Entity> {
+        let kind = self.random_kind();
+        self.spawn(kind, player_pos)
+    }
+
+    /// Spawns one enemy of a randomly chosen kind on `spawn_pos`, as long as it is still a free
+    /// floor tile with somewhere to go. Returns `None` otherwise.
+    pub fn spawn_random_on(&mut self, spawn_pos: IVec2) -> Option<Entity> {
+        let free = !grid_movement::is_wall(spawn_pos, &self.map_data)
+            && !self.map_data.is_lava(spawn_pos)
+            && !self.reservations.0.contains_key(&spawn_pos)
+            && SPAWN_DIRECTIONS
+                .iter()
+                .any(|&dir| !grid_movement::is_wall(spawn_pos + dir, &self.map_data));
+        if !free {
+            return None;
+        }
+        let kind = self.random_kind();
+        Some(self.spawn_at(kind, spawn_pos))
+    }
+
+    fn random_kind(&mut self) -> EnemyKind {
+        *self.kind_table.0.pick(&mut self.rng)
+    }
+
+    /// Spawns an enemy on `spawn_pos`, which must be a cell from `spawn_tiles`, heading down a
+    /// random open corridor.
+    fn spawn_at(&mut self, kind: EnemyKind, spawn_pos: IVec2) -> Entity {
+        let open_directions: Vec<IVec2> = SPAWN_DIRECTIONS
+            .into_iter()
+            .filter(|&dir| !grid_movement::is_wall(spawn_pos + dir, &self.map_data))
+            .collect();
+        let start_dir = *random_pick(&mut self.rng, &open_directions);
+        self.spawn_facing(kind, spawn_pos, start_dir)
+    }
+
+    /// Spawns one enemy of `kind` on `spawn_pos`, heading in `start_dir`, without checking the
+    /// tile is free. Used to put back the enemies of a saved round.
+    pub fn spawn_facing(&mut self, kind: EnemyKind, spawn_pos: IVec2, start_dir: IVec2) -> Entity {
+        let difficulty = self.difficulty.0;
+
+        let color = self.enemy_colors.of(kind);
+        // The enemy's own generator, so its rolls don't depend on how many other entities drew
+        // from the global one first. The AIs decide without chance, so for now only the debris
+        // thrown when it dies (`spawn_enemy_debris`) draws from it.
+        let entity_rng = self.rng.fork_rng();
+        let mut entity = self.commands.spawn((
+            Sprite {
+                color,
+                ..atlas_sprite(self.atlas.as_deref(), &self.game_assets, AtlasSprite::Enemy)
+            },
+            Transform::from_xyz(0.0, 0.0, 0.9),
+            Enemy,
+            Faction::Enemies,
+            GridMover {
+                grid_pos: spawn_pos,
+                direction: IVec2::ZERO,
+                progress: 0.0,
+                speed: DEFAULT_PLAYER_SPEED * difficulty.enemy_speed_factor(),
+            },
+            IntendedDirection(start_dir),
+            GridReserver,
+            PreviousTranslation::default(),
+            SeparationOffset::default(),
+            Collider {
+                size: Vec2::splat(TILE_SIZE * 0.5),
+                hurtbox_scale: ENEMY_HURTBOX_SCALE,
+                ..default()
+            },
+            entity_rng,
+            GameEntity,
+        ));
+        match kind.turn_preference() {
+            Some(preference) => entity.insert((
+                Turner {
                     last_known_direction: start_dir,
                 },
-                GridReserver,
-                Collider {
-                    size: Vec2::splat(TILE_SIZE * 0.5),
-                },
-                GameEntity,
-            ))
-            .id();
-        reservations.0.insert(spawn_pos, entity);
+                preference,
+            )),
+            None => entity.insert(Chaser),
+        };
+        let entity = entity.id();
+        self.reservations.0.insert(spawn_pos, entity);
+        self.spawned_events.write(EnemySpawned(entity));
+        entity
     }
 }
 
-/// The AI system for LeftTurner enemies.
-/// It decides on a new direction when the current path is blocked.
-fn update_left_turners(
-    mut query: Query<(Entity, &mut IntendedDirection, &GridMover, &mut LeftTurner)>,
+/// Spawns all initial enemies in random, valid locations.
+pub fn spawn_enemies(
+    mut spawner: EnemySpawner,
+    player_query: Query<&GridMover, With<Player>>,
+    enemy_group_size: Res<EnemyGroupSize>,
+    difficulty: Res<DifficultySetting>,
+) {
+    let player_pos = player_query.single().unwrap().grid_pos;
+    info!("Spawning enemies, player position: {:?}", player_pos);
+
+    let per_type = difficulty.0.enemy_group_size(enemy_group_size.0);
+    let kinds: Vec<EnemyKind> = [EnemyKind::LeftTurner, EnemyKind::RightTurner]
+        .into_iter()
+        .flat_map(|kind| std::iter::repeat_n(kind, per_type as usize))
+        .collect();
+    spawner.spawn_many(&kinds, player_pos);
+}
+
+/// The AI system for turner enemies.
+/// It decides on a new direction when the current path is blocked, trying each turn in the
+/// enemy's `TurnPreference` order.
+#[allow(clippy::type_complexity)]
+fn update_turners(
+    mut query: Query<(
+        Entity,
+        &mut IntendedDirection,
+        &GridMover,
+        &mut Turner,
+        &TurnPreference,
+        Option<&mut AiDebugInfo>,
+    )>,
     reservations: Res<GridReservations>,
     map_data: Res<MapData>,
+    timings: Res<SystemTimings>,
 ) {
-    for (entity, mut intended, mover, mut turner) in &mut query {
+    let _span = timings.span("update_turners");
+    for (entity, mut intended, mover, mut turner, preference, debug_info) in &mut query {
         // If the entity is moving, update its last known direction and do nothing else.
         if intended.0 != IVec2::ZERO {
             turner.last_known_direction = intended.0;
+            stop_before_lava(&mut intended, mover, &map_data);
+            continue;
+        }
+        // Stopping short of lava; decide once the current move is finished.
+        if mover.direction != IVec2::ZERO {
             continue;
         }
 
@@ -209,68 +473,219 @@ fn update_left_turners(
         let forward_dir = turner.last_known_direction;
         let current_pos = mover.grid_pos;
 
-        // Priority: Left, Right, Back.
-        let left_dir = IVec2::new(forward_dir.y, -forward_dir.x);
-        let right_dir = IVec2::new(-forward_dir.y, forward_dir.x);
-        let back_dir = -forward_dir;
-
-        let new_dir = if !is_blocked(current_pos + left_dir, entity, &reservations, &map_data) {
-            left_dir
-        } else if !is_blocked(current_pos + right_dir, entity, &reservations, &map_data) {
-            right_dir
-        } else {
-            back_dir
-        };
+        // Each choice is paired with the last tile rejected on the way to it; the forward tile
+        // was rejected by the movement system, which is why the enemy stopped.
+        let [first, second, last] = preference.order.map(|choice| choice.apply(forward_dir));
+        let mut rejected = current_pos + forward_dir;
+        let mut new_dir = last;
+        for dir in [first, second] {
+            if !is_blocked(current_pos + dir, entity, &reservations, &map_data) {
+                new_dir = dir;
+                break;
+            }
+            rejected = current_pos + dir;
+        }
+        if let Some(mut info) = debug_info {
+            info.rejected = Some(rejected);
+        }
 
         intended.0 = new_dir;
         turner.last_known_direction = new_dir;
     }
 }
 
-/// The AI system for RightTurner enemies.
-/// It decides on a new direction when the current path is blocked.
-fn update_right_turners(
-    mut query: Query<(Entity, &mut IntendedDirection, &GridMover, &mut RightTurner)>,
+/// The AI system for chasers.
+///
+/// A chaser picks its next tile from the one it is on or moving into: of the open neighbours,
+/// the one nearest the player, measured in steps ignoring walls. A neighbour that `DangerMap`
+/// marks as in the line of fire is passed over for a safe one up to `CHASER_DANGER_SLACK`
+/// steps further away. Ties keep the chaser going the way it already is. It only looks one tile
+/// ahead, so a wall between it and the player can hold it up.
+#[allow(clippy::type_complexity)]
+fn update_chasers(
+    mut query: Query<
+        (
+            Entity,
+            &mut IntendedDirection,
+            &GridMover,
+            Option<&mut AiDebugInfo>,
+        ),
+        With<Chaser>,
+    >,
+    player: Query<&GridMover, (With<Player>, Without<Chaser>)>,
     reservations: Res<GridReservations>,
     map_data: Res<MapData>,
+    danger: Res<DangerMap>,
+    timings: Res<SystemTimings>,
 ) {
-    for (entity, mut intended, mover, mut turner) in &mut query {
-        // If the entity is moving, update its last known direction and do nothing else.
-        if intended.0 != IVec2::ZERO {
-            turner.last_known_direction = intended.0;
+    let _span = timings.span("update_chasers");
+    let Ok(player) = player.single() else {
+        return;
+    };
+    for (entity, mut intended, mover, debug_info) in &mut query {
+        let from = mover.grid_pos + mover.direction;
+        let heading = if mover.direction != IVec2::ZERO {
+            mover.direction
+        } else {
+            intended.0
+        };
+        // The current heading first, so it wins ties.
+        let mut open: Vec<(IVec2, i32, bool)> = Vec::new();
+        for dir in std::iter::once(heading).chain(SPAWN_DIRECTIONS) {
+            let tile = from + dir;
+            if dir == IVec2::ZERO
+                || open.iter().any(|&(d, ..)| d == dir)
+                || is_blocked(tile, entity, &reservations, &map_data)
+            {
+                continue;
+            }
+            let distance = (player.grid_pos - tile).abs().element_sum();
+            open.push((dir, distance, danger.is_threatened(tile)));
+        }
+        let Some(&(nearest, nearest_distance, _)) =
+            open.iter().min_by_key(|&&(_, distance, _)| distance)
+        else {
+            // Boxed in; wait for a way to open up.
+            intended.0 = IVec2::ZERO;
             continue;
+        };
+        let choice = open
+            .iter()
+            .filter(|&&(_, distance, threatened)| {
+                !threatened && distance <= nearest_distance + CHASER_DANGER_SLACK
+            })
+            .min_by_key(|&&(_, distance, _)| distance)
+            .map_or(nearest, |&(dir, ..)| dir);
+        if let Some(mut info) = debug_info {
+            info.rejected = (choice != nearest).then_some(from + nearest);
         }
+        intended.0 = choice;
+    }
+}
 
-        // The entity has been stopped. Decide where to go next based on its last direction.
-        let forward_dir = turner.last_known_direction;
-        let current_pos = mover.grid_pos;
+/// Pushes apart enemies whose sprites overlap heavily while moving between tiles.
+///
+/// Neighbours are found through `GridReservations`, which already indexes every enemy by the
+/// tiles it occupies or is moving into. Each overlapping enemy eases towards an offset
+/// perpendicular to its movement direction; once the overlap resolves the offset decays back
+/// to zero. The offset is only drawn, by `apply_separation_offsets`.
+fn separate_overlapping_enemies(
+    mut query: Query<
+        (
+            Entity,
+            &GridMover,
+            &Collider,
+            &Transform,
+            &mut SeparationOffset,
+        ),
+        With<Enemy>,
+    >,
+    reservations: Res<GridReservations>,
+    viewport: Res<ViewportTiles>,
+    time: Res<Time>,
+) {
+    // --- Pass 1: compute a target offset for every visible enemy ---
+    let mut targets: Vec<(Entity, Vec2)> = Vec::new();
+    for (entity, mover, collider, transform, _) in &query {
+        let pos = transform.translation.xy();
+        if !viewport.is_in_view(pos) {
+            continue;
+        }
 
-        // Priority: Right, Left, Back.
-        let right_dir = IVec2::new(-forward_dir.y, forward_dir.x);
-        let left_dir = IVec2::new(forward_dir.y, -forward_dir.x);
-        let back_dir = -forward_dir;
+        let mut push = Vec2::ZERO;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let cell = mover.grid_pos + IVec2::new(dx, dy);
+                let Some(&other) = reservations.0.get(&cell) else {
+                    continue;
+                };
+                if other == entity {
+                    continue;
+                }
+                let Ok((_, _, other_collider, other_transform, _)) = query.get(other) else {
+                    continue;
+                };
+
+                // Measure the overlapping area relative to this enemy's collider.
+                let delta = pos - other_transform.translation.xy();
+                let overlap = (collider.size + other_collider.size) / 2.0 - delta.abs();
+                if overlap.x <= 0.0 || overlap.y <= 0.0 {
+                    continue;
+                }
+                let ratio = (overlap.x * overlap.y) / (collider.size.x * collider.size.y);
+                if ratio < SEPARATION_OVERLAP_THRESHOLD {
+                    continue;
+                }
 
-        let new_dir = if !is_blocked(current_pos + right_dir, entity, &reservations, &map_data) {
-            right_dir
-        } else if !is_blocked(current_pos + left_dir, entity, &reservations, &map_data) {
-            left_dir
-        } else {
-            back_dir
+                // Push sideways relative to our own movement, away from the other enemy.
+                let dir = mover.direction.as_vec2().normalize_or_zero();
+                let perp = Vec2::new(-dir.y, dir.x);
+                let side = perp.dot(delta);
+                let sign = if side.abs() > f32::EPSILON {
+                    side.signum()
+                } else if entity < other {
+                    1.0 // Tie-break on entity order so the pair splits symmetrically.
+                } else {
+                    -1.0
+                };
+                push += perp * sign * ratio;
+            }
+        }
+
+        let target = push.clamp_length_max(1.0) * MAX_SEPARATION_OFFSET;
+        targets.push((entity, target));
+    }
+
+    // --- Pass 2: ease each offset towards its target ---
+    // Query iteration order is stable within a system, so the targets line up with this pass.
+    let t = 1.0 - (-time.delta_secs() / SEPARATION_TAU).exp();
+    let mut target_iter = targets.into_iter().peekable();
+    for (entity, _, _, _, mut offset) in &mut query {
+        let target = match target_iter.peek() {
+            Some(&(target_entity, target)) if target_entity == entity => {
+                target_iter.next();
+                target
+            }
+            _ => Vec2::ZERO,
         };
+        offset.0 = offset.0.lerp(target, t);
+        if offset.0.length_squared() < 0.01 {
+            offset.0 = Vec2::ZERO;
+        }
+    }
+}
 
-        intended.0 = new_dir;
-        turner.last_known_direction = new_dir;
+/// Moves each enemy's sprite by its separation offset for drawing.
+fn apply_separation_offsets(mut query: Query<(&mut Transform, &SeparationOffset)>) {
+    for (mut transform, offset) in &mut query {
+        transform.translation += offset.0.extend(0.0);
+    }
+}
+
+/// Takes the separation offsets back off before any game logic runs.
+fn remove_separation_offsets(mut query: Query<(&mut Transform, &SeparationOffset)>) {
+    for (mut transform, offset) in &mut query {
+        transform.translation -= offset.0.extend(0.0);
     }
 }
 
-/// Helper to check if a target grid cell is a wall or reserved by another entity.
+/// Clears `intended` if carrying straight on after the current move would step onto lava, so
+/// the mover stops on the tile it's heading into and picks a new direction there.
+fn stop_before_lava(intended: &mut IntendedDirection, mover: &GridMover, map_data: &MapData) {
+    if map_data.is_lava(mover.grid_pos + mover.direction + intended.0) {
+        intended.0 = IVec2::ZERO;
+    }
+}
+
+/// Helper to check if a target grid cell is a wall, lava or reserved by another entity.
 fn is_blocked(
     target_pos: IVec2,
     self_entity: Entity,
     reservations: &GridReservations,
     map_data: &MapData,
 ) -> bool {
-    if grid_movement::is_wall(target_pos, map_data) {
+    // Enemies steer around lava as if it were a wall.
+    if grid_movement::is_wall(target_pos, map_data) || map_data.is_lava(target_pos) {
         return true;
     }
     if let Some(&occupant) = reservations.0.get(&target_pos) {
@@ -282,40 +697,217 @@ fn is_blocked(
     false
 }
 
-/// Finds a random, non-wall, non-reserved grid cell to spawn an entity, ensuring it's at least 32 cells away from the player using Euclidean distance.
-fn find_valid_spawn(
-    rng: &mut GlobalEntropy<WyRand>,
-    map_data: &MapData,
-    reservations: &GridReservations,
-    directions: &[IVec2],
+/// The cells an enemy may spawn on: floor, unreserved, at least `min_distance` cells from the
+/// player, and with at least one open neighbour to move into.
+pub fn spawn_tiles<'a>(
+    map_data: &'a MapData,
+    reservations: &'a GridReservations,
     player_pos: IVec2,
-) -> (IVec2, IVec2) {
-    let width = map_data.width as i32;
-    let height = map_data.height as i32;
-    const MIN_DIST_SQ: i64 = 32 * 32;
-
-    loop {
-        let x = (random_float(rng) * width as f32) as i32;
-        let y = (random_float(rng) * height as f32) as i32;
-        let pos = IVec2::new(x, y);
-
-        let dx = (x - player_pos.x) as i64;
-        let dy = (y - player_pos.y) as i64;
-        let dist_sq = dx * dx + dy * dy;
-
-        if dist_sq >= MIN_DIST_SQ
-            && !grid_movement::is_wall(pos, map_data)
-            && !reservations.0.contains_key(&pos)
-        {
-            // Found a valid position. Now find a valid starting direction.
-            let start_idx = (random_float(rng) * directions.len() as f32) as usize;
-            for i in 0..directions.len() {
-                let dir = directions[(start_idx + i) % directions.len()];
-                if !grid_movement::is_wall(pos + dir, map_data) {
-                    return (pos, dir);
-                }
-            }
-            // If all directions are blocked, we'll loop and find a new spawn point.
+    min_distance: i64,
+) -> impl Iterator<Item = IVec2> + 'a {
+    let min_dist_sq = min_distance * min_distance;
+    let (width, height) = (map_data.width as i32, map_data.height as i32);
+    (0..height)
+        .flat_map(move |y| (0..width).map(move |x| IVec2::new(x, y)))
+        .filter(move |&pos| {
+            let offset = (pos - player_pos).as_i64vec2();
+            offset.length_squared() >= min_dist_sq
+                && !grid_movement::is_wall(pos, map_data)
+                && !map_data.is_lava(pos)
+                && !reservations.0.contains_key(&pos)
+                && SPAWN_DIRECTIONS
+                    .iter()
+                    .any(|&dir| !grid_movement::is_wall(pos + dir, map_data))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// A world with a 3 by 3 open map and a turner stopped in the middle, last moving along x.
+    fn stopped_turner(preference: TurnPreference) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(MapData {
+            width: 3,
+            height: 3,
+            is_wall: vec![false; 9],
+            is_lava: vec![false; 9],
+        });
+        world.init_resource::<GridReservations>();
+        world.init_resource::<SystemTimings>();
+        let turner = world
+            .spawn((
+                IntendedDirection(IVec2::ZERO),
+                GridMover {
+                    grid_pos: IVec2::ONE,
+                    direction: IVec2::ZERO,
+                    progress: 0.0,
+                    speed: DEFAULT_PLAYER_SPEED,
+                },
+                Turner {
+                    last_known_direction: IVec2::X,
+                },
+                preference,
+                AiDebugInfo::default(),
+            ))
+            .id();
+        (world, turner)
+    }
+
+    /// Runs the turner AI once and returns the direction it chose and the tile it rejected.
+    fn decide(world: &mut World, turner: Entity) -> (IVec2, Option<IVec2>) {
+        world.run_system_once(update_turners).unwrap();
+        let intended = world.get::<IntendedDirection>(turner).unwrap().0;
+        assert_eq!(
+            world.get::<Turner>(turner).unwrap().last_known_direction,
+            intended
+        );
+        (intended, world.get::<AiDebugInfo>(turner).unwrap().rejected)
+    }
+
+    fn block(world: &mut World, tile: IVec2) {
+        world.resource_mut::<MapData>().set_wall(tile, true);
+    }
+
+    #[test]
+    fn turns_are_relative_to_forward() {
+        for forward in [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y] {
+            let left = TurnChoice::Left.apply(forward);
+            let right = TurnChoice::Right.apply(forward);
+            assert_eq!(left, -right);
+            assert_eq!(left.dot(forward), 0);
+            assert_eq!(TurnChoice::Back.apply(forward), -forward);
+            // Four turns the same way come back round.
+            let around = (0..4).fold(forward, |dir, _| TurnChoice::Left.apply(dir));
+            assert_eq!(around, forward);
         }
     }
+
+    #[test]
+    fn turner_takes_its_first_open_turn() {
+        let left = TurnChoice::Left.apply(IVec2::X);
+        let right = TurnChoice::Right.apply(IVec2::X);
+
+        let (mut world, turner) = stopped_turner(TurnPreference::LEFT);
+        assert_eq!(decide(&mut world, turner), (left, Some(IVec2::new(2, 1))));
+
+        let (mut world, turner) = stopped_turner(TurnPreference::RIGHT);
+        assert_eq!(decide(&mut world, turner), (right, Some(IVec2::new(2, 1))));
+
+        // With its preferred side walled off, it takes the other.
+        let (mut world, turner) = stopped_turner(TurnPreference::LEFT);
+        block(&mut world, IVec2::ONE + left);
+        assert_eq!(decide(&mut world, turner), (right, Some(IVec2::ONE + left)));
+    }
+
+    #[test]
+    fn turner_treats_reserved_tiles_as_blocked() {
+        let right = TurnChoice::Right.apply(IVec2::X);
+        let (mut world, turner) = stopped_turner(TurnPreference::RIGHT);
+        let other = world.spawn_empty().id();
+        world
+            .resource_mut::<GridReservations>()
+            .0
+            .insert(IVec2::ONE + right, other);
+        let left = TurnChoice::Left.apply(IVec2::X);
+        assert_eq!(decide(&mut world, turner).0, left);
+
+        // Its own reservation doesn't get in its way.
+        let (mut world, turner) = stopped_turner(TurnPreference::RIGHT);
+        world
+            .resource_mut::<GridReservations>()
+            .0
+            .insert(IVec2::ONE + right, turner);
+        assert_eq!(decide(&mut world, turner).0, right);
+    }
+
+    #[test]
+    fn boxed_in_turner_turns_back() {
+        let (mut world, turner) = stopped_turner(TurnPreference::LEFT);
+        let right = TurnChoice::Right.apply(IVec2::X);
+        block(&mut world, IVec2::ONE + TurnChoice::Left.apply(IVec2::X));
+        block(&mut world, IVec2::ONE + right);
+        // Back is taken whether or not it's open, and the last side tried is reported.
+        block(&mut world, IVec2::ONE + IVec2::NEG_X);
+        assert_eq!(
+            decide(&mut world, turner),
+            (IVec2::NEG_X, Some(IVec2::ONE + right))
+        );
+    }
+
+    #[test]
+    fn moving_turner_only_remembers_its_direction() {
+        let (mut world, turner) = stopped_turner(TurnPreference::LEFT);
+        world.get_mut::<IntendedDirection>(turner).unwrap().0 = IVec2::Y;
+        assert_eq!(decide(&mut world, turner), (IVec2::Y, None));
+    }
+
+    /// A world with a 5 by 3 open map, a chaser stopped at its left edge heading along x, and
+    /// the player at `player_pos`.
+    fn stopped_chaser(player_pos: IVec2) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(MapData {
+            width: 5,
+            height: 3,
+            is_wall: vec![false; 15],
+            is_lava: vec![false; 15],
+        });
+        world.init_resource::<GridReservations>();
+        world.init_resource::<SystemTimings>();
+        world.init_resource::<DangerMap>();
+        let mover = |grid_pos| GridMover {
+            grid_pos,
+            direction: IVec2::ZERO,
+            progress: 0.0,
+            speed: DEFAULT_PLAYER_SPEED,
+        };
+        world.spawn((Player, mover(player_pos)));
+        let chaser = world
+            .spawn((
+                IntendedDirection(IVec2::X),
+                mover(IVec2::new(0, 1)),
+                Chaser,
+                AiDebugInfo::default(),
+            ))
+            .id();
+        (world, chaser)
+    }
+
+    fn threaten(world: &mut World, tile: IVec2) {
+        world.resource_mut::<DangerMap>().0.insert(tile, f32::MAX);
+    }
+
+    /// Runs the chaser AI once and returns the direction it chose and the tile it passed over.
+    fn chase(world: &mut World, chaser: Entity) -> (IVec2, Option<IVec2>) {
+        world.run_system_once(update_chasers).unwrap();
+        let intended = world.get::<IntendedDirection>(chaser).unwrap().0;
+        (intended, world.get::<AiDebugInfo>(chaser).unwrap().rejected)
+    }
+
+    #[test]
+    fn chaser_heads_for_the_player() {
+        let (mut world, chaser) = stopped_chaser(IVec2::new(0, 2));
+        assert_eq!(chase(&mut world, chaser), (IVec2::Y, None));
+
+        // Of two equally short ways, it keeps going the way it was.
+        let (mut world, chaser) = stopped_chaser(IVec2::new(4, 2));
+        assert_eq!(chase(&mut world, chaser), (IVec2::X, None));
+    }
+
+    #[test]
+    fn chaser_steps_out_of_the_line_of_fire_when_it_costs_little() {
+        let (mut world, chaser) = stopped_chaser(IVec2::new(4, 2));
+        threaten(&mut world, IVec2::new(1, 1));
+        assert_eq!(
+            chase(&mut world, chaser),
+            (IVec2::Y, Some(IVec2::new(1, 1)))
+        );
+
+        // When every safe tile is a long way round, it takes its chances.
+        let (mut world, chaser) = stopped_chaser(IVec2::new(4, 1));
+        threaten(&mut world, IVec2::new(1, 1));
+        assert_eq!(chase(&mut world, chaser), (IVec2::X, None));
+    }
 }
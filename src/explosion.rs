@@ -1,31 +1,139 @@
-use crate::assets::GameAssets;
+use crate::assets::{GameAssets, EXPLOSION_FRAMES};
+use crate::atlas::{atlas_sprite, AtlasSprite, GameAtlas};
 use crate::audio;
-use crate::components::{EnemyDied, GameEntity, GameSpeed, GameState, PlayerDied};
+use crate::collider::{resolve_damage, DamageEvent};
+use crate::components::{
+    Dying, EnemyDied, GameEntity, GameSpeed, GameState, KillSource, Lifetime, PlayerDied,
+};
+use crate::demo::Demo;
+use crate::enemy::Enemy;
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{is_wall, GridMover};
+use crate::grid_reservation::GridReservations;
+use crate::map::MapData;
 use crate::random::{random_colour, random_float};
+use crate::tilemap::GridSpace;
 use bevy::prelude::*;
 use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use serde::Deserialize;
 
 pub struct ExplosionPlugin;
 
 impl Plugin for ExplosionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                spawn_enemy_explosions,
-                spawn_player_explosions,
-                update_explosions,
-                check_player_explosions,
+        app.insert_resource(ChainExplosions(true))
+            .init_resource::<ChainQueue>()
+            .add_systems(OnEnter(GameState::Playing), warn_missing_explosion_sheet)
+            .add_systems(OnExit(GameState::Playing), clear_chain_queue)
+            .add_systems(
+                Update,
+                (
+                    spawn_enemy_explosions,
+                    spawn_player_explosions,
+                    update_explosions,
+                    check_player_explosions,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
             )
-                .chain()
-                .run_if(in_state(GameState::Playing)),
-        );
+            .add_systems(
+                Update,
+                (queue_chain_explosions, process_chain_explosions)
+                    .chain()
+                    .before(resolve_damage)
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and(chain_explosions_enabled)
+                            .and(simulation_running),
+                    ),
+            );
+    }
+}
+
+/// Setting that lets enemy explosions kill neighbouring enemies, which may chain further.
+#[derive(Resource)]
+pub struct ChainExplosions(pub bool);
+
+/// Explosions waiting to detonate their neighbours, drained over successive frames.
+#[derive(Resource, Default)]
+struct ChainQueue(Vec<PendingChain>);
+
+/// A single queued chain hop.
+struct PendingChain {
+    /// The grid cell the explosion occurred in.
+    cell: IVec2,
+    /// Where the explosion was, in world space.
+    origin: Vec3,
+    /// Seconds until this hop detonates its neighbours.
+    delay: f32,
+}
+
+/// Tunable explosion parameters, loaded from the `explosion` section of `config.ron`.
+#[derive(Resource, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ExplosionConfig {
+    /// Seconds an explosion lasts once its animation starts.
+    pub lifetime: f32,
+    /// How much an explosion grows over its lifetime, as a multiple of its starting scale.
+    pub end_scale: f32,
+    /// Number of explosions spawned when the player dies.
+    pub player_explosion_count: u32,
+    /// Width of the square (in pixels) that player explosions are scattered across.
+    pub player_scatter: f32,
+    /// Maximum random delay (in seconds) before each player explosion starts.
+    pub player_stagger: f32,
+    pub enemy_sfx_volume: f32,
+    pub player_sfx_volume: f32,
+    /// Delay between one explosion and the neighbours it sets off.
+    pub chain_hop_delay: f32,
+    /// Debris particles thrown by a dying enemy, picked between min and max.
+    pub enemy_particles_min: u32,
+    pub enemy_particles_max: u32,
+    /// Sparks thrown when a projectile is destroyed against a wall.
+    pub impact_particles: u32,
+    /// Screen shake trauma added per enemy death.
+    pub enemy_shake_trauma: f32,
+    /// Screen shake trauma added when the player dies.
+    pub player_shake_trauma: f32,
+}
+
+impl Default for ExplosionConfig {
+    fn default() -> Self {
+        ExplosionConfig {
+            lifetime: 0.375,
+            end_scale: 1.4,
+            player_explosion_count: 16,
+            player_scatter: 20.0,
+            player_stagger: 2.0,
+            enemy_sfx_volume: 0.3,
+            player_sfx_volume: 0.5,
+            chain_hop_delay: 0.1,
+            enemy_particles_min: 8,
+            enemy_particles_max: 12,
+            impact_particles: 4,
+            enemy_shake_trauma: 0.1,
+            player_shake_trauma: 0.8,
+        }
     }
 }
 
 #[derive(Component)]
 pub struct Explosion {
     pub timer: f32,
+    /// The current sprite sheet frame, advanced from `timer` by `update_explosions`.
+    pub frame: usize,
+}
+
+impl Explosion {
+    /// Creates an explosion whose animation starts after `delay` seconds, with a `Lifetime`
+    /// that covers the delay and the animation.
+    pub fn delayed(delay: f32, config: &ExplosionConfig) -> (Self, Lifetime) {
+        let explosion = Explosion {
+            timer: -delay,
+            frame: 0,
+        };
+        (explosion, Lifetime::from_seconds(delay + config.lifetime))
+    }
 }
 
 #[derive(Component)]
@@ -34,55 +142,95 @@ pub struct PlayerExplosion;
 #[derive(Resource)]
 pub struct PlayerIsDead;
 
-const EXPLOSION_LIFETIME: f32 = 0.375;
+/// Builds the sprite for an explosion, using the animated sprite sheet when it is available
+/// and falling back to the single-frame texture otherwise.
+pub fn explosion_sprite(
+    game_assets: &GameAssets,
+    atlas: Option<&GameAtlas>,
+    images: &Assets<Image>,
+    color: Color,
+) -> Sprite {
+    // The atlas only holds the frames if the sheet had loaded when it was built.
+    let animated = match atlas {
+        Some(atlas) => atlas.index(AtlasSprite::ExplosionFrames).is_some(),
+        None => images.contains(&game_assets.explosion_sheet),
+    };
+    let key = if animated {
+        AtlasSprite::ExplosionFrames
+    } else {
+        AtlasSprite::Explosion
+    };
+    Sprite {
+        color,
+        ..atlas_sprite(atlas, game_assets, key)
+    }
+}
+
+// logs once per round if the explosion sheet could not be loaded
+fn warn_missing_explosion_sheet(game_assets: Res<GameAssets>, images: Res<Assets<Image>>) {
+    if !images.contains(&game_assets.explosion_sheet) {
+        warn!("Explosion sprite sheet not loaded; falling back to single-frame explosions");
+    }
+}
 
 // spawns an explosion at the position of any enemy that has just died
 fn spawn_enemy_explosions(
     mut commands: Commands,
     mut dead_events: EventReader<EnemyDied>,
     game_assets: Res<GameAssets>,
+    images: Res<Assets<Image>>,
+    atlas: Option<Res<GameAtlas>>,
+    config: Res<ExplosionConfig>,
     mut rng: GlobalEntropy<WyRand>,
 ) {
     for EnemyDied(pos) in dead_events.read() {
-        audio::play_with_volume(&mut commands, game_assets.explosion_sfx.clone(), 0.3);
+        audio::play_varied(
+            &mut commands,
+            audio::SfxCategory::Explosion,
+            &game_assets.explosion_sfx,
+            config.enemy_sfx_volume,
+            audio::PITCH_VARIATION,
+            &mut rng,
+        );
+        let color = random_colour(&mut rng, &game_assets);
         commands.spawn((
-            Sprite {
-                image: game_assets.explosion_texture.clone(),
-                color: random_colour(&mut rng, &game_assets),
-                ..Default::default()
-            },
+            explosion_sprite(&game_assets, atlas.as_deref(), &images, color),
             Transform::from_translation(*pos),
-            Explosion { timer: 0.0 },
+            Explosion::delayed(0.0, &config),
             GameEntity,
         ));
     }
 }
 
-const NUM_PLAYER_EXPLOSIONS: i32 = 16;
-
 // spawns multiple explosions at player's location
 fn spawn_player_explosions(
     mut commands: Commands,
     mut player_died_events: EventReader<PlayerDied>,
     game_assets: Res<GameAssets>,
+    images: Res<Assets<Image>>,
+    atlas: Option<Res<GameAtlas>>,
+    config: Res<ExplosionConfig>,
     mut rng: GlobalEntropy<WyRand>,
 ) {
     for PlayerDied(pos) in player_died_events.read() {
         info!("player died");
-        audio::play_with_volume(&mut commands, game_assets.explosion_sfx.clone(), 0.5);
-        for _ in 0..NUM_PLAYER_EXPLOSIONS {
-            let offset_x = (random_float(&mut rng) - 0.5) * 20.0;
-            let offset_y = (random_float(&mut rng) - 0.5) * 20.0;
+        audio::play_varied(
+            &mut commands,
+            audio::SfxCategory::Explosion,
+            &game_assets.explosion_sfx,
+            config.player_sfx_volume,
+            audio::PITCH_VARIATION,
+            &mut rng,
+        );
+        for _ in 0..config.player_explosion_count {
+            let offset_x = (random_float(&mut rng) - 0.5) * config.player_scatter;
+            let offset_y = (random_float(&mut rng) - 0.5) * config.player_scatter;
+            let color = random_colour(&mut rng, &game_assets);
             commands.spawn((
-                Sprite {
-                    image: game_assets.explosion_texture.clone(),
-                    color: random_colour(&mut rng, &game_assets),
-                    ..Default::default()
-                },
+                explosion_sprite(&game_assets, atlas.as_deref(), &images, color),
                 Transform::from_translation(*pos + Vec3::new(offset_x, offset_y, 0.)),
-                Explosion {
-                    timer: -2. * random_float(&mut rng), // stagger the explosion dissipation over time
-                },
+                // stagger the explosion dissipation over time
+                Explosion::delayed(config.player_stagger * random_float(&mut rng), &config),
                 PlayerExplosion,
                 GameEntity,
             ));
@@ -91,42 +239,149 @@ fn spawn_player_explosions(
     }
 }
 
-// fades out explosions over time, despawning when done
+// animates explosions through their sprite sheet frames (or fades them out when only the
+// single-frame texture is available), growing them slightly; their `Lifetime` despawns them
 fn update_explosions(
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut Explosion, &mut Sprite)>,
+    mut query: Query<(&mut Explosion, &mut Sprite, &mut Transform)>,
     time: Res<Time>,
+    config: Res<ExplosionConfig>,
+    game_atlas: Option<Res<GameAtlas>>,
 ) {
-    for (entity, mut explosion, mut sprite) in query.iter_mut() {
+    let lifetime = config.lifetime;
+    for (mut explosion, mut sprite, mut transform) in query.iter_mut() {
         explosion.timer += time.delta_secs();
-        if explosion.timer > EXPLOSION_LIFETIME {
-            commands.entity(entity).despawn();
+
+        // Staggered explosions sit on their first frame until their timer becomes positive.
+        let progress = (explosion.timer / lifetime).clamp(0.0, 1.0);
+        transform.scale = Vec3::splat(1.0 + (config.end_scale - 1.0) * progress);
+
+        // Frames sit at adjacent indices, starting from 0 in the standalone sheet.
+        let first_frame = match game_atlas.as_deref() {
+            Some(game_atlas) if sprite.image == game_atlas.image => {
+                game_atlas.index(AtlasSprite::ExplosionFrames)
+            }
+            _ => sprite.texture_atlas.as_ref().map(|_| 0),
+        };
+        if let (Some(first), Some(atlas)) = (first_frame, sprite.texture_atlas.as_mut()) {
+            let frame =
+                ((progress * EXPLOSION_FRAMES as f32) as usize).min(EXPLOSION_FRAMES as usize - 1);
+            explosion.frame = frame;
+            atlas.index = first + frame;
         } else {
-            let alpha = if explosion.timer < EXPLOSION_LIFETIME / 2.0 {
+            let alpha = if explosion.timer < lifetime / 2.0 {
                 1.0
             } else {
-                1.0 - (explosion.timer - EXPLOSION_LIFETIME / 2.0) / (EXPLOSION_LIFETIME / 2.0)
+                1.0 - (explosion.timer - lifetime / 2.0) / (lifetime / 2.0)
             };
             sprite.color = sprite.color.with_alpha(alpha);
         }
     }
 }
 
+fn chain_explosions_enabled(setting: Res<ChainExplosions>) -> bool {
+    setting.0
+}
+
+// queues a chain hop at the grid cell of every enemy that has just died
+fn queue_chain_explosions(
+    mut dead_events: EventReader<EnemyDied>,
+    mut queue: ResMut<ChainQueue>,
+    grid_space: GridSpace,
+    config: Res<ExplosionConfig>,
+) {
+    for EnemyDied(pos) in dead_events.read() {
+        // Invert the grid-to-world conversion to recover the cell the enemy died in.
+        let cell = grid_space.world_to_tile(pos.xy());
+        queue.0.push(PendingChain {
+            cell,
+            origin: *pos,
+            delay: config.chain_hop_delay,
+        });
+    }
+}
+
+// detonates queued chain hops whose delay has elapsed, killing enemies within one tile
+// that are in line of sight of the explosion
+fn process_chain_explosions(
+    mut queue: ResMut<ChainQueue>,
+    mut damage_events: EventWriter<DamageEvent>,
+    reservations: Res<GridReservations>,
+    map_data: Res<MapData>,
+    enemy_query: Query<&GridMover, (With<Enemy>, Without<Dying>)>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    for pending in queue.0.iter_mut() {
+        pending.delay -= dt;
+    }
+
+    // Split off the hops that are due this frame. Deaths they cause are queued next frame.
+    let (due, waiting): (Vec<_>, Vec<_>) = queue.0.drain(..).partition(|p| p.delay <= 0.0);
+    queue.0 = waiting;
+
+    for hop in due {
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                let offset = IVec2::new(dx, dy);
+                if offset == IVec2::ZERO {
+                    continue;
+                }
+                let Some(&victim) = reservations.0.get(&(hop.cell + offset)) else {
+                    continue;
+                };
+                let Ok(mover) = enemy_query.get(victim) else {
+                    continue;
+                };
+                let delta = mover.grid_pos - hop.cell;
+                if delta.x.abs() > 1 || delta.y.abs() > 1 {
+                    continue; // Only reserved the cell as a destination; still too far away.
+                }
+                if has_line_of_sight(hop.cell, delta, &map_data) {
+                    damage_events.write(DamageEvent {
+                        victim,
+                        amount: 1,
+                        source: KillSource::Explosion,
+                        position: hop.origin,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// a neighbouring cell is visible unless a wall is in the way; diagonals are blocked
+// only when both of the orthogonal cells between them are walls
+fn has_line_of_sight(from: IVec2, delta: IVec2, map_data: &MapData) -> bool {
+    if delta.x == 0 || delta.y == 0 {
+        return true;
+    }
+    !is_wall(from + IVec2::new(delta.x, 0), map_data)
+        || !is_wall(from + IVec2::new(0, delta.y), map_data)
+}
+
+fn clear_chain_queue(mut queue: ResMut<ChainQueue>) {
+    queue.0.clear();
+}
+
 // checks if the player is dead and player explosions have finished,
-// in which case, return to title screen
+// in which case, show the game over screen (or quietly restart the demo)
 fn check_player_explosions(
     mut commands: Commands,
     option_dead: Option<Res<PlayerIsDead>>,
     player_explosion_query: Query<Entity, With<PlayerExplosion>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut game_speed: ResMut<GameSpeed>,
+    demo: Res<Demo>,
 ) {
-    if let Some(_) = option_dead {
-        if player_explosion_query.is_empty() {
-            next_state.set(GameState::Title);
-            game_speed.value = 1.0;
-            commands.remove_resource::<PlayerIsDead>();
-            info!("player dead::switching to title");
+    if option_dead.is_some() && player_explosion_query.is_empty() {
+        if demo.0 {
+            next_state.set(GameState::Restarting);
+            info!("player dead::restarting demo");
+        } else {
+            next_state.set(GameState::GameOver);
+            info!("player dead::switching to game over");
         }
+        game_speed.value = 1.0;
+        commands.remove_resource::<PlayerIsDead>();
     }
 }
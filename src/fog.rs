@@ -0,0 +1,181 @@
+// fog.rs
+
+//! Optional fog of war, turned on from the settings page.
+//!
+//! Tiles the player hasn't had line of sight to this round are drawn black, tiles seen before
+//! but out of sight now are drawn darkened, and enemies, spawners and pickups are only drawn on
+//! tiles in sight. What has been seen is remembered until the next map is generated.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::arena::carry_over_arena;
+use crate::components::GameState;
+use crate::enemy::Enemy;
+use crate::grid_movement::{GridMover, MovementSystems};
+use crate::map::{generate_map, MapData};
+use crate::pickup::Pickup;
+use crate::player::Player;
+use crate::spawner::Spawner;
+use crate::tilemap::TileRepaint;
+
+/// How far the player can see, in tiles.
+const SIGHT_RADIUS: i32 = 10;
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FogOfWar(false))
+            .init_resource::<TileVisibility>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                reset_visibility
+                    .after(generate_map)
+                    .run_if(not(carry_over_arena)),
+            )
+            .add_systems(
+                Update,
+                (
+                    update_visibility.before(TileRepaint),
+                    hide_fogged_entities.after(MovementSystems::ApplyOffsetChanges),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Whether the fog is on; set from `Settings`.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FogOfWar(pub bool);
+
+/// How a tile is shown under the fog.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FogState {
+    /// Never seen this round.
+    Unseen,
+    /// Seen before, but not in sight now.
+    Remembered,
+    Visible,
+}
+
+/// Which tiles the player has seen this round and which are in sight now, indexed like
+/// `MapData::is_wall`. Only kept up to date while the fog is on.
+#[derive(Resource, Default)]
+pub struct TileVisibility {
+    pub seen: Vec<bool>,
+    pub visible: Vec<bool>,
+    /// The tile the player was on when `visible` was worked out.
+    origin: Option<IVec2>,
+}
+
+impl TileVisibility {
+    pub fn state(&self, map_data: &MapData, pos: IVec2) -> FogState {
+        let Some(idx) = map_data.index(pos) else {
+            return FogState::Unseen;
+        };
+        if self.visible.get(idx).copied().unwrap_or(false) {
+            FogState::Visible
+        } else if self.seen.get(idx).copied().unwrap_or(false) {
+            FogState::Remembered
+        } else {
+            FogState::Unseen
+        }
+    }
+}
+
+/// The fog as the renderers see it: every tile is visible while the fog is off.
+#[derive(SystemParam)]
+pub struct FogView<'w> {
+    fog: Res<'w, FogOfWar>,
+    visibility: Res<'w, TileVisibility>,
+}
+
+impl FogView<'_> {
+    pub fn state(&self, map_data: &MapData, pos: IVec2) -> FogState {
+        if self.fog.0 {
+            self.visibility.state(map_data, pos)
+        } else {
+            FogState::Visible
+        }
+    }
+
+    pub fn is_visible(&self, map_data: &MapData, pos: IVec2) -> bool {
+        self.state(map_data, pos) == FogState::Visible
+    }
+}
+
+/// Forgets the previous map's explored area.
+fn reset_visibility(mut visibility: ResMut<TileVisibility>) {
+    *visibility = TileVisibility::default();
+}
+
+/// Works out what's in sight whenever the player moves to another tile or the walls change.
+fn update_visibility(
+    fog: Res<FogOfWar>,
+    map_data: Res<MapData>,
+    player: Query<&GridMover, With<Player>>,
+    mut visibility: ResMut<TileVisibility>,
+) {
+    if !fog.0 {
+        return;
+    }
+    let Ok(mover) = player.single() else {
+        return;
+    };
+    let origin = mover.grid_pos;
+    let size = (map_data.width * map_data.height) as usize;
+    let up_to_date = visibility.origin == Some(origin) && visibility.seen.len() == size;
+    if up_to_date && !map_data.is_changed() && !fog.is_changed() {
+        return;
+    }
+
+    let visibility = &mut *visibility;
+    visibility.seen.resize(size, false);
+    visibility.visible.clear();
+    visibility.visible.resize(size, false);
+    visibility.origin = Some(origin);
+    for dy in -SIGHT_RADIUS..=SIGHT_RADIUS {
+        for dx in -SIGHT_RADIUS..=SIGHT_RADIUS {
+            if dx * dx + dy * dy > SIGHT_RADIUS * SIGHT_RADIUS {
+                continue;
+            }
+            let pos = origin + IVec2::new(dx, dy);
+            let Some(idx) = map_data.index(pos) else {
+                continue;
+            };
+            if map_data.line_of_sight(origin, pos) {
+                visibility.visible[idx] = true;
+                visibility.seen[idx] = true;
+            }
+        }
+    }
+}
+
+/// Hides enemies, spawners and pickups that aren't on a tile in sight.
+#[allow(clippy::type_complexity)]
+fn hide_fogged_entities(
+    fog: FogView,
+    map_data: Res<MapData>,
+    mut enemies: Query<(&GridMover, &mut Visibility), With<Enemy>>,
+    mut pickups: Query<(&Pickup, &mut Visibility), Without<Enemy>>,
+    mut spawners: Query<(&Spawner, &mut Visibility), (Without<Enemy>, Without<Pickup>)>,
+) {
+    let shown = |visible: bool| {
+        if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    };
+    for (mover, mut visibility) in &mut enemies {
+        visibility.set_if_neq(shown(fog.is_visible(&map_data, mover.grid_pos)));
+    }
+    for (pickup, mut visibility) in &mut pickups {
+        let pos = pickup.map_pos.round().as_ivec2();
+        visibility.set_if_neq(shown(fog.is_visible(&map_data, pos)));
+    }
+    for (spawner, mut visibility) in &mut spawners {
+        visibility.set_if_neq(shown(fog.is_visible(&map_data, spawner.tile)));
+    }
+}
@@ -0,0 +1,150 @@
+// frame_step.rs
+
+//! Frame-step mode, for chasing one-frame ordering bugs such as reservation handoffs or
+//! projectile tunneling.
+//!
+//! While stepping is on, the gameplay systems (movement, enemy AI, collisions, explosions,
+//! projectiles, particles and pickups) only run on frames where a step was requested.
+//! Rendering, the debug overlays and menus keep running. F10 or the `step` console command
+//! turns it on and off; with debugging enabled, comma also toggles it and period advances one
+//! frame.
+
+use bevy::prelude::*;
+
+use crate::components::GameState;
+use crate::console::{handle_console_input, CommandResult, ConsoleCommands};
+use crate::debug::DebugFlags;
+use crate::hit_stop::HitStop;
+
+pub struct FrameStepPlugin;
+
+impl Plugin for FrameStepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameStep>()
+            .init_resource::<ConsoleCommands>()
+            .add_systems(Startup, spawn_step_text)
+            // Runs before `Update`, so a step requested this frame applies to this frame.
+            .add_systems(
+                PreUpdate,
+                handle_frame_step_input.after(handle_console_input),
+            )
+            .add_systems(Update, update_step_text)
+            .add_systems(Last, finish_frame.run_if(in_state(GameState::Playing)));
+
+        app.world_mut().resource_mut::<ConsoleCommands>().register(
+            "step",
+            "step [on|off]",
+            "toggles frame-step mode",
+            step_command,
+        );
+    }
+}
+
+/// Whether the simulation is frozen, and whether it should advance one frame anyway.
+#[derive(Resource, Default)]
+pub struct FrameStep {
+    pub paused: bool,
+    /// Set by the advance key; cleared once the gameplay systems have run for one frame.
+    pub step_requested: bool,
+    /// Gameplay frames simulated so far, shown on screen so logs can be matched to frames.
+    pub frame: u64,
+}
+
+/// A run condition for gameplay systems: passes unless frame-step mode is holding them, a
+/// hit-stop freeze is running, or virtual time is paused, as it is while the window is
+/// unfocused.
+pub fn simulation_running(
+    step: Res<FrameStep>,
+    hit_stop: Res<HitStop>,
+    time: Res<Time<Virtual>>,
+) -> bool {
+    (!step.paused || step.step_requested) && !hit_stop.active() && !time.is_paused()
+}
+
+fn handle_frame_step_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    flags: Res<DebugFlags>,
+    mut step: ResMut<FrameStep>,
+) {
+    let toggle =
+        keys.just_pressed(KeyCode::F10) || (flags.master && keys.just_pressed(KeyCode::Comma));
+    if toggle {
+        step.paused = !step.paused;
+        step.step_requested = false;
+        info!("Frame-step mode: {} (frame {})", step.paused, step.frame);
+    }
+    if step.paused && flags.master && keys.just_pressed(KeyCode::Period) {
+        step.step_requested = true;
+    }
+}
+
+/// Counts the frame and clears the step request once this frame's gameplay systems have run.
+fn finish_frame(mut step: ResMut<FrameStep>) {
+    if step.paused && !step.step_requested {
+        return;
+    }
+    step.frame += 1;
+    if step.step_requested {
+        step.step_requested = false;
+        info!("Stepped to frame {}", step.frame);
+    }
+}
+
+#[derive(Component)]
+struct StepText;
+
+/// The step mode banner at the top of the screen. Uses Bevy's built-in font so it works in any
+/// state.
+fn spawn_step_text(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 12.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.4, 0.4)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.0),
+            left: Val::Percent(45.0),
+            ..default()
+        },
+        GlobalZIndex(20),
+        Visibility::Hidden,
+        StepText,
+    ));
+}
+
+fn update_step_text(
+    step: Res<FrameStep>,
+    mut query: Query<(&mut Text, &mut Visibility), With<StepText>>,
+) {
+    if !step.is_changed() {
+        return;
+    }
+    let Ok((mut text, mut visibility)) = query.single_mut() else {
+        return;
+    };
+    if step.paused {
+        *visibility = Visibility::Inherited;
+        text.0 = format!("STEP MODE \u{2014} frame {}", step.frame);
+    } else {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn step_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let mut step = world.resource_mut::<FrameStep>();
+    step.paused = match args {
+        [] => !step.paused,
+        ["on"] => true,
+        ["off"] => false,
+        _ => return Err("expected on or off".to_string()),
+    };
+    step.step_requested = false;
+    Ok(format!(
+        "Frame-step mode {} at frame {}",
+        if step.paused { "on" } else { "off" },
+        step.frame
+    ))
+}
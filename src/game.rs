@@ -1,25 +1,74 @@
 use bevy::prelude::*;
 
+use crate::achievements;
+use crate::arena;
 use crate::assets;
+use crate::atlas;
 use crate::audio;
+use crate::autotile;
+use crate::backdrop;
 use crate::border;
+use crate::bump;
 use crate::collate_src;
 use crate::collider;
 use crate::components;
+use crate::config;
+use crate::console;
+use crate::crt;
+use crate::danger;
 use crate::debug;
+use crate::demo;
 use crate::diagnostics;
+use crate::difficulty;
+use crate::endless;
 use crate::enemy;
 use crate::explosion;
+use crate::fog;
+use crate::frame_step;
+use crate::game_over;
+use crate::gems;
 use crate::grid_movement;
 use crate::grid_reservation;
+use crate::highscore;
+use crate::hints;
+use crate::hit_stop;
+use crate::hud;
+use crate::input;
+use crate::lava;
 use crate::map;
+use crate::map_export;
+use crate::message_log;
+use crate::mouse_aim;
+use crate::music;
+use crate::palette;
+use crate::particle;
+use crate::pickup;
+use crate::pixel_snap;
 use crate::player;
+use crate::popup;
+use crate::profiler;
 use crate::projectile;
+use crate::quicksave;
+use crate::radar;
 use crate::random;
 use crate::resolution;
+use crate::restart;
+use crate::reticle;
+use crate::round_intro;
+use crate::round_timer;
+use crate::save;
 use crate::score;
+use crate::screen_flash;
+use crate::screen_shake;
+use crate::screenshot;
+use crate::seed;
+use crate::settings;
+use crate::spawner;
+use crate::spectator;
 use crate::tilemap;
+use crate::time_attack;
 use crate::title;
+use crate::toast;
 use crate::ui_scaling;
 use crate::victory;
 pub struct GamePlugin;
@@ -28,7 +77,6 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
             // max of 15 plugins in a tuple
-            collate_src::CollateSrcPlugin,
             components::ComponentsPlugin,
             resolution::ResolutionPlugin,
             random::RandomPlugin,
@@ -52,11 +100,70 @@ impl Plugin for GamePlugin {
             diagnostics::DiagnosticsPlugin,
             explosion::ExplosionPlugin,
             victory::VictoryPlugin,
+            pickup::PickupPlugin,
+            particle::ParticlePlugin,
+            screen_flash::ScreenFlashPlugin,
+            config::ConfigPlugin,
+            highscore::HighScorePlugin,
+            popup::PopupPlugin,
+        ))
+        .add_plugins((
+            round_timer::RoundTimerPlugin,
+            game_over::GameOverPlugin,
+            restart::RestartPlugin,
+            round_intro::RoundIntroPlugin,
+            settings::SettingsPlugin,
+            difficulty::DifficultyPlugin,
+            endless::EndlessPlugin,
+            save::SavePlugin,
+            toast::ToastPlugin,
+            screen_shake::ScreenShakePlugin,
+            demo::DemoPlugin,
+            input::InputPlugin,
+            seed::SeedPlugin,
+            music::MusicPlugin,
+            palette::PalettePlugin,
+        ))
+        .add_plugins((
+            atlas::AtlasPlugin,
+            pixel_snap::PixelSnapPlugin,
+            console::ConsolePlugin,
+            frame_step::FrameStepPlugin,
+            profiler::ProfilerPlugin,
+            screenshot::ScreenshotPlugin,
+            crt::CrtPlugin,
+            radar::RadarPlugin,
+            quicksave::QuickSavePlugin,
+            map_export::MapExportPlugin,
+            autotile::AutotilePlugin,
+            fog::FogPlugin,
+            lava::LavaPlugin,
+            backdrop::BackdropPlugin,
+            hud::HudPlugin,
+        ))
+        .add_plugins((
+            message_log::MessageLogPlugin,
+            hit_stop::HitStopPlugin,
+            spectator::SpectatorPlugin,
+            hints::HintsPlugin,
+            achievements::AchievementsPlugin,
+            bump::BumpPlugin,
+            spawner::SpawnerPlugin,
+            gems::GemsPlugin,
+            time_attack::TimeAttackPlugin,
+            reticle::ReticlePlugin,
+            mouse_aim::MouseAimPlugin,
+            danger::DangerPlugin,
+            arena::ArenaPlugin,
         ))
         .add_systems(Startup, setup_scene);
+
+        if collate_src::collation_enabled() {
+            app.add_plugins(collate_src::CollateSrcPlugin);
+        }
     }
 }
 
 fn setup_scene(mut commands: Commands) {
-    commands.spawn(Camera2d::default());
+    commands.spawn(Camera2d);
 }
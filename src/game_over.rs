@@ -0,0 +1,205 @@
+// game_over.rs
+
+//! The run summary shown after the player dies (or a time attack course runs out of time), over
+//! the frozen battlefield.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::{CurrentRound, GameEntity, GameMode, GameState};
+use crate::difficulty::DifficultySetting;
+use crate::highscore::{record_high_score, HighScores};
+use crate::input::InputMap;
+use crate::round_timer::{format_time, RoundTimer};
+use crate::score::{RunStats, Score};
+use crate::seed::{format_seed, MapSeed};
+use crate::time_attack::ActiveCourse;
+
+/// Seconds before the summary returns to the title screen on its own.
+const GAME_OVER_TIMEOUT: f32 = 8.0;
+
+/// Seconds before input is accepted, so a held fire key doesn't skip the summary.
+const GAME_OVER_INPUT_DELAY: f32 = 0.5;
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            spawn_game_over.after(record_high_score),
+        )
+        .add_systems(
+            OnExit(GameState::GameOver),
+            (despawn_game_over, cleanup_game),
+        )
+        .add_systems(
+            Update,
+            handle_game_over_input.run_if(in_state(GameState::GameOver)),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct GameOverTimer(Timer);
+
+#[derive(Component)]
+struct GameOverText;
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_game_over(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    round: Res<CurrentRound>,
+    round_timer: Res<RoundTimer>,
+    high_scores: Res<HighScores>,
+    difficulty: Res<DifficultySetting>,
+    mode: Res<GameMode>,
+    seed: Res<MapSeed>,
+    course: Option<Res<ActiveCourse>>,
+) {
+    let endless = *mode == GameMode::Endless;
+    let course = course.filter(|_| *mode == GameMode::TimeAttack);
+    let heading = if course
+        .as_ref()
+        .is_some_and(|course| course.0.time_left(round_timer.round) <= 0.0)
+    {
+        "TIME UP"
+    } else {
+        "GAME OVER"
+    };
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            // Dim the battlefield left behind the summary.
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            GlobalZIndex(10),
+            GameOverText,
+        ))
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new(heading),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[2]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+
+        // Survival time is the headline stat in endless mode.
+        if endless {
+            parent.spawn((
+                Text::new(format!("survived {}", format_time(round_timer.total))),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[3]),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        }
+
+        let banner = if endless {
+            high_scores.newest_endless.map(|_| "NEW BEST TIME")
+        } else {
+            high_scores.newest.map(|_| "NEW HIGH SCORE")
+        };
+        if let Some(banner) = banner {
+            parent.spawn((
+                Text::new(banner),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[5]),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        }
+
+        let mut lines = vec![format!("{} {}", mode.label(), difficulty.0.label())];
+        if let Some(course) = &course {
+            lines.push(format!("course: {}", course.0.name));
+        }
+        lines.push(format!("score: {}", score.0));
+        if *mode == GameMode::Classic {
+            lines.push(format!("rounds survived: {}", round.0 - 1));
+        }
+        lines.extend([
+            format!("enemies killed: {}", stats.enemies_killed),
+            format!("best combo: {}", stats.best_combo),
+        ]);
+        if !endless {
+            lines.push(format!("time: {}", format_time(round_timer.total)));
+        }
+        lines.push(format!("seed: {}", format_seed(seed.current)));
+        for line in lines.into_iter().chain(stats.kill_breakdown()) {
+            parent.spawn((
+                Text::new(line),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[4]),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        }
+    });
+
+    commands.insert_resource(GameOverTimer(Timer::from_seconds(
+        GAME_OVER_TIMEOUT,
+        TimerMode::Once,
+    )));
+}
+
+fn despawn_game_over(mut commands: Commands, query: Query<Entity, With<GameOverText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<GameOverTimer>();
+}
+
+/// Restarts on the restart key, otherwise returns to the title on any key or click, or once
+/// the timeout runs out.
+fn handle_game_over_input(
+    mut timer: ResMut<GameOverTimer>,
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    timer.0.tick(time.delta());
+    let accepting_input = timer.0.elapsed_secs() >= GAME_OVER_INPUT_DELAY;
+    let pressed =
+        keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some();
+    if accepting_input && keys.just_pressed(input_map.restart) {
+        next_state.set(GameState::Restarting);
+    } else if timer.0.finished() || (accepting_input && pressed) {
+        next_state.set(GameState::Title);
+    }
+}
+
+fn cleanup_game(mut commands: Commands, query: Query<Entity, With<GameEntity>>) {
+    info!("Cleaning up game entities");
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
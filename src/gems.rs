@@ -0,0 +1,223 @@
+// gems.rs
+
+//! Gems scattered over each map, rewarding the player for exploring it.
+//!
+//! Between `MIN_GEMS` and `MAX_GEMS` gems are placed when a round starts, on floor tiles the
+//! player can walk to. A breadth-first distance field from the player's start weights the
+//! tiles: the further away a tile is the likelier it is to get a gem, and dead ends are
+//! likelier still. The gems are ordinary `Pickup`s, collected and chimed by the pickup systems;
+//! this module counts them, scores them, and pays a bonus on victory if every one was found.
+//! A resumed quicksave brings back the gems it was saved with. A persistent arena keeps the gems
+//! left over from the last round, and their count, rather than scattering more.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use std::collections::VecDeque;
+
+use crate::arena::{carry_over_arena, ArenaEntity};
+use crate::assets::GameAssets;
+use crate::atlas::GameAtlas;
+use crate::components::GameState;
+use crate::enemy::spawn_enemies;
+use crate::grid_movement::is_wall;
+use crate::grid_reservation::GridReservations;
+use crate::map::MapData;
+use crate::message_log::{GameMessage, MessageKind};
+use crate::pickup::{spawn_pickup, PickupCollected, PickupKind};
+use crate::player::PlayerSpawn;
+use crate::quicksave::PendingRestore;
+use crate::random::{random_range, WeightedTable};
+use crate::score::{PointsAwarded, RunStats, Score};
+
+const MIN_GEMS: i32 = 30;
+const MAX_GEMS: i32 = 60;
+
+/// Points for each gem collected.
+const GEM_POINTS: u64 = 25;
+
+/// Points for collecting every gem on the map before the round is won.
+const ALL_GEMS_POINTS: u64 = 1000;
+
+/// Extra weight for the furthest tile from the start, scaled down linearly for nearer ones.
+/// Every tile starts from a weight of 1.
+const DISTANCE_WEIGHT: f32 = 2.0;
+
+/// Extra weight for a dead end: a floor tile with only one open neighbour.
+const DEAD_END_WEIGHT: f32 = 6.0;
+
+const NEIGHBOURS: [IVec2; 4] = [IVec2::Y, IVec2::NEG_Y, IVec2::X, IVec2::NEG_X];
+
+pub struct GemsPlugin;
+
+impl Plugin for GemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GemCount>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    reset_gem_count,
+                    scatter_gems.run_if(not(resource_exists::<PendingRestore>)),
+                )
+                    .chain()
+                    // After the enemies, and so the spawners, to keep off their tiles.
+                    .after(spawn_enemies)
+                    .run_if(not(carry_over_arena)),
+            )
+            .add_systems(OnEnter(GameState::Victory), award_all_gems_bonus)
+            .add_systems(Update, count_gems.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Gems collected this round, out of how many were placed.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GemCount {
+    pub collected: u32,
+    pub total: u32,
+    /// Whether the bonus for collecting them all has been paid, so a carried-over arena
+    /// doesn't pay it again every round.
+    pub bonus_awarded: bool,
+}
+
+impl GemCount {
+    /// Whether the round had gems and every one of them was collected.
+    pub fn all_collected(&self) -> bool {
+        self.total > 0 && self.collected >= self.total
+    }
+}
+
+pub fn reset_gem_count(mut count: ResMut<GemCount>) {
+    *count = GemCount::default();
+}
+
+/// Places the round's gems, favouring tiles far from the start and dead ends.
+#[allow(clippy::too_many_arguments)]
+pub fn scatter_gems(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    atlas: Option<Res<GameAtlas>>,
+    map_data: Res<MapData>,
+    reservations: Res<GridReservations>,
+    spawn: Res<PlayerSpawn>,
+    mut count: ResMut<GemCount>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    let distances = distance_field(&map_data, spawn.0);
+    let furthest = distances
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let (width, height) = (map_data.width as i32, map_data.height as i32);
+    let candidates = (0..height)
+        .flat_map(|y| (0..width).map(move |x| IVec2::new(x, y)))
+        .filter_map(|pos| {
+            let distance = distances[map_data.index(pos)?]?;
+            // The start tile itself is at distance 0.
+            if distance == 0 || map_data.is_lava(pos) || reservations.0.contains_key(&pos) {
+                return None;
+            }
+            let open_neighbours = NEIGHBOURS
+                .iter()
+                .filter(|&&dir| !is_wall(pos + dir, &map_data))
+                .count();
+            let dead_end = if open_neighbours == 1 {
+                DEAD_END_WEIGHT
+            } else {
+                0.0
+            };
+            let weight = 1.0 + DISTANCE_WEIGHT * distance as f32 / furthest as f32 + dead_end;
+            Some((pos, weight))
+        });
+    let Ok(table) = WeightedTable::new(candidates) else {
+        warn!("No room for any gems");
+        return;
+    };
+    let wanted = random_range(&mut rng, MIN_GEMS..MAX_GEMS + 1) as usize;
+    let tiles: Vec<IVec2> = table
+        .pick_unique_n(&mut rng, wanted)
+        .into_iter()
+        .copied()
+        .collect();
+    for &tile in &tiles {
+        let gem = spawn_pickup(
+            &mut commands,
+            &game_assets,
+            atlas.as_deref(),
+            PickupKind::Gem,
+            tile,
+        );
+        commands.entity(gem).insert(ArenaEntity);
+    }
+    count.total = tiles.len() as u32;
+    info!("Scattered {} gems", count.total);
+}
+
+/// Walking distance in tiles from `start` to every tile, or `None` for walls and tiles that
+/// can't be reached. Indexed like `MapData::is_wall`.
+fn distance_field(map: &MapData, start: IVec2) -> Vec<Option<u32>> {
+    let mut distances = vec![None; map.is_wall.len()];
+    let Some(start_index) = map.index(start) else {
+        return distances;
+    };
+    distances[start_index] = Some(0);
+    let mut queue = VecDeque::from([(start, 0)]);
+    while let Some((pos, distance)) = queue.pop_front() {
+        for dir in NEIGHBOURS {
+            let next = pos + dir;
+            if is_wall(next, map) {
+                continue;
+            }
+            let Some(index) = map.index(next) else {
+                continue;
+            };
+            if distances[index].is_none() {
+                distances[index] = Some(distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+    distances
+}
+
+/// Counts and scores each gem as it is collected.
+fn count_gems(
+    mut events: EventReader<PickupCollected>,
+    mut count: ResMut<GemCount>,
+    mut score: ResMut<Score>,
+    mut stats: ResMut<RunStats>,
+    mut awarded_events: EventWriter<PointsAwarded>,
+) {
+    for event in events.read() {
+        if event.kind != PickupKind::Gem {
+            continue;
+        }
+        count.collected += 1;
+        stats.gems += 1;
+        score.0 += GEM_POINTS;
+        awarded_events.write(PointsAwarded {
+            position: event.position,
+            points: GEM_POINTS,
+            combo: 1,
+        });
+    }
+}
+
+fn award_all_gems_bonus(
+    mut count: ResMut<GemCount>,
+    mut score: ResMut<Score>,
+    mut stats: ResMut<RunStats>,
+    mut messages: EventWriter<GameMessage>,
+) {
+    if !count.all_collected() || count.bonus_awarded {
+        return;
+    }
+    count.bonus_awarded = true;
+    score.0 += ALL_GEMS_POINTS;
+    stats.all_gems_rounds += 1;
+    messages.write(GameMessage(
+        format!("all gems found! +{}", ALL_GEMS_POINTS),
+        MessageKind::Pickup,
+    ));
+}
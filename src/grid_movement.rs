@@ -12,10 +12,12 @@ use bevy::ecs::schedule::SystemSet;
 use bevy::prelude::*;
 
 use crate::components::GameState;
+use crate::frame_step::simulation_running;
 use crate::grid_reservation::{GridReservations, GridReserver};
 use crate::map::MapData;
-use crate::projectile::{Bouncable, Projectile};
-use crate::tilemap::{MapOffset, TileOffset, HALF_HEIGHT, HALF_WIDTH, TILE_SIZE};
+use crate::profiler::SystemTimings;
+use crate::projectile::{Bouncable, Projectile, ProjectileBounced, ProjectileWallImpact};
+use crate::tilemap::{GridSpace, MapOffset, TileOffset, ViewportTiles, TILE_SIZE};
 
 /// A component that enables grid-based movement for an entity.
 #[derive(Component)]
@@ -41,6 +43,13 @@ pub struct GridMover {
 #[derive(Component)]
 pub struct IntendedDirection(pub IVec2);
 
+/// Stores an entity's world-space translation from the previous frame.
+///
+/// Together with the current `Transform`, this gives the segment the entity travelled
+/// during the frame, which swept collision checks use to avoid tunneling.
+#[derive(Component, Default)]
+pub struct PreviousTranslation(pub Vec2);
+
 /// Defines a strict order of execution for systems related to movement.
 ///
 /// This is crucial to prevent issues like one-frame delays between input and movement,
@@ -77,7 +86,7 @@ impl Plugin for GridMovementPlugin {
                     MovementSystems::ApplyOffsetChanges.after(MovementSystems::AdjustScroll),
                 )
                     .chain()
-                    .run_if(in_state(GameState::Playing)),
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
             )
             // Add the systems to their respective sets.
             .add_systems(
@@ -86,12 +95,18 @@ impl Plugin for GridMovementPlugin {
             )
             .add_systems(
                 Update,
-                update_grid_positions.in_set(MovementSystems::UpdatePosition),
+                (store_previous_translations, update_grid_positions)
+                    .chain()
+                    .in_set(MovementSystems::UpdatePosition),
             )
             .add_systems(
                 Update,
                 update_grid_positions
-                    .run_if(resource_changed::<MapOffset>.or(resource_changed::<TileOffset>))
+                    .run_if(
+                        resource_changed::<MapOffset>
+                            .or(resource_changed::<TileOffset>)
+                            .or(resource_changed::<ViewportTiles>),
+                    )
                     .in_set(MovementSystems::ApplyOffsetChanges),
             );
     }
@@ -104,8 +119,7 @@ impl Plugin for GridMovementPlugin {
 /// - Advancing movement progress frame-by-frame.
 /// - Reaching a destination tile and deciding what to do next (stop, continue, or change direction).
 /// - Handling collisions with walls, including logic for bouncing projectiles.
-/// The core system that updates the state of all GridMover components.
-#[allow(clippy::too_many_arguments)]
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn update_grid_movement(
     mut commands: Commands,
     mut query: Query<(
@@ -115,12 +129,18 @@ fn update_grid_movement(
         Option<&GridReserver>,
         Option<&mut Bouncable>,
         Option<&Projectile>,
+        &Transform,
     )>,
     time: Res<Time>,
     map_data: Res<MapData>,
     mut reservations: ResMut<GridReservations>,
+    mut impact_events: EventWriter<ProjectileWallImpact>,
+    mut bounce_events: EventWriter<ProjectileBounced>,
+    timings: Res<SystemTimings>,
 ) {
-    for (entity, mut mover, mut intended, reserver, bouncable, projectile) in &mut query {
+    let _span = timings.span("update_grid_movement");
+    for (entity, mut mover, mut intended, reserver, bouncable, projectile, transform) in &mut query
+    {
         // --- State 1: Entity is stationary ---
         if mover.direction == IVec2::ZERO {
             let new_dir = intended.0;
@@ -200,7 +220,7 @@ fn update_grid_movement(
                         }
                     } else {
                         // Wall or reserved tile detected ahead.
-                        let can_bounce = bouncable.as_ref().map_or(false, |b| b.remaining > 0);
+                        let can_bounce = bouncable.as_ref().is_some_and(|b| b.remaining > 0);
                         if can_bounce {
                             // --- Bouncing Logic ---
                             let new_dir =
@@ -210,6 +230,7 @@ fn update_grid_movement(
                             if let Some(mut b) = bouncable {
                                 b.remaining -= 1;
                             }
+                            bounce_events.write(ProjectileBounced(transform.translation));
                             // Adjust progress based on new direction's length to maintain speed.
                             let old_length = current_direction.as_vec2().length();
                             let new_length = new_dir.as_vec2().length();
@@ -232,6 +253,7 @@ fn update_grid_movement(
                             // If it's a projectile, despawn it on impact.
                             if projectile.is_some() {
                                 commands.entity(entity).despawn();
+                                impact_events.write(ProjectileWallImpact(transform.translation));
                             }
                         }
                     }
@@ -275,7 +297,7 @@ fn update_grid_movement(
 /// - If the horizontal path is clear, it reflects vertically (y -> -y).
 /// - If the vertical path is clear, it reflects horizontally (x -> -x).
 /// - If both are blocked (a corner), it reflects both (x -> -x, y -> -y).
-fn calculate_reflection(dir: IVec2, grid_pos: IVec2, map_data: &MapData) -> IVec2 {
+pub fn calculate_reflection(dir: IVec2, grid_pos: IVec2, map_data: &MapData) -> IVec2 {
     let dx = dir.x;
     let dy = dir.y;
 
@@ -294,28 +316,27 @@ fn calculate_reflection(dir: IVec2, grid_pos: IVec2, map_data: &MapData) -> IVec
     }
 }
 
+/// Records each entity's translation before this frame's movement is applied.
+fn store_previous_translations(mut query: Query<(&Transform, &mut PreviousTranslation)>) {
+    for (transform, mut previous) in &mut query {
+        previous.0 = transform.translation.xy();
+    }
+}
+
 /// Translates the logical `GridMover` position into a final `Transform` for rendering.
 ///
 /// This system runs after `update_grid_movement`, ensuring it uses the most up-to-date
 /// grid position and progress. It accounts for the global map and tile offsets to correctly
 /// position the entity within the camera's viewport.
-fn update_grid_positions(
-    map_offset: Res<MapOffset>,
-    tile_offset: Res<TileOffset>,
-    mut query: Query<(&GridMover, &mut Transform)>,
-) {
+fn update_grid_positions(grid_space: GridSpace, mut query: Query<(&GridMover, &mut Transform)>) {
     for (mover, mut trans) in &mut query {
         // Calculate the effective position, including the fractional progress towards the next tile.
         let effective_pos = mover.grid_pos.as_vec2() + mover.direction.as_vec2() * mover.progress;
 
         // Convert the effective grid position to world coordinates.
-        let x =
-            (effective_pos.x - map_offset.0.x as f32 - HALF_WIDTH) * TILE_SIZE + tile_offset.0.x;
-        let y =
-            (effective_pos.y - map_offset.0.y as f32 - HALF_HEIGHT) * TILE_SIZE + tile_offset.0.y;
-
-        trans.translation.x = x;
-        trans.translation.y = y;
+        let world = grid_space.grid_to_world(effective_pos);
+        trans.translation.x = world.x;
+        trans.translation.y = world.y;
     }
 }
 
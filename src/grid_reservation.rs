@@ -1,13 +1,13 @@
 // src/grid_reservation.rs
 use crate::assets::GameAssets;
+use crate::atlas::{atlas_sprite, AtlasSprite, GameAtlas};
 use crate::components::{GameEntity, GameState};
-use crate::tilemap::{MapOffset, TileOffset, HALF_HEIGHT, HALF_WIDTH, TILE_SIZE};
+use crate::debug::debug_layer;
+use crate::profiler::SystemTimings;
+use crate::tilemap::GridSpace;
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet};
 
-/// When set to true, spawns a sprite for each grid cell reservation for debugging.
-const VISUAL_DEBUG_RESERVATIONS: bool = !true;
-
 pub struct GridReservationPlugin;
 
 impl Plugin for GridReservationPlugin {
@@ -15,17 +15,18 @@ impl Plugin for GridReservationPlugin {
         app.init_resource::<GridReservations>()
             // This system runs after all other updates, ensuring that it catches any
             // entities that were despawned during the frame.
-            .add_systems(PostUpdate, cleanup_dangling_reservations);
-
-        // If the debug flag is enabled, add the visualization systems.
-        if VISUAL_DEBUG_RESERVATIONS {
-            app.add_systems(
+            .add_systems(PostUpdate, cleanup_dangling_reservations)
+            // A sprite for each grid cell reservation, while the reservations debug layer is on.
+            .add_systems(
                 Update,
-                (sync_reservation_visuals, update_visualizer_positions)
-                    .chain()
+                (
+                    (sync_reservation_visuals, update_visualizer_positions)
+                        .chain()
+                        .run_if(debug_layer(|f| f.reservations)),
+                    clear_reservation_visuals.run_if(not(debug_layer(|f| f.reservations))),
+                )
                     .run_if(in_state(GameState::Playing)),
             );
-        }
     }
 }
 
@@ -50,9 +51,12 @@ fn sync_reservation_visuals(
     mut commands: Commands,
     reservations: Res<GridReservations>,
     game_assets: Res<GameAssets>,
+    atlas: Option<Res<GameAtlas>>,
     // Query for all existing visualizer entities
     visualizer_query: Query<(Entity, &ReservationVisualizer)>,
+    timings: Res<SystemTimings>,
 ) {
+    let _span = timings.span("sync_reservation_visuals");
     // Collect all grid positions that are currently reserved.
     let needed_visuals: HashSet<IVec2> = reservations.0.keys().cloned().collect();
 
@@ -74,10 +78,7 @@ fn sync_reservation_visuals(
     for pos in needed_visuals {
         if !current_visuals.contains_key(&pos) {
             commands.spawn((
-                Sprite {
-                    image: game_assets.reservation_texture.clone(),
-                    ..default()
-                },
+                atlas_sprite(atlas.as_deref(), &game_assets, AtlasSprite::Reservation),
                 ReservationVisualizer(pos),
                 // GameEntity ensures it's cleaned up when we exit the Playing state.
                 GameEntity,
@@ -89,23 +90,28 @@ fn sync_reservation_visuals(
     }
 }
 
+/// Removes the visualizers once the layer is turned off.
+fn clear_reservation_visuals(
+    mut commands: Commands,
+    visualizer_query: Query<Entity, With<ReservationVisualizer>>,
+) {
+    for entity in &visualizer_query {
+        commands.entity(entity).despawn();
+    }
+}
+
 /// Updates the world-space transform of each visualizer sprite based on its grid position
 /// and the current camera scroll offsets.
 fn update_visualizer_positions(
-    map_offset: Res<MapOffset>,
-    tile_offset: Res<TileOffset>,
+    grid_space: GridSpace,
     mut query: Query<(&ReservationVisualizer, &mut Transform)>,
 ) {
     for (visualizer, mut trans) in &mut query {
-        let pos = visualizer.0;
-
-        // This calculation is identical to how other grid-based entities are positioned,
+        // This conversion is identical to how other grid-based entities are positioned,
         // ensuring the debug sprite is perfectly centered on the tile.
-        let x = (pos.x as f32 - map_offset.0.x as f32 - HALF_WIDTH) * TILE_SIZE + tile_offset.0.x;
-        let y = (pos.y as f32 - map_offset.0.y as f32 - HALF_HEIGHT) * TILE_SIZE + tile_offset.0.y;
-
-        trans.translation.x = x;
-        trans.translation.y = y;
+        let world = grid_space.tile_to_world(visualizer.0);
+        trans.translation.x = world.x;
+        trans.translation.y = world.y;
     }
 }
 
@@ -0,0 +1,703 @@
+// headless.rs
+
+//! Runs the game logic without a window, renderer or audio, for tests of movement,
+//! reservations and collisions. Only built for `cargo test`.
+//!
+//! `headless_app` builds an `App` with `MinimalPlugins` and only the gameplay plugins. The
+//! textures, sounds and font are replaced by default handles, which every gameplay system
+//! already tolerates since they only pass them on to sprites and audio players. Time advances
+//! by a fixed sixtieth of a second per update, so a seeded run plays out the same every time.
+//!
+//! `SimulationHarness` drives such an app: it starts a seeded round, steers the player and
+//! fires shots directly, and steps the schedule. The tests below run each check with every one
+//! of `CHECK_SEEDS`.
+
+use bevy::asset::AssetPlugin;
+use bevy::ecs::system::RunSystemOnce;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rand::prelude::{Entropy, GlobalEntropy, WyRand};
+use rand_core::SeedableRng;
+use std::time::Duration;
+
+use crate::arena;
+use crate::assets::GameAssets;
+use crate::bump;
+use crate::collider;
+use crate::components::{self, CurrentRound, Dying, EnemyGroupSize, GameState, Health};
+use crate::config;
+use crate::danger;
+use crate::debug::DebugFlags;
+use crate::demo::Demo;
+use crate::difficulty;
+use crate::enemy::{self, Enemy, EnemyKind, EnemySpawner};
+use crate::explosion;
+use crate::fog;
+use crate::frame_step;
+use crate::grid_movement::{self, is_wall, GridMover, IntendedDirection};
+use crate::grid_reservation::{self, GridReservations, GridReserver};
+use crate::hit_stop::{self, HitStopIntensity};
+use crate::input;
+use crate::lava;
+use crate::map::{self, MapData};
+use crate::message_log::GameMessage;
+use crate::mouse_aim::AimMode;
+use crate::palette::{self, Palettes, DEFAULT_PALETTE};
+use crate::particle;
+use crate::pickup;
+use crate::player::{self, fire_projectile, ControlSource, Invulnerable, Player};
+use crate::profiler;
+use crate::projectile;
+use crate::random::{self, random_bool, random_pick};
+use crate::round_timer::RoundTimer;
+use crate::score::{self, EnemyCount};
+use crate::seed::{self, MapSeed};
+use crate::spawner;
+use crate::tilemap::{self, GridSpace};
+
+/// How far time advances per update.
+const STEP: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// Seeds the checks run with, so a failure can be reproduced.
+const CHECK_SEEDS: [u64; 3] = [1, 0xC0FFEE, 0xDEADBEEF];
+
+/// The seed `TURNER_TRACE` was recorded with.
+const TURNER_TRACE_SEED: u64 = 0xC0FFEE;
+
+/// The enemies' grid positions every 50 frames for the first 500 of `TURNER_TRACE_SEED`, in
+/// spawn order, with the player standing still. Recorded with the separate left and right
+/// turner systems `update_turners` replaced, so it changes if the way turners decide does.
+#[rustfmt::skip]
+const TURNER_TRACE: &[&[(i32, i32)]] = &[
+    &[(13, 77), (17, 77), (16, 64), (33, 40), (34, 41), (14, 34), (40, 12), (13, 70)],
+    &[(18, 76), (19, 77), (16, 71), (39, 40), (40, 41), (14, 40), (33, 12), (13, 64)],
+    &[(18, 70), (13, 77), (16, 77), (46, 40), (47, 41), (14, 47), (27, 12), (13, 57)],
+    &[(18, 63), (8, 76), (18, 77), (52, 40), (53, 41), (14, 53), (20, 12), (13, 51)],
+    &[(12, 63), (12, 76), (13, 75), (54, 35), (54, 47), (14, 60), (19, 12), (13, 44)],
+    &[(5, 63), (17, 77), (13, 69), (54, 29), (54, 53), (14, 66), (25, 11), (13, 38)],
+    &[(2, 66), (16, 77), (13, 62), (54, 22), (54, 60), (14, 73), (31, 11), (13, 31)],
+    &[(2, 73), (10, 77), (13, 56), (54, 16), (54, 66), (13, 76), (38, 11), (13, 25)],
+    &[(3, 74), (10, 77), (13, 49), (54, 9), (53, 71), (18, 77), (44, 11), (14, 19)],
+    &[(3, 68), (13, 74), (13, 43), (54, 3), (46, 71), (14, 75), (51, 11), (15, 22)],
+];
+
+const DIRECTIONS: [IVec2; 4] = [IVec2::Y, IVec2::NEG_Y, IVec2::X, IVec2::NEG_X];
+
+/// Builds an app with the gameplay plugins and nothing that needs a window, a GPU or a sound
+/// device. The resources the menus and UI plugins would otherwise provide are inserted here.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<TextureAtlasLayout>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(STEP))
+        .init_state::<GameState>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<ButtonInput<MouseButton>>()
+        .add_event::<GameMessage>()
+        .init_resource::<DebugFlags>()
+        .init_resource::<Demo>()
+        .init_resource::<AimMode>()
+        .init_resource::<RoundTimer>()
+        .insert_resource(CurrentRound(1))
+        .insert_resource(EnemyGroupSize(1))
+        .add_plugins((
+            components::ComponentsPlugin,
+            random::RandomPlugin,
+            palette::PalettePlugin,
+            profiler::ProfilerPlugin,
+            config::ConfigPlugin,
+            difficulty::DifficultyPlugin,
+            input::InputPlugin,
+            seed::SeedPlugin,
+            frame_step::FrameStepPlugin,
+            score::ScorePlugin,
+            map::MapPlugin,
+            tilemap::TilemapPlugin,
+            player::PlayerPlugin,
+            grid_movement::GridMovementPlugin,
+            grid_reservation::GridReservationPlugin,
+        ))
+        .add_plugins((
+            collider::ColliderPlugin,
+            danger::DangerPlugin,
+            projectile::ProjectilePlugin,
+            enemy::EnemyPlugin,
+            explosion::ExplosionPlugin,
+            particle::ParticlePlugin,
+            pickup::PickupPlugin,
+            fog::FogPlugin,
+            lava::LavaPlugin,
+            hit_stop::HitStopPlugin,
+            bump::BumpPlugin,
+            spawner::SpawnerPlugin,
+            arena::ArenaPlugin,
+        ))
+        // The checks count frames, so freezes would only make them slower to reach.
+        .insert_resource(HitStopIntensity::Off);
+    let palette = app
+        .world()
+        .resource::<Palettes>()
+        .get(DEFAULT_PALETTE)
+        .clone();
+    app.insert_resource(placeholder_assets(palette));
+    app
+}
+
+/// Stand-ins for the loaded assets: every handle is the default one, which points at nothing.
+fn placeholder_assets(palette: palette::Palette) -> GameAssets {
+    GameAssets {
+        wall_texture: Handle::default(),
+        player_texture: Handle::default(),
+        reservation_texture: Handle::default(),
+        enemy_texture: Handle::default(),
+        explosion_texture: Handle::default(),
+        explosion_sheet: Handle::default(),
+        explosion_layout: Handle::default(),
+        font: Handle::default(),
+        shoot_sfx: vec![Handle::default()],
+        explosion_sfx: vec![Handle::default()],
+        bounce_sfx: vec![Handle::default()],
+        pickup_sfx: Handle::default(),
+        tick_sfx: Handle::default(),
+        fanfare_sfx: Handle::default(),
+        death_sfx: Handle::default(),
+        heartbeat_sfx: Handle::default(),
+        title_music: Handle::default(),
+        game_music: Handle::default(),
+        palette,
+    }
+}
+
+/// Drives a headless app through a seeded round.
+pub struct SimulationHarness {
+    pub app: App,
+    player: Entity,
+}
+
+impl SimulationHarness {
+    /// Starts the first round of a run with the given map seed. The player is taken off the
+    /// keyboard, so only `set_direction` steers them.
+    pub fn new(seed: u64) -> Self {
+        Self::with_enemy_groups(seed, 1)
+    }
+
+    /// Like `new`, but with `groups` enemies of each kind, as in a later round.
+    pub fn with_enemy_groups(seed: u64, groups: u32) -> Self {
+        let mut app = headless_app();
+        app.insert_resource(EnemyGroupSize(groups));
+        app.world_mut().resource_mut::<MapSeed>().next = Some(seed);
+        app.finish();
+        app.cleanup();
+        app.update();
+        // Through the title screen, which is where the enemy colors are rolled.
+        for state in [GameState::Title, GameState::Playing] {
+            app.world_mut()
+                .resource_mut::<NextState<GameState>>()
+                .set(state);
+            app.update();
+        }
+        let player = app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(app.world())
+            .expect("the round should have spawned the player");
+        app.world_mut()
+            .entity_mut(player)
+            .insert(ControlSource::Bot);
+        SimulationHarness { app, player }
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    /// Runs the schedule `frames` times.
+    pub fn step(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// The player's grid position, or `None` once they're gone.
+    pub fn player_pos(&self) -> Option<IVec2> {
+        self.world()
+            .get::<GridMover>(self.player)
+            .map(|mover| mover.grid_pos)
+    }
+
+    /// Sets the direction the player tries to move in, as the movement keys would.
+    pub fn set_direction(&mut self, dir: IVec2) {
+        if let Some(mut intended) = self
+            .app
+            .world_mut()
+            .get_mut::<IntendedDirection>(self.player)
+        {
+            intended.0 = dir;
+        }
+    }
+
+    /// Keeps the player from taking damage, so a check isn't cut short by a stray enemy.
+    pub fn make_player_invulnerable(&mut self) {
+        let forever = Timer::new(Duration::from_secs(u32::MAX as u64), TimerMode::Once);
+        self.app
+            .world_mut()
+            .entity_mut(self.player)
+            .insert(Invulnerable(forever));
+    }
+
+    /// Gives the player `hearts` hearts and takes away any invulnerability, so touching an
+    /// enemy hurts them without ending the round.
+    pub fn set_player_hearts(&mut self, hearts: u32) {
+        let mut player = self.app.world_mut().entity_mut(self.player);
+        player.insert(Health::new(hearts));
+        player.remove::<Invulnerable>();
+    }
+
+    /// A floor tile next to the player that nothing has reserved, and the direction it lies in.
+    pub fn free_tile_next_to_player(&self) -> Option<(IVec2, IVec2)> {
+        let pos = self.player_pos()?;
+        let map_data = self.world().resource::<MapData>();
+        let reservations = self.world().resource::<GridReservations>();
+        DIRECTIONS
+            .into_iter()
+            .map(|dir| (pos + dir, dir))
+            .find(|&(tile, _)| {
+                !is_wall(tile, map_data)
+                    && !map_data.is_lava(tile)
+                    && !reservations.0.contains_key(&tile)
+            })
+    }
+
+    /// Spawns a left turner on `tile` heading in `heading`, without checking the tile is free.
+    pub fn spawn_enemy(&mut self, tile: IVec2, heading: IVec2) -> Entity {
+        self.app
+            .world_mut()
+            .run_system_once(move |mut spawner: EnemySpawner| {
+                spawner.spawn_facing(EnemyKind::LeftTurner, tile, heading)
+            })
+            .unwrap()
+    }
+
+    /// Fails unless `EnemyCount` agrees with the number of enemies in the world.
+    pub fn check_enemy_count(&mut self, when: &str) -> Result<(), String> {
+        let counted = self.world().resource::<EnemyCount>().value as usize;
+        let actual = self
+            .app
+            .world_mut()
+            .query_filtered::<(), With<Enemy>>()
+            .iter(self.app.world())
+            .count();
+        if counted != actual {
+            return Err(format!(
+                "{} the enemy count is {} but there are {} enemies",
+                when, counted, actual
+            ));
+        }
+        Ok(())
+    }
+
+    /// Advances time by `step` per update from now on, instead of a sixtieth of a second.
+    pub fn set_step(&mut self, step: Duration) {
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(step));
+    }
+
+    /// The first straight run of `len` free floor tiles, as its first tile and the direction it
+    /// runs in. Free means no wall, no lava and no reservation.
+    pub fn straight_corridor(&self, len: i32) -> Option<(IVec2, IVec2)> {
+        let map_data = self.world().resource::<MapData>();
+        let reservations = self.world().resource::<GridReservations>();
+        let free = |tile: IVec2| {
+            !is_wall(tile, map_data)
+                && !map_data.is_lava(tile)
+                && !reservations.0.contains_key(&tile)
+        };
+        let (width, height) = (map_data.width as i32, map_data.height as i32);
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| IVec2::new(x, y)))
+            .flat_map(|start| [(start, IVec2::X), (start, IVec2::Y)])
+            .find(|&(start, dir)| (0..len).all(|i| free(start + dir * i)))
+    }
+
+    /// Sets how fast `entity` moves on the grid.
+    pub fn set_speed(&mut self, entity: Entity, speed: f32) {
+        if let Some(mut mover) = self.app.world_mut().get_mut::<GridMover>(entity) {
+            mover.speed = speed;
+        }
+    }
+
+    /// Fires a player shot from `from` in `dir`, which appears on the next tile over. Returns
+    /// false if that tile is a wall.
+    pub fn shoot(&mut self, from: IVec2, dir: IVec2) -> bool {
+        let fire = move |mut commands: Commands,
+                         game_assets: Res<GameAssets>,
+                         map_data: Res<MapData>,
+                         mut rng: GlobalEntropy<WyRand>| {
+            let mover = GridMover {
+                grid_pos: from,
+                direction: dir,
+                progress: 0.0,
+                speed: player::DEFAULT_PLAYER_SPEED,
+            };
+            fire_projectile(
+                &mut commands,
+                &game_assets,
+                None,
+                &mover,
+                dir,
+                &map_data,
+                0,
+                &mut rng,
+            )
+        };
+        self.app.world_mut().run_system_once(fire).unwrap_or(false)
+    }
+
+    /// Shoots `target` from the tile behind it, along its heading so the shot chases it if it
+    /// is moving away, until it dies. Tougher enemies take more than one hit, so up to five
+    /// shots are fired, half a second apart.
+    pub fn shoot_until_dead(&mut self, target: Entity) -> Result<(), String> {
+        for _ in 0..5 {
+            let Some(&(_, pos, direction)) = self.enemies().iter().find(|(e, ..)| *e == target)
+            else {
+                break;
+            };
+            let dir = if direction == IVec2::ZERO {
+                IVec2::X
+            } else {
+                direction
+            };
+            if !self.shoot(pos - dir, dir) {
+                return Err(format!("couldn't fire onto the enemy's tile {}", pos));
+            }
+            self.step(30);
+        }
+        if self.world().get_entity(target).is_ok() {
+            return Err("the enemy survived five hits".to_string());
+        }
+        Ok(())
+    }
+
+    /// The tiles the camera shows, in map coordinates.
+    pub fn view_rect(&mut self) -> IRect {
+        self.app
+            .world_mut()
+            .run_system_once(|grid_space: GridSpace| grid_space.viewport_rect_in_map())
+            .unwrap()
+    }
+
+    /// The living enemies and their grid positions and directions.
+    pub fn enemies(&mut self) -> Vec<(Entity, IVec2, IVec2)> {
+        self.app
+            .world_mut()
+            .query_filtered::<(Entity, &GridMover), (With<Enemy>, Without<Dying>)>()
+            .iter(self.app.world())
+            .map(|(entity, mover)| (entity, mover.grid_pos, mover.direction))
+            .collect()
+    }
+}
+
+/// Runs `check` with every seed, failing on the first seed it fails for.
+fn check_every_seed(check: fn(u64) -> Result<(), String>) {
+    for seed in CHECK_SEEDS {
+        if let Err(err) = check(seed) {
+            panic!("seed {:X}: {}", seed, err);
+        }
+    }
+}
+
+#[test]
+fn player_cant_walk_through_walls() {
+    check_every_seed(check_walls_block_player);
+}
+
+#[test]
+fn projectile_kills_enemy() {
+    check_every_seed(check_projectile_kills_enemy);
+}
+
+#[test]
+fn offscreen_enemy_can_be_sniped() {
+    check_every_seed(check_offscreen_enemy_dies);
+}
+
+#[test]
+fn enemy_count_matches_the_world() {
+    check_every_seed(check_enemy_count_follows_kills);
+}
+
+#[test]
+fn fast_shot_hits_oncoming_enemy() {
+    check_every_seed(check_head_on_hit);
+}
+
+#[test]
+fn adjacent_enemy_shot_on_contact_dies_once() {
+    check_every_seed(check_contact_and_shot_count_once);
+}
+
+#[test]
+fn reservations_never_dangle() {
+    check_every_seed(check_reservations_never_dangle);
+}
+
+#[test]
+fn same_seed_plays_out_the_same() {
+    check_every_seed(check_runs_repeat);
+}
+
+#[test]
+fn turners_follow_the_recorded_trace() {
+    let trace = turner_trace(TURNER_TRACE_SEED);
+    assert_eq!(trace, TURNER_TRACE);
+}
+
+/// Walks into a wall and stays put, then wanders at random without ever ending up inside one.
+fn check_walls_block_player(seed: u64) -> Result<(), String> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    let start = sim.player_pos().ok_or("no player")?;
+    let map_data = sim.world().resource::<MapData>();
+    let wall_dir = DIRECTIONS
+        .into_iter()
+        .find(|&dir| is_wall(start + dir, map_data));
+    if let Some(dir) = wall_dir {
+        sim.set_direction(dir);
+        sim.step(60);
+        let pos = sim.player_pos().ok_or("the player is gone")?;
+        if pos != start {
+            return Err(format!(
+                "walking {} into a wall moved the player from {} to {}",
+                dir, start, pos
+            ));
+        }
+    }
+
+    let mut rng = Entropy::<WyRand>::seed_from_u64(seed);
+    for frame in 0..600 {
+        if frame % 15 == 0 {
+            sim.set_direction(*random_pick(&mut rng, &DIRECTIONS));
+        }
+        sim.step(1);
+        let pos = sim.player_pos().ok_or("the player is gone")?;
+        if is_wall(pos, sim.world().resource::<MapData>()) {
+            return Err(format!(
+                "the player is inside a wall at {} on frame {}",
+                pos, frame
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Fires at an enemy until it dies, and checks the enemy count went down.
+fn check_projectile_kills_enemy(seed: u64) -> Result<(), String> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    let (target, ..) = *sim.enemies().first().ok_or("the round has no enemies")?;
+    let count_before = sim.world().resource::<EnemyCount>().value;
+    sim.shoot_until_dead(target)?;
+    let count_after = sim.world().resource::<EnemyCount>().value;
+    if count_after >= count_before {
+        return Err(format!(
+            "the enemy count went from {} to {}",
+            count_before, count_after
+        ));
+    }
+    Ok(())
+}
+
+/// Shoots an enemy outside the camera's view, and checks the kill registers: the enemy dies and
+/// the enemy count drops by exactly one.
+fn check_offscreen_enemy_dies(seed: u64) -> Result<(), String> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    let view = sim.view_rect();
+    let (target, pos, _) = *sim
+        .enemies()
+        .iter()
+        .find(|(_, pos, _)| !view.contains(*pos))
+        .ok_or("every enemy is in view")?;
+    let count_before = sim.world().resource::<EnemyCount>().value;
+    sim.shoot_until_dead(target)
+        .map_err(|err| format!("off-screen at {}: {}", pos, err))?;
+    let count_after = sim.world().resource::<EnemyCount>().value;
+    if count_after + 1 != count_before {
+        return Err(format!(
+            "killing the off-screen enemy at {} took the count from {} to {}",
+            pos, count_before, count_after
+        ));
+    }
+    Ok(())
+}
+
+/// Kills one enemy with a shot and another by contact with the player, and checks after each
+/// that the enemy count still matches the enemies in the world.
+fn check_enemy_count_follows_kills(seed: u64) -> Result<(), String> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    let (shot, ..) = *sim.enemies().first().ok_or("the round has no enemies")?;
+    sim.shoot_until_dead(shot)?;
+    sim.check_enemy_count("after the shot")?;
+
+    // Hearts to spare, so the player survives the contact and the round goes on.
+    sim.set_player_hearts(3);
+    let (tile, dir) = sim
+        .free_tile_next_to_player()
+        .ok_or("the player is boxed in")?;
+    let touched = sim.spawn_enemy(tile, dir);
+    sim.step(5);
+    if sim.world().get_entity(touched).is_ok() {
+        return Err(format!(
+            "the enemy touching the player at {} survived",
+            tile
+        ));
+    }
+    sim.check_enemy_count("after the contact")
+}
+
+/// Sends a 500-speed enemy and a 1500-speed shot at each other down a corridor at 20 frames a
+/// second, fast enough that they pass each other between frames, and checks the shot hits.
+fn check_head_on_hit(seed: u64) -> Result<(), String> {
+    // Long enough that, sampled once a frame, they go from just short of touching to just past
+    // each other: only a swept check catches the hit.
+    const LEN: i32 = 14;
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    sim.set_step(Duration::from_millis(50));
+    let (start, dir) = sim
+        .straight_corridor(LEN)
+        .ok_or("the map has no straight corridor")?;
+    let target = sim.spawn_enemy(start, dir);
+    sim.set_speed(target, 500.0);
+    // The shot appears on the far end of the corridor, heading back down it.
+    if !sim.shoot(start + dir * LEN, -dir) {
+        return Err(format!("couldn't fire down the corridor from {}", start));
+    }
+    sim.step(20);
+    if sim.world().get_entity(target).is_ok() {
+        return Err(format!(
+            "the shot passed the enemy in the corridor from {} along {}",
+            start, dir
+        ));
+    }
+    Ok(())
+}
+
+/// Spawns an enemy next to the player and fires onto its tile in the same frame, so the shot
+/// and the contact both kill it, and checks the enemy count only drops by one.
+fn check_contact_and_shot_count_once(seed: u64) -> Result<(), String> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.set_player_hearts(3);
+    let pos = sim.player_pos().ok_or("no player")?;
+    let (tile, dir) = sim
+        .free_tile_next_to_player()
+        .ok_or("the player is boxed in")?;
+    let count_before = sim.world().resource::<EnemyCount>().value;
+    let target = sim.spawn_enemy(tile, dir);
+    if !sim.shoot(pos, dir) {
+        return Err(format!("couldn't fire onto the enemy's tile {}", tile));
+    }
+    sim.step(5);
+    if sim.world().get_entity(target).is_ok() {
+        return Err(format!("the enemy at {} survived", tile));
+    }
+    let count_after = sim.world().resource::<EnemyCount>().value;
+    if count_after != count_before {
+        return Err(format!(
+            "spawning and killing one enemy took the count from {} to {}",
+            count_before, count_after
+        ));
+    }
+    sim.check_enemy_count("after the double kill")
+}
+
+/// Wanders and shoots for 1000 frames, checking after each one that every reservation belongs
+/// to a living entity that reserves cells.
+fn check_reservations_never_dangle(seed: u64) -> Result<(), String> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    let mut rng = Entropy::<WyRand>::seed_from_u64(seed);
+    for frame in 0..1000 {
+        if frame % 10 == 0 {
+            let dir = *random_pick(&mut rng, &DIRECTIONS);
+            sim.set_direction(dir);
+            if random_bool(&mut rng, 0.5) {
+                if let Some(pos) = sim.player_pos() {
+                    sim.shoot(pos, dir);
+                }
+            }
+        }
+        sim.step(1);
+        let world = sim.world();
+        for (cell, &entity) in &world.resource::<GridReservations>().0 {
+            if world.get::<GridReserver>(entity).is_none() {
+                return Err(format!(
+                    "cell {} is reserved by {}, which is gone, on frame {}",
+                    cell, entity, frame
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Plays the same seeded round twice for 1000 frames, with the player wandering and shooting
+/// the same way both times, and checks the enemies end up in the same places.
+fn check_runs_repeat(seed: u64) -> Result<(), String> {
+    let first = play_scripted(seed, 1000);
+    let second = play_scripted(seed, 1000);
+    if first != second {
+        return Err(format!(
+            "the enemies ended up at {:?} the first time and {:?} the second",
+            first, second
+        ));
+    }
+    Ok(())
+}
+
+/// Plays `frames` frames of a seeded round, steering and firing on a script drawn from `seed`,
+/// and returns the grid positions and directions of the living enemies in spawn order.
+fn play_scripted(seed: u64, frames: u32) -> Vec<(IVec2, IVec2)> {
+    let mut sim = SimulationHarness::new(seed);
+    sim.make_player_invulnerable();
+    let mut rng = Entropy::<WyRand>::seed_from_u64(seed);
+    for frame in 0..frames {
+        if frame % 20 == 0 {
+            let dir = *random_pick(&mut rng, &DIRECTIONS);
+            sim.set_direction(dir);
+            if let Some(pos) = sim.player_pos() {
+                sim.shoot(pos, dir);
+            }
+        }
+        sim.step(1);
+    }
+    let mut enemies = sim.enemies();
+    enemies.sort_by_key(|&(entity, ..)| entity);
+    enemies
+        .into_iter()
+        .map(|(_, pos, direction)| (pos, direction))
+        .collect()
+}
+
+/// Stands the player still for 500 frames of a seeded round with four enemies of each kind,
+/// and records where the enemies are every 50, in spawn order.
+fn turner_trace(seed: u64) -> Vec<Vec<(i32, i32)>> {
+    let mut sim = SimulationHarness::with_enemy_groups(seed, 4);
+    sim.make_player_invulnerable();
+    (0..10)
+        .map(|_| {
+            sim.step(50);
+            let mut enemies = sim.enemies();
+            enemies.sort_by_key(|&(entity, ..)| entity);
+            enemies
+                .into_iter()
+                .map(|(_, pos, _)| (pos.x, pos.y))
+                .collect()
+        })
+        .collect()
+}
@@ -0,0 +1,355 @@
+// highscore.rs
+
+//! Keeps the top ten scores and persists them to disk between sessions.
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::components::{CurrentRound, GameMode, GameState};
+use crate::config::{parse_ron, save_ron};
+use crate::demo::Demo;
+use crate::platform_io::{read_config, since_epoch};
+use crate::player::Player;
+use crate::round_timer::RoundTimer;
+use crate::score::Score;
+
+/// Where the high score table is saved, relative to the working directory.
+pub const HIGH_SCORES_PATH: &str = "save/highscores.ron";
+
+/// How many entries the table keeps.
+pub const MAX_HIGH_SCORES: usize = 10;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .add_systems(OnEnter(GameState::GameOver), record_high_score)
+            .add_systems(Last, record_high_score_on_exit);
+    }
+}
+
+/// A single entry in the high score table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HighScoreEntry {
+    pub score: u64,
+    pub round: u32,
+    /// The date the score was set, as YYYY-MM-DD.
+    pub date: String,
+}
+
+/// A single entry in the endless mode table, ranked by survival time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SurvivalEntry {
+    /// Seconds survived.
+    pub seconds: f32,
+    pub score: u64,
+    /// The date the time was set, as YYYY-MM-DD.
+    pub date: String,
+}
+
+/// The high score tables, each sorted from best to worst.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct HighScores {
+    pub entries: Vec<HighScoreEntry>,
+    /// Fastest clear time (in seconds) for each round number.
+    #[serde(default)]
+    pub best_round_times: BTreeMap<u32, f32>,
+    /// The endless mode table, ranked by survival time.
+    #[serde(default)]
+    pub endless: Vec<SurvivalEntry>,
+    /// Index of the entry added by the most recent run, if it made the table.
+    #[serde(skip)]
+    pub newest: Option<usize>,
+    /// Index of the endless entry added by the most recent run, if it made the table.
+    #[serde(skip)]
+    pub newest_endless: Option<usize>,
+}
+
+impl HighScores {
+    /// Loads the table from disk, starting fresh if it is missing or unreadable.
+    pub fn load() -> Self {
+        read_config(HIGH_SCORES_PATH)
+            .map_or_else(Self::default, |contents| Self::from_ron(&contents))
+    }
+
+    /// Parses the saved table, starting fresh if it is unreadable.
+    fn from_ron(contents: &str) -> Self {
+        let mut scores: HighScores = parse_ron(HIGH_SCORES_PATH, contents).unwrap_or_default();
+        scores.sort_and_truncate();
+        scores
+    }
+
+    pub fn save(&self) {
+        save_ron(HIGH_SCORES_PATH, self);
+    }
+
+    /// Inserts an entry, keeping the table sorted and capped at `MAX_HIGH_SCORES`.
+    /// Returns the entry's position, or `None` if it didn't make the table.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        // Place new entries after existing ones with the same score.
+        let index = self
+            .entries
+            .iter()
+            .position(|existing| existing.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if index >= MAX_HIGH_SCORES {
+            self.newest = None;
+            return None;
+        }
+        self.entries.insert(index, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        self.newest = Some(index);
+        self.newest
+    }
+
+    /// Inserts an endless mode entry, keeping that table sorted and capped at `MAX_HIGH_SCORES`.
+    /// Returns the entry's position, or `None` if it didn't make the table.
+    pub fn insert_survival(&mut self, entry: SurvivalEntry) -> Option<usize> {
+        let index = self
+            .endless
+            .iter()
+            .position(|existing| existing.seconds < entry.seconds)
+            .unwrap_or(self.endless.len());
+        if index >= MAX_HIGH_SCORES {
+            self.newest_endless = None;
+            return None;
+        }
+        self.endless.insert(index, entry);
+        self.endless.truncate(MAX_HIGH_SCORES);
+        self.newest_endless = Some(index);
+        self.newest_endless
+    }
+
+    /// Records a clear time for `round`, returning true if it beats the previous best.
+    pub fn record_round_time(&mut self, round: u32, seconds: f32) -> bool {
+        let is_best = self
+            .best_round_times
+            .get(&round)
+            .is_none_or(|&best| seconds < best);
+        if is_best {
+            self.best_round_times.insert(round, seconds);
+        }
+        is_best
+    }
+
+    /// Returns true if `score` would earn a place in the table.
+    pub fn qualifies(&self, score: u64) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    fn sort_and_truncate(&mut self) {
+        self.entries
+            .sort_by_key(|entry| std::cmp::Reverse(entry.score));
+        self.entries.truncate(MAX_HIGH_SCORES);
+        self.endless.sort_by(|a, b| b.seconds.total_cmp(&a.seconds));
+        self.endless.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+/// Records the finished run's score when the game over summary is shown.
+///
+/// Endless runs go into their own table, ranked by how long the player survived. Time attack
+/// runs have no table; their clear times are kept in the save file instead.
+pub fn record_high_score(
+    mut high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    round: Res<CurrentRound>,
+    mode: Res<GameMode>,
+    round_timer: Res<RoundTimer>,
+) {
+    high_scores.newest = None;
+    high_scores.newest_endless = None;
+    if *mode == GameMode::TimeAttack {
+        return;
+    }
+    if *mode == GameMode::Endless {
+        let entry = SurvivalEntry {
+            seconds: round_timer.total,
+            score: score.0,
+            date: today(),
+        };
+        if high_scores.insert_survival(entry).is_some() {
+            info!("New endless best: {:.1}s", round_timer.total);
+            high_scores.save();
+        }
+        return;
+    }
+    if score.0 == 0 {
+        return;
+    }
+    let entry = HighScoreEntry {
+        score: score.0,
+        round: round.0,
+        date: today(),
+    };
+    if high_scores.insert(entry).is_some() {
+        info!("New high score: {}", score.0);
+        high_scores.save();
+    }
+}
+
+/// Records the score if the game is closed while a run is still alive (e.g. during victory).
+#[allow(clippy::too_many_arguments)]
+fn record_high_score_on_exit(
+    mut exit_events: EventReader<AppExit>,
+    state: Option<Res<State<GameState>>>,
+    player_query: Query<(), With<Player>>,
+    high_scores: ResMut<HighScores>,
+    score: Res<Score>,
+    round: Res<CurrentRound>,
+    mode: Res<GameMode>,
+    round_timer: Res<RoundTimer>,
+    demo: Res<Demo>,
+) {
+    if exit_events.read().next().is_none() {
+        return;
+    }
+    let in_run = !demo.0
+        && state.is_some_and(|s| matches!(s.get(), GameState::Playing | GameState::Victory));
+    if in_run && !player_query.is_empty() {
+        record_high_score(high_scores, score, round, mode, round_timer);
+    }
+}
+
+/// Returns today's date (UTC) formatted as YYYY-MM-DD.
+fn today() -> String {
+    let secs = since_epoch().as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts days since the Unix epoch into a (year, month, day) civil date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(score: u64) -> HighScoreEntry {
+        HighScoreEntry {
+            score,
+            round: 1,
+            date: "2024-01-01".to_string(),
+        }
+    }
+
+    fn scores(table: &HighScores) -> Vec<u64> {
+        table.entries.iter().map(|entry| entry.score).collect()
+    }
+
+    #[test]
+    fn insert_keeps_the_table_sorted() {
+        let mut table = HighScores::default();
+        assert_eq!(table.insert(entry(50)), Some(0));
+        assert_eq!(table.insert(entry(80)), Some(0));
+        assert_eq!(table.insert(entry(60)), Some(1));
+        assert_eq!(scores(&table), [80, 60, 50]);
+        assert_eq!(table.newest, Some(1));
+    }
+
+    #[test]
+    fn insert_places_ties_after_existing_scores() {
+        let mut table = HighScores::default();
+        table.insert(entry(50));
+        let mut tie = entry(50);
+        tie.round = 2;
+        assert_eq!(table.insert(tie), Some(1));
+        assert_eq!(table.entries[0].round, 1);
+    }
+
+    #[test]
+    fn insert_caps_the_table() {
+        let mut table = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u64 {
+            table.insert(entry(score * 10));
+        }
+        assert!(!table.qualifies(10));
+        assert_eq!(table.insert(entry(10)), None);
+        assert_eq!(table.newest, None);
+        assert!(table.qualifies(15));
+        assert_eq!(table.insert(entry(15)), Some(MAX_HIGH_SCORES - 1));
+        assert_eq!(table.entries.len(), MAX_HIGH_SCORES);
+        assert_eq!(table.entries.last().unwrap().score, 15);
+    }
+
+    #[test]
+    fn tables_survive_a_round_trip_through_ron() {
+        let mut table = HighScores::default();
+        table.insert(entry(80));
+        table.insert(entry(50));
+        table.record_round_time(1, 12.5);
+        table.record_round_time(3, 40.25);
+        table.insert_survival(SurvivalEntry {
+            seconds: 95.5,
+            score: 30,
+            date: "2024-02-29".to_string(),
+        });
+        // Written the way `save_ron` writes it.
+        let contents =
+            ron::ser::to_string_pretty(&table, ron::ser::PrettyConfig::default()).unwrap();
+
+        let loaded = HighScores::from_ron(&contents);
+        assert_eq!(loaded.entries, table.entries);
+        assert_eq!(loaded.best_round_times, table.best_round_times);
+        assert_eq!(loaded.endless, table.endless);
+        // Which entries are new only matters to the run that set them.
+        assert_eq!(loaded.newest, None);
+        assert_eq!(loaded.newest_endless, None);
+    }
+
+    #[test]
+    fn loading_sorts_and_caps_the_tables() {
+        let entries: Vec<String> = (1..=MAX_HIGH_SCORES as u64 + 2)
+            .map(|score| format!("(score: {}, round: 1, date: \"2024-01-01\")", score))
+            .collect();
+        let loaded = HighScores::from_ron(&format!("(entries: [{}])", entries.join(", ")));
+        let expected: Vec<u64> = (3..=MAX_HIGH_SCORES as u64 + 2).rev().collect();
+        assert_eq!(scores(&loaded), expected);
+        // The fields added later default when an older file lacks them.
+        assert!(loaded.best_round_times.is_empty());
+        assert!(loaded.endless.is_empty());
+    }
+
+    #[test]
+    fn corrupt_tables_load_as_empty() {
+        for contents in [
+            "",
+            "not ron at all",
+            "(entries: [(score: 80, round: 1, date: \"2024-01-01\")",
+            "(entries: [(score: -5, round: 1, date: \"2024-01-01\")])",
+            "[1, 2, 3]",
+        ] {
+            let loaded = HighScores::from_ron(contents);
+            assert!(loaded.entries.is_empty(), "{:?} loaded entries", contents);
+            assert!(loaded.best_round_times.is_empty());
+            assert!(loaded.endless.is_empty());
+        }
+    }
+
+    #[test]
+    fn civil_from_days_matches_known_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(10_957), (2000, 1, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), (2024, 3, 1));
+        assert_eq!(civil_from_days(20_088), (2024, 12, 31));
+    }
+}
@@ -0,0 +1,267 @@
+// hints.rs
+
+//! One-time hints for new players, shown in a small box near the bottom of the screen when
+//! the moment calls for them: how to move, how to shoot once an enemy is close, and so on.
+//!
+//! Hints are listed in `HINTS`, each with a condition on the `HintContext` gathered every
+//! frame. Adding a hint means adding an entry there, plus a field to the context if it needs
+//! something new. Each hint is shown once ever: its id is recorded in `SaveData` when it is
+//! shown. Turning the Hints setting off and on again clears that record.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::{EnemyKilled, Faction, GameState};
+use crate::demo::in_demo;
+use crate::enemy::Enemy;
+use crate::grid_movement::{GridMover, IntendedDirection};
+use crate::input::InputMap;
+use crate::player::Player;
+use crate::projectile::{Bouncable, Projectile};
+use crate::save::SaveData;
+
+/// Seconds a hint stays up, unless it has its own `done` condition.
+const HINT_LIFETIME: f32 = 4.0;
+
+/// Seconds at the end of a hint's life over which it fades out.
+const HINT_FADE_TIME: f32 = 0.5;
+
+/// An enemy this many tiles from the player or closer prompts the shooting hint.
+const SHOOT_HINT_DISTANCE: f32 = 8.0;
+
+/// A bounced shot of the player's this many tiles from them or closer counts as passing by.
+const BOUNCE_HINT_DISTANCE: f32 = 2.0;
+
+const HINT_FONT_SIZE: f32 = 8.0;
+
+const HINT_BACKGROUND_ALPHA: f32 = 0.7;
+
+pub struct HintsPlugin;
+
+impl Plugin for HintsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ShowHints(true))
+            .init_resource::<ActiveHint>()
+            .add_systems(OnExit(GameState::Loading), spawn_hint_box)
+            .add_systems(OnExit(GameState::Playing), clear_active_hint)
+            .add_systems(
+                Update,
+                (
+                    reset_seen_hints.run_if(resource_changed::<ShowHints>),
+                    (update_hints, update_hint_box)
+                        .chain()
+                        .run_if(in_state(GameState::Playing).and(not(in_demo))),
+                ),
+            );
+    }
+}
+
+/// Whether hints are shown at all, pushed from `Settings`.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShowHints(pub bool);
+
+/// What the hint conditions can look at, gathered fresh each frame.
+struct HintContext {
+    /// The player is trying to move.
+    moving: bool,
+    /// Distance in tiles from the player to the nearest enemy.
+    nearest_enemy: Option<f32>,
+    /// Distance in tiles from the player to the nearest of their own shots that has bounced.
+    nearest_bounced_shot: Option<f32>,
+    /// An enemy was killed this frame.
+    killed: bool,
+}
+
+struct Hint {
+    /// Recorded in `SaveData::seen_hints` once shown, so keep it stable.
+    id: &'static str,
+    text: fn(&InputMap) -> String,
+    /// Shows the hint the first time it holds while no other hint is up.
+    when: fn(&HintContext) -> bool,
+    /// Takes the hint down early. Without one, it stays up for `HINT_LIFETIME`.
+    done: Option<fn(&HintContext) -> bool>,
+}
+
+const HINTS: [Hint; 4] = [
+    Hint {
+        id: "move",
+        text: |keys| format!("{} TO MOVE", keys.movement_label()),
+        when: |_| true,
+        done: Some(|context| context.moving),
+    },
+    Hint {
+        id: "shoot",
+        text: |keys| format!("{} TO SHOOT", keys.fire_label()),
+        when: |context| {
+            context
+                .nearest_enemy
+                .is_some_and(|distance| distance <= SHOOT_HINT_DISTANCE)
+        },
+        done: None,
+    },
+    Hint {
+        id: "bounce",
+        text: |_| "SHOTS MUST BOUNCE BEFORE THEY CAN HURT YOU".to_string(),
+        when: |context| {
+            context
+                .nearest_bounced_shot
+                .is_some_and(|distance| distance <= BOUNCE_HINT_DISTANCE)
+        },
+        done: None,
+    },
+    Hint {
+        id: "kill",
+        text: |_| "ENEMY KILLED: WATCH THE COUNTER".to_string(),
+        when: |context| context.killed,
+        done: None,
+    },
+];
+
+/// The hint on screen, as an index into `HINTS`, and how long it has been up.
+#[derive(Resource, Default)]
+struct ActiveHint(Option<(usize, f32)>);
+
+#[derive(Component)]
+struct HintBox;
+
+fn spawn_hint_box(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(40.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            GlobalZIndex(20),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: HINT_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[12]),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::BLACK.with_alpha(HINT_BACKGROUND_ALPHA)),
+                Visibility::Hidden,
+                HintBox,
+            ));
+        });
+}
+
+fn clear_active_hint(mut active: ResMut<ActiveHint>) {
+    active.0 = None;
+}
+
+/// Forgets which hints were seen when hints are switched back on.
+fn reset_seen_hints(show: Res<ShowHints>, mut save: ResMut<SaveData>) {
+    if show.0 && !show.is_added() && !save.seen_hints.is_empty() {
+        save.seen_hints.clear();
+        save.save();
+    }
+}
+
+/// Gathers the context, ages or ends the hint on screen, and shows the next one that is due.
+#[allow(clippy::too_many_arguments)]
+fn update_hints(
+    show: Res<ShowHints>,
+    mut active: ResMut<ActiveHint>,
+    mut save: ResMut<SaveData>,
+    mut kills: EventReader<EnemyKilled>,
+    player_query: Query<(&GridMover, &IntendedDirection), With<Player>>,
+    enemy_query: Query<&GridMover, With<Enemy>>,
+    shot_query: Query<(&GridMover, &Bouncable, &Faction), With<Projectile>>,
+    time: Res<Time<Real>>,
+) {
+    let killed = kills.read().count() > 0;
+    if !show.0 {
+        active.0 = None;
+        return;
+    }
+    let Ok((player, intended)) = player_query.single() else {
+        return;
+    };
+    let distance = |mover: &GridMover| mover.grid_pos.as_vec2().distance(player.grid_pos.as_vec2());
+    let context = HintContext {
+        moving: intended.0 != IVec2::ZERO,
+        nearest_enemy: enemy_query.iter().map(distance).reduce(f32::min),
+        nearest_bounced_shot: shot_query
+            .iter()
+            .filter(|(_, bouncable, faction)| {
+                **faction == Faction::Players && bouncable.remaining < bouncable.initial
+            })
+            .map(|(mover, _, _)| distance(mover))
+            .reduce(f32::min),
+        killed,
+    };
+
+    if let Some((index, age)) = &mut active.0 {
+        *age += time.delta_secs();
+        let hint = &HINTS[*index];
+        let finished = match hint.done {
+            Some(done) => done(&context),
+            None => *age >= HINT_LIFETIME,
+        };
+        if !finished {
+            return;
+        }
+        active.0 = None;
+    }
+
+    let due = HINTS
+        .iter()
+        .position(|hint| !save.seen_hints.iter().any(|id| id == hint.id) && (hint.when)(&context));
+    if let Some(index) = due {
+        info!("Hint: {}", HINTS[index].id);
+        save.seen_hints.push(HINTS[index].id.to_string());
+        // Written at once, since a hint can be seen in a round that is never finished.
+        save.save();
+        active.0 = Some((index, 0.0));
+    }
+}
+
+fn update_hint_box(
+    active: Res<ActiveHint>,
+    input_map: Res<InputMap>,
+    game_assets: Res<GameAssets>,
+    mut query: Query<
+        (
+            &mut Text,
+            &mut TextColor,
+            &mut BackgroundColor,
+            &mut Visibility,
+        ),
+        With<HintBox>,
+    >,
+    mut shown: Local<Option<usize>>,
+) {
+    let Ok((mut text, mut color, mut background, mut visibility)) = query.single_mut() else {
+        return;
+    };
+    let Some((index, age)) = active.0 else {
+        *shown = None;
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    let hint = &HINTS[index];
+    if *shown != Some(index) {
+        *shown = Some(index);
+        text.0 = (hint.text)(&input_map);
+    }
+    // Hints that wait for the player don't fade.
+    let alpha = match hint.done {
+        Some(_) => 1.0,
+        None => ((HINT_LIFETIME - age) / HINT_FADE_TIME).clamp(0.0, 1.0),
+    };
+    color.0 = game_assets.palette.colors[12].with_alpha(alpha);
+    background.0 = Color::BLACK.with_alpha(HINT_BACKGROUND_ALPHA * alpha);
+    visibility.set_if_neq(Visibility::Inherited);
+}
@@ -0,0 +1,155 @@
+// hit_stop.rs
+
+//! Hit-stop: the whole simulation freezes for a few hundredths of a second on an impactful
+//! kill, so it lands with some weight. A ricochet kill or the last enemy of a round outside
+//! endless mode sets it off.
+//!
+//! The freeze holds the gameplay systems through `simulation_running`, the same run condition
+//! frame-step mode uses, while rendering, the camera, particles already drawn and the UI keep
+//! going. It is timed in real time, so slow motion doesn't stretch it and it always wins over
+//! `GameSpeed`. Several kills in one frame clamp to the longest freeze rather than adding up,
+//! and kills from chain explosions can only set one off every `CASCADE_COOLDOWN`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::components::{EnemyKilled, GameMode, GameState, KillSource};
+use crate::score::{update_enemy_count, EnemyCount};
+
+/// Shortest time between two freezes set off by chain explosion kills.
+const CASCADE_COOLDOWN: Duration = Duration::from_millis(500);
+
+pub struct HitStopPlugin;
+
+impl Plugin for HitStopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HitStop>()
+            .insert_resource(HitStopIntensity::default())
+            .add_systems(OnExit(GameState::Playing), clear_hit_stop)
+            // Ticks before `Update`, so the frame a freeze runs out on is simulated.
+            .add_systems(PreUpdate, tick_hit_stop)
+            .add_systems(
+                Update,
+                trigger_hit_stop
+                    .after(update_enemy_count)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// How long hit-stop freezes last, pushed from `Settings`.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HitStopIntensity {
+    Off,
+    Subtle,
+    #[default]
+    Full,
+}
+
+impl HitStopIntensity {
+    pub fn label(self) -> &'static str {
+        match self {
+            HitStopIntensity::Off => "OFF",
+            HitStopIntensity::Subtle => "SUBTLE",
+            HitStopIntensity::Full => "FULL",
+        }
+    }
+
+    /// The next intensity in the given direction, wrapping around.
+    pub fn cycled(self, step: i32) -> Self {
+        const ORDER: [HitStopIntensity; 3] = [
+            HitStopIntensity::Off,
+            HitStopIntensity::Subtle,
+            HitStopIntensity::Full,
+        ];
+        let index = ORDER.iter().position(|&mode| mode == self).unwrap_or(0) as i32;
+        ORDER[(index + step).rem_euclid(ORDER.len() as i32) as usize]
+    }
+
+    /// Length of one freeze, or `None` when hit-stop is off.
+    fn freeze(self) -> Option<Duration> {
+        match self {
+            HitStopIntensity::Off => None,
+            HitStopIntensity::Subtle => Some(Duration::from_millis(40)),
+            HitStopIntensity::Full => Some(Duration::from_millis(80)),
+        }
+    }
+}
+
+/// The freeze in progress, if any. Finished when the simulation is free to run.
+#[derive(Resource)]
+pub struct HitStop(pub Timer);
+
+impl Default for HitStop {
+    fn default() -> Self {
+        let mut timer = Timer::new(Duration::ZERO, TimerMode::Once);
+        timer.tick(Duration::ZERO);
+        HitStop(timer)
+    }
+}
+
+impl HitStop {
+    pub fn active(&self) -> bool {
+        !self.0.finished()
+    }
+
+    /// Freezes for at least `duration` from now. A freeze already running longer is kept, so
+    /// overlapping triggers never add up.
+    fn freeze(&mut self, duration: Duration) {
+        if self.0.remaining() < duration {
+            self.0 = Timer::new(duration, TimerMode::Once);
+        }
+    }
+}
+
+fn tick_hit_stop(mut hit_stop: ResMut<HitStop>, time: Res<Time<Real>>) {
+    if hit_stop.active() {
+        hit_stop.0.tick(time.delta());
+    }
+}
+
+fn clear_hit_stop(mut hit_stop: ResMut<HitStop>) {
+    *hit_stop = HitStop::default();
+}
+
+/// Starts a freeze on a ricochet kill, or when a winnable round's last enemy goes down.
+#[allow(clippy::too_many_arguments)]
+pub fn trigger_hit_stop(
+    mut events: EventReader<EnemyKilled>,
+    enemy_count: Res<EnemyCount>,
+    mode: Res<GameMode>,
+    intensity: Res<HitStopIntensity>,
+    time: Res<Time<Real>>,
+    mut hit_stop: ResMut<HitStop>,
+    mut last_count: Local<u32>,
+    mut last_cascade: Local<Option<Duration>>,
+) {
+    let mut ricochet = false;
+    let mut cascade = false;
+    for event in events.read() {
+        match event.source {
+            KillSource::Ricochet { .. } => ricochet = true,
+            KillSource::Explosion => cascade = true,
+            _ => {}
+        }
+    }
+    // The count is reset to zero at the start of each round, so only a drop counts.
+    let final_kill = *mode != GameMode::Endless && *last_count > 0 && enemy_count.value == 0;
+    *last_count = enemy_count.value;
+
+    let Some(duration) = intensity.freeze() else {
+        return;
+    };
+    if !ricochet && !final_kill {
+        return;
+    }
+    if !ricochet && cascade {
+        let now = time.elapsed();
+        if last_cascade.is_some_and(|last| now - last < CASCADE_COOLDOWN) {
+            return;
+        }
+        *last_cascade = Some(now);
+    }
+    hit_stop.freeze(duration);
+}
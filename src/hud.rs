@@ -0,0 +1,354 @@
+// hud.rs
+
+//! The in-game HUD: a bar across the top of the screen with the player's hearts and shots in
+//! flight on the left, the round number in the middle, and the score, enemy count, gem count and round time on the
+//! right. On a time attack course the middle shows the course name and a large countdown
+//! instead, colored for the best medal still within reach.
+//!
+//! The bar is laid out with flex nodes, so `UiScale` keeps it in proportion. Each element is
+//! only spawned when the resource it shows exists, and only rewritten when that resource
+//! changes.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::{CurrentRound, GameEntity, GameMode, GameState, Health};
+use crate::gems::{scatter_gems, GemCount};
+use crate::player::Player;
+use crate::projectile::Projectile;
+use crate::round_timer::{format_time, tick_round_timer, RoundTimer};
+use crate::score::{reset_displayed_score, reset_enemy_count, DisplayedScore, EnemyCount};
+use crate::time_attack::{medal_color, start_course, ActiveCourse};
+
+const HUD_FONT_SIZE: f32 = 16.0;
+
+/// The time attack countdown is shown larger than the rest of the bar.
+const CLOCK_FONT_SIZE: f32 = 32.0;
+
+/// Space between the bar and the edges of the screen.
+const HUD_MARGIN: f32 = 10.0;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_hud
+                .after(reset_displayed_score)
+                .after(reset_enemy_count)
+                .after(scatter_gems)
+                .after(start_course),
+        )
+        .add_systems(
+            Update,
+            (
+                update_hearts_text,
+                update_shots_text,
+                update_round_text.run_if(resource_exists_and_changed::<CurrentRound>),
+                update_score_text.run_if(resource_exists_and_changed::<DisplayedScore>),
+                update_enemy_count_text.run_if(resource_exists_and_changed::<EnemyCount>),
+                update_gem_count_text.run_if(resource_exists_and_changed::<GemCount>),
+                update_round_time_text
+                    .after(tick_round_timer)
+                    .run_if(resource_exists::<RoundTimer>),
+                update_course_clock_text
+                    .after(tick_round_timer)
+                    .run_if(resource_exists::<RoundTimer>)
+                    .run_if(resource_exists::<ActiveCourse>),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct HeartsText;
+
+#[derive(Component)]
+struct ShotsText;
+
+#[derive(Component)]
+struct RoundText;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct EnemyCountText;
+
+#[derive(Component)]
+struct GemCountText;
+
+#[derive(Component)]
+struct RoundTimeText;
+
+#[derive(Component)]
+struct CourseClockText;
+
+/// The label for the enemy count, which counts down to victory outside endless mode.
+fn enemy_count_label(mode: GameMode, count: u32) -> String {
+    match mode {
+        GameMode::Classic | GameMode::TimeAttack => format!("remaining: {}", count),
+        GameMode::Endless => format!("alive: {}", count),
+    }
+}
+
+fn gem_count_label(count: GemCount) -> String {
+    format!("gems: {}/{}", count.collected, count.total)
+}
+
+/// The countdown's text and color after `elapsed` seconds of the course. Rounds up, so it
+/// reads 00:00 only once time has run out.
+fn course_clock(course: &ActiveCourse, elapsed: f32, game_assets: &GameAssets) -> (String, Color) {
+    let text = format_time(course.0.time_left(elapsed).ceil());
+    let color = medal_color(course.0.par.medal(elapsed), &game_assets.palette);
+    (text, color)
+}
+
+/// A line of HUD text in the game's font.
+fn hud_text(
+    game_assets: &GameAssets,
+    text: String,
+    color: Color,
+    justify: JustifyText,
+) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font: game_assets.font.clone(),
+            font_size: HUD_FONT_SIZE,
+            ..default()
+        },
+        TextColor(color),
+        TextLayout::new_with_justify(justify),
+    )
+}
+
+/// One of the bar's three columns, with its lines lined up along `align`.
+fn hud_column(align: AlignItems) -> Node {
+    Node {
+        flex_direction: FlexDirection::Column,
+        flex_basis: Val::Px(0.0),
+        flex_grow: 1.0,
+        align_items: align,
+        ..default()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_hud(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mode: Res<GameMode>,
+    round: Option<Res<CurrentRound>>,
+    score: Option<Res<DisplayedScore>>,
+    enemy_count: Option<Res<EnemyCount>>,
+    gem_count: Option<Res<GemCount>>,
+    timer: Option<Res<RoundTimer>>,
+    course: Option<Res<ActiveCourse>>,
+) {
+    let text_color = game_assets.palette.colors[3];
+    let hearts_color = game_assets.palette.colors[2];
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(HUD_MARGIN),
+                left: Val::Px(HUD_MARGIN),
+                right: Val::Px(HUD_MARGIN),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|bar| {
+            bar.spawn(hud_column(AlignItems::FlexStart))
+                .with_children(|left| {
+                    // Filled in when the player's health is first seen.
+                    left.spawn((
+                        hud_text(&game_assets, String::new(), hearts_color, JustifyText::Left),
+                        HeartsText,
+                    ));
+                    // Filled in on the first frame.
+                    left.spawn((
+                        hud_text(&game_assets, String::new(), text_color, JustifyText::Left),
+                        ShotsText,
+                    ));
+                });
+            bar.spawn(hud_column(AlignItems::Center))
+                .with_children(|center| {
+                    if let (Some(course), Some(timer)) = (&course, &timer) {
+                        center.spawn(hud_text(
+                            &game_assets,
+                            course.0.name.clone(),
+                            text_color,
+                            JustifyText::Center,
+                        ));
+                        let (clock, clock_color) = course_clock(course, timer.round, &game_assets);
+                        center.spawn((
+                            Text::new(clock),
+                            TextFont {
+                                font: game_assets.font.clone(),
+                                font_size: CLOCK_FONT_SIZE,
+                                ..default()
+                            },
+                            TextColor(clock_color),
+                            TextLayout::new_with_justify(JustifyText::Center),
+                            CourseClockText,
+                        ));
+                    } else if let Some(round) = &round {
+                        center.spawn((
+                            hud_text(
+                                &game_assets,
+                                format!("round {}", round.0),
+                                text_color,
+                                JustifyText::Center,
+                            ),
+                            RoundText,
+                        ));
+                    }
+                });
+            bar.spawn(hud_column(AlignItems::FlexEnd))
+                .with_children(|right| {
+                    if let Some(score) = &score {
+                        right.spawn((
+                            hud_text(
+                                &game_assets,
+                                format!("score: {}", score.shown()),
+                                text_color,
+                                JustifyText::Right,
+                            ),
+                            ScoreText,
+                        ));
+                    }
+                    if let Some(enemy_count) = &enemy_count {
+                        right.spawn((
+                            hud_text(
+                                &game_assets,
+                                enemy_count_label(*mode, enemy_count.value),
+                                text_color,
+                                JustifyText::Right,
+                            ),
+                            EnemyCountText,
+                        ));
+                    }
+                    if let Some(gem_count) = &gem_count {
+                        right.spawn((
+                            hud_text(
+                                &game_assets,
+                                gem_count_label(**gem_count),
+                                text_color,
+                                JustifyText::Right,
+                            ),
+                            GemCountText,
+                        ));
+                    }
+                    // The countdown in the middle takes the place of the round time.
+                    if let (Some(timer), None) = (&timer, &course) {
+                        right.spawn((
+                            hud_text(
+                                &game_assets,
+                                format_time(timer.round),
+                                text_color,
+                                JustifyText::Right,
+                            ),
+                            RoundTimeText,
+                        ));
+                    }
+                });
+        });
+}
+
+fn update_hearts_text(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut text_query: Query<&mut Text, With<HeartsText>>,
+) {
+    if let (Ok(health), Ok(mut text)) = (player_query.single(), text_query.single_mut()) {
+        text.0 = format!("hearts: {}/{}", health.current, health.max);
+    }
+}
+
+/// Shows how many of the player's shots are still bouncing around, rewritten only when that
+/// changes.
+fn update_shots_text(
+    projectiles: Query<(), With<Projectile>>,
+    mut query: Query<&mut Text, With<ShotsText>>,
+    mut last_shown: Local<Option<usize>>,
+) {
+    let Ok(mut text) = query.single_mut() else {
+        return;
+    };
+    let count = projectiles.iter().count();
+    // A freshly spawned HUD starts empty, whatever was shown last round.
+    if *last_shown != Some(count) || text.0.is_empty() {
+        *last_shown = Some(count);
+        text.0 = format!("shots: {}", count);
+    }
+}
+
+fn update_round_text(round: Res<CurrentRound>, mut query: Query<&mut Text, With<RoundText>>) {
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format!("round {}", round.0);
+    }
+}
+
+fn update_score_text(score: Res<DisplayedScore>, mut query: Query<&mut Text, With<ScoreText>>) {
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format!("score: {}", score.shown());
+    }
+}
+
+fn update_enemy_count_text(
+    enemy_count: Res<EnemyCount>,
+    mode: Res<GameMode>,
+    mut query: Query<&mut Text, With<EnemyCountText>>,
+) {
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = enemy_count_label(*mode, enemy_count.value);
+    }
+}
+
+fn update_gem_count_text(
+    gem_count: Res<GemCount>,
+    mut query: Query<&mut Text, With<GemCountText>>,
+) {
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = gem_count_label(*gem_count);
+    }
+}
+
+/// The timer runs every frame, so this only rewrites the text when the shown second changes.
+fn update_round_time_text(
+    timer: Res<RoundTimer>,
+    mut query: Query<&mut Text, With<RoundTimeText>>,
+    mut last_shown: Local<u32>,
+) {
+    let whole = timer.round as u32;
+    if whole == *last_shown && whole != 0 {
+        return;
+    }
+    *last_shown = whole;
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format_time(timer.round);
+    }
+}
+
+/// Like the round time, only rewritten when the shown second changes.
+fn update_course_clock_text(
+    timer: Res<RoundTimer>,
+    course: Res<ActiveCourse>,
+    game_assets: Res<GameAssets>,
+    mut query: Query<(&mut Text, &mut TextColor), With<CourseClockText>>,
+    mut last_shown: Local<Option<u32>>,
+) {
+    let whole = timer.round as u32;
+    if *last_shown == Some(whole) {
+        return;
+    }
+    *last_shown = Some(whole);
+    if let Ok((mut text, mut color)) = query.single_mut() {
+        (text.0, color.0) = course_clock(&course, timer.round, &game_assets);
+    }
+}
@@ -0,0 +1,121 @@
+// input.rs
+
+//! Gameplay key bindings.
+//!
+//! Systems that read gameplay keys look them up in `InputMap` rather than naming `KeyCode`s
+//! directly, so the bindings (and the controls shown on the How to Play page) live in one place.
+//!
+//! A `KeyCode` names a physical key by where it sits on a US QWERTY keyboard, not the character
+//! it types, so the WASD cluster stays under the same fingers on other layouts (it is ZQSD on
+//! AZERTY). The arrow keys always move and Enter always fires as well, whatever the bindings.
+
+use bevy::prelude::*;
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputMap>();
+    }
+}
+
+/// The keys bound to each gameplay action.
+#[derive(Resource, Clone, Debug)]
+pub struct InputMap {
+    pub move_up: KeyCode,
+    pub move_down: KeyCode,
+    pub move_left: KeyCode,
+    pub move_right: KeyCode,
+    pub fire: KeyCode,
+    pub restart: KeyCode,
+    pub mute: KeyCode,
+    pub zoom_in: KeyCode,
+    pub zoom_out: KeyCode,
+    /// Cycles the camera between the remaining enemies after the player dies.
+    pub spectate_next: KeyCode,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap {
+            move_up: KeyCode::KeyW,
+            move_down: KeyCode::KeyS,
+            move_left: KeyCode::KeyA,
+            move_right: KeyCode::KeyD,
+            fire: KeyCode::Space,
+            restart: KeyCode::KeyR,
+            mute: KeyCode::KeyM,
+            zoom_in: KeyCode::Equal,
+            zoom_out: KeyCode::Minus,
+            spectate_next: KeyCode::Tab,
+        }
+    }
+}
+
+/// Fires alongside the bound fire key.
+const ALT_FIRE: KeyCode = KeyCode::Enter;
+
+impl InputMap {
+    /// The keys that move up: the bound key and the up arrow.
+    pub fn up_keys(&self) -> [KeyCode; 2] {
+        [self.move_up, KeyCode::ArrowUp]
+    }
+
+    pub fn down_keys(&self) -> [KeyCode; 2] {
+        [self.move_down, KeyCode::ArrowDown]
+    }
+
+    pub fn left_keys(&self) -> [KeyCode; 2] {
+        [self.move_left, KeyCode::ArrowLeft]
+    }
+
+    pub fn right_keys(&self) -> [KeyCode; 2] {
+        [self.move_right, KeyCode::ArrowRight]
+    }
+
+    /// The keys that fire: the bound key and Enter.
+    pub fn fire_keys(&self) -> [KeyCode; 2] {
+        [self.fire, ALT_FIRE]
+    }
+
+    /// The movement direction held on the keyboard, one step on each axis. Opposite keys
+    /// cancel out.
+    pub fn movement(&self, keys: &ButtonInput<KeyCode>) -> IVec2 {
+        let axis = |negative: [KeyCode; 2], positive: [KeyCode; 2]| {
+            i32::from(keys.any_pressed(positive)) - i32::from(keys.any_pressed(negative))
+        };
+        IVec2::new(
+            axis(self.left_keys(), self.right_keys()),
+            axis(self.down_keys(), self.up_keys()),
+        )
+    }
+
+    /// The movement keys, in up/left/down/right order, and the arrows, as shown to the player.
+    pub fn movement_label(&self) -> String {
+        let bound = [
+            self.move_up,
+            self.move_left,
+            self.move_down,
+            self.move_right,
+        ]
+        .map(key_label)
+        .join(" ");
+        format!("{} / ARROWS", bound)
+    }
+
+    /// The fire keys, as shown to the player.
+    pub fn fire_label(&self) -> String {
+        format!("{} / {}", key_label(self.fire), key_label(ALT_FIRE))
+    }
+}
+
+/// A short, upper-case name for a key, e.g. "W" for `KeyCode::KeyW`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    let short = name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .or_else(|| name.strip_prefix("Arrow"))
+        .unwrap_or(&name);
+    short.to_uppercase()
+}
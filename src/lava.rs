@@ -0,0 +1,117 @@
+// lava.rs
+
+//! Lava tiles, which the map generator leaves in a few dead-end alcoves.
+//!
+//! Anything that finishes a move onto lava is hurt: the player loses a heart and is knocked
+//! back to the tile it came from, and enemies die outright. Projectiles fly over it. Enemy AI
+//! won't step onto lava, so it's mostly a hazard for a player dashing past.
+//!
+//! All lava pulses together from one timer; the tilemap reads `LavaPulse` to recolor it.
+
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::collider::{resolve_damage, DamageEvent};
+use crate::components::{Dying, GameState, KillSource};
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{GridMover, IntendedDirection, MovementSystems};
+use crate::grid_reservation::GridReservations;
+use crate::map::MapData;
+use crate::player::Player;
+use crate::projectile::Projectile;
+
+/// How long lava shows each of its two colors, so a full cycle takes half a second.
+const LAVA_PULSE_TIME: f32 = 0.25;
+
+pub struct LavaPlugin;
+
+impl Plugin for LavaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LavaPulse>()
+            .add_systems(Update, tick_lava_pulse.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                burn_lava_walkers
+                    .after(MovementSystems::UpdateMover)
+                    .before(MovementSystems::UpdatePosition)
+                    .before(resolve_damage)
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
+            );
+    }
+}
+
+/// Which of its two colors lava is showing; flips every `LAVA_PULSE_TIME`.
+#[derive(Resource)]
+pub struct LavaPulse {
+    timer: Timer,
+    pub phase: usize,
+}
+
+impl Default for LavaPulse {
+    fn default() -> Self {
+        LavaPulse {
+            timer: Timer::from_seconds(LAVA_PULSE_TIME, TimerMode::Repeating),
+            phase: 0,
+        }
+    }
+}
+
+fn tick_lava_pulse(mut pulse: ResMut<LavaPulse>, time: Res<Time>) {
+    // Only touched when the phase flips, so the tilemap can run on `resource_changed`.
+    let flips = pulse
+        .bypass_change_detection()
+        .timer
+        .tick(time.delta())
+        .times_finished_this_tick();
+    if flips % 2 == 1 {
+        pulse.phase ^= 1;
+    }
+}
+
+/// Hurts every mover that has just arrived on a lava tile, as seen from the tile it was on
+/// last frame.
+#[allow(clippy::type_complexity)]
+fn burn_lava_walkers(
+    map_data: Res<MapData>,
+    mut reservations: ResMut<GridReservations>,
+    mut movers: Query<
+        (
+            Entity,
+            &mut GridMover,
+            &mut IntendedDirection,
+            &Transform,
+            Has<Player>,
+        ),
+        (Without<Projectile>, Without<Dying>),
+    >,
+    mut last_tiles: Local<HashMap<Entity, IVec2>>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let mut tiles = HashMap::with_capacity(last_tiles.len());
+    for (entity, mut mover, mut intended, transform, is_player) in &mut movers {
+        let came_from = last_tiles
+            .get(&entity)
+            .copied()
+            .filter(|&from| from != mover.grid_pos && map_data.is_lava(mover.grid_pos));
+        if let Some(from) = came_from {
+            damage_events.write(DamageEvent {
+                victim: entity,
+                // One heart for the player; anything else burns up.
+                amount: if is_player { 1 } else { u32::MAX },
+                source: KillSource::Lava,
+                position: transform.translation,
+            });
+            if is_player {
+                // Back where it came from, dropping the tiles it held on the way.
+                reservations.0.retain(|_, e| *e != entity);
+                reservations.0.insert(from, entity);
+                mover.grid_pos = from;
+                mover.direction = IVec2::ZERO;
+                mover.progress = 0.0;
+                intended.0 = IVec2::ZERO;
+            }
+        }
+        tiles.insert(entity, mover.grid_pos);
+    }
+    *last_tiles = tiles;
+}
@@ -2,28 +2,80 @@ use bevy::prelude::*;
 
 //link our modules to our project
 
+pub mod achievements;
+pub mod arena;
 pub mod assets;
+pub mod atlas;
 pub mod audio;
+pub mod autotile;
+pub mod backdrop;
 pub mod border;
+pub mod bump;
 pub mod collate_src;
 pub mod collider;
 pub mod components;
+pub mod config;
+pub mod console;
+pub mod crt;
 pub mod custom_window;
+pub mod danger;
 pub mod debug;
+pub mod demo;
 pub mod diagnostics;
+pub mod difficulty;
+pub mod endless;
 pub mod enemy;
 pub mod explosion;
+pub mod fog;
+pub mod frame_step;
 pub mod game;
+pub mod game_over;
+pub mod gems;
 pub mod grid_movement;
 pub mod grid_reservation;
+#[cfg(test)]
+mod headless;
+pub mod highscore;
+pub mod hints;
+pub mod hit_stop;
+pub mod hud;
+pub mod input;
+pub mod lava;
 pub mod map;
+pub mod map_export;
+pub mod message_log;
+pub mod mouse_aim;
+pub mod music;
+pub mod palette;
+pub mod particle;
+pub mod pickup;
+pub mod pixel_snap;
+pub mod platform_io;
 pub mod player;
+pub mod popup;
+pub mod profiler;
 pub mod projectile;
+pub mod quicksave;
+pub mod radar;
 pub mod random;
 pub mod resolution;
+pub mod restart;
+pub mod reticle;
+pub mod round_intro;
+pub mod round_timer;
+pub mod save;
 pub mod score;
+pub mod screen_flash;
+pub mod screen_shake;
+pub mod screenshot;
+pub mod seed;
+pub mod settings;
+pub mod spawner;
+pub mod spectator;
 pub mod tilemap;
+pub mod time_attack;
 pub mod title;
+pub mod toast;
 pub mod ui_scaling;
 pub mod victory;
 
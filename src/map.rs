@@ -1,25 +1,121 @@
+use crate::arena::carry_over_arena;
 use crate::components::GameState;
-use crate::random::random_float;
+use crate::grid_movement::is_wall;
+use crate::random::{random_bool, random_pick, random_range};
 use bevy::prelude::*;
-use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use bevy_rand::prelude::{Entropy, GlobalEntropy, WyRand};
 
 pub const MAP_WIDTH: u32 = 80;
 pub const MAP_HEIGHT: u32 = 80;
 pub const NUM_WALKS: usize = 128;
 pub const BORDER_WIDTH: i32 = 2;
+/// Chance that a dead-end tile gets lava in it.
+pub const LAVA_CHANCE: f32 = 0.15;
 
 #[derive(Resource)]
 pub struct MapData {
     pub width: u32,
     pub height: u32,
     pub is_wall: Vec<bool>,
+    /// Floor tiles that hurt whatever steps onto them, indexed like `is_wall`.
+    pub is_lava: Vec<bool>,
+}
+
+impl MapData {
+    /// The index into `is_wall` of a grid position, or `None` if it's off the map.
+    /// Grid y counts up from the bottom, while the rows are stored top first.
+    pub fn index(&self, pos: IVec2) -> Option<usize> {
+        if pos.x < 0 || pos.y < 0 || pos.x >= self.width as i32 || pos.y >= self.height as i32 {
+            return None;
+        }
+        let flipped_y = self.height - 1 - pos.y as u32;
+        Some((flipped_y * self.width + pos.x as u32) as usize)
+    }
+
+    /// Turns the tile at `pos` into a wall or floor. Returns `false` if `pos` is off the map.
+    /// The tilemap repaints itself whenever `MapData` changes.
+    pub fn set_wall(&mut self, pos: IVec2, wall: bool) -> bool {
+        match self.index(pos) {
+            Some(idx) => {
+                self.is_wall[idx] = wall;
+                if let Some(lava) = self.is_lava.get_mut(idx) {
+                    *lava = false;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the tile at `pos` is lava. Anything off the map isn't.
+    pub fn is_lava(&self, pos: IVec2) -> bool {
+        self.index(pos)
+            .is_some_and(|idx| self.is_lava.get(idx).copied().unwrap_or(false))
+    }
+
+    /// Whether `to` can be seen from `from`: no wall lies on the straight line between them.
+    /// The end tiles themselves don't block, so a wall can be seen but not seen through.
+    pub fn line_of_sight(&self, from: IVec2, to: IVec2) -> bool {
+        // Bresenham's line, checking each tile strictly between the two ends.
+        let delta = (to - from).abs();
+        let step = (to - from).signum();
+        let mut error = delta.x - delta.y;
+        let mut pos = from;
+        while pos != to {
+            let doubled = 2 * error;
+            if doubled > -delta.y {
+                error -= delta.y;
+                pos.x += step.x;
+            }
+            if doubled < delta.x {
+                error += delta.x;
+                pos.y += step.y;
+            }
+            if pos != to && self.index(pos).is_none_or(|idx| self.is_wall[idx]) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Splits the map into `count` regions around random centres, each tile going to the
+    /// nearest one. Returns each tile's region, indexed like `is_wall`.
+    pub fn roll_regions(&self, rng: &mut Entropy<WyRand>, count: usize) -> Vec<u8> {
+        let centres: Vec<IVec2> = (0..count.clamp(1, u8::MAX as usize))
+            .map(|_| {
+                IVec2::new(
+                    random_range(rng, 0..self.width as i32),
+                    random_range(rng, 0..self.height as i32),
+                )
+            })
+            .collect();
+        (0..self.is_wall.len())
+            .map(|idx| {
+                let row = (idx / self.width as usize) as i32;
+                let pos = IVec2::new(
+                    (idx % self.width as usize) as i32,
+                    self.height as i32 - 1 - row,
+                );
+                let nearest = centres
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, centre)| (**centre - pos).length_squared())
+                    .map_or(0, |(region, _)| region);
+                nearest as u8
+            })
+            .collect()
+    }
 }
 
 pub struct MapPlugin;
 
 impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Playing), generate_map);
+        // A persistent arena keeps the previous round's map.
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            generate_map.run_if(not(carry_over_arena)),
+        );
     }
 }
 
@@ -45,10 +141,8 @@ pub fn generate_map(mut commands: Commands, mut rng: GlobalEntropy<WyRand>) {
         let mut x;
         let mut y;
         loop {
-            x = (random_float(&mut rng) * (max_coord - min_coord + 1) as f32).floor() as i32
-                + min_coord;
-            y = (random_float(&mut rng) * (max_coord - min_coord + 1) as f32).floor() as i32
-                + min_coord;
+            x = random_range(&mut rng, min_coord..max_coord + 1);
+            y = random_range(&mut rng, min_coord..max_coord + 1);
             // Ensure secondary tile (x+1 or y+1) is also within bounds
             if x + 1 < max_coord && y + 1 < max_coord {
                 break;
@@ -57,10 +151,9 @@ pub fn generate_map(mut commands: Commands, mut rng: GlobalEntropy<WyRand>) {
         let mut pos = IVec2::new(x, y);
 
         // First leg of the walk
-        let dir_idx = (random_float(&mut rng) * 4.0).floor() as usize;
-        let mut dir = directions[dir_idx];
+        let mut dir = *random_pick(&mut rng, &directions);
         // Halve the walk length to account for double tile carving
-        let n = (random_float(&mut rng) * (width - 1) as f32 / 2.0).floor() as i32 + 1;
+        let n = random_range(&mut rng, 0..width as i32 / 2) + 1;
         for _ in 0..n {
             let next_pos = pos + dir;
             // Check if primary tile is within bounds
@@ -76,7 +169,7 @@ pub fn generate_map(mut commands: Commands, mut rng: GlobalEntropy<WyRand>) {
         }
 
         // Turn 90 degrees
-        let clockwise = random_float(&mut rng) < 0.5;
+        let clockwise = random_bool(&mut rng, 0.5);
         dir = if clockwise {
             IVec2::new(dir.y, -dir.x) // Clockwise: (x,y) -> (y,-x)
         } else {
@@ -84,7 +177,7 @@ pub fn generate_map(mut commands: Commands, mut rng: GlobalEntropy<WyRand>) {
         };
 
         // Second leg of the walk
-        let m = (random_float(&mut rng) * (height - 1) as f32 / 2.0).floor() as i32 + 1;
+        let m = random_range(&mut rng, 0..height as i32 / 2) + 1;
         for _ in 0..m {
             let next_pos = pos + dir;
             if next_pos.x < min_coord
@@ -99,15 +192,43 @@ pub fn generate_map(mut commands: Commands, mut rng: GlobalEntropy<WyRand>) {
         }
     }
 
-    commands.insert_resource(MapData {
+    let mut map_data = MapData {
         width,
         height,
+        is_lava: vec![false; is_wall.len()],
         is_wall,
-    });
+    };
+    place_lava(&mut map_data, &mut rng);
+    commands.insert_resource(map_data);
+}
+
+/// Fills a few dead-end alcoves with lava. A tile counts as one when at least five of its
+/// eight neighbours are walls, which is the tip of a corridor or the outside of a bend; the
+/// rest of the corridor stays open, so lava never cuts the map in two.
+fn place_lava(map_data: &mut MapData, rng: &mut Entropy<WyRand>) {
+    for y in 0..map_data.height as i32 {
+        for x in 0..map_data.width as i32 {
+            let pos = IVec2::new(x, y);
+            let Some(idx) = map_data.index(pos) else {
+                continue;
+            };
+            if map_data.is_wall[idx] {
+                continue;
+            }
+            let walls = (-1..=1)
+                .flat_map(|dy| (-1..=1).map(move |dx| IVec2::new(dx, dy)))
+                .filter(|&offset| offset != IVec2::ZERO)
+                .filter(|&offset| is_wall(pos + offset, map_data))
+                .count();
+            if walls >= 5 && random_bool(rng, LAVA_CHANCE) {
+                map_data.is_lava[idx] = true;
+            }
+        }
+    }
 }
 
 // Sets two adjacent tiles to floor (not wall) based on the direction of movement, respecting the flipped y-indexing.
-fn set_floor(is_wall: &mut Vec<bool>, pos: IVec2, dir: IVec2, width: u32, height: u32) {
+fn set_floor(is_wall: &mut [bool], pos: IVec2, dir: IVec2, width: u32, height: u32) {
     let x = pos.x as usize;
     let y = pos.y as usize;
     let flipped_y = (height - 1 - y as u32) as usize;
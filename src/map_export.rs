@@ -0,0 +1,214 @@
+// map_export.rs
+
+//! F11 (while debugging) or the `export_map` console command saves the current map as two PNGs
+//! in `screenshots/maps/`, named after the seed and round: the raw map at one pixel per tile,
+//! and a larger copy marking the player's spawn point, the player and the enemies.
+//!
+//! Handy for reporting a bad layout ("seed X, round 2 generates this") and for comparing
+//! generator output while tuning it. Not available in the browser.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::CurrentRound;
+use crate::console::{CommandResult, ConsoleCommands};
+use crate::debug::DebugFlags;
+use crate::enemy::Enemy;
+use crate::grid_movement::GridMover;
+use crate::map::MapData;
+use crate::player::{Player, PlayerSpawn};
+use crate::seed::{format_seed, MapSeed};
+
+const EXPORT_KEY: KeyCode = KeyCode::F11;
+
+/// Directory the maps are written to, relative to the working directory.
+const MAP_EXPORT_DIR: &str = "screenshots/maps";
+
+/// Pixels per tile in the annotated image.
+const ANNOTATED_SCALE: u32 = 4;
+
+/// Used for walls if the game assets haven't loaded.
+const FALLBACK_WALL_COLOR: Color = Color::srgb(0.58, 0.69, 0.76);
+const FLOOR_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
+const LAVA_COLOR: Color = Color::srgb(1.0, 0.45, 0.1);
+const SPAWN_COLOR: Color = Color::srgb(0.2, 0.85, 0.3);
+const PLAYER_COLOR: Color = Color::WHITE;
+const ENEMY_COLOR: Color = Color::srgb(0.9, 0.2, 0.25);
+
+pub struct MapExportPlugin;
+
+impl Plugin for MapExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConsoleCommands>()
+            .add_systems(Update, export_map_on_key);
+
+        app.world_mut().resource_mut::<ConsoleCommands>().register(
+            "export_map",
+            "export_map",
+            "saves the current map as PNGs in screenshots/maps/",
+            export_map_command,
+        );
+    }
+}
+
+fn export_map_on_key(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    flags: Res<DebugFlags>,
+) {
+    if flags.master && keys.just_pressed(EXPORT_KEY) {
+        commands.queue(|world: &mut World| match export_map(world) {
+            Ok(message) => info!("{}", message),
+            Err(err) => warn!("Couldn't export the map: {}", err),
+        });
+    }
+}
+
+fn export_map_command(world: &mut World, args: &[&str]) -> CommandResult {
+    if !args.is_empty() {
+        return Err("expected no arguments".to_string());
+    }
+    export_map(world)
+}
+
+/// Writes the raw and annotated map images, returning where they went.
+fn export_map(world: &mut World) -> Result<String, String> {
+    let player = world
+        .query_filtered::<&GridMover, With<Player>>()
+        .iter(world)
+        .next()
+        .map(|mover| mover.grid_pos);
+    let enemies: Vec<IVec2> = world
+        .query_filtered::<&GridMover, With<Enemy>>()
+        .iter(world)
+        .map(|mover| mover.grid_pos)
+        .collect();
+    let map = world
+        .get_resource::<MapData>()
+        .filter(|map| map.width > 0 && map.height > 0)
+        .ok_or("no map has been generated yet")?;
+    let wall_color = world
+        .get_resource::<GameAssets>()
+        .map_or(FALLBACK_WALL_COLOR, |assets| assets.palette.wall_color());
+    let seed = world.resource::<MapSeed>().current;
+    let round = world
+        .get_resource::<CurrentRound>()
+        .map_or(1, |round| round.0);
+
+    let raw = map_image(map, 1, wall_color);
+    let mut annotated = map_image(map, ANNOTATED_SCALE, wall_color);
+    for &enemy in &enemies {
+        fill_tile(&mut annotated, map, ANNOTATED_SCALE, enemy, ENEMY_COLOR, 1);
+    }
+    // The spawn point is only meaningful while a round is being played.
+    if let Some(player) = player {
+        fill_tile(
+            &mut annotated,
+            map,
+            ANNOTATED_SCALE,
+            player,
+            PLAYER_COLOR,
+            1,
+        );
+        let spawn = world.resource::<PlayerSpawn>().0;
+        outline_tile(&mut annotated, map, ANNOTATED_SCALE, spawn, SPAWN_COLOR);
+    }
+
+    let stem = format!(
+        "{}/map-{}-round{}",
+        MAP_EXPORT_DIR,
+        format_seed(seed),
+        round
+    );
+    let raw_path = format!("{}.png", stem);
+    let annotated_path = format!("{}-annotated.png", stem);
+    save_png(raw, &raw_path)?;
+    save_png(annotated, &annotated_path)?;
+    Ok(format!("Saved {} and {}", raw_path, annotated_path))
+}
+
+/// The map's walls, floor and lava at `scale` pixels per tile.
+fn map_image(map: &MapData, scale: u32, wall_color: Color) -> Image {
+    use bevy::asset::RenderAssetUsages;
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: map.width * scale,
+            height: map.height * scale,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::MAIN_WORLD,
+    );
+    for y in 0..map.height as i32 {
+        for x in 0..map.width as i32 {
+            let pos = IVec2::new(x, y);
+            let is_wall = map.index(pos).is_some_and(|idx| map.is_wall[idx]);
+            let color = if is_wall {
+                wall_color
+            } else if map.is_lava(pos) {
+                LAVA_COLOR
+            } else {
+                FLOOR_COLOR
+            };
+            fill_tile(&mut image, map, scale, pos, color, 0);
+        }
+    }
+    image
+}
+
+/// Fills the tile at grid position `pos`, leaving a margin of `inset` pixels on each side.
+/// Grid y counts up from the bottom, while image rows count down from the top.
+fn fill_tile(image: &mut Image, map: &MapData, scale: u32, pos: IVec2, color: Color, inset: u32) {
+    let Some(corner) = tile_corner(map, scale, pos) else {
+        return;
+    };
+    let inset = inset.min(scale.saturating_sub(1) / 2);
+    for dy in inset..scale - inset {
+        for dx in inset..scale - inset {
+            // In bounds, since the tile is on the map.
+            let _ = image.set_color_at(corner.x + dx, corner.y + dy, color);
+        }
+    }
+}
+
+/// Draws a one pixel border around the tile at grid position `pos`.
+fn outline_tile(image: &mut Image, map: &MapData, scale: u32, pos: IVec2, color: Color) {
+    let Some(corner) = tile_corner(map, scale, pos) else {
+        return;
+    };
+    for dy in 0..scale {
+        for dx in 0..scale {
+            if dx == 0 || dy == 0 || dx == scale - 1 || dy == scale - 1 {
+                let _ = image.set_color_at(corner.x + dx, corner.y + dy, color);
+            }
+        }
+    }
+}
+
+/// The top-left pixel of a tile, or `None` if it's off the map.
+fn tile_corner(map: &MapData, scale: u32, pos: IVec2) -> Option<UVec2> {
+    map.index(pos)?;
+    let row = map.height - 1 - pos.y as u32;
+    Some(UVec2::new(pos.x as u32 * scale, row * scale))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_png(image: Image, path: &str) -> Result<(), String> {
+    std::fs::create_dir_all(MAP_EXPORT_DIR)
+        .map_err(|err| format!("couldn't create {}/: {}", MAP_EXPORT_DIR, err))?;
+    let dynamic = image
+        .try_into_dynamic()
+        .map_err(|err| format!("couldn't convert {}: {}", path, err))?;
+    dynamic
+        .save(path)
+        .map_err(|err| format!("couldn't write {}: {}", path, err))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_png(_image: Image, _path: &str) -> Result<(), String> {
+    Err("map export isn't supported in the browser".to_string())
+}
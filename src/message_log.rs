@@ -0,0 +1,194 @@
+// message_log.rs
+
+//! A short log of game messages in the bottom-left corner: trick kills, pickups, unlocks and
+//! the like. Any system can add a line by sending a `GameMessage`.
+//!
+//! At most `MAX_LINES` are shown, newest at the bottom, and each fades out at the end of its
+//! `LINE_LIFETIME`. The same message sent again within `REPEAT_WINDOW` bumps a counter on its
+//! line ("x3") instead of adding another. Like toasts, the log isn't tied to a game state.
+
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::assets::GameAssets;
+use crate::components::GameState;
+use crate::palette::Palette;
+
+/// Most lines shown at once; the oldest is dropped to make room.
+const MAX_LINES: usize = 5;
+
+/// Seconds a line stays on screen.
+const LINE_LIFETIME: f32 = 4.0;
+
+/// Seconds at the end of a line's life over which it fades out.
+const LINE_FADE_TIME: f32 = 1.0;
+
+/// A repeat of a message this soon after it is folded into its line.
+const REPEAT_WINDOW: f32 = 1.0;
+
+const LINE_FONT_SIZE: f32 = 8.0;
+
+pub struct MessageLogPlugin;
+
+impl Plugin for MessageLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameMessage>()
+            .init_resource::<MessageLog>()
+            .add_systems(OnExit(GameState::Loading), spawn_message_log)
+            .add_systems(
+                Update,
+                (read_game_messages, age_message_log, update_message_lines)
+                    .chain()
+                    .run_if(resource_exists::<GameAssets>),
+            );
+    }
+}
+
+/// Adds a line to the message log.
+#[derive(Event, Clone, Debug)]
+pub struct GameMessage(pub String, pub MessageKind);
+
+/// What a message is about, which picks its color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    Kill,
+    Pickup,
+    Unlock,
+    #[allow(dead_code)] // For announcements that fit none of the others, such as waves.
+    Info,
+}
+
+impl MessageKind {
+    fn color(self, palette: &Palette) -> Color {
+        let slot = match self {
+            MessageKind::Kill => 4,    // yellow
+            MessageKind::Pickup => 10, // blue
+            MessageKind::Unlock => 5,
+            MessageKind::Info => 12, // white
+        };
+        palette.colors[slot]
+    }
+}
+
+struct LogLine {
+    text: String,
+    kind: MessageKind,
+    /// How many times the message was sent, shown when more than once.
+    count: u32,
+    /// Seconds since the message was last sent.
+    age: f32,
+}
+
+/// The lines currently shown, oldest first. Only marked changed when a line is added,
+/// counted again or dropped, not as the lines age.
+#[derive(Resource, Default)]
+struct MessageLog {
+    lines: VecDeque<LogLine>,
+}
+
+/// One of the `MAX_LINES` text slots, numbered from the top. The newest line is always in
+/// the bottom slot.
+#[derive(Component)]
+struct MessageSlot(usize);
+
+fn spawn_message_log(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            GlobalZIndex(20),
+        ))
+        .with_children(|parent| {
+            for slot in 0..MAX_LINES {
+                parent.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font: game_assets.font.clone(),
+                        font_size: LINE_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(Color::NONE),
+                    TextLayout::new_with_justify(JustifyText::Left),
+                    MessageSlot(slot),
+                ));
+            }
+        });
+}
+
+fn read_game_messages(mut events: EventReader<GameMessage>, mut log: ResMut<MessageLog>) {
+    for GameMessage(text, kind) in events.read() {
+        let repeat = log
+            .lines
+            .back_mut()
+            .filter(|line| line.text == *text && line.kind == *kind && line.age < REPEAT_WINDOW);
+        if let Some(line) = repeat {
+            line.count += 1;
+            line.age = 0.0;
+            continue;
+        }
+        log.lines.push_back(LogLine {
+            text: text.clone(),
+            kind: *kind,
+            count: 1,
+            age: 0.0,
+        });
+        if log.lines.len() > MAX_LINES {
+            log.lines.pop_front();
+        }
+    }
+}
+
+/// Ages every line and drops the ones that have run their course.
+fn age_message_log(mut log: ResMut<MessageLog>, time: Res<Time<Real>>) {
+    let lines = &mut log.bypass_change_detection().lines;
+    for line in lines.iter_mut() {
+        line.age += time.delta_secs();
+    }
+    // Lines are sent in order, so the oldest always expires first.
+    let before = lines.len();
+    while lines.front().is_some_and(|line| line.age >= LINE_LIFETIME) {
+        lines.pop_front();
+    }
+    if lines.len() != before {
+        log.set_changed();
+    }
+}
+
+/// Rewrites the slots when the log changes, and fades the lines near the end of their lives.
+fn update_message_lines(
+    log: Res<MessageLog>,
+    game_assets: Res<GameAssets>,
+    mut slots: Query<(&MessageSlot, &mut Text, &mut TextColor)>,
+) {
+    // The bottom slots hold the lines, so the newest is always at the bottom.
+    let first_used = MAX_LINES - log.lines.len();
+    for (slot, mut text, mut color) in &mut slots {
+        let Some(line) = slot
+            .0
+            .checked_sub(first_used)
+            .and_then(|i| log.lines.get(i))
+        else {
+            if log.is_changed() {
+                text.0.clear();
+            }
+            continue;
+        };
+        if log.is_changed() {
+            text.0 = if line.count > 1 {
+                format!("{} x{}", line.text, line.count)
+            } else {
+                line.text.clone()
+            };
+        }
+        let alpha = ((LINE_LIFETIME - line.age) / LINE_FADE_TIME).clamp(0.0, 1.0);
+        color.set_if_neq(TextColor(
+            line.kind.color(&game_assets.palette).with_alpha(alpha),
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+// mouse_aim.rs
+
+//! Mouse aim: an optional control scheme where the left mouse button fires towards the cursor
+//! instead of along the player's movement.
+//!
+//! The cursor goes from window space to world space through the camera, whose projection
+//! already accounts for the window size and `Resolution::zoom`, then to map coordinates. The
+//! direction from the player's tile to that point is snapped to the nearest of the eight grid
+//! directions. The fire keys still fire along the movement direction in either scheme, for
+//! players who mix the two. With keyboard aim (the default) the mouse button does the same.
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use serde::{Deserialize, Serialize};
+use std::f32::consts::FRAC_PI_4;
+
+use crate::assets::GameAssets;
+use crate::atlas::GameAtlas;
+use crate::bump::Stunned;
+use crate::components::GameState;
+use crate::difficulty::DifficultySetting;
+use crate::grid_movement::{GridMover, MovementSystems};
+use crate::map::MapData;
+use crate::player::{fire_projectile, ControlSource, Player};
+use crate::tilemap::GridSpace;
+
+pub struct MouseAimPlugin;
+
+impl Plugin for MouseAimPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AimMode::default())
+            .init_resource::<MouseAim>()
+            .add_systems(
+                Update,
+                (
+                    update_mouse_aim,
+                    fire_at_cursor.run_if(resource_equals(AimMode::Mouse)),
+                )
+                    .chain()
+                    .in_set(MovementSystems::Input)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// What the left mouse button aims at, pushed from `Settings`.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimMode {
+    /// Fires along the player's movement, like the fire key.
+    #[default]
+    Keyboard,
+    /// Fires towards the cursor.
+    Mouse,
+}
+
+impl AimMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            AimMode::Keyboard => "KEYBOARD",
+            AimMode::Mouse => "MOUSE",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            AimMode::Keyboard => AimMode::Mouse,
+            AimMode::Mouse => AimMode::Keyboard,
+        }
+    }
+}
+
+/// The direction mouse aim points in this frame. `None` with keyboard aim, without a player
+/// to aim from, or while the cursor is outside the window or over the player's own tile.
+#[derive(Resource, Default, Debug, PartialEq)]
+pub struct MouseAim(pub Option<IVec2>);
+
+/// The grid direction from `from` towards `target`, both in map coordinates, snapped to the
+/// nearest of the eight directions. `None` when the target is within `from`'s own tile.
+pub fn aim_direction(from: Vec2, target: Vec2) -> Option<IVec2> {
+    let delta = target - from;
+    if delta.abs().max_element() < 0.5 {
+        return None;
+    }
+    let octant = (delta.to_angle() / FRAC_PI_4).round();
+    Some(Vec2::from_angle(octant * FRAC_PI_4).round().as_ivec2())
+}
+
+/// Works out where the cursor points the player's next mouse shot.
+fn update_mouse_aim(
+    mode: Res<AimMode>,
+    mut aim: ResMut<MouseAim>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    grid_space: GridSpace,
+    player: Query<&GridMover, With<Player>>,
+) {
+    let direction = || {
+        let cursor = windows.single().ok()?.cursor_position()?;
+        let (camera, camera_transform) = cameras.single().ok()?;
+        let world = camera.viewport_to_world_2d(camera_transform, cursor).ok()?;
+        let mover = player.single().ok()?;
+        aim_direction(mover.grid_pos.as_vec2(), grid_space.world_to_grid(world))
+    };
+    let direction = match *mode {
+        AimMode::Keyboard => None,
+        AimMode::Mouse => direction(),
+    };
+    aim.set_if_neq(MouseAim(direction));
+}
+
+/// Fires towards the cursor on a left click. Like the fire key, it does nothing while the
+/// player is stunned or the tile in that direction is a wall.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn fire_at_cursor(
+    mouse: Res<ButtonInput<MouseButton>>,
+    aim: Res<MouseAim>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    query: Query<(&GridMover, &ControlSource), (With<Player>, Without<Stunned>)>,
+    map_data: Res<MapData>,
+    difficulty: Res<DifficultySetting>,
+    mut rng: GlobalEntropy<WyRand>,
+    atlas: Option<Res<GameAtlas>>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let (Some(dir), Ok((mover, ControlSource::Keyboard))) = (aim.0, query.single()) else {
+        return;
+    };
+    fire_projectile(
+        &mut commands,
+        &game_assets,
+        atlas.as_deref(),
+        mover,
+        dir,
+        &map_data,
+        difficulty.0.projectile_bounces(),
+        &mut rng,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aim_snaps_to_the_nearest_of_eight_directions() {
+        let from = Vec2::new(5.0, 5.0);
+        assert_eq!(aim_direction(from, Vec2::new(9.0, 5.3)), Some(IVec2::X));
+        assert_eq!(aim_direction(from, Vec2::new(5.2, 1.0)), Some(IVec2::NEG_Y));
+        assert_eq!(aim_direction(from, Vec2::new(8.0, 7.5)), Some(IVec2::ONE));
+        assert_eq!(
+            aim_direction(from, Vec2::new(2.0, 8.2)),
+            Some(IVec2::new(-1, 1))
+        );
+        // Straight behind, where the angle wraps from pi to -pi.
+        assert_eq!(aim_direction(from, Vec2::new(1.0, 4.9)), Some(IVec2::NEG_X));
+        assert_eq!(aim_direction(from, Vec2::new(1.0, 5.1)), Some(IVec2::NEG_X));
+    }
+
+    #[test]
+    fn aim_within_the_own_tile_is_none() {
+        let from = Vec2::new(5.0, 5.0);
+        assert_eq!(aim_direction(from, from), None);
+        assert_eq!(aim_direction(from, Vec2::new(5.4, 4.6)), None);
+        assert!(aim_direction(from, Vec2::new(5.6, 5.0)).is_some());
+    }
+}
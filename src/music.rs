@@ -0,0 +1,239 @@
+// music.rs
+
+//! Looping background music, with a crossfade whenever the track changes, and the stingers
+//! layered over it: the victory fanfare, the death sting and the last-enemies heartbeat.
+//!
+//! Each playing track is its own entity with a `MusicTrack` fade level that is eased towards 1
+//! (the wanted track) or 0 (the old one, which is despawned once silent). The sink volume is
+//! recomputed every frame from the fade, the music volume setting and any ducking, so it is
+//! independent of the global volume used for sound effects. Stingers are sound effects and
+//! follow the SFX volume.
+
+use bevy::asset::LoadState;
+use bevy::audio::{AudioPlayer, AudioSinkPlayback, PlaybackSettings, Volume};
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::audio::{self, AudioSettings};
+use crate::components::{GameMode, GameState, PlayerDied};
+use crate::demo::Demo;
+use crate::explosion::PlayerIsDead;
+use crate::score::EnemyCount;
+
+/// Seconds taken to fade one track out and the next one in.
+const CROSSFADE_TIME: f32 = 1.5;
+
+/// Music volume while the victory fanfare plays.
+const DUCK_VOLUME: f32 = 0.3;
+
+/// Seconds the music stays ducked for the victory fanfare.
+const FANFARE_TIME: f32 = 2.5;
+
+/// The heartbeat plays while this many enemies or fewer remain in a winnable round.
+const HEARTBEAT_THRESHOLD: u32 = 5;
+
+const DEATH_STING_VOLUME: f32 = 0.8;
+const HEARTBEAT_VOLUME: f32 = 0.7;
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Victory), play_fanfare)
+            .add_systems(
+                Update,
+                (
+                    select_music.run_if(state_changed::<GameState>),
+                    tick_music_duck.run_if(resource_exists::<MusicDuck>),
+                    fade_music,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (play_death_sting, update_heartbeat).run_if(resource_exists::<GameAssets>),
+            );
+    }
+}
+
+/// The pieces of music the game can play.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Track {
+    Title,
+    Gameplay,
+}
+
+impl Track {
+    fn handle(self, game_assets: &GameAssets) -> Handle<AudioSource> {
+        match self {
+            Track::Title => game_assets.title_music.clone(),
+            Track::Gameplay => game_assets.game_music.clone(),
+        }
+    }
+}
+
+/// A playing music track and how far it has faded in.
+#[derive(Component)]
+struct MusicTrack {
+    track: Track,
+    /// Current fade level, from 0.0 (silent) to 1.0 (full).
+    fade: f32,
+    /// The fade level being eased towards.
+    target: f32,
+}
+
+/// The looping heartbeat played while only a few enemies remain.
+#[derive(Component)]
+struct Heartbeat;
+
+/// Present while the music is ducked under the victory fanfare.
+#[derive(Resource)]
+struct MusicDuck(Timer);
+
+/// The track that belongs to a state, if any. The attract mode demo keeps the title music.
+fn track_for(state: &GameState, demo: &Demo) -> Option<Track> {
+    match state {
+        GameState::Loading => None,
+        GameState::Title => Some(Track::Title),
+        _ if demo.0 => Some(Track::Title),
+        _ => Some(Track::Gameplay),
+    }
+}
+
+/// Fades in the track for the new state (starting it if needed) and fades out the others.
+fn select_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    demo: Res<Demo>,
+    game_assets: Option<Res<GameAssets>>,
+    asset_server: Res<AssetServer>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    let Some(game_assets) = game_assets else {
+        return;
+    };
+    let wanted = track_for(state.get(), &demo);
+
+    let mut already_playing = false;
+    for mut music in &mut tracks {
+        if Some(music.track) == wanted {
+            music.target = 1.0;
+            already_playing = true;
+        } else {
+            music.target = 0.0;
+        }
+    }
+
+    let Some(track) = wanted.filter(|_| !already_playing) else {
+        return;
+    };
+    let handle = track.handle(&game_assets);
+    // The music files are optional. One that isn't there was never loaded, and one that failed
+    // is skipped rather than retried every frame.
+    if audio::is_silent(&handle) {
+        return;
+    }
+    if let Some(LoadState::Failed(error)) = asset_server.get_load_state(&handle) {
+        warn!("Skipping {:?} music, it failed to load: {}", track, error);
+        return;
+    }
+    commands.spawn((
+        AudioPlayer::new(handle),
+        PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+        MusicTrack {
+            track,
+            fade: 0.0,
+            target: 1.0,
+        },
+    ));
+}
+
+fn play_fanfare(mut commands: Commands, game_assets: Res<GameAssets>) {
+    audio::play(&mut commands, game_assets.fanfare_sfx.clone());
+    commands.insert_resource(MusicDuck(Timer::from_seconds(
+        FANFARE_TIME,
+        TimerMode::Once,
+    )));
+}
+
+fn play_death_sting(
+    mut commands: Commands,
+    mut player_died: EventReader<PlayerDied>,
+    game_assets: Res<GameAssets>,
+) {
+    if player_died.read().next().is_some() {
+        audio::play_with_volume(
+            &mut commands,
+            game_assets.death_sfx.clone(),
+            DEATH_STING_VOLUME,
+        );
+    }
+}
+
+/// Starts the heartbeat when a winnable round is down to its last few enemies and stops it once
+/// they are all gone, the player dies or the round ends. Looking for the existing loop each frame
+/// means a count that bounces around the threshold never starts a second one.
+fn update_heartbeat(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    state: Res<State<GameState>>,
+    mode: Res<GameMode>,
+    enemy_count: Res<EnemyCount>,
+    player_dead: Option<Res<PlayerIsDead>>,
+    heartbeat: Query<Entity, With<Heartbeat>>,
+) {
+    let wanted = *state.get() == GameState::Playing
+        && *mode != GameMode::Endless
+        && player_dead.is_none()
+        && (1..=HEARTBEAT_THRESHOLD).contains(&enemy_count.value);
+    match (wanted, heartbeat.iter().next()) {
+        (true, None) if !audio::is_silent(&game_assets.heartbeat_sfx) => {
+            commands.spawn((
+                AudioPlayer::new(game_assets.heartbeat_sfx.clone()),
+                PlaybackSettings::LOOP.with_volume(Volume::Linear(HEARTBEAT_VOLUME)),
+                // Lets live volume changes reach the loop like any other sound effect.
+                audio::Sfx {
+                    volume: HEARTBEAT_VOLUME,
+                },
+                Heartbeat,
+            ));
+        }
+        (false, Some(entity)) => {
+            commands.entity(entity).despawn();
+        }
+        _ => {}
+    }
+}
+
+fn tick_music_duck(mut commands: Commands, mut duck: ResMut<MusicDuck>, time: Res<Time<Real>>) {
+    duck.0.tick(time.delta());
+    if duck.0.finished() {
+        commands.remove_resource::<MusicDuck>();
+    }
+}
+
+/// Eases each track's fade towards its target and applies the resulting volume.
+fn fade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<AudioSettings>,
+    duck: Option<Res<MusicDuck>>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&mut AudioSink>)>,
+) {
+    let step = time.delta_secs() / CROSSFADE_TIME;
+    let duck = if duck.is_some() { DUCK_VOLUME } else { 1.0 };
+    for (entity, mut music, sink) in &mut tracks {
+        music.fade = if music.fade < music.target {
+            (music.fade + step).min(music.target)
+        } else {
+            (music.fade - step).max(music.target)
+        };
+        if music.fade <= 0.0 && music.target <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(settings.music_level() * duck * music.fade));
+        }
+    }
+}
@@ -0,0 +1,294 @@
+// palette.rs
+
+//! The named color palettes the game can be drawn with, and the rebuild path that runs when
+//! the player picks a different one.
+//!
+//! Every palette has the same sixteen slots, ordered by role rather than hue (slot 12 is the
+//! main text color, slot 5 the menu highlight, and so on), so swapping palettes keeps the
+//! game readable. Each palette also declares which slot the walls use.
+//!
+//! Besides the built-in palettes, any lospec `.hex` file (one RRGGBB per line) dropped into
+//! `assets/palettes/` is loaded at startup and listed by its file name. Files are read with
+//! `std::fs`, so on the web only the built-in palettes are available. A file's colors should
+//! follow the slot order above; walls use slot 13.
+
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::asset::io::file::FileAssetReader;
+use bevy::prelude::*;
+
+use crate::assets::{color_from_hex, GameAssets};
+
+/// https://lospec.com/palette-list/sweetie-16 by GrafxKid
+const SWEETIE_16: [&str; 16] = [
+    "#1a1c2c", "#5d275d", "#b13e53", "#ef7d57", "#ffcd75", "#a7f070", "#38b764", "#257179",
+    "#29366f", "#3b5dc9", "#41a6f6", "#73eff7", "#f4f4f4", "#94b0c2", "#566c86", "#333c57",
+];
+
+/// https://lospec.com/palette-list/pico-8 by Lexaloffle, reordered to match the Sweetie-16 roles.
+const PICO_8: [&str; 16] = [
+    "#000000", "#7e2553", "#ff004d", "#ffa300", "#ffec27", "#00e436", "#008751", "#ab5236",
+    "#1d2b53", "#29adff", "#ff77a8", "#ffccaa", "#fff1e8", "#c2c3c7", "#83769c", "#5f574f",
+];
+
+/// Built from the Okabe-Ito and Paul Tol color sets, which stay distinguishable under the
+/// common forms of color blindness. Red and green roles become vermillion and bluish green.
+const COLORBLIND_SAFE: [&str; 16] = [
+    "#000000", "#332288", "#d55e00", "#e69f00", "#f0e442", "#56b4e9", "#009e73", "#117733",
+    "#1b2a49", "#0072b2", "#88ccee", "#44aa99", "#ffffff", "#bbbbbb", "#777777", "#333333",
+];
+
+/// The palette used when nothing else has been chosen.
+pub const DEFAULT_PALETTE: &str = "SWEETIE-16";
+
+/// Folder inside `assets/` that `.hex` palette files are loaded from.
+#[cfg(not(target_arch = "wasm32"))]
+const PALETTE_DIR: &str = "palettes";
+
+const MIN_PALETTE_COLORS: usize = 8;
+const MAX_PALETTE_COLORS: usize = 32;
+
+/// The game refers to slots up to 15, so smaller palettes are padded out to this many.
+const PALETTE_SLOTS: usize = 16;
+
+/// The wall slot for palettes loaded from files, matching the built-in palettes.
+const FILE_WALL_INDEX: usize = 13;
+
+/// How close two color channels must be to count as the same palette entry when recoloring.
+const COLOR_MATCH_EPSILON: f32 = 1.0 / 512.0;
+
+pub struct PalettePlugin;
+
+impl Plugin for PalettePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Palettes::load())
+            .insert_resource(PaletteChoice(DEFAULT_PALETTE.to_string()))
+            .add_event::<PaletteChanged>()
+            .add_systems(
+                Update,
+                (
+                    // `resource_exists` comes first so the choice still reads as changed once
+                    // the assets have loaded.
+                    apply_palette.run_if(
+                        resource_exists::<GameAssets>.and(resource_changed::<PaletteChoice>),
+                    ),
+                    recolor_entities.run_if(on_event::<PaletteChanged>),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Palette {
+    pub name: String,
+    pub colors: Vec<Color>,
+    /// Which entry of `colors` the walls are drawn with.
+    pub wall_index: usize,
+}
+
+impl Palette {
+    /// Builds a palette from hex strings. Panics on a malformed entry, so only use it for
+    /// palettes compiled into the game.
+    fn from_hex(name: &str, hex: &[&str], wall_index: usize) -> Self {
+        Palette {
+            name: name.to_string(),
+            colors: hex.iter().map(|h| color_from_hex(h).unwrap()).collect(),
+            wall_index,
+        }
+    }
+
+    pub fn wall_color(&self) -> Color {
+        self.colors[self.wall_index]
+    }
+}
+
+/// Every palette that can be picked in the settings, in display order.
+#[derive(Resource)]
+pub struct Palettes(pub Vec<Palette>);
+
+impl Palettes {
+    fn built_in() -> Self {
+        Palettes(vec![
+            Palette::from_hex(DEFAULT_PALETTE, &SWEETIE_16, 13),
+            Palette::from_hex("PICO-8", &PICO_8, 13),
+            Palette::from_hex("COLORBLIND SAFE", &COLORBLIND_SAFE, 13),
+        ])
+    }
+
+    /// The built-in palettes plus any found in `assets/palettes/`. A file with the same name as
+    /// a built-in palette replaces it.
+    fn load() -> Self {
+        let mut palettes = Palettes::built_in();
+        for palette in load_palette_files() {
+            match palettes.0.iter_mut().find(|p| p.name == palette.name) {
+                Some(existing) => *existing = palette,
+                None => palettes.0.push(palette),
+            }
+        }
+        palettes
+    }
+
+    /// The palette called `name`, or the first one if there's no such palette.
+    pub fn get(&self, name: &str) -> &Palette {
+        self.0.iter().find(|p| p.name == name).unwrap_or(&self.0[0])
+    }
+
+    /// The name of the palette `step` places after `name`, wrapping around the list.
+    pub fn cycle(&self, name: &str, step: i32) -> String {
+        let current = self.0.iter().position(|p| p.name == name).unwrap_or(0) as i32;
+        let next = (current + step).rem_euclid(self.0.len() as i32) as usize;
+        self.0[next].name.clone()
+    }
+}
+
+/// Reads every `.hex` file in `assets/palettes/`, in file name order. Files that fail to parse
+/// are skipped with a warning.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_palette_files() -> Vec<Palette> {
+    let dir = FileAssetReader::get_base_path()
+        .join("assets")
+        .join(PALETTE_DIR);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("hex"))
+        })
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .filter_map(|path| {
+            let name = path
+                .file_stem()?
+                .to_string_lossy()
+                .to_uppercase()
+                .replace('_', " ");
+            let parsed = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| parse_hex_palette(&name, &text));
+            match parsed {
+                Ok(palette) => Some(palette),
+                Err(err) => {
+                    warn!("Skipping palette {}: {}", path.display(), err);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_palette_files() -> Vec<Palette> {
+    Vec::new()
+}
+
+/// Parses the contents of a lospec `.hex` file: one RRGGBB color per line, blank lines
+/// ignored. Errors name the offending line.
+pub fn parse_hex_palette(name: &str, text: &str) -> Result<Palette, String> {
+    let mut colors = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() {
+            continue;
+        }
+        // `color_from_hex` slices by byte, so reject anything that isn't plain ASCII first.
+        let color = if line.is_ascii() {
+            color_from_hex(line)
+        } else {
+            Err("Not a hex color")
+        };
+        colors.push(color.map_err(|e| format!("line {}: {}", i + 1, e))?);
+    }
+    if !(MIN_PALETTE_COLORS..=MAX_PALETTE_COLORS).contains(&colors.len()) {
+        return Err(format!(
+            "has {} colors, expected {} to {}",
+            colors.len(),
+            MIN_PALETTE_COLORS,
+            MAX_PALETTE_COLORS
+        ));
+    }
+    // Smaller palettes repeat from the start to fill every slot the game uses.
+    let count = colors.len();
+    for i in count..PALETTE_SLOTS {
+        colors.push(colors[i % count]);
+    }
+    Ok(Palette {
+        name: name.to_string(),
+        colors,
+        wall_index: FILE_WALL_INDEX,
+    })
+}
+
+/// The name of the palette in use, pushed from `Settings`.
+#[derive(Resource, PartialEq)]
+pub struct PaletteChoice(pub String);
+
+/// Sent after `GameAssets::palette` has been swapped, carrying the palette it replaced.
+#[derive(Event)]
+pub struct PaletteChanged {
+    pub old: Palette,
+}
+
+pub fn apply_palette(
+    choice: Res<PaletteChoice>,
+    palettes: Res<Palettes>,
+    mut game_assets: ResMut<GameAssets>,
+    mut changed: EventWriter<PaletteChanged>,
+) {
+    let palette = palettes.get(&choice.0);
+    if game_assets.palette.name == palette.name {
+        return;
+    }
+    info!("Switching to the {} palette", palette.name);
+    let old = std::mem::replace(&mut game_assets.palette, palette.clone());
+    changed.write(PaletteChanged { old });
+}
+
+/// Moves every text, background and sprite that uses a palette color onto the same slot of the
+/// new palette, keeping its alpha. Colors that aren't from the palette are left alone. The
+/// tiles are meshes rather than sprites; `update_tile_colors` repaints them from the
+/// regenerated `FloorPalette`.
+pub fn recolor_entities(
+    mut changed: EventReader<PaletteChanged>,
+    game_assets: Res<GameAssets>,
+    mut texts: Query<&mut TextColor>,
+    mut backgrounds: Query<&mut BackgroundColor>,
+    mut sprites: Query<&mut Sprite>,
+) {
+    let Some(PaletteChanged { old }) = changed.read().last() else {
+        return;
+    };
+    let new = &game_assets.palette;
+    for mut color in &mut texts {
+        if let Some(c) = remap(color.0, old, new) {
+            color.0 = c;
+        }
+    }
+    for mut color in &mut backgrounds {
+        if let Some(c) = remap(color.0, old, new) {
+            color.0 = c;
+        }
+    }
+    for mut sprite in &mut sprites {
+        if let Some(c) = remap(sprite.color, old, new) {
+            sprite.color = c;
+        }
+    }
+}
+
+/// Finds `color` in `old` (ignoring alpha) and returns the matching entry of `new`.
+fn remap(color: Color, old: &Palette, new: &Palette) -> Option<Color> {
+    let srgba = color.to_srgba();
+    let index = old.colors.iter().position(|c| {
+        let c = c.to_srgba();
+        (c.red - srgba.red).abs() < COLOR_MATCH_EPSILON
+            && (c.green - srgba.green).abs() < COLOR_MATCH_EPSILON
+            && (c.blue - srgba.blue).abs() < COLOR_MATCH_EPSILON
+    })?;
+    new.colors.get(index).map(|c| c.with_alpha(srgba.alpha))
+}
@@ -0,0 +1,154 @@
+// particle.rs
+
+//! A lightweight particle system for death debris and impact sparks.
+//!
+//! Particles are plain sprites moved in world space by `Velocity`, `Damping` and `Gravity`,
+//! which carry them along as the map scrolls. They take no part in grid movement or collision,
+//! and a global budget keeps mass deaths from flooding the renderer.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{Entropy, Global, GlobalEntropy, WyRand};
+
+use crate::assets::GameAssets;
+use crate::collider::resolve_damage;
+use crate::components::{Damping, Dying, GameEntity, GameState, Gravity, Lifetime, Velocity};
+use crate::enemy::Enemy;
+use crate::explosion::ExplosionConfig;
+use crate::frame_step::simulation_running;
+use crate::projectile::ProjectileWallImpact;
+use crate::random::{random_float, random_index};
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // Dying enemies are still around (with their sprites) until the end of the frame.
+                spawn_enemy_debris.after(resolve_damage),
+                spawn_impact_sparks,
+                update_particles,
+            )
+                .run_if(in_state(GameState::Playing).and(simulation_running)),
+        );
+    }
+}
+
+/// A short-lived, purely visual particle. Its `Lifetime` despawns it, and it shrinks and fades
+/// as that runs out.
+#[derive(Component)]
+pub struct Particle;
+
+/// Maximum number of live particles. Spawning is skipped while over budget.
+const MAX_PARTICLES: usize = 500;
+
+/// Starting edge length of a particle sprite, in pixels.
+const PARTICLE_SIZE: f32 = 8.0;
+
+/// How long a particle lives, in seconds.
+const PARTICLE_LIFETIME: f32 = 0.5;
+
+/// Air drag on a particle, so a burst spreads out fast and then slows before gravity takes over.
+const PARTICLE_DAMPING: f32 = 3.0;
+
+/// Spawns a burst of `count` particles at `pos`, flying outwards in random directions.
+///
+/// `live` is the number of particles currently alive; the burst is trimmed to fit the budget.
+pub fn spawn_particles(
+    commands: &mut Commands,
+    rng: &mut Entropy<WyRand>,
+    live: usize,
+    pos: Vec3,
+    color: Color,
+    count: usize,
+    speed: f32,
+) {
+    let count = count.min(MAX_PARTICLES.saturating_sub(live));
+    for _ in 0..count {
+        let angle = random_float(rng) * std::f32::consts::TAU;
+        let magnitude = speed * (0.5 + random_float(rng) * 0.5);
+        let lifetime = PARTICLE_LIFETIME * (0.75 + random_float(rng) * 0.5);
+        commands.spawn((
+            Sprite {
+                color,
+                custom_size: Some(Vec2::splat(PARTICLE_SIZE)),
+                ..default()
+            },
+            Transform::from_translation(pos.with_z(1.2)),
+            Particle,
+            Lifetime::from_seconds(lifetime),
+            Velocity {
+                velocity: Vec2::from_angle(angle) * magnitude,
+            },
+            Damping(PARTICLE_DAMPING),
+            Gravity(600.0),
+            GameEntity,
+        ));
+    }
+}
+
+/// Throws debris from every enemy whose death was resolved this frame, tinted like the enemy.
+#[allow(clippy::type_complexity)]
+fn spawn_enemy_debris(
+    mut commands: Commands,
+    mut rng: GlobalEntropy<WyRand>,
+    mut dying: Query<
+        (&Transform, &Sprite, Option<&mut Entropy<WyRand>>),
+        (With<Enemy>, Added<Dying>, Without<Global>),
+    >,
+    particles: Query<(), With<Particle>>,
+    config: Res<ExplosionConfig>,
+) {
+    let mut live = particles.iter().len();
+    let min = config.enemy_particles_min as usize;
+    let spread = config
+        .enemy_particles_max
+        .saturating_sub(config.enemy_particles_min) as usize;
+    for (transform, sprite, entity_rng) in &mut dying {
+        // Rolled from the enemy's own generator where it has one, so the debris doesn't depend
+        // on which other enemies died in the same frame.
+        let debris_rng: &mut Entropy<WyRand> = match entity_rng {
+            Some(entity_rng) => entity_rng.into_inner(),
+            None => &mut rng,
+        };
+        let count = min + random_index(debris_rng, spread + 1);
+        spawn_particles(
+            &mut commands,
+            debris_rng,
+            live,
+            transform.translation,
+            sprite.color,
+            count,
+            300.0,
+        );
+        live += count;
+    }
+}
+
+/// Throws a few sparks where a projectile was destroyed against a wall.
+fn spawn_impact_sparks(
+    mut commands: Commands,
+    mut rng: GlobalEntropy<WyRand>,
+    mut impacts: EventReader<ProjectileWallImpact>,
+    game_assets: Res<GameAssets>,
+    particles: Query<(), With<Particle>>,
+    config: Res<ExplosionConfig>,
+) {
+    let mut live = particles.iter().len();
+    let color = game_assets.palette.wall_color();
+    for ProjectileWallImpact(pos) in impacts.read() {
+        let count = config.impact_particles as usize;
+        spawn_particles(&mut commands, &mut rng, live, *pos, color, count, 200.0);
+        live += count;
+    }
+}
+
+/// Shrinks and fades particles with what is left of their lifetime.
+fn update_particles(mut query: Query<(&Lifetime, &mut Transform, &mut Sprite), With<Particle>>) {
+    for (lifetime, mut transform, mut sprite) in &mut query {
+        let remaining = lifetime.0.fraction_remaining();
+        transform.scale = Vec3::splat(remaining);
+        sprite.color = sprite.color.with_alpha(remaining);
+    }
+}
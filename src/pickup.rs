@@ -0,0 +1,243 @@
+// pickup.rs
+
+//! Collectible pickups and their collision channel.
+//!
+//! Pickups sit in map space rather than on the grid movement system: they have no `GridMover`
+//! and never reserve tiles, so enemies and projectiles pass straight over them. Only the player
+//! can collect them, either by touching one or by drawing it in with their `PickupMagnet`.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::atlas::{atlas_sprite, AtlasSprite, GameAtlas};
+use crate::audio;
+use crate::collider::{Collider, ColliderShape};
+use crate::components::{Faction, GameEntity, GameState};
+use crate::explosion::{explosion_sprite, Explosion, ExplosionConfig};
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{GridMover, MovementSystems};
+use crate::message_log::{GameMessage, MessageKind};
+use crate::player::Player;
+use crate::tilemap::{GridSpace, TILE_SIZE};
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PickupCollected>().add_systems(
+            Update,
+            (attract_pickups, update_pickup_positions, collect_pickups)
+                .chain()
+                .after(MovementSystems::ApplyOffsetChanges)
+                .run_if(in_state(GameState::Playing).and(simulation_running)),
+        );
+    }
+}
+
+/// The different kinds of pickup that can be collected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PickupKind {
+    Gem,
+}
+
+impl PickupKind {
+    /// The name used in the message log.
+    pub fn label(self) -> &'static str {
+        match self {
+            PickupKind::Gem => "gem",
+        }
+    }
+}
+
+/// A collectible item.
+#[derive(Component)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// The pickup's position in map coordinates (tiles). Fractional while being magnetised.
+    pub map_pos: Vec2,
+}
+
+/// Attracts nearby pickups towards the entity carrying it (the player).
+#[derive(Component)]
+pub struct PickupMagnet {
+    /// Pickups within this many tiles start drifting towards the player.
+    pub radius: f32,
+    /// How fast attracted pickups move, in pixels per second.
+    pub speed: f32,
+}
+
+impl Default for PickupMagnet {
+    fn default() -> Self {
+        PickupMagnet {
+            radius: 2.0,
+            speed: 400.0,
+        }
+    }
+}
+
+/// Event fired when the player collects a pickup.
+#[derive(Event)]
+pub struct PickupCollected {
+    pub kind: PickupKind,
+    pub position: Vec3,
+}
+
+/// Grab radius multiplier for pickups, deliberately forgiving.
+const PICKUP_HURTBOX_SCALE: f32 = 1.5;
+
+/// How far pickups bob up and down, in pixels, and how fast, in radians per second.
+const BOB_HEIGHT: f32 = 3.0;
+const BOB_SPEED: f32 = 3.0;
+
+/// Pickups collected within this many seconds of the last one raise the chime's pitch.
+const CHIME_STREAK_WINDOW: f32 = 2.0;
+
+/// How much each pickup in a streak raises the chime's playback speed, and the most it can.
+const CHIME_PITCH_STEP: f32 = 0.08;
+const MAX_CHIME_PITCH: f32 = 2.0;
+
+/// Spawns a pickup of the given kind on a map tile.
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    atlas: Option<&GameAtlas>,
+    kind: PickupKind,
+    grid_pos: IVec2,
+) -> Entity {
+    let color = match kind {
+        PickupKind::Gem => game_assets.palette.colors[11],
+    };
+    commands
+        .spawn((
+            Sprite {
+                color,
+                custom_size: Some(Vec2::splat(TILE_SIZE * 0.5)),
+                ..atlas_sprite(atlas, game_assets, AtlasSprite::Player)
+            },
+            Transform::from_xyz(0.0, 0.0, 0.8),
+            Pickup {
+                kind,
+                map_pos: grid_pos.as_vec2(),
+            },
+            Faction::Neutral,
+            Collider {
+                size: Vec2::splat(TILE_SIZE * 0.5),
+                hurtbox_scale: PICKUP_HURTBOX_SCALE,
+                shape: ColliderShape::Circle {
+                    radius: TILE_SIZE * 0.25,
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .id()
+}
+
+/// Moves pickups within the player's magnet radius towards the player.
+fn attract_pickups(
+    player_query: Query<(&GridMover, &PickupMagnet), With<Player>>,
+    mut pickups: Query<&mut Pickup>,
+    time: Res<Time>,
+) {
+    let Ok((mover, magnet)) = player_query.single() else {
+        return;
+    };
+    let player_pos = mover.grid_pos.as_vec2() + mover.direction.as_vec2() * mover.progress;
+    let step = magnet.speed * time.delta_secs() / TILE_SIZE;
+
+    for mut pickup in &mut pickups {
+        let to_player = player_pos - pickup.map_pos;
+        let distance = to_player.length();
+        if distance <= magnet.radius && distance > 0.0 {
+            pickup.map_pos += to_player / distance * step.min(distance);
+        }
+    }
+}
+
+/// Positions pickups in the world from their map coordinates so they scroll with the map, with
+/// a gentle bob. Neighbouring pickups bob out of step.
+fn update_pickup_positions(
+    grid_space: GridSpace,
+    time: Res<Time>,
+    mut query: Query<(&Pickup, &mut Transform)>,
+) {
+    for (pickup, mut trans) in &mut query {
+        let world = grid_space.grid_to_world(pickup.map_pos);
+        let phase = pickup.map_pos.x + pickup.map_pos.y;
+        let bob = (time.elapsed_secs() * BOB_SPEED + phase).sin() * BOB_HEIGHT;
+        trans.translation.x = world.x;
+        trans.translation.y = world.y + bob;
+    }
+}
+
+/// Pickups collected in quick succession: how many followed the first, and when the last was.
+#[derive(Default)]
+struct ChimeStreak {
+    count: u32,
+    last: Option<f32>,
+}
+
+/// Collects any pickup whose grab radius overlaps the player, with a chime that rises in pitch
+/// through a streak.
+#[allow(clippy::too_many_arguments)]
+fn collect_pickups(
+    mut commands: Commands,
+    mut collected_events: EventWriter<PickupCollected>,
+    mut messages: EventWriter<GameMessage>,
+    game_assets: Res<GameAssets>,
+    images: Res<Assets<Image>>,
+    atlas: Option<Res<GameAtlas>>,
+    config: Res<ExplosionConfig>,
+    player_query: Query<(&Transform, &Collider), With<Player>>,
+    pickups: Query<(Entity, &Pickup, &Transform, &Collider)>,
+    time: Res<Time>,
+    mut streak: Local<ChimeStreak>,
+) {
+    let Ok((player_transform, player_collider)) = player_query.single() else {
+        return;
+    };
+    let player_pos = player_collider.center(player_transform.translation);
+
+    for (entity, pickup, transform, collider) in &pickups {
+        let pos = collider.center(transform.translation);
+        if !collider
+            .hurtbox()
+            .overlaps(pos, player_collider, player_pos)
+        {
+            continue;
+        }
+
+        collected_events.write(PickupCollected {
+            kind: pickup.kind,
+            position: transform.translation,
+        });
+        messages.write(GameMessage(
+            format!("{} collected", pickup.kind.label()),
+            MessageKind::Pickup,
+        ));
+        commands.entity(entity).despawn();
+
+        // A quick sparkle where the pickup was, reusing the explosion fade.
+        let mut sparkle = explosion_sprite(
+            &game_assets,
+            atlas.as_deref(),
+            &images,
+            game_assets.palette.colors[12],
+        );
+        sparkle.custom_size = Some(Vec2::splat(TILE_SIZE * 0.25));
+        commands.spawn((
+            sparkle,
+            Transform::from_translation(transform.translation),
+            Explosion::delayed(0.0, &config),
+            GameEntity,
+        ));
+        let now = time.elapsed_secs();
+        let in_streak = streak
+            .last
+            .is_some_and(|last| now - last <= CHIME_STREAK_WINDOW);
+        streak.count = if in_streak { streak.count + 1 } else { 0 };
+        streak.last = Some(now);
+        let pitch = (1.0 + CHIME_PITCH_STEP * streak.count as f32).min(MAX_CHIME_PITCH);
+        audio::play_with_pitch(&mut commands, game_assets.pickup_sfx.clone(), 0.4, pitch);
+    }
+}
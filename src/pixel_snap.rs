@@ -0,0 +1,95 @@
+// pixel_snap.rs
+
+//! Rounds every sprite's rendered position to the screen's pixel grid, so slow scrolling
+//! doesn't make the pixel art shimmer between sub-pixel positions.
+//!
+//! Only what gets drawn is snapped: the original translations are put back at the start of the
+//! next frame, so movement, scrolling and collisions keep working with fractional positions.
+//! Positions are snapped relative to the scroll offset, and the offset is snapped separately,
+//! so every entity moves by the same whole number of pixels when the view scrolls.
+
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::resolution::Resolution;
+use crate::tilemap::TileOffset;
+
+pub struct PixelSnapPlugin;
+
+impl Plugin for PixelSnapPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PixelSnap(true))
+            .init_resource::<SnappedTranslations>()
+            .add_systems(First, restore_translations)
+            .add_systems(
+                PostUpdate,
+                // Runs after every Update system has placed its entities, and before the
+                // positions are propagated for rendering.
+                snap_translations
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(resource_equals(PixelSnap(true))),
+            );
+    }
+}
+
+/// Whether rendered positions are snapped to the pixel grid, pushed from `Settings`.
+#[derive(Resource, PartialEq)]
+pub struct PixelSnap(pub bool);
+
+/// The unsnapped translation of every entity moved by `snap_translations` this frame.
+#[derive(Resource, Default)]
+pub struct SnappedTranslations(Vec<(Entity, Vec3)>);
+
+#[allow(clippy::type_complexity)]
+pub fn snap_translations(
+    resolution: Res<Resolution>,
+    tile_offset: Option<Res<TileOffset>>,
+    cameras: Query<&Projection, With<Camera2d>>,
+    mut sprites: Query<
+        (Entity, &mut Transform),
+        (Or<(With<Sprite>, With<Text2d>)>, Without<ChildOf>),
+    >,
+    mut camera_transforms: Query<(Entity, &mut Transform), (With<Camera2d>, Without<Sprite>)>,
+    mut snapped: ResMut<SnappedTranslations>,
+) {
+    let Ok(Projection::Orthographic(ortho)) = cameras.single() else {
+        return;
+    };
+    // The size of one physical screen pixel in world units.
+    let pixel = ortho.scale / resolution.pixel_ratio.max(0.001);
+    if pixel <= 0.0 {
+        return;
+    }
+    let round = |v: Vec2| (v / pixel).round() * pixel;
+    let scroll = tile_offset.map_or(Vec2::ZERO, |offset| offset.0);
+    let snapped_scroll = round(scroll);
+
+    for (entity, mut transform) in &mut sprites {
+        let original = transform.translation;
+        let position = round(original.xy() - scroll) + snapped_scroll;
+        if position != original.xy() {
+            snapped.0.push((entity, original));
+            transform.translation = position.extend(original.z);
+        }
+    }
+    for (entity, mut transform) in &mut camera_transforms {
+        let original = transform.translation;
+        let position = round(original.xy());
+        if position != original.xy() {
+            snapped.0.push((entity, original));
+            transform.translation = position.extend(original.z);
+        }
+    }
+}
+
+/// Puts back the fractional translations before any game logic runs.
+pub fn restore_translations(
+    mut snapped: ResMut<SnappedTranslations>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (entity, original) in snapped.0.drain(..) {
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.translation = original;
+        }
+    }
+}
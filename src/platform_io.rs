@@ -0,0 +1,120 @@
+// platform_io.rs
+
+//! File access for settings, saves and logs that also works in the browser.
+//!
+//! On native builds a name is a path relative to the working directory. There is no
+//! filesystem on the web, so there each name is a key in `localStorage` instead, and logs are
+//! dropped. Everything that persists between sessions should go through here rather than
+//! `std::fs`, which compiles for wasm32 but fails at runtime.
+
+use std::time::Duration;
+
+/// Reads the whole of `name`, or `None` if it doesn't exist or can't be read.
+pub fn read_config(name: &str) -> Option<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::read_to_string(name).ok()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        local_storage()?.get_item(name).ok().flatten()
+    }
+}
+
+/// Replaces the contents of `name` with `bytes`.
+///
+/// On native the data is written to a temporary file next to the target and then renamed over
+/// it, so a crash mid-write never leaves a truncated file behind. Parent directories are
+/// created as needed.
+pub fn write_config(name: &str, bytes: &[u8]) -> Result<(), String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = std::path::Path::new(name).parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let tmp_path = format!("{}.tmp", name);
+            std::fs::write(&tmp_path, bytes)?;
+            std::fs::rename(&tmp_path, name)
+        })();
+        result.map_err(|err| err.to_string())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let text = std::str::from_utf8(bytes).map_err(|err| err.to_string())?;
+        local_storage()
+            .ok_or("local storage is unavailable")?
+            .set_item(name, text)
+            .map_err(|_| "local storage is full or disabled".to_string())
+    }
+}
+
+/// Whether `name` exists.
+pub fn config_exists(name: &str) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::path::Path::new(name).exists()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        read_config(name).is_some()
+    }
+}
+
+/// Moves `from` to `to`, replacing anything already there.
+pub fn rename_config(from: &str, to: &str) -> Result<(), String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::rename(from, to).map_err(|err| err.to_string())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let contents = read_config(from).ok_or_else(|| format!("{} doesn't exist", from))?;
+        write_config(to, contents.as_bytes())?;
+        let storage = local_storage().ok_or("local storage is unavailable")?;
+        storage
+            .remove_item(from)
+            .map_err(|_| format!("couldn't remove {}", from))
+    }
+}
+
+/// Appends `text` to the log file `name`, creating it if needed. Does nothing on the web.
+pub fn append_log(name: &str, text: &str) -> Result<(), String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        use std::io::Write;
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(name)
+            .map_err(|err| err.to_string())?;
+        file.write_all(text.as_bytes())
+            .map_err(|err| err.to_string())
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = (name, text);
+        Ok(())
+    }
+}
+
+/// Time since the Unix epoch. `SystemTime::now` panics on wasm32, so the browser's clock is
+/// used there instead.
+pub fn since_epoch() -> Duration {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
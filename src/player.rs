@@ -6,18 +6,26 @@
 use bevy::prelude::*;
 
 use crate::assets::GameAssets;
+use crate::atlas::{atlas_sprite, AtlasSprite, GameAtlas};
 use crate::audio;
-use crate::collider::Collider;
-use crate::components::{GameEntity, GameState};
-use crate::grid_movement::{is_wall, GridMover, IntendedDirection, MovementSystems};
+use crate::bump::Stunned;
+use crate::collider::{Collider, ColliderShape};
+use crate::components::{Faction, GameEntity, GameState, Health};
+use crate::demo::Demo;
+use crate::difficulty::DifficultySetting;
+use crate::grid_movement::{
+    is_wall, GridMover, IntendedDirection, MovementSystems, PreviousTranslation,
+};
 use crate::grid_reservation::{GridReservations, GridReserver};
+use crate::input::InputMap;
 use crate::map::{generate_map, MapData};
+use crate::mouse_aim::AimMode;
+use crate::pickup::PickupMagnet;
 use crate::projectile::{Bouncable, Projectile};
-use crate::random::random_float;
-use crate::tilemap::{
-    MapOffset, TileOffset, HALF_HEIGHT, HALF_WIDTH, RENDERED_HEIGHT, RENDERED_WIDTH, TILE_SIZE,
-};
+use crate::random::random_range;
+use crate::tilemap::{setup_floor_palette, MapOffset, TileOffset, ViewportTiles, TILE_SIZE};
 use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use serde::Deserialize;
 
 /// A plugin responsible for managing player-related logic.
 ///
@@ -27,21 +35,26 @@ pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::Playing),
-            spawn_player.after(generate_map),
-        )
-        .add_systems(
-            Update,
-            (
-                // Player input systems are grouped in the `Input` set from MovementSystems.
-                handle_player_input.in_set(MovementSystems::Input),
-                handle_shoot.in_set(MovementSystems::Input),
-                // Camera scrolling logic runs after the player's position has been updated.
-                smooth_adjust_scroll.in_set(MovementSystems::AdjustScroll),
+        app.init_resource::<CameraDebug>()
+            .init_resource::<CameraTarget>()
+            .init_resource::<PlayerSpawn>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                // Drawn from the RNG after the floor palette, so a seeded run spawns identically.
+                spawn_player.after(generate_map).after(setup_floor_palette),
             )
-                .run_if(in_state(GameState::Playing)),
-        );
+            .add_systems(
+                Update,
+                (
+                    // Player input systems are grouped in the `Input` set from MovementSystems.
+                    handle_player_input.in_set(MovementSystems::Input),
+                    handle_shoot.in_set(MovementSystems::Input),
+                    // Camera scrolling logic runs after the player's position has been updated.
+                    smooth_adjust_scroll.in_set(MovementSystems::AdjustScroll),
+                    tick_invulnerability,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
@@ -49,32 +62,104 @@ impl Plugin for PlayerPlugin {
 #[derive(Component)]
 pub struct Player;
 
+/// The tile the player started the current round on.
+#[derive(Resource, Default, Debug)]
+pub struct PlayerSpawn(pub IVec2);
+
 /// The base speed multiplier for player and projectile movement.
 pub const DEFAULT_PLAYER_SPEED: f32 = 1000.0;
 
-/// Defines the size of the "camera deadzone" in tiles. The camera will not scroll
-/// until the player moves beyond this buffer area from the center of the screen.
-const BUFFER_TILES: Vec2 = Vec2::new(2.0, 2.0);
+/// Hurtbox multiplier for the player, slightly generous so near-misses still register.
+const PLAYER_HURTBOX_SCALE: f32 = 2.5;
 
-/// Constants for controlling the smoothness of the camera follow.
-/// BASE_TAU is the base time constant (in seconds) for the exponential lerp when the player is just outside the buffer.
-/// TAU_SCALE determines how much the time constant decreases (speed increases) for each additional tile beyond the buffer.
-const BASE_TAU: f32 = 4.0;
-const BASE_TAU_SCALE: f32 = 1.0;
+/// Seconds the player can't be hurt after losing a heart.
+pub const HIT_INVULNERABILITY_TIME: f32 = 1.5;
+
+/// How many times per second the player blinks while invulnerable.
+const INVULNERABILITY_BLINK_RATE: f32 = 10.0;
+
+/// Makes the player immune to damage until the timer finishes.
+#[derive(Component)]
+pub struct Invulnerable(pub Timer);
+
+impl Invulnerable {
+    pub fn after_hit() -> Self {
+        Invulnerable(Timer::from_seconds(
+            HIT_INVULNERABILITY_TIME,
+            TimerMode::Once,
+        ))
+    }
+}
+
+/// What steers the player: the keyboard, or the attract mode bot.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControlSource {
+    Keyboard,
+    Bot,
+}
+
+/// Tunable camera parameters, loaded from the `camera` section of `config.ron`.
+#[derive(Resource, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CameraConfig {
+    /// Size of the "camera deadzone" in tiles. The camera will not scroll until the player
+    /// moves beyond this buffer area around the center of the screen.
+    pub buffer_width: f32,
+    pub buffer_height: f32,
+    /// Base time constant (in seconds) for the exponential lerp when the player is just
+    /// outside the buffer.
+    pub base_tau: f32,
+    /// How much the time constant decreases (speed increases) for each additional tile beyond
+    /// the buffer.
+    pub tau_scale: f32,
+    /// How far ahead of the player (in tiles) the camera leads in the direction of movement.
+    pub lookahead_tiles: f32,
+    /// Seconds of sustained movement for the lookahead to reach its full distance.
+    pub lookahead_ramp: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        CameraConfig {
+            buffer_width: 2.0,
+            buffer_height: 2.0,
+            base_tau: 4.0,
+            tau_scale: 1.0,
+            lookahead_tiles: 4.0,
+            lookahead_ramp: 0.5,
+        }
+    }
+}
+
+/// What the camera follow did this frame, kept for the camera debug overlay. Positions are in
+/// map coordinates.
+#[derive(Resource, Default)]
+pub struct CameraDebug {
+    pub view_center: Vec2,
+    pub player: Vec2,
+    /// The player's position plus the lookahead.
+    pub target: Vec2,
+    /// The lerp time constant, or `None` while the target is inside the buffer.
+    pub tau: Option<f32>,
+}
 
 /// Spawns the player entity at a random, valid (non-wall) location on the map.
 ///
 /// This system runs once when entering the `GameState::Playing` state. It also
-/// calculates the initial map and tile offsets to center the camera on the
-/// newly spawned player.
+/// centers the camera on the newly spawned player.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_player(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
     mut rng: GlobalEntropy<WyRand>,
     map_data: Res<MapData>,
+    viewport: Res<ViewportTiles>,
     mut map_offset: ResMut<MapOffset>,
     mut tile_offset: ResMut<TileOffset>,
     mut reservations: ResMut<GridReservations>,
+    difficulty: Res<DifficultySetting>,
+    demo: Res<Demo>,
+    atlas: Option<Res<GameAtlas>>,
 ) {
     let width = map_data.width as i32;
     let height = map_data.height as i32;
@@ -83,40 +168,36 @@ pub fn spawn_player(
 
     // Loop until a valid, non-wall starting position is found.
     loop {
-        mx = (random_float(&mut rng) * width as f32) as i32;
-        my = (random_float(&mut rng) * height as f32) as i32;
+        mx = random_range(&mut rng, 0..width);
+        my = random_range(&mut rng, 0..height);
         let flipped_y = (height - 1 - my) as u32; // Map data is stored with Y-axis flipped.
         let idx = (flipped_y * map_data.width + mx as u32) as usize;
         if let Some(&is_wall) = map_data.is_wall.get(idx) {
-            if !is_wall {
+            if !is_wall && !map_data.is_lava(IVec2::new(mx, my)) {
                 break; // Found a valid spot.
             }
         }
     }
 
-    // Calculate the initial integer-based map offset to position the player near the center of the view.
-    // This is clamped to ensure the view doesn't go outside the map boundaries.
-    let ox =
-        ((mx as f32 - HALF_WIDTH).floor() as i32).clamp(0, (width - RENDERED_WIDTH as i32).max(0));
-    let oy = ((my as f32 - HALF_HEIGHT).floor() as i32)
-        .clamp(0, (height - RENDERED_HEIGHT as i32).max(0));
-    map_offset.0 = IVec2::new(ox, oy);
-
-    // Calculate the fractional (sub-tile) offset needed for smooth scrolling.
-    let frac_x = mx as f32 - ox as f32 - HALF_WIDTH;
-    let frac_y = my as f32 - oy as f32 - HALF_HEIGHT;
-    tile_offset.0 = Vec2::new(-frac_x * TILE_SIZE, -frac_y * TILE_SIZE);
+    center_view_on(
+        IVec2::new(mx, my),
+        &viewport,
+        &map_data,
+        &mut map_offset,
+        &mut tile_offset,
+    );
+    commands.insert_resource(PlayerSpawn(IVec2::new(mx, my)));
 
     // Spawn the player entity with all its necessary components.
     let player_entity = commands
         .spawn((
             Sprite {
                 color: Color::WHITE,
-                image: game_assets.player_texture.clone(),
-                ..default()
+                ..atlas_sprite(atlas.as_deref(), &game_assets, AtlasSprite::Player)
             },
             Transform::from_xyz(0.0, 0.0, 1.0), // Initial position is centered, adjusted by GridMover.
             Player,
+            Faction::Players,
             GridMover {
                 grid_pos: IVec2::new(mx, my),
                 direction: IVec2::ZERO,
@@ -127,8 +208,18 @@ pub fn spawn_player(
             GameEntity, // Marker for cleanup when returning to the title screen.
             Collider {
                 size: Vec2::splat(TILE_SIZE * 0.5), // A smaller collider than the tile size.
+                hurtbox_scale: PLAYER_HURTBOX_SCALE,
+                ..default()
             },
             GridReserver, // Add the reserver component
+            PreviousTranslation::default(),
+            PickupMagnet::default(),
+            Health::new(difficulty.0.player_hearts()),
+            if demo.0 {
+                ControlSource::Bot
+            } else {
+                ControlSource::Keyboard
+            },
         ))
         .id();
 
@@ -136,162 +227,288 @@ pub fn spawn_player(
     reservations.0.insert(IVec2::new(mx, my), player_entity);
 }
 
-/// Reads keyboard input (W, A, S, D) to set the player's intended direction of movement.
+/// Scrolls the view so `pos` is as near its center as the map edges allow.
+pub fn center_view_on(
+    pos: IVec2,
+    viewport: &ViewportTiles,
+    map_data: &MapData,
+    map_offset: &mut MapOffset,
+    tile_offset: &mut TileOffset,
+) {
+    // Calculate the integer-based map offset to position the view near `pos`.
+    // This is clamped to ensure the view doesn't go outside the map boundaries.
+    let max_offset = viewport.max_offset(map_data);
+    let ox = ((pos.x as f32 - viewport.half_width()).floor() as i32).clamp(0, max_offset.x);
+    let oy = ((pos.y as f32 - viewport.half_height()).floor() as i32).clamp(0, max_offset.y);
+    map_offset.0 = IVec2::new(ox, oy);
+
+    // Calculate the fractional (sub-tile) offset needed for smooth scrolling.
+    let frac_x = pos.x as f32 - ox as f32 - viewport.half_width();
+    let frac_y = pos.y as f32 - oy as f32 - viewport.half_height();
+    tile_offset.0 = Vec2::new(-frac_x * TILE_SIZE, -frac_y * TILE_SIZE);
+}
+
+/// Reads the movement keys (and arrows) from the `InputMap` to set the player's intended direction
+/// of movement.
 ///
 /// This system updates the `IntendedDirection` component, which is then used by the
 /// `update_grid_movement` system to control the `GridMover`.
 fn handle_player_input(
     keys: Res<ButtonInput<KeyCode>>,
-    mut query: Query<&mut IntendedDirection, With<Player>>,
+    input_map: Res<InputMap>,
+    mut query: Query<(&mut IntendedDirection, &ControlSource), With<Player>>,
 ) {
-    if let Ok(mut intended) = query.single_mut() {
-        let mut dx = 0i32;
-        if keys.pressed(KeyCode::KeyA) {
-            dx -= 1;
-        }
-        if keys.pressed(KeyCode::KeyD) {
-            dx += 1;
-        }
-        let mut dy = 0i32;
-        if keys.pressed(KeyCode::KeyS) {
-            dy -= 1;
-        }
-        if keys.pressed(KeyCode::KeyW) {
-            dy += 1;
-        }
-        intended.0 = IVec2::new(dx, dy);
+    if let Ok((mut intended, ControlSource::Keyboard)) = query.single_mut() {
+        intended.0 = input_map.movement(&keys);
     }
 }
 
 /// Handles the player's shooting action based on keyboard input.
 ///
-/// When the Space key is pressed, this system spawns a projectile entity.
-/// The projectile is spawned one tile ahead of the player in their current
-/// intended direction of movement. No projectile is fired if the player is stationary
-/// or aiming at a wall.
+/// When a fire key (or, with keyboard aim, the left mouse button) is pressed, this system fires a
+/// projectile in the player's current intended direction of movement. No projectile is fired if
+/// the player is stationary. With mouse aim the button is handled by `mouse_aim` instead.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn handle_shoot(
     keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
     mouse: Res<ButtonInput<MouseButton>>,
+    aim_mode: Res<AimMode>,
     mut commands: Commands,
     game_assets: Res<GameAssets>,
-    query: Query<(&GridMover, &IntendedDirection), With<Player>>,
+    query: Query<
+        (&GridMover, &IntendedDirection, &ControlSource),
+        (With<Player>, Without<Stunned>),
+    >,
     map_data: Res<MapData>,
+    difficulty: Res<DifficultySetting>,
+    mut rng: GlobalEntropy<WyRand>,
+    atlas: Option<Res<GameAtlas>>,
 ) {
     // Check for the shoot button press.
-    if keys.just_pressed(KeyCode::Space) || mouse.just_pressed(MouseButton::Left) {
-        if let Ok((mover, intended)) = query.single() {
+    let clicked = *aim_mode == AimMode::Keyboard && mouse.just_pressed(MouseButton::Left);
+    if keys.any_just_pressed(input_map.fire_keys()) || clicked {
+        if let Ok((mover, intended, ControlSource::Keyboard)) = query.single() {
             // Only shoot if the player has a direction.
             if intended.0 != IVec2::ZERO {
-                let dir = intended.0;
-                let spawn_pos = mover.grid_pos + dir; // Spawn in the next tile over.
-
-                // Prevent spawning a projectile inside a wall.
-                if is_wall(spawn_pos, &map_data) {
-                    return;
-                }
-                let color = game_assets.palette.colors[5]; // Use palette index 5 for initial color.
-
-                // Spawn the projectile entity.
-                commands.spawn((
-                    Sprite {
-                        color,
-                        image: game_assets.player_texture.clone(), // Uses player texture for now.
-                        ..default()
-                    },
-                    Transform::from_xyz(0.0, 0.0, 1.0),
-                    Projectile,
-                    GridMover {
-                        grid_pos: spawn_pos,
-                        direction: dir,
-                        progress: 0.0,
-                        speed: mover.speed * 1.5, // Projectiles are 1.5x faster than player.
-                    },
-                    IntendedDirection(dir), // The projectile continues in the player's direction.
-                    Bouncable {
-                        initial: 3, // If a projectile has bounced at least once, it can now hit the player.
-                        remaining: 3,
-                    }, // Can bounce off walls 3 times.
-                    Collider {
-                        size: Vec2::splat(TILE_SIZE * 0.5),
-                    },
-                    GameEntity,
-                ));
-                // Play the shooting sound effect.
-                audio::play(&mut commands, game_assets.shoot_sfx.clone());
+                fire_projectile(
+                    &mut commands,
+                    &game_assets,
+                    atlas.as_deref(),
+                    mover,
+                    intended.0,
+                    &map_data,
+                    difficulty.0.projectile_bounces(),
+                    &mut rng,
+                );
             }
         }
     }
 }
 
+/// Spawns a player projectile one tile ahead of `mover`, travelling in `dir`.
+///
+/// Returns false without firing if that tile is a wall.
+#[allow(clippy::too_many_arguments)]
+pub fn fire_projectile(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    atlas: Option<&GameAtlas>,
+    mover: &GridMover,
+    dir: IVec2,
+    map_data: &MapData,
+    bounces: u32,
+    rng: &mut GlobalEntropy<WyRand>,
+) -> bool {
+    let spawn_pos = mover.grid_pos + dir; // Spawn in the next tile over.
+
+    // Prevent spawning a projectile inside a wall.
+    if is_wall(spawn_pos, map_data) {
+        return false;
+    }
+    let color = game_assets.palette.colors[5]; // Use palette index 5 for initial color.
+
+    // Spawn the projectile entity.
+    commands.spawn((
+        Sprite {
+            color,
+            // Uses player texture for now.
+            ..atlas_sprite(atlas, game_assets, AtlasSprite::Player)
+        },
+        Transform::from_xyz(0.0, 0.0, 1.0),
+        Projectile,
+        Faction::Players,
+        GridMover {
+            grid_pos: spawn_pos,
+            direction: dir,
+            progress: 0.0,
+            speed: mover.speed * 1.5, // Projectiles are 1.5x faster than player.
+        },
+        IntendedDirection(dir), // The projectile continues in the player's direction.
+        // If a projectile has bounced at least once, it can now hit the player.
+        Bouncable {
+            initial: bounces,
+            remaining: bounces,
+        },
+        Collider {
+            size: Vec2::splat(TILE_SIZE * 0.5),
+            shape: ColliderShape::Circle {
+                radius: TILE_SIZE * 0.25,
+            },
+            ..default()
+        },
+        PreviousTranslation::default(),
+        GameEntity,
+    ));
+    // Play the shooting sound effect.
+    audio::play_varied(
+        commands,
+        audio::SfxCategory::Shot,
+        &game_assets.shoot_sfx,
+        1.0,
+        audio::PITCH_VARIATION,
+        rng,
+    );
+    true
+}
+
+/// What the camera looks at. `smooth_adjust_scroll` steers the view towards it, so anything
+/// that wants the camera (spectating, the attract mode, cutscenes) sets this instead of
+/// moving the offsets itself.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq)]
+pub enum CameraTarget {
+    /// The player, with the buffer zone and lookahead.
+    #[default]
+    FollowPlayer,
+    /// A view center in map coordinates, moved by whoever set it.
+    FreePan(Vec2),
+    /// Any entity with a transform, kept in the middle of the view.
+    FollowEntity(Entity),
+}
+
 /// Implements smooth camera scrolling by lerping the map and tile offsets.
 ///
-/// This function uses an exponential lerp to smoothly adjust the view center towards the player's
-/// map position when the player is outside the central buffer zone. The lerp strength increases
-/// (time constant decreases) as the player gets farther from the center, preventing the player
-/// from racing too far offscreen. The view is clamped to the map boundaries.
+/// While following the player, this function uses an exponential lerp to smoothly adjust the
+/// view center towards the player's map position when the player is outside the central buffer
+/// zone. The lerp strength increases (time constant decreases) as the player gets farther from
+/// the center, preventing the player from racing too far offscreen. The other `CameraTarget`s
+/// are centered on directly. The view is clamped to the map boundaries.
+///
+/// The player target is biased ahead of the player in their direction of movement. The bias
+/// ramps in over `CameraConfig::lookahead_ramp` seconds and back out when they stop or turn, so
+/// quick taps barely move it. The buffer zone is measured against the biased target.
+#[allow(clippy::too_many_arguments)]
 fn smooth_adjust_scroll(
     query_player: Query<(&Transform, &GridMover), With<Player>>,
+    target_query: Query<&Transform>,
+    camera_target: Res<CameraTarget>,
     mut map_offset: ResMut<MapOffset>,
     mut tile_offset: ResMut<TileOffset>,
     map_data: Res<MapData>,
+    viewport: Res<ViewportTiles>,
     time: Res<Time>,
+    config: Res<CameraConfig>,
+    mut lookahead: Local<Vec2>,
+    mut camera_debug: ResMut<CameraDebug>,
 ) {
     // Compute the current view center in map coordinates.
-    let mut current_view_center = Vec2::new(
-        map_offset.0.x as f32 - tile_offset.0.x / TILE_SIZE + HALF_WIDTH,
-        map_offset.0.y as f32 - tile_offset.0.y / TILE_SIZE + HALF_HEIGHT,
-    );
+    let half = viewport.half_extent();
+    let current_view_center = viewport.view_center(&map_offset, &tile_offset);
 
-    if let Ok((player_tr, grid_mover)) = query_player.single() {
+    let player = match *camera_target {
+        CameraTarget::FollowPlayer => query_player.single().ok(),
+        _ => None,
+    };
+    let new_view_center = if let Some((player_tr, grid_mover)) = player {
         let player_screen = player_tr.translation.xy();
 
         // Compute the player's current position in map coordinates.
-        let player_map_pos = Vec2::new(
-            (player_screen.x - tile_offset.0.x) / TILE_SIZE + map_offset.0.x as f32 + HALF_WIDTH,
-            (player_screen.y - tile_offset.0.y) / TILE_SIZE + map_offset.0.y as f32 + HALF_HEIGHT,
-        );
+        let player_map_pos = viewport.world_to_map(player_screen, &map_offset, &tile_offset);
 
-        // Adjust TAU_SCALE based on player's speed relative to DEFAULT_PLAYER_SPEED.
+        // Adjust tau_scale based on player's speed relative to DEFAULT_PLAYER_SPEED.
         let speed_ratio = grid_mover.speed / DEFAULT_PLAYER_SPEED;
-        let dynamic_tau_scale = BASE_TAU_SCALE / speed_ratio.max(0.001); // Prevent division by zero
+        let dynamic_tau_scale = config.tau_scale / speed_ratio.max(0.001); // Prevent division by zero
+
+        // Slide the lookahead towards its goal at a constant rate, so it takes the ramp time
+        // to cover the full distance.
+        let goal = grid_mover.direction.as_vec2() * config.lookahead_tiles;
+        let rate = config.lookahead_tiles / config.lookahead_ramp.max(0.001);
+        *lookahead = lookahead.move_towards(goal, rate * time.delta_secs());
+        let target = player_map_pos + *lookahead;
 
-        // Calculate the desired view center (player position) and interpolate.
-        let diff = player_map_pos - current_view_center;
+        // Calculate the desired view center (the biased target) and interpolate.
+        let diff = target - current_view_center;
         let abs_diff = diff.abs();
-        let half_buf = BUFFER_TILES / 2.0;
+        let half_buf = Vec2::new(config.buffer_width, config.buffer_height) / 2.0;
 
         // Initialize t (interpolation factor) to 0.0 (no movement if within buffer).
         let mut t = 0.0;
+        let mut live_tau = None;
 
         // Check if player is outside the buffer zone on either axis.
         if abs_diff.x > half_buf.x || abs_diff.y > half_buf.y {
             // Compute interpolation factor t based on distance beyond buffer.
             let extra = (abs_diff - half_buf).max(Vec2::ZERO);
-            let tau = BASE_TAU / (1.0 + extra.length() / dynamic_tau_scale);
+            let tau = config.base_tau / (1.0 + extra.length() / dynamic_tau_scale);
             t = 1.0 - (-time.delta_secs() / tau).exp();
+            live_tau = Some(tau);
         }
 
-        // Use Vec2::lerp to interpolate towards the player's position.
-        current_view_center = current_view_center.lerp(player_map_pos, t);
+        // The view center is filled in below, once clamped.
+        *camera_debug = CameraDebug {
+            view_center: current_view_center,
+            player: player_map_pos,
+            target,
+            tau: live_tau,
+        };
 
-        // Compute the new view left and top edges.
-        let mut new_view_left = current_view_center.x - HALF_WIDTH;
-        let mut new_view_top = current_view_center.y - HALF_HEIGHT;
+        // Use Vec2::lerp to interpolate towards the target.
+        current_view_center.lerp(target, t)
+    } else {
+        *lookahead = Vec2::ZERO;
+        match *camera_target {
+            CameraTarget::FollowPlayer => return,
+            CameraTarget::FreePan(center) => center,
+            CameraTarget::FollowEntity(entity) => {
+                let Ok(transform) = target_query.get(entity) else {
+                    return;
+                };
+                viewport.world_to_map(transform.translation.xy(), &map_offset, &tile_offset)
+            }
+        }
+    };
 
-        // Clamp to map boundaries.
-        let max_left = (map_data.width as f32 - RENDERED_WIDTH as f32).max(0.0);
-        let max_top = (map_data.height as f32 - RENDERED_HEIGHT as f32).max(0.0);
-        new_view_left = new_view_left.clamp(0.0, max_left);
-        new_view_top = new_view_top.clamp(0.0, max_top);
+    // Compute the new view left and top edges, clamped to the map boundaries.
+    let max_offset = viewport.max_offset(&map_data).as_vec2();
+    let view_corner = (new_view_center - half).clamp(Vec2::ZERO, max_offset);
+    camera_debug.view_center = view_corner + half;
 
-        // Update map_offset and tile_offset for X.
-        map_offset.0.x = new_view_left.floor() as i32;
-        let frac_x = new_view_left - map_offset.0.x as f32;
-        tile_offset.0.x = -frac_x * TILE_SIZE;
+    // The whole-tile part goes in map_offset and the remainder in tile_offset. map_offset
+    // is only written when it moves, since the tile colors are repainted whenever it does.
+    let view_origin = view_corner.floor().as_ivec2();
+    map_offset.set_if_neq(MapOffset(view_origin));
+    let frac = view_corner - view_origin.as_vec2();
+    tile_offset.0 = -frac * TILE_SIZE;
+}
 
-        // Update map_offset and tile_offset for Y.
-        map_offset.0.y = new_view_top.floor() as i32;
-        let frac_y = new_view_top - map_offset.0.y as f32;
-        tile_offset.0.y = -frac_y * TILE_SIZE;
+/// Counts down the player's post-hit immunity, blinking the sprite until it ends.
+fn tick_invulnerability(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Invulnerable, &mut Visibility), With<Player>>,
+) {
+    for (entity, mut invulnerable, mut visibility) in &mut query {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            *visibility = Visibility::Inherited;
+            commands.entity(entity).remove::<Invulnerable>();
+        } else {
+            let blink = (invulnerable.0.elapsed_secs() * INVULNERABILITY_BLINK_RATE) as u32;
+            *visibility = if blink.is_multiple_of(2) {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
     }
 }
@@ -0,0 +1,112 @@
+// popup.rs
+
+//! Floating score popups shown where enemies die.
+//!
+//! Popups live in map coordinates and are positioned through `GridSpace` every frame, the
+//! same way `ReservationVisualizer` is, so they stay pinned to the spot of the kill while the
+//! camera scrolls. Their `Lifetime` times the rise and fade and despawns them.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::{GameEntity, GameState, Lifetime};
+use crate::grid_movement::MovementSystems;
+use crate::score::PointsAwarded;
+use crate::tilemap::GridSpace;
+
+pub struct PopupPlugin;
+
+impl Plugin for PopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_score_popups, update_score_popups)
+                .chain()
+                .after(MovementSystems::ApplyOffsetChanges)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// A floating score popup.
+#[derive(Component)]
+struct ScorePopup {
+    /// Where the popup was spawned, in map coordinates.
+    map_pos: Vec2,
+    color: Color,
+}
+
+/// Seconds a popup stays on screen.
+const POPUP_LIFETIME: f32 = 0.8;
+
+/// How far a popup drifts upward over its lifetime, in pixels.
+const POPUP_RISE: f32 = 20.0;
+
+/// Maximum number of popups on screen; the oldest are dropped first.
+const MAX_POPUPS: usize = 30;
+
+fn spawn_score_popups(
+    mut commands: Commands,
+    mut awarded_events: EventReader<PointsAwarded>,
+    game_assets: Res<GameAssets>,
+    grid_space: GridSpace,
+    existing: Query<(Entity, &Lifetime), With<ScorePopup>>,
+) {
+    let events: Vec<&PointsAwarded> = awarded_events.read().collect();
+    if events.is_empty() {
+        return;
+    }
+
+    // Make room for the new popups by dropping the oldest ones.
+    let overflow = (existing.iter().len() + events.len()).saturating_sub(MAX_POPUPS);
+    if overflow > 0 {
+        let mut by_age: Vec<(Entity, f32)> = existing
+            .iter()
+            .map(|(entity, lifetime)| (entity, lifetime.0.elapsed_secs()))
+            .collect();
+        by_age.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (entity, _) in by_age.into_iter().take(overflow) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let palette = &game_assets.palette.colors;
+    // Only the newest popups survive if a single frame produced more than the cap.
+    for event in events.iter().rev().take(MAX_POPUPS) {
+        let color = match event.combo {
+            0..=2 => palette[12], // white
+            3..=5 => palette[4],  // yellow
+            _ => palette[3],      // orange
+        };
+        commands.spawn((
+            Text2d::new(event.points.to_string()),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_translation(event.position.with_z(2.5)),
+            ScorePopup {
+                map_pos: grid_space.world_to_grid(event.position.xy()),
+                color,
+            },
+            Lifetime::from_seconds(POPUP_LIFETIME),
+            GameEntity,
+        ));
+    }
+}
+
+/// Drifts popups upward from their map position and fades them out.
+fn update_score_popups(
+    mut query: Query<(&ScorePopup, &Lifetime, &mut Transform, &mut TextColor)>,
+    grid_space: GridSpace,
+) {
+    for (popup, lifetime, mut transform, mut text_color) in &mut query {
+        let t = lifetime.0.fraction();
+        let pos = grid_space.grid_to_world(popup.map_pos);
+        transform.translation.x = pos.x;
+        transform.translation.y = pos.y + POPUP_RISE * t;
+        text_color.0 = popup.color.with_alpha(1.0 - t);
+    }
+}
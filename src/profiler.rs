@@ -0,0 +1,162 @@
+// profiler.rs
+
+//! Lightweight timings for the systems most likely to eat a frame, shown in the debug panel.
+//!
+//! An instrumented system takes `Res<SystemTimings>` and holds a `span` for its body. The
+//! resource only reads the clock while the debug panel is showing, so the spans cost a branch
+//! the rest of the time. Taking it as `Res` rather than `ResMut` keeps the instrumented systems
+//! free to run in parallel; the samples sit behind a mutex instead.
+
+use bevy::platform::time::Instant;
+use bevy::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::console::{CommandResult, ConsoleCommands};
+use crate::debug::DebugFlags;
+
+/// Frames of samples kept per system.
+const TIMING_FRAMES: usize = 60;
+
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SystemTimings>()
+            .init_resource::<ConsoleCommands>()
+            .add_systems(First, enable_timings)
+            .add_systems(Last, roll_timings);
+
+        app.world_mut().resource_mut::<ConsoleCommands>().register(
+            "timings",
+            "timings dump",
+            "logs the system timings",
+            timings_command,
+        );
+    }
+}
+
+/// Time spent in one system: the running total for this frame and the last `TIMING_FRAMES`
+/// frame totals.
+#[derive(Default)]
+struct TimingTrack {
+    current: Option<Duration>,
+    samples: VecDeque<Duration>,
+}
+
+/// Recent per-frame timings of the instrumented systems, by system name.
+#[derive(Resource, Default)]
+pub struct SystemTimings {
+    /// Whether spans read the clock; follows the debug panel's visibility.
+    enabled: bool,
+    tracks: Mutex<BTreeMap<&'static str, TimingTrack>>,
+}
+
+impl SystemTimings {
+    /// Starts timing `name`; the time is recorded when the returned span is dropped.
+    pub fn span(&self, name: &'static str) -> TimingSpan<'_> {
+        TimingSpan {
+            timings: self,
+            name,
+            start: self.enabled.then(Instant::now),
+        }
+    }
+
+    fn record(&self, name: &'static str, elapsed: Duration) {
+        let mut tracks = self.tracks.lock().unwrap();
+        let track = tracks.entry(name).or_default();
+        *track.current.get_or_insert_default() += elapsed;
+    }
+
+    /// The average and maximum frame time of each system, slowest average first.
+    pub fn summary(&self) -> Vec<(&'static str, Duration, Duration)> {
+        let tracks = self.tracks.lock().unwrap();
+        let mut rows: Vec<_> = tracks
+            .iter()
+            .filter(|(_, track)| !track.samples.is_empty())
+            .map(|(&name, track)| {
+                let total: Duration = track.samples.iter().sum();
+                let max = track.samples.iter().max().copied().unwrap_or_default();
+                (name, total / track.samples.len() as u32, max)
+            })
+            .collect();
+        rows.sort_by_key(|row| std::cmp::Reverse(row.1));
+        rows
+    }
+}
+
+/// Times a system body from creation until dropped.
+pub struct TimingSpan<'a> {
+    timings: &'a SystemTimings,
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Drop for TimingSpan<'_> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.timings.record(self.name, start.elapsed());
+        }
+    }
+}
+
+/// Formats the timings table, one system per line, in milliseconds.
+pub fn format_timings(rows: &[(&'static str, Duration, Duration)]) -> String {
+    rows.iter()
+        .map(|(name, avg, max)| {
+            format!(
+                "{} {:.3}/{:.3}",
+                name,
+                avg.as_secs_f64() * 1000.0,
+                max.as_secs_f64() * 1000.0
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Only measures while the debug panel is on. Turning it off drops the old samples, so the
+/// table doesn't show stale numbers when it comes back.
+fn enable_timings(flags: Res<DebugFlags>, mut timings: ResMut<SystemTimings>) {
+    let enabled = flags.master && flags.panel;
+    if timings.enabled == enabled {
+        return;
+    }
+    timings.enabled = enabled;
+    if !enabled {
+        timings.tracks.get_mut().unwrap().clear();
+    }
+}
+
+/// Closes off the frame, moving each system's total into its ring buffer.
+fn roll_timings(timings: Res<SystemTimings>) {
+    if !timings.enabled {
+        return;
+    }
+    let mut tracks = timings.tracks.lock().unwrap();
+    for track in tracks.values_mut() {
+        if let Some(total) = track.current.take() {
+            if track.samples.len() == TIMING_FRAMES {
+                track.samples.pop_front();
+            }
+            track.samples.push_back(total);
+        }
+    }
+}
+
+fn timings_command(world: &mut World, args: &[&str]) -> CommandResult {
+    if args != ["dump"] {
+        return Err("expected dump".to_string());
+    }
+    let rows = world.resource::<SystemTimings>().summary();
+    if rows.is_empty() {
+        return Err("nothing recorded; timings are only taken while the debug panel is on".into());
+    }
+    info!(
+        "System timings over the last {} frames (avg/max ms):\n{}",
+        TIMING_FRAMES,
+        format_timings(&rows)
+    );
+    Ok(format!("Logged timings for {} systems", rows.len()))
+}
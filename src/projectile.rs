@@ -1,11 +1,15 @@
 // projectile.rs
 use crate::assets::GameAssets;
-use crate::collider::ProjectileCollision;
-use crate::components::{EnemyDied, GameState, PlayerDied};
-use crate::enemy::Enemy;
+use crate::audio;
+use crate::collider::{check_projectile_collisions, DamageEvent, ProjectileCollision};
+use crate::components::{Dying, GameState, KillSource};
+use crate::frame_step::simulation_running;
 use crate::grid_movement::MovementSystems;
-use crate::player::Player;
 use bevy::prelude::*;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+
+/// Volume of the bounce sound, quieter than shots since bounces are frequent.
+const BOUNCE_SFX_VOLUME: f32 = 0.5;
 
 #[derive(Component)]
 pub struct Projectile;
@@ -16,46 +20,73 @@ pub struct Bouncable {
     pub remaining: u32, // Tracks the remaining bounces
 }
 
+/// Event fired when a projectile runs out of bounces and is destroyed against a wall.
+#[derive(Event)]
+pub struct ProjectileWallImpact(pub Vec3);
+
+/// Event fired when a projectile bounces off a wall.
+#[derive(Event)]
+pub struct ProjectileBounced(pub Vec3);
+
 pub struct ProjectilePlugin;
 
 impl Plugin for ProjectilePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                handle_projectile_collisions,
-                update_projectile_colors.after(MovementSystems::UpdateMover),
-            )
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.add_event::<ProjectileWallImpact>()
+            .add_event::<ProjectileBounced>()
+            .add_systems(
+                Update,
+                (
+                    handle_projectile_collisions.after(check_projectile_collisions),
+                    update_projectile_colors.after(MovementSystems::UpdateMover),
+                    play_bounce_sounds.after(MovementSystems::UpdateMover),
+                )
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
+            );
     }
 }
 
 /// Listens for `ProjectileCollision` events and handles the consequences.
-fn handle_projectile_collisions(
+///
+/// The projectile is spent on any confirmed collision, and the victim is sent a `DamageEvent`
+/// so its death is resolved alongside every other kill this frame.
+pub fn handle_projectile_collisions(
     mut commands: Commands,
     mut collision_events: EventReader<ProjectileCollision>,
-    mut player_died_events: EventWriter<PlayerDied>,
-    mut enemy_died_events: EventWriter<EnemyDied>,
-    // Query to determine if the victim was a Player or an Enemy.
-    victim_query: Query<(Has<Player>, Has<Enemy>, &Transform)>,
+    mut damage_events: EventWriter<DamageEvent>,
+    projectile_query: Query<(&Bouncable, &Transform), Without<Dying>>,
 ) {
     for event in collision_events.read() {
-        // Despawn the projectile on any confirmed collision.
-        commands.entity(event.projectile).despawn();
+        // A projectile that was already spent can't hit anything else.
+        let Ok((bouncable, transform)) = projectile_query.get(event.projectile) else {
+            continue;
+        };
+        commands.entity(event.projectile).try_insert(Dying);
+        let bounces = bouncable.initial.saturating_sub(bouncable.remaining);
+        damage_events.write(DamageEvent {
+            victim: event.victim,
+            amount: 1,
+            source: KillSource::projectile(bounces),
+            position: transform.translation,
+        });
+    }
+}
 
-        // Check what the victim was and react accordingly.
-        if let Ok((is_player, is_enemy, transform)) = victim_query.get(event.victim) {
-            let pos = transform.translation;
-            if is_player {
-                commands.entity(event.victim).despawn();
-                player_died_events.write(PlayerDied(pos));
-                info!("Player was hit by a projectile!");
-            } else if is_enemy {
-                commands.entity(event.victim).despawn();
-                enemy_died_events.write(EnemyDied(pos));
-            }
-        }
+fn play_bounce_sounds(
+    mut commands: Commands,
+    mut bounce_events: EventReader<ProjectileBounced>,
+    game_assets: Res<GameAssets>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    for _ in bounce_events.read() {
+        audio::play_varied(
+            &mut commands,
+            audio::SfxCategory::Bounce,
+            &game_assets.bounce_sfx,
+            BOUNCE_SFX_VOLUME,
+            audio::PITCH_VARIATION,
+            &mut rng,
+        );
     }
 }
 
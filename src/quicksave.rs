@@ -0,0 +1,683 @@
+// quicksave.rs
+
+//! Quick save and load of a round in progress.
+//!
+//! Page Up writes a snapshot of the round to `quicksave.ron`; Page Down, or CONTINUE on the
+//! title screen, resumes it. The snapshot is made of plain data structs rather than entities,
+//! converted both ways here. Anything caught between two tiles is saved mid-step, along with the
+//! tiles it has reserved. The round and run times, the difficulty and the gems still on the map
+//! are saved with it. Projectiles and explosions in flight aren't, nor how far along each
+//! spawner is towards its next enemy.
+//!
+//! Resuming goes through the usual round start. `PendingRestore` holds the snapshot while
+//! `OnEnter(Playing)` runs: the round number, difficulty and seed are put back before the map is
+//! generated, the normal enemy, spawner and gem placement is skipped, and once the player is
+//! spawned the saved map, player, spawners, gems, timer and enemies replace the generated ones.
+//!
+//! The file starts with a format version, checked before anything else is read, so a save
+//! from an older build is turned away with a message instead of being misread.
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_rand::prelude::{ForkableRng, GlobalEntropy, WyRand};
+use serde::{Deserialize, Serialize};
+
+use crate::arena::ArenaEntity;
+use crate::assets::GameAssets;
+use crate::atlas::GameAtlas;
+use crate::components::{CurrentRound, Dying, EnemyGroupSize, GameMode, GameState, Health};
+use crate::demo::in_demo;
+use crate::difficulty::{Difficulty, DifficultySetting};
+use crate::enemy::{spawn_enemies, Enemy, EnemyKind, EnemySpawner, TurnPreference};
+use crate::explosion::PlayerIsDead;
+use crate::gems::{reset_gem_count, GemCount};
+use crate::grid_movement::{GridMover, IntendedDirection};
+use crate::grid_reservation::GridReservations;
+use crate::map::{generate_map, MapData};
+use crate::pickup::{spawn_pickup, Pickup, PickupKind};
+use crate::platform_io::{config_exists, read_config};
+use crate::player::{center_view_on, spawn_player, Player, PlayerSpawn};
+use crate::round_timer::{start_round_timer, RoundTimer};
+use crate::score::Score;
+use crate::seed::{seed_round, MapSeed};
+use crate::settings::Settings;
+use crate::spawner::{spawn_spawner, Spawner, SPAWNER_HEALTH};
+use crate::tilemap::{MapOffset, TileOffset, ViewportTiles};
+use crate::toast::ShowToast;
+
+/// Where the quicksave is kept, relative to the working directory.
+pub const QUICKSAVE_PATH: &str = "save/quicksave.ron";
+
+/// Bumped whenever the snapshot format changes in a way older saves can't be read as.
+const QUICKSAVE_VERSION: u32 = 3;
+
+// Clear of the F keys, which the debug layers and tools use.
+const SAVE_KEY: KeyCode = KeyCode::PageUp;
+const LOAD_KEY: KeyCode = KeyCode::PageDown;
+
+const WALL_CHAR: char = '#';
+const FLOOR_CHAR: char = '.';
+const LAVA_CHAR: char = '~';
+
+pub struct QuickSavePlugin;
+
+impl Plugin for QuickSavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<LoadQuickSave>()
+            .insert_resource(QuickSaveExists(config_exists(QUICKSAVE_PATH)))
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    begin_restore.before(seed_round),
+                    (
+                        restore_map_and_player,
+                        restore_spawners,
+                        restore_gems,
+                        restore_round_timer,
+                        restore_enemies,
+                    )
+                        .chain()
+                        .after(generate_map)
+                        .after(spawn_player)
+                        .after(spawn_enemies)
+                        .after(reset_gem_count)
+                        .after(start_round_timer),
+                )
+                    .run_if(resource_exists::<PendingRestore>),
+            )
+            .add_systems(
+                Update,
+                (
+                    // The time attack clock isn't part of the snapshot, so saving would stop it.
+                    quicksave_on_key.run_if(
+                        in_state(GameState::Playing)
+                            .and(not(resource_equals(GameMode::TimeAttack))),
+                    ),
+                    request_load_on_key
+                        .run_if(in_state(GameState::Playing).or(in_state(GameState::Title))),
+                    load_quicksave.run_if(on_event::<LoadQuickSave>),
+                )
+                    .chain()
+                    .run_if(not(in_demo)),
+            );
+    }
+}
+
+/// Request to resume the quicksave, from the title screen or mid-round.
+#[derive(Event)]
+pub struct LoadQuickSave;
+
+/// Whether there is a quicksave to resume, for the title screen's CONTINUE entry.
+#[derive(Resource)]
+pub struct QuickSaveExists(pub bool);
+
+/// The snapshot being resumed, present from the load request until the round has been rebuilt.
+#[derive(Resource)]
+pub struct PendingRestore(RunSnapshot);
+
+/// Read first, so a save in another format is recognized before the rest is parsed.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Everything needed to put a round back as it was.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct RunSnapshot {
+    version: u32,
+    seed: u64,
+    round: u32,
+    enemy_group_size: u32,
+    mode: GameMode,
+    difficulty: Difficulty,
+    score: u64,
+    /// Seconds into the round, and into the run as a whole.
+    round_time: f32,
+    run_time: f32,
+    map: MapSnapshot,
+    player: PlayerSnapshot,
+    enemies: Vec<EnemySnapshot>,
+    /// Missing from saves made before spawners existed, which had none.
+    #[serde(default)]
+    spawners: Vec<SpawnerSnapshot>,
+    gems: GemsSnapshot,
+    reservations: Vec<ReservationSnapshot>,
+}
+
+/// The map as rows of `#` (wall), `.` (floor) and `~` (lava), top row first, so the file can be read.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct MapSnapshot {
+    width: u32,
+    height: u32,
+    rows: Vec<String>,
+}
+
+/// Positions are `(x, y)` tuples since glam's serde support isn't enabled.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PlayerSnapshot {
+    pos: (i32, i32),
+    step: StepSnapshot,
+    health: u32,
+    max_health: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct EnemySnapshot {
+    kind: EnemyKind,
+    pos: (i32, i32),
+    step: StepSnapshot,
+    /// The direction the AI was heading in.
+    heading: (i32, i32),
+}
+
+/// A step between two tiles: the direction of travel, `(0, 0)` when standing still, and how far
+/// along it is.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct StepSnapshot {
+    direction: (i32, i32),
+    progress: f32,
+}
+
+/// The gems not yet collected, by tile, and the round's tally so far.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct GemsSnapshot {
+    remaining: Vec<(i32, i32)>,
+    collected: u32,
+    total: u32,
+    bonus_awarded: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct SpawnerSnapshot {
+    pos: (i32, i32),
+    health: u32,
+}
+
+/// Who holds a reservation; enemies are referred to by their index in `RunSnapshot::enemies`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+enum ReservationOwner {
+    Player,
+    Enemy(usize),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct ReservationSnapshot {
+    cell: (i32, i32),
+    owner: ReservationOwner,
+}
+
+fn to_tuple(v: IVec2) -> (i32, i32) {
+    (v.x, v.y)
+}
+
+fn to_ivec2((x, y): (i32, i32)) -> IVec2 {
+    IVec2::new(x, y)
+}
+
+impl StepSnapshot {
+    fn from_mover(mover: &GridMover) -> Self {
+        StepSnapshot {
+            direction: to_tuple(mover.direction),
+            progress: mover.progress,
+        }
+    }
+
+    fn apply(&self, mover: &mut GridMover) {
+        mover.direction = to_ivec2(self.direction);
+        mover.progress = self.progress;
+    }
+}
+
+impl MapSnapshot {
+    fn from_map(map: &MapData) -> Self {
+        let rows = map
+            .is_wall
+            .chunks(map.width as usize)
+            .zip(map.is_lava.chunks(map.width as usize))
+            .map(|(walls, lava)| {
+                walls
+                    .iter()
+                    .zip(lava)
+                    .map(|(&wall, &lava)| match (wall, lava) {
+                        (true, _) => WALL_CHAR,
+                        (false, true) => LAVA_CHAR,
+                        (false, false) => FLOOR_CHAR,
+                    })
+                    .collect()
+            })
+            .collect();
+        MapSnapshot {
+            width: map.width,
+            height: map.height,
+            rows,
+        }
+    }
+
+    fn to_map(&self) -> Result<MapData, String> {
+        if self.rows.len() != self.height as usize {
+            return Err(format!(
+                "map has {} rows, expected {}",
+                self.rows.len(),
+                self.height
+            ));
+        }
+        let mut is_wall = Vec::with_capacity((self.width * self.height) as usize);
+        let mut is_lava = Vec::with_capacity(is_wall.capacity());
+        for (y, row) in self.rows.iter().enumerate() {
+            if row.chars().count() != self.width as usize {
+                return Err(format!("map row {} isn't {} tiles wide", y, self.width));
+            }
+            for c in row.chars() {
+                let (wall, lava) = match c {
+                    WALL_CHAR => (true, false),
+                    FLOOR_CHAR => (false, false),
+                    LAVA_CHAR => (false, true),
+                    _ => return Err(format!("unexpected {:?} in map row {}", c, y)),
+                };
+                is_wall.push(wall);
+                is_lava.push(lava);
+            }
+        }
+        Ok(MapData {
+            width: self.width,
+            height: self.height,
+            is_wall,
+            is_lava,
+        })
+    }
+}
+
+impl RunSnapshot {
+    /// Checks everything a restore relies on, so a damaged file is rejected before the current
+    /// round is thrown away.
+    fn validate(&self) -> Result<(), String> {
+        let map = self.map.to_map()?;
+        let on_floor = |pos: (i32, i32)| map.index(to_ivec2(pos)).is_some_and(|i| !map.is_wall[i]);
+        if !on_floor(self.player.pos) {
+            return Err("the player isn't on a floor tile".to_string());
+        }
+        if self.player.health == 0 || self.player.health > self.player.max_health {
+            return Err("the player's health is out of range".to_string());
+        }
+        if let Some(enemy) = self.enemies.iter().find(|e| !on_floor(e.pos)) {
+            return Err(format!("an enemy at {:?} isn't on a floor tile", enemy.pos));
+        }
+        let steps = std::iter::once((self.player.pos, self.player.step))
+            .chain(self.enemies.iter().map(|e| (e.pos, e.step)));
+        for (pos, step) in steps {
+            let direction = to_ivec2(step.direction);
+            let target = to_tuple(to_ivec2(pos) + direction);
+            if !(0.0..1.0).contains(&step.progress)
+                || (direction != IVec2::ZERO && !on_floor(target))
+            {
+                return Err(format!("the step from {:?} is out of range", pos));
+            }
+        }
+        if let Some(gem) = self.gems.remaining.iter().find(|&&pos| !on_floor(pos)) {
+            return Err(format!("a gem at {:?} isn't on a floor tile", gem));
+        }
+        if self.gems.collected > self.gems.total {
+            return Err("more gems were collected than placed".to_string());
+        }
+        if let Some(spawner) = self.spawners.iter().find(|s| !on_floor(s.pos)) {
+            return Err(format!(
+                "a spawner at {:?} isn't on a floor tile",
+                spawner.pos
+            ));
+        }
+        if self
+            .spawners
+            .iter()
+            .any(|s| s.health == 0 || s.health > SPAWNER_HEALTH)
+        {
+            return Err("a spawner's health is out of range".to_string());
+        }
+        for reservation in &self.reservations {
+            if let ReservationOwner::Enemy(i) = reservation.owner {
+                if i >= self.enemies.len() {
+                    return Err(format!("a reservation belongs to missing enemy {}", i));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads and checks the quicksave, describing what's wrong with it if it can't be resumed.
+fn read_quicksave() -> Result<RunSnapshot, String> {
+    let contents =
+        read_config(QUICKSAVE_PATH).ok_or_else(|| format!("couldn't read {}", QUICKSAVE_PATH))?;
+    let header: SaveHeader = ron::from_str(&contents)
+        .map_err(|err| format!("{} isn't a quicksave: {}", QUICKSAVE_PATH, err))?;
+    if header.version != QUICKSAVE_VERSION {
+        return Err(format!(
+            "the quicksave is from an incompatible version ({}, expected {})",
+            header.version, QUICKSAVE_VERSION
+        ));
+    }
+    let snapshot: RunSnapshot = ron::from_str(&contents)
+        .map_err(|err| format!("{} is damaged: {}", QUICKSAVE_PATH, err))?;
+    snapshot.validate()?;
+    Ok(snapshot)
+}
+
+/// The round-wide resources that go into a snapshot, alongside the entities.
+#[derive(SystemParam)]
+struct RunProgress<'w> {
+    seed: Res<'w, MapSeed>,
+    round: Res<'w, CurrentRound>,
+    group_size: Res<'w, EnemyGroupSize>,
+    mode: Res<'w, GameMode>,
+    difficulty: Res<'w, DifficultySetting>,
+    score: Res<'w, Score>,
+    timer: Res<'w, RoundTimer>,
+    gems: Res<'w, GemCount>,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn quicksave_on_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    map_data: Res<MapData>,
+    progress: RunProgress,
+    player: Query<(Entity, &GridMover, &Health), With<Player>>,
+    enemies: Query<
+        (
+            Entity,
+            &GridMover,
+            &IntendedDirection,
+            Option<&TurnPreference>,
+        ),
+        (With<Enemy>, Without<Dying>),
+    >,
+    spawners: Query<(&Spawner, &Health), Without<Dying>>,
+    pickups: Query<&Pickup>,
+    reservations: Res<GridReservations>,
+    player_dead: Option<Res<PlayerIsDead>>,
+    mut exists: ResMut<QuickSaveExists>,
+    mut toasts: EventWriter<ShowToast>,
+) {
+    if !keys.just_pressed(SAVE_KEY) {
+        return;
+    }
+    let Ok((player_entity, player_mover, health)) = player.single() else {
+        return;
+    };
+    if player_dead.is_some() {
+        toasts.write(ShowToast("CAN'T SAVE NOW".to_string()));
+        return;
+    }
+
+    let enemy_list: Vec<_> = enemies.iter().collect();
+    let enemy_index = |entity: Entity| enemy_list.iter().position(|(e, ..)| *e == entity);
+    let snapshot = RunSnapshot {
+        version: QUICKSAVE_VERSION,
+        seed: progress.seed.current,
+        round: progress.round.0,
+        enemy_group_size: progress.group_size.0,
+        mode: *progress.mode,
+        difficulty: progress.difficulty.0,
+        score: progress.score.0,
+        round_time: progress.timer.round,
+        run_time: progress.timer.total,
+        map: MapSnapshot::from_map(&map_data),
+        player: PlayerSnapshot {
+            pos: to_tuple(player_mover.grid_pos),
+            step: StepSnapshot::from_mover(player_mover),
+            health: health.current,
+            max_health: health.max,
+        },
+        enemies: enemy_list
+            .iter()
+            .map(|(_, mover, intended, preference)| EnemySnapshot {
+                kind: EnemyKind::of(*preference),
+                pos: to_tuple(mover.grid_pos),
+                step: StepSnapshot::from_mover(mover),
+                heading: to_tuple(if mover.direction != IVec2::ZERO {
+                    mover.direction
+                } else {
+                    intended.0
+                }),
+            })
+            .collect(),
+        spawners: spawners
+            .iter()
+            .map(|(spawner, health)| SpawnerSnapshot {
+                pos: to_tuple(spawner.tile),
+                health: health.current,
+            })
+            .collect(),
+        gems: GemsSnapshot {
+            // A gem drifting towards the player is saved on the tile it's nearest.
+            remaining: pickups
+                .iter()
+                .filter(|pickup| pickup.kind == PickupKind::Gem)
+                .map(|pickup| to_tuple(pickup.map_pos.round().as_ivec2()))
+                .collect(),
+            collected: progress.gems.collected,
+            total: progress.gems.total,
+            bonus_awarded: progress.gems.bonus_awarded,
+        },
+        reservations: reservations
+            .0
+            .iter()
+            .filter_map(|(&cell, &owner)| {
+                let owner = if owner == player_entity {
+                    ReservationOwner::Player
+                } else {
+                    ReservationOwner::Enemy(enemy_index(owner)?)
+                };
+                Some(ReservationSnapshot {
+                    cell: to_tuple(cell),
+                    owner,
+                })
+            })
+            .collect(),
+    };
+    crate::config::save_ron(QUICKSAVE_PATH, &snapshot);
+    exists.0 = config_exists(QUICKSAVE_PATH);
+    info!(
+        "Quicksaved round {} with {} enemies",
+        snapshot.round,
+        snapshot.enemies.len()
+    );
+    toasts.write(ShowToast("GAME SAVED".to_string()));
+}
+
+fn request_load_on_key(keys: Res<ButtonInput<KeyCode>>, mut events: EventWriter<LoadQuickSave>) {
+    if keys.just_pressed(LOAD_KEY) {
+        events.write(LoadQuickSave);
+    }
+}
+
+/// Reads the quicksave and starts resuming it. Mid-round, the current round is torn down
+/// through `Restarting` first.
+fn load_quicksave(
+    mut commands: Commands,
+    mut events: EventReader<LoadQuickSave>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut toasts: EventWriter<ShowToast>,
+) {
+    events.clear();
+    match read_quicksave() {
+        Ok(snapshot) => {
+            info!("Resuming quicksave at round {}", snapshot.round);
+            commands.insert_resource(PendingRestore(snapshot));
+            next_state.set(if *state.get() == GameState::Playing {
+                GameState::Restarting
+            } else {
+                GameState::Playing
+            });
+        }
+        Err(err) => {
+            warn!("Can't resume the quicksave: {}", err);
+            toasts.write(ShowToast("CAN'T LOAD SAVE".to_string()));
+        }
+    }
+}
+
+/// Puts back the run's progress before the map is generated from its seed.
+///
+/// The saved difficulty becomes the selected one, so the rest of the run is played at it.
+#[allow(clippy::too_many_arguments)]
+fn begin_restore(
+    pending: Res<PendingRestore>,
+    mut seed: ResMut<MapSeed>,
+    mut round: ResMut<CurrentRound>,
+    mut group_size: ResMut<EnemyGroupSize>,
+    mut mode: ResMut<GameMode>,
+    mut score: ResMut<Score>,
+    mut difficulty: ResMut<DifficultySetting>,
+    mut settings: ResMut<Settings>,
+) {
+    let snapshot = &pending.0;
+    round.0 = snapshot.round;
+    group_size.0 = snapshot.enemy_group_size;
+    *mode = snapshot.mode;
+    score.0 = snapshot.score;
+    difficulty.0 = snapshot.difficulty;
+    if settings.difficulty != snapshot.difficulty {
+        settings.difficulty = snapshot.difficulty;
+    }
+    seed.current = snapshot.seed;
+    // The seed is only picked on a run's first round; later rounds derive from `current`.
+    if snapshot.round == 1 {
+        seed.next = Some(snapshot.seed);
+    }
+}
+
+/// Replaces the generated map with the saved one, which may have changed since it was
+/// generated, and puts the player back where they were.
+#[allow(clippy::too_many_arguments)]
+fn restore_map_and_player(
+    pending: Res<PendingRestore>,
+    mut map_data: ResMut<MapData>,
+    mut player: Query<(Entity, &mut GridMover, &mut IntendedDirection, &mut Health), With<Player>>,
+    mut reservations: ResMut<GridReservations>,
+    viewport: Res<ViewportTiles>,
+    mut map_offset: ResMut<MapOffset>,
+    mut tile_offset: ResMut<TileOffset>,
+    mut spawn: ResMut<PlayerSpawn>,
+) {
+    let snapshot = &pending.0;
+    // Checked when the file was read.
+    if let Ok(map) = snapshot.map.to_map() {
+        *map_data = map;
+    }
+    let Ok((entity, mut mover, mut intended, mut health)) = player.single_mut() else {
+        return;
+    };
+    let pos = to_ivec2(snapshot.player.pos);
+    // The saved reservations are put back once the enemies they refer to exist.
+    reservations.0.retain(|_, owner| *owner != entity);
+    mover.grid_pos = pos;
+    snapshot.player.step.apply(&mut mover);
+    intended.0 = IVec2::ZERO;
+    *health = Health {
+        current: snapshot.player.health,
+        max: snapshot.player.max_health,
+    };
+    center_view_on(pos, &viewport, &map_data, &mut map_offset, &mut tile_offset);
+    // The original spawn point isn't saved, so the round counts as starting here.
+    spawn.0 = pos;
+}
+
+/// Spawns the saved spawners, reserving their tiles.
+fn restore_spawners(
+    mut commands: Commands,
+    pending: Res<PendingRestore>,
+    game_assets: Res<GameAssets>,
+    atlas: Option<Res<GameAtlas>>,
+    mut reservations: ResMut<GridReservations>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    for spawner in &pending.0.spawners {
+        spawn_spawner(
+            &mut commands,
+            &game_assets,
+            atlas.as_deref(),
+            &mut reservations,
+            to_ivec2(spawner.pos),
+            Health {
+                current: spawner.health,
+                max: SPAWNER_HEALTH,
+            },
+            rng.fork_rng(),
+        );
+    }
+}
+
+/// Spawns the gems that were still on the map, and puts back the round's tally.
+fn restore_gems(
+    mut commands: Commands,
+    pending: Res<PendingRestore>,
+    game_assets: Res<GameAssets>,
+    atlas: Option<Res<GameAtlas>>,
+    mut count: ResMut<GemCount>,
+) {
+    let gems = &pending.0.gems;
+    for &pos in &gems.remaining {
+        let gem = spawn_pickup(
+            &mut commands,
+            &game_assets,
+            atlas.as_deref(),
+            PickupKind::Gem,
+            to_ivec2(pos),
+        );
+        commands.entity(gem).insert(ArenaEntity);
+    }
+    *count = GemCount {
+        collected: gems.collected,
+        total: gems.total,
+        bonus_awarded: gems.bonus_awarded,
+    };
+}
+
+fn restore_round_timer(pending: Res<PendingRestore>, mut timer: ResMut<RoundTimer>) {
+    timer.round = pending.0.round_time;
+    timer.total = pending.0.run_time;
+}
+
+/// Spawns the saved enemies mid-step, then hands the saved reservations back to the player and
+/// the enemies in place of the ones made while spawning. Their owners were checked against the
+/// enemy list when the file was read.
+fn restore_enemies(
+    mut commands: Commands,
+    pending: Res<PendingRestore>,
+    player: Query<Entity, With<Player>>,
+    mut spawner: EnemySpawner,
+) {
+    let enemies: Vec<Entity> = pending
+        .0
+        .enemies
+        .iter()
+        .map(|enemy| {
+            let entity =
+                spawner.spawn_facing(enemy.kind, to_ivec2(enemy.pos), to_ivec2(enemy.heading));
+            let step = enemy.step;
+            commands
+                .entity(entity)
+                .entry::<GridMover>()
+                .and_modify(move |mut mover| step.apply(&mut mover));
+            entity
+        })
+        .collect();
+    let Ok(player) = player.single() else {
+        return;
+    };
+    let reservations = &mut spawner.reservations_mut().0;
+    reservations.retain(|_, owner| *owner != player && !enemies.contains(owner));
+    for reservation in &pending.0.reservations {
+        let owner = match reservation.owner {
+            ReservationOwner::Player => player,
+            ReservationOwner::Enemy(i) => enemies[i],
+        };
+        reservations.insert(to_ivec2(reservation.cell), owner);
+    }
+    info!(
+        "Restored round {} with {} enemies",
+        pending.0.round,
+        pending.0.enemies.len()
+    );
+    commands.remove_resource::<PendingRestore>();
+}
@@ -0,0 +1,189 @@
+// radar.rs
+
+//! Arrows on the inside edge of the play area pointing at the nearest off-screen enemies, so
+//! the last few stragglers of a round are easy to find.
+//!
+//! A fixed pool of arrows is spawned with each round and reused every frame; arrows without an
+//! enemy to point at are hidden. Each arrow takes its enemy's colour and fades with distance.
+//! Under fog of war only enemies in sight are pointed at, which off-screen ones never are.
+
+use bevy::prelude::*;
+use bevy::sprite::AlphaMode2d;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{GameEntity, GameState};
+use crate::enemy::Enemy;
+use crate::fog::FogView;
+use crate::grid_movement::{GridMover, MovementSystems};
+use crate::map::MapData;
+use crate::player::Player;
+use crate::score::EnemyCount;
+use crate::tilemap::{GridSpace, TILE_SIZE};
+
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RadarMode::default())
+            .add_systems(OnEnter(GameState::Playing), spawn_radar_arrows)
+            .add_systems(
+                Update,
+                update_radar_arrows
+                    .after(MovementSystems::ApplyOffsetChanges)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// When the radar arrows are shown, pushed from `Settings`.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RadarMode {
+    Off,
+    /// Only once a round is down to its last `AUTO_RADAR_ENEMIES` enemies.
+    #[default]
+    Auto,
+    Always,
+}
+
+impl RadarMode {
+    pub fn label(self) -> &'static str {
+        match self {
+            RadarMode::Off => "OFF",
+            RadarMode::Auto => "AUTO",
+            RadarMode::Always => "ON",
+        }
+    }
+
+    /// The next mode in the given direction, wrapping around.
+    pub fn cycled(self, step: i32) -> Self {
+        const ORDER: [RadarMode; 3] = [RadarMode::Off, RadarMode::Auto, RadarMode::Always];
+        let index = ORDER.iter().position(|&mode| mode == self).unwrap_or(0) as i32;
+        ORDER[(index + step).rem_euclid(ORDER.len() as i32) as usize]
+    }
+}
+
+/// In `Auto` mode, the radar comes on once this many enemies or fewer are left.
+const AUTO_RADAR_ENEMIES: u32 = 10;
+
+/// The most enemies pointed at at once.
+const MAX_ARROWS: usize = 5;
+
+/// Distance of the arrows from the edge of the play area, in pixels.
+const ARROW_INSET: f32 = TILE_SIZE;
+
+const ARROW_LENGTH: f32 = TILE_SIZE * 0.6;
+
+/// Enemies this many tiles from the player or closer get a fully opaque arrow.
+const NEAR_DISTANCE: f32 = 20.0;
+
+/// Enemies this many tiles away or further get the faintest arrow.
+const FAR_DISTANCE: f32 = 60.0;
+
+const MIN_ARROW_ALPHA: f32 = 0.3;
+
+/// One arrow in the pool, with its own material so it can be tinted and faded on its own.
+#[derive(Component)]
+struct RadarArrow(Handle<ColorMaterial>);
+
+fn spawn_radar_arrows(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // Points along +x, so an arrow's rotation is just the angle to its enemy.
+    let mesh = meshes.add(Triangle2d::new(
+        Vec2::new(ARROW_LENGTH / 2.0, 0.0),
+        Vec2::new(-ARROW_LENGTH / 2.0, ARROW_LENGTH / 3.0),
+        Vec2::new(-ARROW_LENGTH / 2.0, -ARROW_LENGTH / 3.0),
+    ));
+    for _ in 0..MAX_ARROWS {
+        let material = materials.add(ColorMaterial {
+            color: Color::WHITE,
+            alpha_mode: AlphaMode2d::Blend,
+            ..default()
+        });
+        commands.spawn((
+            Mesh2d(mesh.clone()),
+            MeshMaterial2d(material.clone()),
+            Transform::from_xyz(0.0, 0.0, 2.5),
+            Visibility::Hidden,
+            RadarArrow(material),
+            GameEntity,
+        ));
+    }
+}
+
+/// Points the arrow pool at the nearest enemies outside the view.
+#[allow(clippy::too_many_arguments)]
+fn update_radar_arrows(
+    mode: Res<RadarMode>,
+    fog: FogView,
+    map_data: Res<MapData>,
+    enemy_count: Res<EnemyCount>,
+    grid_space: GridSpace,
+    player: Query<&GridMover, With<Player>>,
+    enemies: Query<(&GridMover, &Sprite), With<Enemy>>,
+    mut arrows: Query<(&RadarArrow, &mut Transform, &mut Visibility)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let enabled = match *mode {
+        RadarMode::Off => false,
+        RadarMode::Auto => enemy_count.value <= AUTO_RADAR_ENEMIES,
+        RadarMode::Always => true,
+    };
+    let targets = match player.single() {
+        Ok(player) if enabled => {
+            let view = grid_space.viewport_rect_in_map();
+            nearest_offscreen(player.grid_pos, view, &enemies, &fog, &map_data)
+        }
+        _ => Vec::new(),
+    };
+
+    let half_extents = grid_space.viewport.half_world_size() - Vec2::splat(ARROW_INSET);
+    let mut targets = targets.into_iter();
+    for (arrow, mut transform, mut visibility) in &mut arrows {
+        let Some((grid_pos, distance, color)) = targets.next() else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let direction = grid_space
+            .tile_to_world(grid_pos)
+            .try_normalize()
+            .unwrap_or(Vec2::X);
+        // Walks out from the centre of the view to the inset edge along the arrow's direction.
+        let reach = (half_extents / direction.abs().max(Vec2::splat(f32::EPSILON))).min_element();
+        let position = direction * reach;
+        transform.translation = position.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(direction.to_angle());
+
+        let closeness = 1.0 - (distance - NEAR_DISTANCE) / (FAR_DISTANCE - NEAR_DISTANCE);
+        let alpha = closeness.clamp(MIN_ARROW_ALPHA, 1.0);
+        if let Some(material) = materials.get_mut(&arrow.0) {
+            material.color = color.with_alpha(alpha);
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+/// Up to `MAX_ARROWS` enemies outside the view, nearest to the player first, with their
+/// distance in tiles and colour. `view` is the map tiles in view.
+fn nearest_offscreen(
+    player_pos: IVec2,
+    view: IRect,
+    enemies: &Query<(&GridMover, &Sprite), With<Enemy>>,
+    fog: &FogView,
+    map_data: &MapData,
+) -> Vec<(IVec2, f32, Color)> {
+    let mut offscreen: Vec<(IVec2, f32, Color)> = enemies
+        .iter()
+        .filter(|(mover, _)| !view.contains(mover.grid_pos))
+        .filter(|(mover, _)| fog.is_visible(map_data, mover.grid_pos))
+        .map(|(mover, sprite)| {
+            let distance = (mover.grid_pos - player_pos).as_vec2().length();
+            (mover.grid_pos, distance, sprite.color)
+        })
+        .collect();
+    offscreen.sort_by(|a, b| a.1.total_cmp(&b.1));
+    offscreen.truncate(MAX_ARROWS);
+    offscreen
+}
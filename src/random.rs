@@ -1,22 +1,26 @@
 // src/random.rs
 
 use bevy::prelude::*;
-use bevy_rand::prelude::{EntropyPlugin, GlobalEntropy, WyRand};
+use bevy_rand::prelude::{Entropy, EntropyPlugin, WyRand};
 
 use crate::assets::GameAssets;
+use crate::platform_io::since_epoch;
 use rand_core::RngCore;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::ops::Range;
 
-/// Plugin for handling random number generation with WyRand
+/// Plugin for handling random number generation with WyRand.
+///
+/// Short-lived, one-off randomness (explosion scatter, sparks) draws from the global
+/// generator. Long-lived entities such as enemies and spawners carry their own
+/// `Entropy<WyRand>`, forked from the global one when they spawn, so what they roll doesn't
+/// depend on query order. The helpers below take an `&mut Entropy<WyRand>`, which a
+/// `GlobalEntropy` derefs to.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct RandomPlugin;
 
 impl Plugin for RandomPlugin {
     fn build(&self, app: &mut App) {
-        let seed = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .expect("Time went backwards")
-            .as_nanos() as u64;
+        let seed = since_epoch().as_nanos() as u64;
 
         // The `with_seed` function expects a byte array.
         // We convert the u64 seed to a little-endian byte array.
@@ -24,49 +28,362 @@ impl Plugin for RandomPlugin {
     }
 }
 
-/// Returns a random float between 0.0 and 1.0
-pub fn random_float(rng: &mut GlobalEntropy<WyRand>) -> f32 {
-    (rng.next_u32() as f32) / (u32::MAX as f32)
+/// Returns a random float in `[0.0, 1.0)`. Never returns 1.0, so it's safe to scale and
+/// truncate, though `random_index` and `random_range` are the better tools for that.
+pub fn random_float(rng: &mut Entropy<WyRand>) -> f32 {
+    unit_float(rng.next_u32())
+}
+
+/// Maps random bits onto `[0.0, 1.0)`. Only the top 24 bits are used, since that's all an
+/// `f32` can hold without rounding up to 1.0.
+fn unit_float(bits: u32) -> f32 {
+    (bits >> 8) as f32 / (1u32 << 24) as f32
+}
+
+/// Returns a random index in `0..len`, or 0 if `len` is 0.
+pub fn random_index(rng: &mut Entropy<WyRand>, len: usize) -> usize {
+    scale_index(rng.next_u32(), len)
+}
+
+/// Scales random bits onto `0..len` with a widening multiply: the result is always below
+/// `len`, and no draws are thrown away.
+fn scale_index(bits: u32, len: usize) -> usize {
+    ((bits as u64 * len as u64) >> 32) as usize
+}
+
+/// Returns a random integer in `range`, or `range.start` if the range is empty.
+pub fn random_range(rng: &mut Entropy<WyRand>, range: Range<i32>) -> i32 {
+    if range.is_empty() {
+        return range.start;
+    }
+    let span = (range.end as i64 - range.start as i64) as usize;
+    (range.start as i64 + random_index(rng, span) as i64) as i32
+}
+
+/// Returns a random element of `items`. Panics if `items` is empty.
+pub fn random_pick<'a, T>(rng: &mut Entropy<WyRand>, items: &'a [T]) -> &'a T {
+    &items[random_index(rng, items.len())]
+}
+
+/// Returns true with the given probability; 0.0 is never and 1.0 is always.
+pub fn random_bool(rng: &mut Entropy<WyRand>, probability: f32) -> bool {
+    random_float(rng) < probability
+}
+
+/// Shuffles `items` in place with a Fisher–Yates shuffle, so every order is equally likely.
+pub fn shuffle<T>(rng: &mut Entropy<WyRand>, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, random_index(rng, i + 1));
+    }
+}
+
+/// Picks `k` items from `items` without replacement in a single pass (reservoir sampling), so
+/// the candidates don't have to be collected first. Returns every item if there are fewer than
+/// `k`. The picks come back in no particular order.
+pub fn sample_k<T>(
+    rng: &mut Entropy<WyRand>,
+    items: impl IntoIterator<Item = T>,
+    k: usize,
+) -> Vec<T> {
+    let mut reservoir = Vec::with_capacity(k);
+    if k == 0 {
+        return reservoir;
+    }
+    for (seen, item) in items.into_iter().enumerate() {
+        if seen < k {
+            reservoir.push(item);
+        } else {
+            let slot = random_index(rng, seen + 1);
+            if slot < k {
+                reservoir[slot] = item;
+            }
+        }
+    }
+    reservoir
 }
 
 /// Returns a random color from the GameAssets palette
-pub fn random_colour(rng: &mut GlobalEntropy<WyRand>, game_assets: &Res<GameAssets>) -> Color {
-    let palette = &game_assets.palette;
-    let index = (random_float(rng) * palette.colors.len() as f32) as usize;
-    palette.colors[index]
+pub fn random_colour(rng: &mut Entropy<WyRand>, game_assets: &Res<GameAssets>) -> Color {
+    *random_pick(rng, &game_assets.palette.colors)
+}
+
+/// A set of options picked at random according to their weights, for spawn and drop tables.
+///
+/// `pick` goes through an alias table built along with the table, so it takes constant time
+/// however many entries there are, for tables sampled thousands of times such as enemy drops.
+#[derive(Clone, Debug)]
+pub struct WeightedTable<T> {
+    items: Vec<T>,
+    weights: Vec<f32>,
+    alias: AliasTable,
 }
 
-/// Returns a random color from the GameAssets palette, excluding the specified color.
-/// If the palette is empty or only contains the excluded color, returns Color::WHITE.
-pub fn random_colour_except(
-    rng: &mut GlobalEntropy<WyRand>,
-    game_assets: &Res<GameAssets>,
-    except_colour: Color,
-) -> Color {
-    let palette = &game_assets.palette;
+/// Vose's alias method: each slot holds its own item with probability `keep[i]`, and
+/// otherwise hands over to `alias[i]`.
+#[derive(Clone, Debug)]
+struct AliasTable {
+    keep: Vec<f32>,
+    alias: Vec<usize>,
+}
 
-    // Handle empty palette
-    if palette.colors.is_empty() {
-        return Color::WHITE;
+impl AliasTable {
+    /// Splits `weights` into equal slots, topping up each light entry's slot with a share of a
+    /// heavy one.
+    fn new(weights: &[f32]) -> Self {
+        let n = weights.len();
+        let total: f32 = weights.iter().sum();
+        let mut scaled: Vec<f32> = weights.iter().map(|w| w * n as f32 / total).collect();
+        let mut keep = vec![1.0; n];
+        let mut alias: Vec<usize> = (0..n).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|&i| scaled[i] < 1.0);
+        while let (Some(s), Some(&l)) = (small.pop(), large.last()) {
+            keep[s] = scaled[s];
+            alias[s] = l;
+            scaled[l] += scaled[s] - 1.0;
+            if scaled[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        // Whatever is left over is 1.0 up to rounding error, and keeps its own slot.
+        AliasTable { keep, alias }
     }
+}
 
-    // Find the index of the excluded color, if it exists
-    let exclude_index = palette.colors.iter().position(|&c| c == except_colour);
+impl<T> WeightedTable<T> {
+    /// Builds a table from `(item, weight)` pairs. Fails if there are no entries or a weight
+    /// isn't a positive, finite number.
+    pub fn new(entries: impl IntoIterator<Item = (T, f32)>) -> Result<Self, String> {
+        let (items, weights): (Vec<T>, Vec<f32>) = entries.into_iter().unzip();
+        if items.is_empty() {
+            return Err("weighted table has no entries".to_string());
+        }
+        if let Some(i) = weights.iter().position(|w| !w.is_finite() || *w <= 0.0) {
+            return Err(format!("entry {} has invalid weight {}", i, weights[i]));
+        }
+        let alias = AliasTable::new(&weights);
+        Ok(WeightedTable {
+            items,
+            weights,
+            alias,
+        })
+    }
 
-    // If palette has only one color and it's the excluded one, return fallback
-    if palette.colors.len() == 1 && exclude_index == Some(0) {
-        return Color::WHITE;
+    /// Picks one item, with probability proportional to its weight.
+    pub fn pick(&self, rng: &mut Entropy<WyRand>) -> &T {
+        let slot = random_index(rng, self.items.len());
+        let index = if random_float(rng) < self.alias.keep[slot] {
+            slot
+        } else {
+            self.alias.alias[slot]
+        };
+        &self.items[index]
     }
 
-    // Calculate the range for random selection (subtract 1 if excluding a color)
-    let range = palette.colors.len() - exclude_index.map_or(0, |_| 1);
-    let idx = (random_float(rng) * range as f32) as usize;
+    /// Picks up to `n` distinct items without replacement, each draw weighted among the items
+    /// not yet picked.
+    pub fn pick_unique_n(&self, rng: &mut Entropy<WyRand>, n: usize) -> Vec<&T> {
+        let mut remaining = self.weights.clone();
+        let mut picked = Vec::with_capacity(n.min(self.items.len()));
+        for _ in 0..n.min(self.items.len()) {
+            let total: f32 = remaining.iter().sum();
+            let mut target = random_float(rng) * total;
+            // Fall back to the last unpicked item if rounding carries `target` past the end.
+            let mut index = remaining.iter().rposition(|&w| w > 0.0).unwrap();
+            for (i, &weight) in remaining.iter().enumerate() {
+                if weight > 0.0 && target < weight {
+                    index = i;
+                    break;
+                }
+                target -= weight;
+            }
+            remaining[index] = 0.0;
+            picked.push(&self.items[index]);
+        }
+        picked
+    }
+}
 
-    // Adjust index to skip the excluded color
-    let final_index = match exclude_index {
-        Some(ex) if idx >= ex => idx + 1,
-        _ => idx,
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_rand::prelude::ForkableRng;
+    use rand_core::SeedableRng;
 
-    palette.colors[final_index]
+    fn rng() -> Entropy<WyRand> {
+        Entropy::<WyRand>::seed_from_u64(0x5EED)
+    }
+
+    #[test]
+    fn unit_float_stays_below_one() {
+        assert_eq!(unit_float(0), 0.0);
+        assert!(unit_float(u32::MAX) < 1.0);
+        assert!(unit_float(u32::MAX / 2) < 0.5);
+    }
+
+    #[test]
+    fn scale_index_covers_the_range_and_stays_below_len() {
+        for len in [1, 2, 3, 7, 100] {
+            assert_eq!(scale_index(0, len), 0);
+            assert_eq!(scale_index(u32::MAX, len), len - 1);
+        }
+        assert_eq!(scale_index(u32::MAX / 2, 2), 0);
+        assert_eq!(scale_index(u32::MAX / 2 + 1, 2), 1);
+        assert_eq!(scale_index(u32::MAX, 0), 0);
+    }
+
+    #[test]
+    fn random_range_hits_both_ends_and_nothing_outside() {
+        let mut rng = rng();
+        let mut seen = [false; 5];
+        for _ in 0..1000 {
+            let value = random_range(&mut rng, -2..3);
+            assert!((-2..3).contains(&value));
+            seen[(value + 2) as usize] = true;
+        }
+        assert!(seen.iter().all(|&s| s));
+        // Empty ranges, including a backwards one.
+        for end in [4, 1] {
+            assert_eq!(random_range(&mut rng, 4..end), 4);
+        }
+        let wide = random_range(&mut rng, i32::MIN..i32::MAX);
+        assert!(wide < i32::MAX);
+    }
+
+    #[test]
+    fn random_pick_and_bool() {
+        let mut rng = rng();
+        let items = ['a', 'b', 'c'];
+        for _ in 0..100 {
+            assert!(items.contains(random_pick(&mut rng, &items)));
+            assert!(!random_bool(&mut rng, 0.0));
+            assert!(random_bool(&mut rng, 1.0));
+        }
+    }
+
+    #[test]
+    fn weighted_table_rejects_bad_weights() {
+        assert!(WeightedTable::<u8>::new([]).is_err());
+        assert!(WeightedTable::new([('a', 1.0), ('b', 0.0)]).is_err());
+        assert!(WeightedTable::new([('a', -1.0)]).is_err());
+        assert!(WeightedTable::new([('a', f32::NAN)]).is_err());
+        assert!(WeightedTable::new([('a', f32::INFINITY)]).is_err());
+        assert!(WeightedTable::new([('a', 0.1)]).is_ok());
+    }
+
+    #[test]
+    fn weighted_pick_follows_the_weights() {
+        // Uneven enough that the alias table has to split most of the slots.
+        let weights = [1.0, 3.0, 0.5, 5.5];
+        let table = WeightedTable::new((0..weights.len()).zip(weights)).unwrap();
+        let mut rng = rng();
+        let draws = 100_000;
+        let mut counts = [0u32; 4];
+        for _ in 0..draws {
+            counts[*table.pick(&mut rng)] += 1;
+        }
+        // Chi-square against the expected counts, with 3 degrees of freedom; 16.3 is the
+        // 0.1% critical value.
+        let total: f32 = weights.iter().sum();
+        let chi_square: f32 = counts
+            .iter()
+            .zip(weights)
+            .map(|(&count, weight)| {
+                let expected = draws as f32 * weight / total;
+                (count as f32 - expected).powi(2) / expected
+            })
+            .sum();
+        assert!(
+            chi_square < 16.3,
+            "counts {:?} give chi-square {}",
+            counts,
+            chi_square
+        );
+    }
+
+    #[test]
+    fn pick_unique_n_never_repeats() {
+        let table = WeightedTable::new([('a', 1.0), ('b', 100.0), ('c', 1.0)]).unwrap();
+        let mut rng = rng();
+        for _ in 0..100 {
+            let mut picked = table.pick_unique_n(&mut rng, 2);
+            assert_eq!(picked.len(), 2);
+            picked.dedup();
+            assert_eq!(picked.len(), 2);
+        }
+        let mut all = table.pick_unique_n(&mut rng, 5);
+        all.sort();
+        assert_eq!(all, [&'a', &'b', &'c']);
+    }
+
+    #[test]
+    fn forked_generators_dont_depend_on_draw_order() {
+        let draws =
+            |rng: &mut Entropy<WyRand>| -> Vec<u32> { (0..4).map(|_| rng.next_u32()).collect() };
+
+        let mut global = Entropy::<WyRand>::seed_from_u64(7);
+        let (mut a, mut b) = (global.fork_rng(), global.fork_rng());
+        let a_first = draws(&mut a);
+        let b_second = draws(&mut b);
+
+        let mut global = Entropy::<WyRand>::seed_from_u64(7);
+        let (mut a, mut b) = (global.fork_rng(), global.fork_rng());
+        let b_first = draws(&mut b);
+        let a_second = draws(&mut a);
+
+        assert_eq!(a_first, a_second);
+        assert_eq!(b_first, b_second);
+        assert_ne!(a_first, b_first);
+    }
+
+    #[test]
+    fn shuffle_reorders_without_losing_anything() {
+        let mut rng = rng();
+        let original: Vec<u32> = (0..20).collect();
+        let mut items = original.clone();
+        shuffle(&mut rng, &mut items);
+        assert_ne!(items, original);
+        items.sort();
+        assert_eq!(items, original);
+
+        let mut empty: [u32; 0] = [];
+        shuffle(&mut rng, &mut empty);
+    }
+
+    #[test]
+    fn sample_k_picks_distinct_items() {
+        let mut rng = rng();
+        let mut picked = sample_k(&mut rng, 0..100, 10);
+        assert_eq!(picked.len(), 10);
+        picked.sort();
+        picked.dedup();
+        assert_eq!(picked.len(), 10);
+        assert!(picked.iter().all(|&item| item < 100));
+
+        let mut few = sample_k(&mut rng, 0..3, 10);
+        few.sort();
+        assert_eq!(few, [0, 1, 2]);
+        assert!(sample_k(&mut rng, 0..3, 0).is_empty());
+    }
+
+    #[test]
+    fn sample_k_is_even_across_the_input() {
+        let mut rng = rng();
+        let mut counts = [0u32; 10];
+        for _ in 0..5000 {
+            for item in sample_k(&mut rng, 0..10, 3) {
+                counts[item] += 1;
+            }
+        }
+        // Each item should be picked 3 times in 10, or 1500 times.
+        for (item, &count) in counts.iter().enumerate() {
+            assert!(
+                (1350..1650).contains(&count),
+                "item {} was picked {} times",
+                item,
+                count
+            );
+        }
+    }
 }
@@ -1,13 +1,23 @@
+use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 use bevy::window::{PrimaryWindow, WindowResized};
 
+use crate::components::GameState;
+use crate::input::InputMap;
+use crate::settings::Settings;
+
 pub struct ResolutionPlugin;
 
 impl Plugin for ResolutionPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(PreStartup, setup_resolution).add_systems(
             Update,
-            (handle_window_resize, update_camera_projection).chain(),
+            (
+                handle_zoom_input.run_if(in_state(GameState::Playing)),
+                handle_window_resize,
+                update_camera_projection,
+            )
+                .chain(),
         );
     }
 }
@@ -15,6 +25,10 @@ impl Plugin for ResolutionPlugin {
 // Increasing this value will result in the projection zooming out, showing more of the render area
 const MASTER_SCALE: f32 = 4.0;
 
+/// Time constant (in seconds) for easing the projection towards a new zoom; it settles in
+/// about 0.2s.
+const ZOOM_TAU: f32 = 0.05;
+
 #[derive(Resource)]
 pub struct Resolution {
     // Pixel dimensions of the screen (width, height)
@@ -27,6 +41,22 @@ pub struct Resolution {
     pub zoom: f32,
 }
 
+impl Resolution {
+    /// The orthographic scale the camera settles on for this window size and zoom.
+    pub fn projection_scale(&self) -> f32 {
+        let scale_x = self.screen_dimensions.x / self.base_resolution.x;
+        let scale_y = self.screen_dimensions.y / self.base_resolution.y;
+        // Use the smaller scale to maintain aspect ratio and avoid stretching
+        let scale = scale_x.min(scale_y) * self.pixel_ratio;
+        (MASTER_SCALE * self.zoom) * 1.0 / scale
+    }
+
+    /// How much of the world the camera shows once it has settled, in world units.
+    pub fn visible_world_size(&self) -> Vec2 {
+        self.screen_dimensions * self.projection_scale()
+    }
+}
+
 fn setup_resolution(mut commands: Commands, window_query: Query<&Window, With<PrimaryWindow>>) {
     if let Ok(window) = window_query.single() {
         let width = window.resolution.width();
@@ -34,7 +64,7 @@ fn setup_resolution(mut commands: Commands, window_query: Query<&Window, With<Pr
 
         commands.insert_resource(Resolution {
             screen_dimensions: Vec2::new(width, height),
-            pixel_ratio: window.scale_factor() as f32,
+            pixel_ratio: window.scale_factor(),
             base_resolution: Vec2::new(800.0, 600.0),
             zoom: 1.0,
         });
@@ -62,27 +92,63 @@ fn handle_window_resize(
             // Compare the event's entity with the primary window's entity
             if event.window == primary_window_entity {
                 resolution.screen_dimensions = Vec2::new(event.width, event.height);
-                resolution.pixel_ratio = primary_window.scale_factor() as f32;
+                resolution.pixel_ratio = primary_window.scale_factor();
                 info!("Window resized to {}x{}", event.width, event.height);
             }
         }
     }
 }
 
+/// Steps the zoom with the zoom keys or the mouse wheel. The change goes through `Settings`, so
+/// it's remembered and reaches `Resolution::zoom` from there.
+fn handle_zoom_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut wheel: EventReader<MouseWheel>,
+    mut settings: ResMut<Settings>,
+) {
+    let mut step = 0;
+    if keys.just_pressed(input_map.zoom_out) {
+        step += 1;
+    }
+    if keys.just_pressed(input_map.zoom_in) {
+        step -= 1;
+    }
+    for event in wheel.read() {
+        // Scrolling up zooms in.
+        step -= event.y.signum() as i32;
+    }
+    if step != 0 {
+        let mut next = settings.clone();
+        next.step_zoom(step);
+        settings.set_if_neq(next);
+    }
+}
+
+/// Eases the camera projection towards the scale for the current window size and zoom. Window
+/// resizes snap straight to the new scale.
 fn update_camera_projection(
     resolution: Res<Resolution>,
+    time: Res<Time<Real>>,
+    mut last_dimensions: Local<Vec2>,
     mut query: Query<&mut Projection, With<Camera2d>>,
 ) {
-    if resolution.is_changed() {
-        for mut projection in query.iter_mut() {
-            if let Projection::Orthographic(ref mut ortho) = &mut *projection {
-                let scale_x = resolution.screen_dimensions.x / resolution.base_resolution.x;
-                let scale_y = resolution.screen_dimensions.y / resolution.base_resolution.y;
-                // Use the smaller scale to maintain aspect ratio and avoid stretching
-                let scale = scale_x.min(scale_y) * resolution.pixel_ratio;
+    let target = resolution.projection_scale();
 
-                ortho.scale = (MASTER_SCALE * resolution.zoom) * 1.0 / scale;
+    let resized = *last_dimensions != resolution.screen_dimensions;
+    *last_dimensions = resolution.screen_dimensions;
+    let blend = 1.0 - (-time.delta_secs() / ZOOM_TAU).exp();
+
+    for mut projection in query.iter_mut() {
+        if let Projection::Orthographic(ref mut ortho) = &mut *projection {
+            if ortho.scale == target {
+                continue;
+            }
+            if resized || (ortho.scale - target).abs() < target * 0.001 {
+                ortho.scale = target;
                 info!("Updated camera projection scale: {}", ortho.scale);
+            } else {
+                ortho.scale += (target - ortho.scale) * blend;
             }
         }
     }
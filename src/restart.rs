@@ -0,0 +1,131 @@
+// restart.rs
+
+//! Quick restart: tears the current run down and starts a fresh one without visiting the title.
+//!
+//! Restarting passes through `GameState::Restarting` for a moment rather than re-entering
+//! `Playing` directly, so every `OnExit` cleanup has run and every per-run resource has been
+//! reset before the `OnEnter(Playing)` systems generate the new map.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::{GameEntity, GameSpeed, GameState};
+use crate::demo::in_demo;
+use crate::explosion::PlayerIsDead;
+use crate::grid_reservation::GridReservations;
+use crate::input::InputMap;
+use crate::tilemap::{MapOffset, TileOffset};
+
+/// Seconds the "restarting..." message is shown before the new run starts.
+const RESTART_DELAY: f32 = 0.3;
+
+pub struct RestartPlugin;
+
+impl Plugin for RestartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Restarting),
+            (teardown_run, spawn_restart_text).chain(),
+        )
+        .add_systems(OnExit(GameState::Restarting), despawn_restart_text)
+        .add_systems(
+            Update,
+            (
+                handle_restart_input.run_if(in_state(GameState::Playing).and(not(in_demo))),
+                handle_restart_timer.run_if(in_state(GameState::Restarting)),
+            ),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct RestartTimer(Timer);
+
+#[derive(Component)]
+struct RestartText;
+
+fn handle_restart_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.just_pressed(input_map.restart) {
+        info!("Restarting run");
+        next_state.set(GameState::Restarting);
+    }
+}
+
+/// Clears everything left over from the previous run. Per-run resources owned by other modules
+/// (score, round timer, round number) are reset by their own `OnEnter(Restarting)` systems.
+pub fn teardown_run(
+    mut commands: Commands,
+    query: Query<Entity, With<GameEntity>>,
+    mut reservations: ResMut<GridReservations>,
+    mut map_offset: ResMut<MapOffset>,
+    mut tile_offset: ResMut<TileOffset>,
+    mut game_speed: ResMut<GameSpeed>,
+) {
+    info!("Cleaning up game entities");
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    reservations.0.clear();
+    map_offset.0 = IVec2::ZERO;
+    tile_offset.0 = Vec2::ZERO;
+    game_speed.value = 1.0;
+    // A restart during the death sequence must not carry the death over to the new run.
+    commands.remove_resource::<PlayerIsDead>();
+}
+
+fn spawn_restart_text(mut commands: Commands, game_assets: Res<GameAssets>) {
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::BLACK),
+            RestartText,
+        ))
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new("restarting..."),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[4]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+    });
+
+    commands.insert_resource(RestartTimer(Timer::from_seconds(
+        RESTART_DELAY,
+        TimerMode::Once,
+    )));
+}
+
+fn despawn_restart_text(mut commands: Commands, query: Query<Entity, With<RestartText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<RestartTimer>();
+}
+
+fn handle_restart_timer(
+    mut timer: ResMut<RestartTimer>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    timer.0.tick(time.delta());
+    if timer.0.finished() {
+        next_state.set(GameState::Playing);
+    }
+}
@@ -0,0 +1,99 @@
+// reticle.rs
+
+//! A faint highlight on the tile the player's next shot would spawn into: the tile next to the
+//! player in the direction they're heading, or with mouse aim the direction of the cursor. It
+//! turns red when that tile is a wall, which is when a shot does nothing.
+//!
+//! The reticle is a plain sprite in map space, like the pickups: it has no collider and never
+//! reserves its tile, so nothing can bump into it. It is hidden while the player has no
+//! direction to fire in, and while the attract mode bot is playing.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::components::{GameEntity, GameState};
+use crate::grid_movement::{is_wall, GridMover, IntendedDirection, MovementSystems};
+use crate::map::MapData;
+use crate::mouse_aim::{AimMode, MouseAim};
+use crate::player::{spawn_player, ControlSource, Player};
+use crate::tilemap::{GridSpace, TILE_SIZE};
+
+/// Opacity of the highlight over an open tile and over a wall.
+const RETICLE_ALPHA: f32 = 0.25;
+const BLOCKED_ALPHA: f32 = 0.4;
+
+pub struct ReticlePlugin;
+
+impl Plugin for ReticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Playing),
+            spawn_reticle.after(spawn_player),
+        )
+        .add_systems(
+            Update,
+            // After the scroll is applied, so the reticle doesn't trail the view by a frame.
+            update_reticle
+                .after(MovementSystems::ApplyOffsetChanges)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct Reticle;
+
+fn spawn_reticle(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.spawn((
+        Sprite::from_color(
+            game_assets.palette.colors[5].with_alpha(RETICLE_ALPHA),
+            Vec2::splat(TILE_SIZE),
+        ),
+        // Under the player and projectiles.
+        Transform::from_xyz(0.0, 0.0, 0.5),
+        Visibility::Hidden,
+        Reticle,
+        GameEntity,
+    ));
+}
+
+/// Moves the reticle onto the tile ahead of the player, colored by whether a shot fits there.
+#[allow(clippy::too_many_arguments)]
+fn update_reticle(
+    grid_space: GridSpace,
+    aim_mode: Res<AimMode>,
+    mouse_aim: Res<MouseAim>,
+    game_assets: Res<GameAssets>,
+    map_data: Res<MapData>,
+    player: Query<(&GridMover, &IntendedDirection, &ControlSource), With<Player>>,
+    mut reticle: Query<(&mut Transform, &mut Sprite, &mut Visibility), With<Reticle>>,
+) {
+    let Ok((mut transform, mut sprite, mut visibility)) = reticle.single_mut() else {
+        return;
+    };
+    let target = player
+        .single()
+        .ok()
+        .filter(|(_, _, control)| **control == ControlSource::Keyboard)
+        .and_then(|(mover, intended, _)| {
+            let dir = match *aim_mode {
+                AimMode::Keyboard => Some(intended.0).filter(|dir| *dir != IVec2::ZERO),
+                AimMode::Mouse => mouse_aim.0,
+            }?;
+            // The same tile `fire_projectile` spawns into.
+            Some(mover.grid_pos + dir)
+        });
+    let Some(tile) = target else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+    let world = grid_space.tile_to_world(tile);
+    transform.translation.x = world.x;
+    transform.translation.y = world.y;
+    sprite.color = if is_wall(tile, &map_data) {
+        game_assets.palette.colors[2].with_alpha(BLOCKED_ALPHA)
+    } else {
+        game_assets.palette.colors[5].with_alpha(RETICLE_ALPHA)
+    };
+}
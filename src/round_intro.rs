@@ -0,0 +1,151 @@
+// round_intro.rs
+
+//! A short "GET READY" countdown at the start of every round.
+//!
+//! The map, player and enemies are spawned as usual on entering `GameState::Playing`, so they
+//! are rendered (and the camera is in place) during the countdown. Only input, enemy AI and
+//! movement are held back until the `RoundIntro` resource is removed.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+use crate::audio;
+use crate::components::GameState;
+use crate::enemy::EnemyMovementAI;
+use crate::grid_movement::MovementSystems;
+
+/// Numbers counted down during the intro.
+const COUNTDOWN_FROM: u32 = 3;
+
+/// Seconds each number of the countdown is shown for.
+const COUNTDOWN_STEP: f32 = 0.7;
+
+pub struct RoundIntroPlugin;
+
+impl Plugin for RoundIntroPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(
+            Update,
+            (
+                MovementSystems::Input,
+                MovementSystems::UpdateMover,
+                EnemyMovementAI,
+            )
+                .run_if(round_in_progress),
+        )
+        .add_systems(OnEnter(GameState::Playing), start_round_intro)
+        .add_systems(OnExit(GameState::Playing), end_round_intro)
+        .add_systems(
+            Update,
+            update_round_intro
+                .run_if(in_state(GameState::Playing).and(resource_exists::<RoundIntro>)),
+        );
+    }
+}
+
+/// Present while the pre-round countdown is running.
+#[derive(Resource)]
+pub struct RoundIntro(pub Timer);
+
+/// Run condition that is true once the countdown has finished.
+pub fn round_in_progress(intro: Option<Res<RoundIntro>>) -> bool {
+    intro.is_none()
+}
+
+#[derive(Component)]
+struct RoundIntroText;
+
+#[derive(Component)]
+struct CountdownText;
+
+/// Returns the countdown number shown after `elapsed` seconds.
+fn countdown_number(elapsed: f32) -> u32 {
+    COUNTDOWN_FROM.saturating_sub((elapsed / COUNTDOWN_STEP) as u32)
+}
+
+fn start_round_intro(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.insert_resource(RoundIntro(Timer::from_seconds(
+        COUNTDOWN_STEP * COUNTDOWN_FROM as f32,
+        TimerMode::Once,
+    )));
+    audio::play(&mut commands, game_assets.tick_sfx.clone());
+
+    let root = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            RoundIntroText,
+        ))
+        .id();
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new("GET READY"),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[3]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+
+        parent.spawn((
+            Text::new(COUNTDOWN_FROM.to_string()),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 40.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[4]),
+            TextLayout::new_with_justify(JustifyText::Center),
+            CountdownText,
+        ));
+    });
+}
+
+/// Ticks the countdown, updating the number with a tick sound, and starts play when it ends.
+fn update_round_intro(
+    mut commands: Commands,
+    mut intro: ResMut<RoundIntro>,
+    // Real time, so the countdown isn't stretched by slow motion.
+    time: Res<Time<Real>>,
+    game_assets: Res<GameAssets>,
+    mut text_query: Query<&mut Text, With<CountdownText>>,
+    root_query: Query<Entity, With<RoundIntroText>>,
+) {
+    let before = countdown_number(intro.0.elapsed_secs());
+    intro.0.tick(time.delta());
+
+    if intro.0.finished() {
+        commands.remove_resource::<RoundIntro>();
+        for entity in &root_query {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let now = countdown_number(intro.0.elapsed_secs());
+    if now != before {
+        audio::play(&mut commands, game_assets.tick_sfx.clone());
+        if let Ok(mut text) = text_query.single_mut() {
+            text.0 = now.to_string();
+        }
+    }
+}
+
+/// Clears the intro if the round ends mid-countdown (e.g. a quick restart).
+fn end_round_intro(mut commands: Commands, root_query: Query<Entity, With<RoundIntroText>>) {
+    commands.remove_resource::<RoundIntro>();
+    for entity in &root_query {
+        commands.entity(entity).despawn();
+    }
+}
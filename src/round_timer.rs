@@ -0,0 +1,84 @@
+// round_timer.rs
+
+//! Times each round and the run as a whole. The HUD shows the round time.
+//!
+//! The timer accumulates real, unscaled time, so slow-motion effects that change `GameSpeed`
+//! don't stretch the clock. It only ticks while in `GameState::Playing`, once the round's
+//! countdown has finished, and stops whenever the simulation does: while the window is away,
+//! in frame-step mode and during a hit-stop freeze.
+
+use bevy::prelude::*;
+
+use crate::components::{CurrentRound, GameMode, GameState};
+use crate::demo::in_demo;
+use crate::frame_step::simulation_running;
+use crate::highscore::HighScores;
+use crate::round_intro::round_in_progress;
+
+pub struct RoundTimerPlugin;
+
+impl Plugin for RoundTimerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RoundTimer>()
+            .add_systems(OnEnter(GameState::Title), reset_run_timer)
+            .add_systems(OnEnter(GameState::Restarting), reset_run_timer)
+            .add_systems(OnEnter(GameState::Playing), start_round_timer)
+            .add_systems(
+                OnEnter(GameState::Victory),
+                // A time attack course is timed against its own par times instead.
+                record_round_time
+                    .run_if(not(in_demo))
+                    .run_if(resource_equals(GameMode::Classic)),
+            )
+            .add_systems(
+                Update,
+                tick_round_timer
+                    .run_if(round_in_progress)
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
+            );
+    }
+}
+
+/// Elapsed real time for the current round and the whole run.
+#[derive(Resource, Default)]
+pub struct RoundTimer {
+    /// Seconds spent in the current round.
+    pub round: f32,
+    /// Seconds spent across every round of the run.
+    pub total: f32,
+    /// Whether the last completed round was a personal best for its round number.
+    pub last_was_best: bool,
+}
+
+/// Formats seconds as mm:ss.
+pub fn format_time(seconds: f32) -> String {
+    let whole = seconds.max(0.0) as u32;
+    format!("{:02}:{:02}", whole / 60, whole % 60)
+}
+
+fn reset_run_timer(mut timer: ResMut<RoundTimer>) {
+    *timer = RoundTimer::default();
+}
+
+pub fn start_round_timer(mut timer: ResMut<RoundTimer>) {
+    timer.round = 0.0;
+    timer.last_was_best = false;
+}
+
+pub fn tick_round_timer(mut timer: ResMut<RoundTimer>, time: Res<Time<Real>>) {
+    let dt = time.delta_secs();
+    timer.round += dt;
+    timer.total += dt;
+}
+
+/// Compares the finished round's time against the personal best for that round number.
+pub fn record_round_time(
+    mut timer: ResMut<RoundTimer>,
+    mut high_scores: ResMut<HighScores>,
+    round: Res<CurrentRound>,
+) {
+    timer.last_was_best = high_scores.record_round_time(round.0, timer.round);
+    if timer.last_was_best {
+        high_scores.save();
+    }
+}
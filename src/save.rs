@@ -0,0 +1,178 @@
+// save.rs
+
+//! Lifetime progress that carries over between sessions, and the unlocks it gates.
+//!
+//! Totals are accumulated in memory while playing and only written to disk when leaving a
+//! round (entering Victory, GameOver or a restart), never per frame; seen hints are the one
+//! exception, written as they are shown. A corrupt save file is moved aside and replaced with
+//! fresh progress rather than preventing the game from starting.
+//! The attract mode demo never counts towards progress.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::achievements::Achievement;
+use crate::components::{CurrentRound, EnemyKilled, GameState};
+use crate::demo::in_demo;
+use crate::message_log::{GameMessage, MessageKind};
+use crate::platform_io::{config_exists, rename_config};
+use crate::toast::ShowToast;
+
+/// Where progress is saved, relative to the working directory.
+pub const SAVE_PATH: &str = "save/progress.ron";
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SaveData::load())
+            .add_systems(
+                OnEnter(GameState::Victory),
+                (record_round_won, write_save).chain().run_if(not(in_demo)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), write_save)
+            .add_systems(
+                OnEnter(GameState::Restarting),
+                write_save.run_if(not(in_demo)),
+            )
+            .add_systems(
+                Update,
+                (count_lifetime_kills, count_play_time)
+                    .run_if(in_state(GameState::Playing).and(not(in_demo))),
+            )
+            .add_systems(Update, announce_unlocks);
+    }
+}
+
+/// Something that becomes available once enough lifetime progress has been made.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Unlock {
+    HardMode,
+}
+
+impl Unlock {
+    pub const ALL: [Unlock; 1] = [Unlock::HardMode];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Unlock::HardMode => "HARD MODE",
+        }
+    }
+}
+
+/// The highest round that must be won to unlock Hard difficulty.
+const HARD_MODE_ROUND: u32 = 3;
+
+/// Lifetime totals across every session.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct SaveData {
+    pub total_kills: u64,
+    pub rounds_won: u32,
+    /// The highest round number won.
+    pub best_round: u32,
+    /// Seconds spent playing, in real time.
+    pub play_time: f64,
+    /// Ids of the hints already shown, which are never shown again.
+    pub seen_hints: Vec<String>,
+    /// Ids of the achievements earned.
+    pub achievements: Vec<String>,
+    /// Fastest clear time (in seconds) of each time attack course, by the course's seed.
+    pub course_times: BTreeMap<u64, f32>,
+}
+
+impl SaveData {
+    /// Loads progress from disk, starting fresh if it is missing or unreadable.
+    pub fn load() -> Self {
+        if let Some(data) = crate::config::load_ron(SAVE_PATH) {
+            return data;
+        }
+        // Keep a corrupt file around for inspection instead of overwriting it on the next save.
+        if config_exists(SAVE_PATH) {
+            let backup = format!("{}.bak", SAVE_PATH);
+            if let Err(err) = rename_config(SAVE_PATH, &backup) {
+                warn!("Failed to move aside {}: {}", SAVE_PATH, err);
+            } else {
+                warn!("Moved unreadable {} to {}", SAVE_PATH, backup);
+            }
+        }
+        SaveData::default()
+    }
+
+    pub fn save(&self) {
+        crate::config::save_ron(SAVE_PATH, self);
+    }
+
+    pub fn is_unlocked(&self, unlock: Unlock) -> bool {
+        match unlock {
+            Unlock::HardMode => self.best_round >= HARD_MODE_ROUND,
+        }
+    }
+
+    pub fn has_achievement(&self, achievement: Achievement) -> bool {
+        self.achievements.iter().any(|id| id == achievement.id())
+    }
+
+    /// Records a clear time for the course with `seed`, returning true if it beats the
+    /// previous best.
+    pub fn record_course_time(&mut self, seed: u64, seconds: f32) -> bool {
+        let is_best = self
+            .course_times
+            .get(&seed)
+            .is_none_or(|&best| seconds < best);
+        if is_best {
+            self.course_times.insert(seed, seconds);
+        }
+        is_best
+    }
+}
+
+fn count_lifetime_kills(mut save: ResMut<SaveData>, mut events: EventReader<EnemyKilled>) {
+    let kills = events.read().count() as u64;
+    if kills > 0 {
+        save.total_kills += kills;
+    }
+}
+
+fn count_play_time(mut save: ResMut<SaveData>, time: Res<Time<Real>>) {
+    save.play_time += time.delta_secs_f64();
+}
+
+fn record_round_won(mut save: ResMut<SaveData>, round: Res<CurrentRound>) {
+    save.rounds_won += 1;
+    save.best_round = save.best_round.max(round.0);
+}
+
+fn write_save(save: Res<SaveData>) {
+    save.save();
+}
+
+/// Shows a toast the first time each unlock becomes available.
+fn announce_unlocks(
+    save: Res<SaveData>,
+    mut announced: Local<Option<Vec<Unlock>>>,
+    mut toasts: EventWriter<ShowToast>,
+    mut messages: EventWriter<GameMessage>,
+) {
+    // Anything already unlocked when the game starts was announced in an earlier session.
+    let announced = announced.get_or_insert_with(|| {
+        Unlock::ALL
+            .into_iter()
+            .filter(|&unlock| save.is_unlocked(unlock))
+            .collect()
+    });
+    if !save.is_changed() {
+        return;
+    }
+    for unlock in Unlock::ALL {
+        if save.is_unlocked(unlock) && !announced.contains(&unlock) {
+            announced.push(unlock);
+            toasts.write(ShowToast(format!("{} UNLOCKED", unlock.label())));
+            messages.write(GameMessage(
+                format!("{} unlocked", unlock.label()),
+                MessageKind::Unlock,
+            ));
+        }
+    }
+}
@@ -1,93 +1,343 @@
 // score.rs
 use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use std::time::Duration;
 
-use crate::assets::GameAssets;
-use crate::components::{EnemyDied, GameEntity, GameState};
-use crate::enemy::{spawn_enemies, Enemy}; // Added spawn_enemies import
+use crate::components::{Dying, EnemyDied, EnemyKilled, EnemySpawned, GameState, KillSource};
+use crate::enemy::Enemy;
+use crate::message_log::{GameMessage, MessageKind};
+use crate::round_timer::RoundTimer;
 
 pub struct ScorePlugin;
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            OnEnter(GameState::Playing),
-            setup_enemy_count.after(spawn_enemies), // Ensure runs after enemies are spawned
-        )
-        .add_systems(
-            Update,
-            (update_enemy_count, update_enemy_count_display)
-                .chain()
-                .run_if(in_state(GameState::Playing)),
-        );
+        app.init_resource::<Score>()
+            .init_resource::<Combo>()
+            .init_resource::<RunStats>()
+            .add_event::<PointsAwarded>()
+            .init_resource::<DisplayedScore>()
+            .init_resource::<EnemyCount>()
+            .add_systems(OnEnter(GameState::Title), reset_score)
+            .add_systems(OnEnter(GameState::Restarting), reset_score)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_enemy_count, reset_displayed_score),
+            )
+            .add_systems(OnEnter(GameState::Victory), award_round_clear_bonus)
+            .add_systems(
+                Update,
+                (
+                    update_enemy_count,
+                    reconcile_enemy_count.run_if(on_timer(Duration::from_secs(1))),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (tick_combo, award_kill_points, animate_score_display)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-#[derive(Resource)]
+/// The player's score for the current run. Carried across victorious rounds and reset when
+/// returning to the title screen.
+#[derive(Resource, Default)]
+pub struct Score(pub u64);
+
+/// Event fired whenever points are earned for a kill, for feedback such as score popups.
+#[derive(Event)]
+pub struct PointsAwarded {
+    pub position: Vec3,
+    pub points: u64,
+    /// The combo count at the time of the kill (1 for a lone kill).
+    pub combo: u32,
+}
+
+/// Statistics for the current run, shown on the victory and game over screens.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub enemies_killed: u32,
+    /// The longest combo reached during the run.
+    pub best_combo: u32,
+    /// Kills by an unbounced projectile.
+    pub shots: u32,
+    /// Kills by a projectile that had bounced.
+    pub ricochets: u32,
+    /// Kills by contact with the player.
+    pub melee: u32,
+    /// Kills by chain explosions.
+    pub explosions: u32,
+    /// Enemies that walked into lava.
+    pub lava: u32,
+    /// Gems collected over the run.
+    pub gems: u32,
+    /// Rounds won with every gem on the map collected.
+    pub all_gems_rounds: u32,
+}
+
+impl RunStats {
+    fn record_kill(&mut self, source: KillSource) {
+        self.enemies_killed += 1;
+        match source {
+            KillSource::Shot => self.shots += 1,
+            KillSource::Ricochet { .. } => self.ricochets += 1,
+            KillSource::Melee => self.melee += 1,
+            KillSource::Explosion => self.explosions += 1,
+            KillSource::Lava => self.lava += 1,
+        }
+    }
+
+    /// Returns one display line per kill source, e.g. "ricochets: 12".
+    pub fn kill_breakdown(&self) -> [String; 5] {
+        [
+            format!("shots: {}", self.shots),
+            format!("ricochets: {}", self.ricochets),
+            format!("melee: {}", self.melee),
+            format!("explosions: {}", self.explosions),
+            format!("lava: {}", self.lava),
+        ]
+    }
+}
+
+/// Tracks kills made in quick succession.
+#[derive(Resource, Default)]
+pub struct Combo {
+    /// Kills in the current chain.
+    pub count: u32,
+    /// Seconds left before the chain expires.
+    pub timer: f32,
+}
+
+/// Seconds allowed between kills for them to count towards the same combo.
+const COMBO_WINDOW: f32 = 1.5;
+
+/// Points for a kill by an unbounced projectile.
+const SHOT_POINTS: u64 = 100;
+
+/// Extra points per bounce the killing projectile had made.
+const BOUNCE_BONUS_POINTS: u64 = 250;
+
+/// Points for an enemy killed by touching the player.
+const MELEE_POINTS: u64 = 50;
+
+/// Points for an enemy caught in a chain explosion.
+const EXPLOSION_POINTS: u64 = 150;
+
+/// Flat bonus for clearing a round.
+const ROUND_CLEAR_POINTS: u64 = 500;
+
+/// Maximum time bonus, awarded for an instant clear.
+const TIME_BONUS_POINTS: f32 = 5000.0;
+
+/// Seconds after which the time bonus has decayed to nothing.
+const TIME_BONUS_WINDOW: f32 = 300.0;
+
+/// Seconds for the displayed score to count up to its new value.
+const SCORE_COUNT_UP_TIME: f32 = 0.3;
+
+/// The value currently shown in the HUD, which counts up towards `Score`.
+#[derive(Resource, Default)]
+pub struct DisplayedScore {
+    /// The displayed value when the score last changed.
+    from: u64,
+    /// The value currently on screen.
+    shown: u64,
+    /// Seconds since the score last changed.
+    elapsed: f32,
+}
+
+impl DisplayedScore {
+    pub fn shown(&self) -> u64 {
+        self.shown
+    }
+}
+
+/// The number of living enemies, kept up to date from `EnemySpawned` and `EnemyDied` events and
+/// periodically checked against the world.
+#[derive(Resource, Default)]
 pub struct EnemyCount {
     pub value: u32,
 }
 
-#[derive(Component)]
-struct EnemyCountText;
+pub fn reset_enemy_count(mut enemy_count: ResMut<EnemyCount>) {
+    // The round's enemies are counted in as their `EnemySpawned` events are read.
+    enemy_count.value = 0;
+}
+
+pub fn update_enemy_count(
+    mut enemy_count: ResMut<EnemyCount>,
+    mut spawned_events: EventReader<EnemySpawned>,
+    mut died_events: EventReader<EnemyDied>,
+) {
+    let spawned = spawned_events.read().count() as u32;
+    let died = died_events.read().count() as u32;
+    if spawned > 0 || died > 0 {
+        enemy_count.value = (enemy_count.value + spawned).saturating_sub(died);
+    }
+}
 
-fn setup_enemy_count(
-    mut commands: Commands,
-    game_assets: Res<GameAssets>,
-    enemy_query: Query<(), With<Enemy>>,
+/// Corrects the enemy count if it has drifted from the number of living enemies in the world.
+pub fn reconcile_enemy_count(
+    mut enemy_count: ResMut<EnemyCount>,
+    enemy_query: Query<(), (With<Enemy>, Without<Dying>)>,
 ) {
-    // Count the number of enemies at the start of the game
-    let initial_count = enemy_query.iter().len() as u32;
-    commands.insert_resource(EnemyCount {
-        value: initial_count,
-    });
-
-    let root = commands
-        .spawn((
-            Node {
-                position_type: PositionType::Absolute,
-                top: Val::Px(10.0),
-                width: Val::Percent(100.0),
-                height: Val::Px(60.0),
-                flex_direction: FlexDirection::Column,
-                justify_content: JustifyContent::FlexStart,
-                align_items: AlignItems::Center,
-                ..default()
-            },
-            BackgroundColor(Color::NONE),
-            GameEntity,
-        ))
-        .id();
-
-    commands.entity(root).with_children(|parent| {
-        parent.spawn((
-            Text::new(format!("remaining: {}", initial_count)),
-            TextFont {
-                font: game_assets.font.clone(),
-                font_size: 16.0,
-                ..default()
-            },
-            TextColor(game_assets.palette.colors[3]),
-            TextLayout::new_with_justify(JustifyText::Center),
-            EnemyCountText,
-        ));
-    });
-}
-
-fn update_enemy_count(mut enemy_count: ResMut<EnemyCount>, mut events: EventReader<EnemyDied>) {
-    for _ in events.read() {
-        if enemy_count.value > 0 {
-            enemy_count.value -= 1;
+    let actual = enemy_query.iter().len() as u32;
+    if enemy_count.value != actual {
+        warn!(
+            "Enemy count drifted: tracked {}, found {}",
+            enemy_count.value, actual
+        );
+        enemy_count.value = actual;
+    }
+}
+
+fn reset_score(
+    mut score: ResMut<Score>,
+    mut displayed: ResMut<DisplayedScore>,
+    mut combo: ResMut<Combo>,
+    mut stats: ResMut<RunStats>,
+) {
+    score.0 = 0;
+    *displayed = DisplayedScore::default();
+    *combo = Combo::default();
+    *stats = RunStats::default();
+}
+
+/// Expires the combo once too long has passed since the last kill.
+fn tick_combo(mut combo: ResMut<Combo>, time: Res<Time>) {
+    if combo.count == 0 {
+        return;
+    }
+    combo.timer -= time.delta_secs();
+    if combo.timer <= 0.0 {
+        combo.count = 0;
+    }
+}
+
+/// Returns the points for a kill by `source`.
+fn kill_points(source: KillSource) -> u64 {
+    match source {
+        KillSource::Shot => SHOT_POINTS,
+        KillSource::Ricochet { bounces } => SHOT_POINTS + BOUNCE_BONUS_POINTS * bounces as u64,
+        KillSource::Melee => MELEE_POINTS,
+        KillSource::Explosion => EXPLOSION_POINTS,
+        // The player didn't do anything for these.
+        KillSource::Lava => 0,
+    }
+}
+
+/// Awards points for every kill according to what killed the enemy.
+fn award_kill_points(
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut stats: ResMut<RunStats>,
+    mut events: EventReader<EnemyKilled>,
+    mut awarded_events: EventWriter<PointsAwarded>,
+    mut messages: EventWriter<GameMessage>,
+) {
+    for event in events.read() {
+        combo.count += 1;
+        combo.timer = COMBO_WINDOW;
+        stats.record_kill(event.source);
+        stats.best_combo = stats.best_combo.max(combo.count);
+
+        let points = kill_points(event.source);
+        score.0 += points;
+        awarded_events.write(PointsAwarded {
+            position: event.position,
+            points,
+            combo: combo.count,
+        });
+        // Only trick kills make the log; a line per kill would drown everything else.
+        if let KillSource::Ricochet { .. } = event.source {
+            messages.write(GameMessage(
+                format!("ricochet kill! +{}", points),
+                MessageKind::Kill,
+            ));
         }
     }
 }
 
-fn update_enemy_count_display(
-    enemy_count: Res<EnemyCount>,
-    mut query: Query<&mut Text, With<EnemyCountText>>,
+/// Returns the time bonus for clearing a round in `seconds`.
+pub fn time_bonus(seconds: f32) -> u64 {
+    let remaining = (1.0 - seconds / TIME_BONUS_WINDOW).clamp(0.0, 1.0);
+    (TIME_BONUS_POINTS * remaining).round() as u64
+}
+
+/// Awards the round-clear bonus plus a time bonus that shrinks the longer the round took.
+fn award_round_clear_bonus(mut score: ResMut<Score>, round_timer: Res<RoundTimer>) {
+    score.0 += ROUND_CLEAR_POINTS + time_bonus(round_timer.round);
+}
+
+/// Starts each round showing the carried-over score without counting up to it.
+pub fn reset_displayed_score(score: Res<Score>, mut displayed: ResMut<DisplayedScore>) {
+    *displayed = DisplayedScore {
+        from: score.0,
+        shown: score.0,
+        elapsed: SCORE_COUNT_UP_TIME,
+    };
+}
+
+/// Counts the displayed score up towards the real score over `SCORE_COUNT_UP_TIME`.
+fn animate_score_display(
+    score: Res<Score>,
+    mut displayed: ResMut<DisplayedScore>,
+    time: Res<Time<Real>>,
 ) {
-    if enemy_count.is_changed() {
-        if let Ok(mut text) = query.single_mut() {
-            text.0 = format!("remaining: {}", enemy_count.value);
+    if score.is_changed() {
+        displayed.from = displayed.shown;
+        displayed.elapsed = 0.0;
+    }
+    if displayed.shown == score.0 {
+        return;
+    }
+
+    displayed.elapsed += time.delta_secs();
+    let t = (displayed.elapsed / SCORE_COUNT_UP_TIME).min(1.0);
+    let from = displayed.from as f64;
+    displayed.shown = (from + (score.0 as f64 - from) * t as f64).round() as u64;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn enemy_count_follows_spawns_and_deaths() {
+        let mut app = App::new();
+        app.add_event::<EnemySpawned>()
+            .add_event::<EnemyDied>()
+            .init_resource::<EnemyCount>()
+            .add_systems(Update, update_enemy_count);
+        for _ in 0..3 {
+            let enemy = app.world_mut().spawn_empty().id();
+            app.world_mut().send_event(EnemySpawned(enemy));
         }
+        app.world_mut().send_event(EnemyDied(Vec3::ZERO));
+        app.update();
+        assert_eq!(app.world().resource::<EnemyCount>().value, 2);
+
+        // A stray death never takes it below zero.
+        for _ in 0..3 {
+            app.world_mut().send_event(EnemyDied(Vec3::ZERO));
+        }
+        app.update();
+        assert_eq!(app.world().resource::<EnemyCount>().value, 0);
+    }
+
+    #[test]
+    fn reconcile_counts_only_living_enemies() {
+        let mut world = World::new();
+        world.insert_resource(EnemyCount { value: 7 });
+        world.spawn(Enemy);
+        world.spawn(Enemy);
+        world.spawn((Enemy, Dying));
+        world.run_system_once(reconcile_enemy_count).unwrap();
+        assert_eq!(world.resource::<EnemyCount>().value, 2);
     }
 }
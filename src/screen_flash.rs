@@ -0,0 +1,207 @@
+// screen_flash.rs
+
+//! A full-screen color flash used to punctuate big moments (player death, round victory) and
+//! to give feedback on hits and pickups, plus a faint red tint while the player is on their
+//! last heart.
+//!
+//! Flashes that overlap are resolved by `FlashKind`: a flash only replaces the current one if
+//! it matters at least as much, so a pickup never hides a hit. The low-health tint sits under
+//! whatever flash is showing.
+//!
+//! Layering: tiles sit at z = 0, game entities between 0.8 and 1.5, and the border blocks at
+//! z = 2. The flash sprite sits at z = 3 so it tints the whole frame, borders included. UI text
+//! is drawn in a separate pass and always stays on top.
+
+use bevy::prelude::*;
+
+use crate::collider::{resolve_damage, DamageEvent};
+use crate::components::{Dying, GameState, Health, PlayerDied};
+use crate::pickup::{PickupCollected, PickupKind};
+use crate::player::{Invulnerable, Player};
+
+pub struct ScreenFlashPlugin;
+
+impl Plugin for ScreenFlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenFlash>()
+            .init_resource::<FlashSettings>()
+            .add_systems(Startup, spawn_flash_overlay)
+            .add_systems(OnEnter(GameState::Victory), flash_on_victory)
+            .add_systems(
+                Update,
+                (
+                    // Before the hits are resolved, so the player isn't invulnerable yet.
+                    flash_on_player_hit.before(resolve_damage),
+                    flash_on_pickup,
+                    flash_on_player_death,
+                    update_screen_flash,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// What a flash is for, in increasing order of importance.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum FlashKind {
+    Pickup,
+    Damage,
+    /// Deaths and victories.
+    Milestone,
+}
+
+impl FlashKind {
+    /// Seconds for a full-strength flash of this kind to fade out.
+    fn fade_time(self) -> f32 {
+        match self {
+            FlashKind::Pickup => 0.25,
+            FlashKind::Damage => 0.4,
+            FlashKind::Milestone => 0.3,
+        }
+    }
+}
+
+/// The current flash state. Event consumers call `trigger`; `update_screen_flash` decays it.
+#[derive(Resource)]
+pub struct ScreenFlash {
+    /// The flash color. Its alpha is the peak opacity at full strength.
+    pub color: Color,
+    /// How strong the flash currently is, from 0.0 (invisible) to 1.0.
+    pub strength: f32,
+    pub kind: FlashKind,
+}
+
+impl Default for ScreenFlash {
+    fn default() -> Self {
+        ScreenFlash {
+            color: Color::NONE,
+            strength: 0.0,
+            kind: FlashKind::Pickup,
+        }
+    }
+}
+
+impl ScreenFlash {
+    /// Starts a flash. Flashes of the same kind in quick succession stack up to full strength.
+    /// A flash that matters less than the one showing is dropped.
+    pub fn trigger(&mut self, kind: FlashKind, color: Color, strength: f32) {
+        if self.strength > 0.0 && kind < self.kind {
+            return;
+        }
+        if kind != self.kind {
+            self.strength = 0.0;
+        }
+        self.kind = kind;
+        self.color = color;
+        self.strength = (self.strength + strength).clamp(0.0, 1.0);
+    }
+}
+
+/// Accessibility settings for the screen flash.
+#[derive(Resource)]
+pub struct FlashSettings {
+    /// Upper bound on the overlay's opacity, regardless of the flash color or stacking.
+    pub max_opacity: f32,
+}
+
+impl Default for FlashSettings {
+    fn default() -> Self {
+        FlashSettings { max_opacity: 0.6 }
+    }
+}
+
+/// Marker for the full-screen overlay sprite.
+#[derive(Component)]
+struct FlashOverlay;
+
+/// The tint kept on screen while the player is on their last heart.
+const LOW_HEALTH_TINT: Color = Color::srgba(1.0, 0.1, 0.1, 0.08);
+
+/// The overlay is far larger than any viewport so it never needs resizing.
+const FLASH_OVERLAY_SIZE: f32 = 100_000.0;
+
+fn spawn_flash_overlay(mut commands: Commands) {
+    commands.spawn((
+        Sprite {
+            color: Color::NONE,
+            custom_size: Some(Vec2::splat(FLASH_OVERLAY_SIZE)),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, 3.0),
+        FlashOverlay,
+    ));
+}
+
+/// Flashes red when a hit lands on the player. Hits they're immune to don't count.
+#[allow(clippy::type_complexity)]
+fn flash_on_player_hit(
+    mut damage_events: EventReader<DamageEvent>,
+    mut flash: ResMut<ScreenFlash>,
+    player: Query<(), (With<Player>, Without<Invulnerable>, Without<Dying>)>,
+) {
+    for hit in damage_events.read() {
+        if hit.amount > 0 && player.contains(hit.victim) {
+            flash.trigger(FlashKind::Damage, Color::srgba(1.0, 0.1, 0.1, 0.35), 1.0);
+        }
+    }
+}
+
+fn flash_on_pickup(
+    mut collected_events: EventReader<PickupCollected>,
+    mut flash: ResMut<ScreenFlash>,
+) {
+    for event in collected_events.read() {
+        let color = match event.kind {
+            PickupKind::Gem => Color::srgba(0.2, 0.5, 1.0, 0.2),
+        };
+        flash.trigger(FlashKind::Pickup, color, 1.0);
+    }
+}
+
+fn flash_on_player_death(
+    mut player_died_events: EventReader<PlayerDied>,
+    mut flash: ResMut<ScreenFlash>,
+) {
+    for _ in player_died_events.read() {
+        flash.trigger(FlashKind::Milestone, Color::srgba(1.0, 0.1, 0.1, 0.6), 1.0);
+    }
+}
+
+fn flash_on_victory(mut flash: ResMut<ScreenFlash>) {
+    flash.trigger(FlashKind::Milestone, Color::srgba(1.0, 1.0, 1.0, 0.5), 1.0);
+}
+
+/// Fades the flash out over time and applies it, or the low-health tint if that's stronger, to
+/// the overlay sprite.
+fn update_screen_flash(
+    mut flash: ResMut<ScreenFlash>,
+    settings: Res<FlashSettings>,
+    mut overlay: Query<&mut Sprite, With<FlashOverlay>>,
+    player: Query<&Health, With<Player>>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    let Ok(mut sprite) = overlay.single_mut() else {
+        return;
+    };
+    if flash.strength > 0.0 {
+        let fade_time = flash.kind.fade_time();
+        flash.strength = (flash.strength - time.delta_secs() / fade_time).max(0.0);
+    }
+
+    let on_last_heart = *state.get() == GameState::Playing
+        && player
+            .single()
+            .is_ok_and(|health| health.max > 1 && health.current == 1);
+    let flash_alpha = flash.color.alpha() * flash.strength;
+    let (color, alpha) = if on_last_heart && LOW_HEALTH_TINT.alpha() > flash_alpha {
+        (LOW_HEALTH_TINT, LOW_HEALTH_TINT.alpha())
+    } else {
+        (flash.color, flash_alpha)
+    };
+    let alpha = alpha.min(settings.max_opacity);
+    // Avoid touching the sprite (and triggering change detection) while idle.
+    if sprite.color != color.with_alpha(alpha) {
+        sprite.color = color.with_alpha(alpha);
+    }
+}
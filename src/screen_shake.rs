@@ -0,0 +1,79 @@
+// screen_shake.rs
+
+//! Trauma-based camera shake for explosions.
+//!
+//! Explosions add trauma, which decays over time; the camera is offset by an amount
+//! proportional to trauma squared so small knocks stay subtle while big ones hit hard. The
+//! player's screen shake setting scales the whole effect and can turn it off.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{GlobalEntropy, WyRand};
+
+use crate::components::{EnemyDied, PlayerDied};
+use crate::explosion::ExplosionConfig;
+use crate::random::random_float;
+use crate::settings::Settings;
+
+/// Largest camera offset, in world units, at full trauma and intensity.
+const MAX_SHAKE_OFFSET: f32 = 24.0;
+
+/// Trauma lost per second.
+const TRAUMA_DECAY: f32 = 1.5;
+
+pub struct ScreenShakePlugin;
+
+impl Plugin for ScreenShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_systems(Update, (add_explosion_trauma, shake_camera).chain());
+    }
+}
+
+/// Current shake trauma, from 0.0 (still) to 1.0 (maximum).
+#[derive(Resource, Default)]
+pub struct ScreenShake {
+    pub trauma: f32,
+}
+
+impl ScreenShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+}
+
+fn add_explosion_trauma(
+    mut shake: ResMut<ScreenShake>,
+    mut enemy_died: EventReader<EnemyDied>,
+    mut player_died: EventReader<PlayerDied>,
+    config: Res<ExplosionConfig>,
+) {
+    for _ in enemy_died.read() {
+        shake.add_trauma(config.enemy_shake_trauma);
+    }
+    for _ in player_died.read() {
+        shake.add_trauma(config.player_shake_trauma);
+    }
+}
+
+fn shake_camera(
+    mut shake: ResMut<ScreenShake>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+    mut rng: GlobalEntropy<WyRand>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    if shake.trauma <= 0.0 {
+        return;
+    }
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * time.delta_secs()).max(0.0);
+
+    let strength = MAX_SHAKE_OFFSET * settings.screen_shake * shake.trauma * shake.trauma;
+    let offset = Vec2::new(
+        random_float(&mut rng) * 2.0 - 1.0,
+        random_float(&mut rng) * 2.0 - 1.0,
+    ) * strength;
+    for mut transform in &mut camera_query {
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
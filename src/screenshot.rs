@@ -0,0 +1,207 @@
+// screenshot.rs
+
+//! F12 saves the current frame as a PNG in `screenshots/`; Shift+F12 (or `screenshot clean` in
+//! the console) leaves out the debug overlays.
+//!
+//! A clean capture takes three frames: the first turns the debug overlays off, the second
+//! requests the capture once they're hidden, and the third puts them back. Turning them back on
+//! any sooner could show them in the captured frame, since the render world extracts the frame
+//! after `Update` has run.
+
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotCaptured;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::render::view::screenshot::{save_to_disk, Screenshot};
+use std::time::Duration;
+
+use crate::assets::GameAssets;
+use crate::audio;
+use crate::console::{handle_console_input, CommandResult, Console, ConsoleCommands};
+use crate::debug::DebugFlags;
+
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+
+/// Directory screenshots are written to, relative to the working directory.
+#[cfg(not(target_arch = "wasm32"))]
+const SCREENSHOT_DIR: &str = "screenshots";
+
+/// How long the white border shown after a capture takes to fade.
+const BORDER_FADE_TIME: Duration = Duration::from_millis(250);
+
+const BORDER_WIDTH: f32 = 6.0;
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenshotRequest>()
+            .init_resource::<ConsoleCommands>()
+            .add_systems(
+                PreUpdate,
+                (request_screenshot_on_key, advance_screenshot)
+                    .chain()
+                    .after(handle_console_input),
+            )
+            .add_systems(Update, fade_capture_border);
+
+        app.world_mut().resource_mut::<ConsoleCommands>().register(
+            "screenshot",
+            "screenshot [clean]",
+            "saves a screenshot, without debug overlays if clean",
+            screenshot_command,
+        );
+    }
+}
+
+/// Where a requested screenshot is in its capture sequence.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+enum CapturePhase {
+    #[default]
+    Idle,
+    Requested {
+        clean: bool,
+    },
+    /// The overlays were turned off last frame; `restore_master` is what to set them back to.
+    Hidden {
+        restore_master: bool,
+    },
+    Captured {
+        restore_master: bool,
+    },
+}
+
+#[derive(Resource, Default)]
+pub struct ScreenshotRequest {
+    phase: CapturePhase,
+}
+
+impl ScreenshotRequest {
+    /// Queues a screenshot for the next frame, unless one is already under way.
+    pub fn request(&mut self, clean: bool) -> bool {
+        if self.phase != CapturePhase::Idle {
+            return false;
+        }
+        self.phase = CapturePhase::Requested { clean };
+        true
+    }
+}
+
+fn request_screenshot_on_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut request: ResMut<ScreenshotRequest>,
+) {
+    if keys.just_pressed(SCREENSHOT_KEY) {
+        let clean = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        request.request(clean);
+    }
+}
+
+/// Moves the capture sequence on by one frame.
+fn advance_screenshot(
+    mut commands: Commands,
+    mut request: ResMut<ScreenshotRequest>,
+    mut flags: ResMut<DebugFlags>,
+) {
+    request.phase = match request.phase {
+        CapturePhase::Idle => return,
+        CapturePhase::Requested { clean: true } => {
+            let restore_master = flags.master;
+            flags.master = false;
+            CapturePhase::Hidden { restore_master }
+        }
+        CapturePhase::Requested { clean: false } => {
+            capture(&mut commands);
+            CapturePhase::Idle
+        }
+        CapturePhase::Hidden { restore_master } => {
+            capture(&mut commands);
+            CapturePhase::Captured { restore_master }
+        }
+        CapturePhase::Captured { restore_master } => {
+            flags.master = restore_master;
+            CapturePhase::Idle
+        }
+    };
+}
+
+/// Asks the renderer for a capture of the primary window, saved under a timestamped name.
+#[cfg(not(target_arch = "wasm32"))]
+fn capture(commands: &mut Commands) {
+    if let Err(err) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+        error!("Couldn't create {}/: {}", SCREENSHOT_DIR, err);
+        return;
+    }
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let path = format!("{}/screenshot-{}.png", SCREENSHOT_DIR, timestamp);
+    info!("Saving screenshot to {}", path);
+    // `save_to_disk` logs write failures rather than panicking.
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(path))
+        .observe(confirm_capture);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn capture(_commands: &mut Commands) {
+    info!("Screenshots aren't supported in the browser");
+}
+
+#[derive(Component)]
+struct CaptureBorder(Timer);
+
+/// Plays a shutter tick and flashes a white border once the frame has been captured, so
+/// neither ends up in the screenshot.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn confirm_capture(
+    _trigger: Trigger<ScreenshotCaptured>,
+    mut commands: Commands,
+    game_assets: Option<Res<GameAssets>>,
+) {
+    if let Some(game_assets) = game_assets {
+        audio::play(&mut commands, game_assets.tick_sfx.clone());
+    }
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            border: UiRect::all(Val::Px(BORDER_WIDTH)),
+            ..default()
+        },
+        BorderColor(Color::WHITE),
+        GlobalZIndex(50),
+        CaptureBorder(Timer::new(BORDER_FADE_TIME, TimerMode::Once)),
+    ));
+}
+
+fn fade_capture_border(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut borders: Query<(Entity, &mut CaptureBorder, &mut BorderColor)>,
+) {
+    for (entity, mut border, mut color) in &mut borders {
+        border.0.tick(time.delta());
+        if border.0.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            color.0 = Color::WHITE.with_alpha(border.0.fraction_remaining());
+        }
+    }
+}
+
+fn screenshot_command(world: &mut World, args: &[&str]) -> CommandResult {
+    let clean = match args {
+        [] => false,
+        ["clean"] => true,
+        _ => return Err("expected clean or nothing".to_string()),
+    };
+    if !world.resource_mut::<ScreenshotRequest>().request(clean) {
+        return Err("a screenshot is already being taken".to_string());
+    }
+    // Closed now so the console isn't in the picture either.
+    world.resource_mut::<Console>().open = false;
+    Ok("Taking a screenshot".to_string())
+}
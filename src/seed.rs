@@ -0,0 +1,69 @@
+// seed.rs
+
+//! Map seeds, so a run can be replayed or shared.
+//!
+//! Every run gets a 64-bit seed, chosen at random unless the player asked for a specific one
+//! (by typing it on the title screen or picking "Replay last map"). At the start of each round
+//! the global RNG is reseeded from the run seed and the round number, so the same seed always
+//! produces the same maps, spawn points and enemy placement.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{Entropy, GlobalEntropy, WyRand};
+use rand_core::{RngCore, SeedableRng};
+
+use crate::components::{CurrentRound, GameState};
+use crate::demo::Demo;
+use crate::map::generate_map;
+
+/// The longest seed that can be typed, in hex digits.
+pub const MAX_SEED_DIGITS: usize = 16;
+
+pub struct SeedPlugin;
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapSeed>()
+            .add_systems(OnEnter(GameState::Playing), seed_round.before(generate_map));
+    }
+}
+
+/// The seed of the current run, and the seeds requested for the next one.
+#[derive(Resource, Default, Debug)]
+pub struct MapSeed {
+    /// Seed of the run in progress (or the most recent one).
+    pub current: u64,
+    /// Seed of the last run the player played, not counting the attract mode demo.
+    pub last_played: Option<u64>,
+    /// Seed to use for the next run instead of a random one.
+    pub next: Option<u64>,
+}
+
+/// Formats a seed the way players type it: upper-case hex.
+pub fn format_seed(seed: u64) -> String {
+    format!("{:X}", seed)
+}
+
+/// Parses a typed seed, returning `None` unless it is 1 to `MAX_SEED_DIGITS` hex digits.
+pub fn parse_seed(text: &str) -> Option<u64> {
+    if text.is_empty() || text.len() > MAX_SEED_DIGITS {
+        return None;
+    }
+    u64::from_str_radix(text, 16).ok()
+}
+
+/// Picks the run's seed on its first round, then reseeds the RNG for this round's generation.
+pub fn seed_round(
+    mut rng: GlobalEntropy<WyRand>,
+    mut seed: ResMut<MapSeed>,
+    round: Res<CurrentRound>,
+    demo: Res<Demo>,
+) {
+    if round.0 == 1 {
+        seed.current = seed.next.take().unwrap_or_else(|| rng.next_u64());
+        if !demo.0 {
+            seed.last_played = Some(seed.current);
+        }
+        info!("Map seed: {}", format_seed(seed.current));
+    }
+    **rng = Entropy::<WyRand>::seed_from_u64(seed.current.wrapping_add(round.0 as u64));
+}
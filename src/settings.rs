@@ -0,0 +1,320 @@
+// settings.rs
+
+//! Player preferences that are remembered between sessions, stored in `settings.ron`.
+//!
+//! Unlike `config.ron`, which holds designer tuning, this file is written by the game itself
+//! whenever a setting changes. A missing or malformed file falls back to the defaults.
+//!
+//! `Settings` is the single source of truth; whenever it changes, each value is pushed into the
+//! resource that uses it (`DifficultySetting`, `AudioSettings`, `Resolution`, `PaletteChoice`,
+//! `PixelSnap`, `CrtEffect`, `RadarMode`, `AutoPause`, `FogOfWar`, `HitStopIntensity`,
+//! `ShowHints`, `ContactMode`, `AimMode`, `PersistentArena`).
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::arena::PersistentArena;
+use crate::audio::AudioSettings;
+use crate::config::{load_ron, save_ron};
+use crate::crt::CrtEffect;
+use crate::custom_window::AutoPause;
+use crate::difficulty::{ContactMode, Difficulty, DifficultySetting};
+use crate::fog::FogOfWar;
+use crate::hints::ShowHints;
+use crate::hit_stop::HitStopIntensity;
+use crate::mouse_aim::AimMode;
+use crate::palette::{PaletteChoice, Palettes, DEFAULT_PALETTE};
+use crate::pixel_snap::PixelSnap;
+use crate::radar::RadarMode;
+use crate::resolution::Resolution;
+
+/// Where settings are saved, relative to the working directory.
+pub const SETTINGS_PATH: &str = "save/settings.ron";
+
+/// Step used when adjusting a volume or the screen shake intensity.
+const LEVEL_STEP: f32 = 0.1;
+
+/// Step used when adjusting the camera zoom.
+const ZOOM_STEP: f32 = 0.1;
+
+const MIN_ZOOM: f32 = 0.5;
+const MAX_ZOOM: f32 = 2.0;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings: Settings = load_ron(SETTINGS_PATH).unwrap_or_default();
+        app.insert_resource(settings.sanitized()).add_systems(
+            Update,
+            (
+                apply_settings,
+                save_settings.run_if(not(resource_added::<Settings>)),
+            )
+                .run_if(resource_changed::<Settings>),
+        );
+    }
+}
+
+/// The contents of `settings.ron`.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: Difficulty,
+    /// What touching an enemy does; `Bump` is the gentler mode.
+    pub contact: ContactMode,
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    /// Screen shake intensity, from 0.0 (off) to 1.0 (full).
+    pub screen_shake: f32,
+    /// Camera zoom, as `Resolution::zoom`.
+    pub zoom: f32,
+    pub muted: bool,
+    /// Name of the color palette; unknown names fall back to the default palette.
+    pub palette: String,
+    /// Rounds rendered positions to the pixel grid to stop scrolling shimmer.
+    pub pixel_snap: bool,
+    /// Draws scanlines and a vignette over the game.
+    pub crt: bool,
+    /// When to show the arrows pointing at off-screen enemies.
+    pub radar: RadarMode,
+    /// Pauses the game while its window is unfocused or minimized.
+    pub auto_pause: bool,
+    /// Hides the parts of the map the player can't see; makes the game much harder.
+    pub fog_of_war: bool,
+    /// How long the game freezes on ricochet kills and round-ending kills.
+    pub hit_stop: HitStopIntensity,
+    /// Shows one-time hints for new players. Switching it back on shows them all again.
+    pub hints: bool,
+    /// Whether the left mouse button fires towards the cursor.
+    pub aim: AimMode,
+    /// Keeps the map from one won round to the next in the classic mode.
+    pub persistent_arena: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            difficulty: Difficulty::default(),
+            contact: ContactMode::default(),
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            screen_shake: 1.0,
+            zoom: 1.0,
+            muted: false,
+            palette: DEFAULT_PALETTE.to_string(),
+            pixel_snap: true,
+            crt: false,
+            radar: RadarMode::default(),
+            auto_pause: true,
+            fog_of_war: false,
+            hit_stop: HitStopIntensity::default(),
+            hints: true,
+            aim: AimMode::default(),
+            persistent_arena: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Clamps every value into its valid range, in case the file was edited by hand.
+    fn sanitized(mut self) -> Self {
+        self.master_volume = self.master_volume.clamp(0.0, 1.0);
+        self.sfx_volume = self.sfx_volume.clamp(0.0, 1.0);
+        self.music_volume = self.music_volume.clamp(0.0, 1.0);
+        self.screen_shake = self.screen_shake.clamp(0.0, 1.0);
+        self.zoom = self.zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        self
+    }
+
+    /// Steps the zoom by one notch; positive steps zoom out.
+    pub fn step_zoom(&mut self, step: i32) {
+        // Counted in whole notches so repeated steps don't drift off the grid.
+        let notches = (self.zoom / ZOOM_STEP).round() as i32 + step;
+        self.zoom = (notches as f32 * ZOOM_STEP).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+/// The adjustable entries on the settings page, top to bottom.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingsEntry {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    ScreenShake,
+    Zoom,
+    Palette,
+    PixelSnap,
+    Crt,
+    Radar,
+    AutoPause,
+    FogOfWar,
+    HitStop,
+    Hints,
+    Difficulty,
+    Contact,
+    Aim,
+    PersistentArena,
+    ResetToDefaults,
+    Back,
+}
+
+impl SettingsEntry {
+    pub const ALL: [SettingsEntry; 19] = [
+        SettingsEntry::MasterVolume,
+        SettingsEntry::SfxVolume,
+        SettingsEntry::MusicVolume,
+        SettingsEntry::ScreenShake,
+        SettingsEntry::Zoom,
+        SettingsEntry::Palette,
+        SettingsEntry::PixelSnap,
+        SettingsEntry::Crt,
+        SettingsEntry::Radar,
+        SettingsEntry::AutoPause,
+        SettingsEntry::FogOfWar,
+        SettingsEntry::HitStop,
+        SettingsEntry::Hints,
+        SettingsEntry::Difficulty,
+        SettingsEntry::Contact,
+        SettingsEntry::Aim,
+        SettingsEntry::PersistentArena,
+        SettingsEntry::ResetToDefaults,
+        SettingsEntry::Back,
+    ];
+
+    /// The entry's text, including its current value where it has one.
+    pub fn label(self, settings: &Settings) -> String {
+        let percent = |value: f32| format!("{:.0}%", value * 100.0);
+        match self {
+            SettingsEntry::MasterVolume => {
+                format!("MASTER VOLUME < {} >", percent(settings.master_volume))
+            }
+            SettingsEntry::SfxVolume => format!("SFX VOLUME < {} >", percent(settings.sfx_volume)),
+            SettingsEntry::MusicVolume => {
+                format!("MUSIC VOLUME < {} >", percent(settings.music_volume))
+            }
+            SettingsEntry::ScreenShake => {
+                format!("SCREEN SHAKE < {} >", percent(settings.screen_shake))
+            }
+            SettingsEntry::Zoom => format!("ZOOM < {:.1} >", settings.zoom),
+            SettingsEntry::Palette => format!("PALETTE < {} >", settings.palette),
+            SettingsEntry::PixelSnap => {
+                let state = if settings.pixel_snap { "ON" } else { "OFF" };
+                format!("PIXEL SNAP < {} >", state)
+            }
+            SettingsEntry::Crt => {
+                let state = if settings.crt { "ON" } else { "OFF" };
+                format!("CRT EFFECT < {} >", state)
+            }
+            SettingsEntry::Radar => format!("RADAR < {} >", settings.radar.label()),
+            SettingsEntry::AutoPause => {
+                let state = if settings.auto_pause { "ON" } else { "OFF" };
+                format!("PAUSE WHEN AWAY < {} >", state)
+            }
+            SettingsEntry::FogOfWar => {
+                let state = if settings.fog_of_war { "ON" } else { "OFF" };
+                format!("FOG OF WAR < {} >", state)
+            }
+            SettingsEntry::HitStop => format!("HIT-STOP < {} >", settings.hit_stop.label()),
+            SettingsEntry::Hints => {
+                let state = if settings.hints { "ON" } else { "OFF" };
+                format!("HINTS < {} >", state)
+            }
+            SettingsEntry::Difficulty => format!("DIFFICULTY < {} >", settings.difficulty.label()),
+            SettingsEntry::Contact => format!("ENEMY CONTACT < {} >", settings.contact.label()),
+            SettingsEntry::Aim => format!("AIM < {} >", settings.aim.label()),
+            SettingsEntry::PersistentArena => {
+                let state = if settings.persistent_arena {
+                    "ON"
+                } else {
+                    "OFF"
+                };
+                format!("PERSISTENT ARENA < {} >", state)
+            }
+            SettingsEntry::ResetToDefaults => "RESET TO DEFAULTS".to_string(),
+            SettingsEntry::Back => "BACK".to_string(),
+        }
+    }
+
+    /// Steps the entry's value by one notch in the direction of `step` (-1 or 1).
+    /// Entries without a value are left alone.
+    pub fn adjust(self, settings: &mut Settings, palettes: &Palettes, step: i32) {
+        let level = |value: f32| (value + LEVEL_STEP * step as f32).clamp(0.0, 1.0);
+        match self {
+            SettingsEntry::MasterVolume => settings.master_volume = level(settings.master_volume),
+            SettingsEntry::SfxVolume => settings.sfx_volume = level(settings.sfx_volume),
+            SettingsEntry::MusicVolume => settings.music_volume = level(settings.music_volume),
+            SettingsEntry::ScreenShake => settings.screen_shake = level(settings.screen_shake),
+            SettingsEntry::Zoom => settings.step_zoom(step),
+            SettingsEntry::Palette => settings.palette = palettes.cycle(&settings.palette, step),
+            SettingsEntry::PixelSnap => settings.pixel_snap = !settings.pixel_snap,
+            SettingsEntry::Crt => settings.crt = !settings.crt,
+            SettingsEntry::Radar => settings.radar = settings.radar.cycled(step),
+            SettingsEntry::AutoPause => settings.auto_pause = !settings.auto_pause,
+            SettingsEntry::FogOfWar => settings.fog_of_war = !settings.fog_of_war,
+            SettingsEntry::HitStop => settings.hit_stop = settings.hit_stop.cycled(step),
+            SettingsEntry::Hints => settings.hints = !settings.hints,
+            SettingsEntry::Difficulty => {
+                settings.difficulty = if step > 0 {
+                    settings.difficulty.harder()
+                } else {
+                    settings.difficulty.easier()
+                };
+            }
+            SettingsEntry::Contact => settings.contact = settings.contact.toggled(),
+            SettingsEntry::Aim => settings.aim = settings.aim.toggled(),
+            SettingsEntry::PersistentArena => {
+                settings.persistent_arena = !settings.persistent_arena
+            }
+            SettingsEntry::ResetToDefaults | SettingsEntry::Back => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_settings(
+    settings: Res<Settings>,
+    mut difficulty: ResMut<DifficultySetting>,
+    mut audio: ResMut<AudioSettings>,
+    mut resolution: ResMut<Resolution>,
+    mut palette: ResMut<PaletteChoice>,
+    mut pixel_snap: ResMut<PixelSnap>,
+    mut crt: ResMut<CrtEffect>,
+    mut radar: ResMut<RadarMode>,
+    mut auto_pause: ResMut<AutoPause>,
+    mut fog: ResMut<FogOfWar>,
+    mut hit_stop: ResMut<HitStopIntensity>,
+    mut hints: ResMut<ShowHints>,
+    mut contact: ResMut<ContactMode>,
+    mut aim: ResMut<AimMode>,
+    mut arena: ResMut<PersistentArena>,
+) {
+    difficulty.set_if_neq(DifficultySetting(settings.difficulty));
+    *audio = AudioSettings {
+        master: settings.master_volume,
+        sfx: settings.sfx_volume,
+        music: settings.music_volume,
+        muted: settings.muted,
+    };
+    // Only touch the resolution when the zoom differs, so the projection isn't recomputed.
+    if resolution.zoom != settings.zoom {
+        resolution.zoom = settings.zoom;
+    }
+    palette.set_if_neq(PaletteChoice(settings.palette.clone()));
+    pixel_snap.set_if_neq(PixelSnap(settings.pixel_snap));
+    crt.set_if_neq(CrtEffect(settings.crt));
+    radar.set_if_neq(settings.radar);
+    auto_pause.set_if_neq(AutoPause(settings.auto_pause));
+    fog.set_if_neq(FogOfWar(settings.fog_of_war));
+    hit_stop.set_if_neq(settings.hit_stop);
+    hints.set_if_neq(ShowHints(settings.hints));
+    contact.set_if_neq(settings.contact);
+    aim.set_if_neq(settings.aim);
+    arena.set_if_neq(PersistentArena(settings.persistent_arena));
+}
+
+fn save_settings(settings: Res<Settings>) {
+    save_ron(SETTINGS_PATH, &*settings);
+}
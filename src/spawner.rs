@@ -0,0 +1,382 @@
+// spawner.rs
+
+//! Enemy spawners: destructible structures placed on the map that keep producing enemies until
+//! they are shot down.
+//!
+//! A spawner takes up one tile. It holds a reservation there like any mover, so nothing can
+//! walk through it, and the projectile collision check finds it the same way it finds enemies;
+//! its hits go through `resolve_damage`, which writes `SpawnerDestroyed` once its `Health` runs
+//! out. Every `SPAWN_INTERVAL` it telegraphs a free neighbouring tile and, `TELEGRAPH_TIME`
+//! later, hatches an enemy there, as long as fewer than `MAX_CHILDREN` of its own are alive.
+//!
+//! Spawners have no `GridMover`: like pickups, they are positioned from their tile each frame.
+//! A classic round isn't won until every spawner is destroyed.
+
+use bevy::prelude::*;
+use bevy_rand::prelude::{Entropy, ForkableRng, Global, GlobalEntropy, WyRand};
+use std::f32::consts::TAU;
+
+use crate::assets::GameAssets;
+use crate::atlas::{atlas_sprite, AtlasSprite, GameAtlas};
+use crate::audio;
+use crate::collider::{resolve_damage, Collider, HitFlash};
+use crate::components::{Dying, Faction, FadeOut, GameEntity, GameState, Health, Lifetime};
+use crate::difficulty::DifficultySetting;
+use crate::enemy::{spawn_enemies, spawn_tiles, Enemy, EnemySpawner};
+use crate::explosion::{explosion_sprite, Explosion, ExplosionConfig};
+use crate::frame_step::simulation_running;
+use crate::grid_movement::{is_wall, GridMover, MovementSystems};
+use crate::grid_reservation::{GridReservations, GridReserver};
+use crate::map::MapData;
+use crate::message_log::{GameMessage, MessageKind};
+use crate::player::{spawn_player, Player};
+use crate::quicksave::PendingRestore;
+use crate::random::{random_float, random_pick, sample_k};
+use crate::score::{PointsAwarded, Score};
+use crate::tilemap::{GridSpace, TILE_SIZE};
+
+/// Hits a spawner takes before it is destroyed.
+pub const SPAWNER_HEALTH: u32 = 5;
+
+/// Seconds between a spawner's enemies.
+const SPAWN_INTERVAL: f32 = 8.0;
+
+/// Seconds a tile is marked before the enemy appears on it.
+const TELEGRAPH_TIME: f32 = 0.8;
+
+/// Most enemies a spawner keeps alive at once; it waits while this many of its own are about.
+const MAX_CHILDREN: usize = 3;
+
+/// Minimum distance in tiles between the player and a newly placed spawner.
+const PLACEMENT_DISTANCE: i64 = 12;
+
+/// Enemies never hatch within this many tiles of the player.
+const HATCH_SAFE_DISTANCE: i32 = 2;
+
+/// Points for destroying a spawner.
+const SPAWNER_POINTS: u64 = 1000;
+
+/// Seconds for one full pulse of a spawner's tint.
+const PULSE_PERIOD: f32 = 1.2;
+
+/// Explosions set off when a spawner is destroyed, spread over a fraction of a second.
+const DESTRUCTION_EXPLOSIONS: usize = 6;
+const DESTRUCTION_SCATTER: f32 = TILE_SIZE * 0.6;
+const DESTRUCTION_STAGGER: f32 = 0.3;
+
+const NEIGHBOURS: [IVec2; 4] = [IVec2::Y, IVec2::NEG_Y, IVec2::X, IVec2::NEG_X];
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnerDestroyed>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                // Before the enemies, so they don't take the spawners' tiles. A resumed
+                // quicksave brings its own spawners.
+                spawn_spawners
+                    .after(spawn_player)
+                    .before(spawn_enemies)
+                    .run_if(not(resource_exists::<PendingRestore>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    (start_telegraphs, hatch_enemies).chain(),
+                    (update_spawner_positions, pulse_spawners)
+                        .chain()
+                        .after(MovementSystems::ApplyOffsetChanges),
+                    destroy_spawners.after(resolve_damage),
+                )
+                    .run_if(in_state(GameState::Playing).and(simulation_running)),
+            );
+    }
+}
+
+/// Sent by `resolve_damage` when a spawner's health runs out.
+#[derive(Event)]
+pub struct SpawnerDestroyed {
+    pub spawner: Entity,
+    /// Where it was, in world space.
+    pub position: Vec3,
+}
+
+/// A structure that produces enemies on the tiles around it.
+#[derive(Component)]
+pub struct Spawner {
+    pub tile: IVec2,
+    /// Time until the next enemy is telegraphed.
+    timer: Timer,
+    /// The tile being telegraphed, and the time until the enemy appears there.
+    pending: Option<(IVec2, Timer)>,
+}
+
+/// Marks an enemy with the spawner that produced it, so the spawner can count its children.
+#[derive(Component)]
+struct SpawnedBy(Entity);
+
+/// The marker on a tile where an enemy is about to appear.
+#[derive(Component)]
+struct SpawnTelegraph(IVec2);
+
+/// Spawns a spawner on `tile` and reserves it. `rng` becomes its own generator, which picks
+/// its hatching tiles and scatters its explosions when it is destroyed.
+pub fn spawn_spawner(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    atlas: Option<&GameAtlas>,
+    reservations: &mut GridReservations,
+    tile: IVec2,
+    health: Health,
+    rng: Entropy<WyRand>,
+) -> Entity {
+    let entity = commands
+        .spawn((
+            Sprite {
+                color: game_assets.palette.colors[2],
+                custom_size: Some(Vec2::splat(TILE_SIZE * 0.9)),
+                ..atlas_sprite(atlas, game_assets, AtlasSprite::Wall)
+            },
+            Transform::from_xyz(0.0, 0.0, 0.85),
+            Spawner {
+                tile,
+                timer: Timer::from_seconds(SPAWN_INTERVAL, TimerMode::Repeating),
+                pending: None,
+            },
+            Faction::Enemies,
+            health,
+            rng,
+            GridReserver,
+            // The hurtbox stays well inside the tile, so standing next to a spawner is safe.
+            Collider {
+                size: Vec2::splat(TILE_SIZE * 0.75),
+                hurtbox_scale: 0.5,
+                ..default()
+            },
+            GameEntity,
+        ))
+        .id();
+    reservations.0.insert(tile, entity);
+    entity
+}
+
+/// Places the round's spawners on free tiles away from the player. The tiles come from the
+/// global generator, which the round has just been seeded from, and each spawner forks its
+/// own from it.
+#[allow(clippy::too_many_arguments)]
+fn spawn_spawners(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    atlas: Option<Res<GameAtlas>>,
+    mut reservations: ResMut<GridReservations>,
+    map_data: Res<MapData>,
+    difficulty: Res<DifficultySetting>,
+    player_query: Query<&GridMover, With<Player>>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    let Ok(player) = player_query.single() else {
+        return;
+    };
+    let count = difficulty.0.spawner_count();
+    let tiles = sample_k(
+        &mut rng,
+        spawn_tiles(
+            &map_data,
+            &reservations,
+            player.grid_pos,
+            PLACEMENT_DISTANCE,
+        ),
+        count,
+    );
+    if tiles.len() < count {
+        warn!("Only room for {} of {} spawners", tiles.len(), count);
+    }
+    for tile in tiles {
+        spawn_spawner(
+            &mut commands,
+            &game_assets,
+            atlas.as_deref(),
+            &mut reservations,
+            tile,
+            Health::new(SPAWNER_HEALTH),
+            rng.fork_rng(),
+        );
+    }
+}
+
+/// Counts down each spawner and, when its time comes and it has room for another child, marks
+/// a free neighbouring tile for the next enemy, picked with the spawner's own generator.
+#[allow(clippy::too_many_arguments)]
+fn start_telegraphs(
+    mut commands: Commands,
+    mut spawners: Query<(Entity, &mut Spawner, &mut Entropy<WyRand>), Without<Dying>>,
+    children: Query<&SpawnedBy, (With<Enemy>, Without<Dying>)>,
+    player_query: Query<&GridMover, With<Player>>,
+    reservations: Res<GridReservations>,
+    map_data: Res<MapData>,
+    game_assets: Res<GameAssets>,
+    atlas: Option<Res<GameAtlas>>,
+    time: Res<Time>,
+) {
+    let player_pos = player_query.single().ok().map(|mover| mover.grid_pos);
+    for (entity, mut spawner, mut rng) in &mut spawners {
+        if spawner.pending.is_some() || !spawner.timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let alive = children.iter().filter(|child| child.0 == entity).count();
+        if alive >= MAX_CHILDREN {
+            continue;
+        }
+        let free: Vec<IVec2> = NEIGHBOURS
+            .iter()
+            .map(|&dir| spawner.tile + dir)
+            .filter(|&tile| {
+                !is_wall(tile, &map_data)
+                    && !map_data.is_lava(tile)
+                    && !reservations.0.contains_key(&tile)
+                    && player_pos.is_none_or(|player| {
+                        (tile - player).abs().max_element() > HATCH_SAFE_DISTANCE
+                    })
+            })
+            .collect();
+        if free.is_empty() {
+            continue;
+        }
+        let tile = *random_pick(&mut rng, &free);
+        spawner.pending = Some((tile, Timer::from_seconds(TELEGRAPH_TIME, TimerMode::Once)));
+        commands.spawn((
+            Sprite {
+                color: game_assets.palette.colors[12],
+                custom_size: Some(Vec2::splat(TILE_SIZE * 0.8)),
+                ..atlas_sprite(atlas.as_deref(), &game_assets, AtlasSprite::Reservation)
+            },
+            Transform::from_xyz(0.0, 0.0, 0.7),
+            SpawnTelegraph(tile),
+            Lifetime::from_seconds(TELEGRAPH_TIME),
+            FadeOut,
+            GameEntity,
+        ));
+    }
+}
+
+/// Hatches an enemy on each telegraphed tile once its time is up. A tile that was taken in the
+/// meantime is given up on until the next interval.
+fn hatch_enemies(
+    mut commands: Commands,
+    mut spawners: Query<(Entity, &mut Spawner), Without<Dying>>,
+    mut enemy_spawner: EnemySpawner,
+    time: Res<Time>,
+) {
+    for (entity, mut spawner) in &mut spawners {
+        let Some((tile, timer)) = &mut spawner.pending else {
+            continue;
+        };
+        if !timer.tick(time.delta()).finished() {
+            continue;
+        }
+        let tile = *tile;
+        spawner.pending = None;
+        if let Some(child) = enemy_spawner.spawn_random_on(tile) {
+            commands.entity(child).insert(SpawnedBy(entity));
+        }
+    }
+}
+
+/// Positions spawners and their telegraphs from their tiles so they scroll with the map.
+fn update_spawner_positions(
+    grid_space: GridSpace,
+    mut spawners: Query<(&Spawner, &mut Transform), Without<SpawnTelegraph>>,
+    mut telegraphs: Query<(&SpawnTelegraph, &mut Transform), Without<Spawner>>,
+) {
+    let positions = spawners
+        .iter_mut()
+        .map(|(spawner, transform)| (spawner.tile, transform))
+        .chain(
+            telegraphs
+                .iter_mut()
+                .map(|(telegraph, transform)| (telegraph.0, transform)),
+        );
+    for (tile, mut transform) in positions {
+        let world = grid_space.tile_to_world(tile);
+        transform.translation.x = world.x;
+        transform.translation.y = world.y;
+    }
+}
+
+/// Pulses the spawners' tint so they stand out from the walls. Skips a spawner while its hit
+/// flash is showing.
+fn pulse_spawners(
+    game_assets: Res<GameAssets>,
+    time: Res<Time>,
+    mut spawners: Query<&mut Sprite, (With<Spawner>, Without<HitFlash>)>,
+) {
+    let pulse = 0.5 + 0.5 * (time.elapsed_secs() * TAU / PULSE_PERIOD).sin();
+    let base = game_assets.palette.colors[2].to_srgba();
+    let bright = game_assets.palette.colors[4].to_srgba();
+    let color: Color = base.mix(&bright, pulse).into();
+    for mut sprite in &mut spawners {
+        sprite.color = color;
+    }
+}
+
+/// Blows up destroyed spawners and awards their bonus. The explosions are scattered with the
+/// spawner's own generator; it is still there, marked `Dying`, until the end of the frame.
+#[allow(clippy::too_many_arguments)]
+fn destroy_spawners(
+    mut commands: Commands,
+    mut events: EventReader<SpawnerDestroyed>,
+    mut score: ResMut<Score>,
+    mut awarded_events: EventWriter<PointsAwarded>,
+    mut messages: EventWriter<GameMessage>,
+    game_assets: Res<GameAssets>,
+    images: Res<Assets<Image>>,
+    atlas: Option<Res<GameAtlas>>,
+    config: Res<ExplosionConfig>,
+    mut spawner_rngs: Query<&mut Entropy<WyRand>, (With<Spawner>, Without<Global>)>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    for &SpawnerDestroyed { spawner, position } in events.read() {
+        score.0 += SPAWNER_POINTS;
+        awarded_events.write(PointsAwarded {
+            position,
+            points: SPAWNER_POINTS,
+            combo: 1,
+        });
+        messages.write(GameMessage(
+            format!("spawner destroyed! +{}", SPAWNER_POINTS),
+            MessageKind::Kill,
+        ));
+        audio::play_varied(
+            &mut commands,
+            audio::SfxCategory::Explosion,
+            &game_assets.explosion_sfx,
+            config.player_sfx_volume,
+            audio::PITCH_VARIATION,
+            &mut rng,
+        );
+        let scatter_rng: &mut Entropy<WyRand> = match spawner_rngs.get_mut(spawner) {
+            Ok(spawner_rng) => spawner_rng.into_inner(),
+            Err(_) => &mut rng,
+        };
+        for i in 0..DESTRUCTION_EXPLOSIONS {
+            // The first goes off at once in the middle, the rest around it.
+            let (offset, delay) = if i == 0 {
+                (Vec2::ZERO, 0.0)
+            } else {
+                let offset = Vec2::new(random_float(scatter_rng), random_float(scatter_rng)) - 0.5;
+                (
+                    offset * DESTRUCTION_SCATTER,
+                    DESTRUCTION_STAGGER * random_float(scatter_rng),
+                )
+            };
+            let color = game_assets.palette.colors[if i % 2 == 0 { 2 } else { 4 }];
+            commands.spawn((
+                explosion_sprite(&game_assets, atlas.as_deref(), &images, color),
+                Transform::from_translation(position + offset.extend(0.0)),
+                Explosion::delayed(delay, &config),
+                GameEntity,
+            ));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+// spectator.rs
+
+//! Spectating after the player dies: while the death explosions play out, the movement keys
+//! pan the camera freely and the spectate key snaps it from one remaining enemy to the next.
+//!
+//! Works through `CameraTarget`, so the usual scrolling does the clamping and offset math. The
+//! camera goes back to following the player as soon as `PlayerIsDead` is gone, whether the
+//! round ended, restarted or was quickloaded.
+
+use bevy::prelude::*;
+
+use crate::components::{Dying, GameState};
+use crate::demo::Demo;
+use crate::enemy::Enemy;
+use crate::explosion::PlayerIsDead;
+use crate::grid_movement::MovementSystems;
+use crate::input::InputMap;
+use crate::map::MapData;
+use crate::player::CameraTarget;
+use crate::tilemap::{MapOffset, TileOffset, ViewportTiles};
+
+/// How fast the free camera pans, in tiles per second.
+const PAN_SPEED: f32 = 15.0;
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameState::Playing), follow_player)
+            .add_systems(
+                Update,
+                spectate
+                    .before(MovementSystems::AdjustScroll)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn follow_player(mut camera_target: ResMut<CameraTarget>) {
+    camera_target.set_if_neq(CameraTarget::FollowPlayer);
+}
+
+/// Steers the camera from the keyboard while the player is dead. Pans in real time, so the
+/// death slow-motion doesn't slow it down.
+#[allow(clippy::too_many_arguments)]
+fn spectate(
+    dead: Option<Res<PlayerIsDead>>,
+    demo: Res<Demo>,
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut camera_target: ResMut<CameraTarget>,
+    enemies: Query<Entity, (With<Enemy>, Without<Dying>)>,
+    transforms: Query<&Transform>,
+    viewport: Res<ViewportTiles>,
+    map_offset: Res<MapOffset>,
+    tile_offset: Res<TileOffset>,
+    map_data: Res<MapData>,
+    time: Res<Time<Real>>,
+) {
+    let Some(dead) = dead.filter(|_| !demo.0) else {
+        camera_target.set_if_neq(CameraTarget::FollowPlayer);
+        return;
+    };
+    let view_center = viewport.view_center(&map_offset, &tile_offset);
+    // Starts from wherever the player died; an enemy that has since died leaves the camera
+    // where it was.
+    let lost_entity = matches!(*camera_target, CameraTarget::FollowEntity(entity)
+        if transforms.get(entity).is_err());
+    if dead.is_added() || lost_entity {
+        *camera_target = CameraTarget::FreePan(view_center);
+    }
+
+    if keys.just_pressed(input_map.spectate_next) {
+        // Cycles in entity order, which stays stable as enemies die.
+        let mut order: Vec<Entity> = enemies.iter().collect();
+        order.sort();
+        let current = match *camera_target {
+            CameraTarget::FollowEntity(entity) => Some(entity),
+            _ => None,
+        };
+        let next = order
+            .iter()
+            .find(|&&entity| current.is_some_and(|current| entity > current))
+            .or(order.first());
+        if let Some(&entity) = next {
+            *camera_target = CameraTarget::FollowEntity(entity);
+        }
+    }
+
+    let pan = input_map.movement(&keys).as_vec2();
+    if pan == Vec2::ZERO {
+        return;
+    }
+    let center = match *camera_target {
+        CameraTarget::FreePan(center) => center,
+        _ => view_center,
+    };
+    // Clamped like the scrolling, so panning back from an edge responds at once.
+    let half = viewport.half_extent();
+    let max_center = viewport.max_offset(&map_data).as_vec2() + half;
+    let center = center + pan.normalize() * PAN_SPEED * time.delta_secs();
+    *camera_target = CameraTarget::FreePan(center.clamp(half, max_center));
+}
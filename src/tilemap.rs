@@ -1,94 +1,566 @@
 // tilemap.rs
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
-use bevy::sprite::Sprite;
-use bevy_rand::prelude::{GlobalEntropy, WyRand};
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::sprite::AlphaMode2d;
+use bevy_rand::prelude::{Entropy, GlobalEntropy, WyRand};
 
+use crate::arena::{carry_over_arena, ArenaEntity};
 use crate::assets::GameAssets;
-use crate::components::{GameEntity, GameState};
+use crate::atlas::GameAtlas;
+use crate::autotile::{wall_mask, TileTextures};
+use crate::components::{CurrentRound, GameEntity, GameState};
+use crate::fog::{FogOfWar, FogState, FogView, TileVisibility};
+use crate::grid_movement::GridMover;
+use crate::lava::LavaPulse;
 use crate::map::{generate_map, MapData};
-use crate::random::random_colour_except;
+use crate::palette::{recolor_entities, Palette, PaletteChanged};
+use crate::player::{center_view_on, Player};
+use crate::profiler::SystemTimings;
+use crate::random::{random_index, random_pick};
+use crate::resolution::Resolution;
 
 pub const TILE_SIZE: f32 = 64.0;
-pub const RENDERED_WIDTH: usize = 36;
-pub const RENDERED_HEIGHT: usize = 28;
-pub const HALF_WIDTH: f32 = (RENDERED_WIDTH as f32 - 1.0) / 2.0;
-pub const HALF_HEIGHT: f32 = (RENDERED_HEIGHT as f32 - 1.0) / 2.0;
+/// The view's size in tiles until the window has been measured, and in the headless
+/// simulation, which has no window.
+const DEFAULT_VIEW_WIDTH: usize = 36;
+const DEFAULT_VIEW_HEIGHT: usize = 28;
+/// The smallest the view gets along either side, however small the window or map.
+const MIN_VIEW_TILES: usize = 8;
+/// How much of the camera's view the tiles fill, leaving the rest to the borders. At the 4:3
+/// design resolution this comes to about the old fixed 36x28.
+const VIEW_FILL: f32 = 0.75;
+/// The tilemap is drawn as chunks of up to this many tiles, one mesh each, so the default view
+/// is four chunks.
+const CHUNK_WIDTH: usize = 18;
+const CHUNK_HEIGHT: usize = 14;
 /// Defines the size of one side of a checkerboard square, in tiles.
 pub const CHECKER_SIZE: u32 = 4;
+/// How bright tiles that were seen before but are out of sight now are drawn under the fog.
+const REMEMBERED_SHADE: f32 = 0.4;
 
-pub const WALL_COLOUR_INDEX: usize = 13; // colour of wall in terms of asset palette index
+/// How many regions each map's floor is split into.
+const FLOOR_REGIONS: usize = 6;
+/// How many floor color pairs the regions share.
+const FLOOR_PAIRS: usize = 3;
+/// How many families of similar hues the palette is split into; each round's floor is drawn
+/// from the next one, so consecutive rounds look different.
+const BIOMES: usize = 3;
+const FLOOR_DARKEN: f32 = 0.25;
+/// The least luminance difference allowed between the walls and a floor color.
+const MIN_FLOOR_CONTRAST: f32 = 0.08;
+/// How many pairs to try before settling for the one that contrasts best with the walls.
+const FLOOR_REROLLS: usize = 8;
 
-#[derive(Resource)]
+/// The size of the rendered view in tiles. Worked out from the window's aspect ratio and the
+/// zoom, so wide windows see more of the map, and clamped to the map so small maps don't leave
+/// empty space. The tilemap covers it with chunk meshes.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ViewportTiles {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for ViewportTiles {
+    fn default() -> Self {
+        ViewportTiles {
+            width: DEFAULT_VIEW_WIDTH,
+            height: DEFAULT_VIEW_HEIGHT,
+        }
+    }
+}
+
+impl ViewportTiles {
+    /// Sizes the view to fill `VIEW_FILL` of `visible` world units.
+    fn fit(visible: Vec2) -> Self {
+        let tiles =
+            |extent: f32| ((extent * VIEW_FILL / TILE_SIZE).floor() as usize).max(MIN_VIEW_TILES);
+        ViewportTiles {
+            width: tiles(visible.x),
+            height: tiles(visible.y),
+        }
+    }
+
+    /// Shrinks the view to no bigger than the map.
+    fn clamped_to(self, map_data: &MapData) -> Self {
+        ViewportTiles {
+            width: self.width.min(map_data.width as usize).max(1),
+            height: self.height.min(map_data.height as usize).max(1),
+        }
+    }
+
+    /// The view's size in tiles, as a vector.
+    pub fn size(&self) -> IVec2 {
+        IVec2::new(self.width as i32, self.height as i32)
+    }
+
+    /// Distance in tiles from the view's first tile to its centre.
+    pub fn half_width(&self) -> f32 {
+        (self.width as f32 - 1.0) / 2.0
+    }
+
+    pub fn half_height(&self) -> f32 {
+        (self.height as f32 - 1.0) / 2.0
+    }
+
+    /// `half_width` and `half_height` together.
+    pub fn half_extent(&self) -> Vec2 {
+        Vec2::new(self.half_width(), self.half_height())
+    }
+
+    /// Half the rendered area's size, in world units.
+    pub fn half_world_size(&self) -> Vec2 {
+        self.size().as_vec2() * TILE_SIZE / 2.0
+    }
+
+    /// The row-major index of a view position, or `None` if it's outside the view.
+    fn index(&self, view_pos: IVec2) -> Option<usize> {
+        let in_view = view_pos.cmpge(IVec2::ZERO).all() && view_pos.cmplt(self.size()).all();
+        in_view.then(|| view_pos.y as usize * self.width + view_pos.x as usize)
+    }
+
+    /// How many chunks the view is split into, across and down.
+    fn chunks(&self) -> IVec2 {
+        IVec2::new(
+            self.width.div_ceil(CHUNK_WIDTH) as i32,
+            self.height.div_ceil(CHUNK_HEIGHT) as i32,
+        )
+    }
+
+    /// The index of the chunk holding the tile at view `index`.
+    fn chunk_of(&self, index: usize) -> usize {
+        let (x, y) = (index % self.width, index / self.width);
+        (y / CHUNK_HEIGHT) * self.chunks().x as usize + x / CHUNK_WIDTH
+    }
+
+    /// Returns true if a world-space position lies within the rendered tile area,
+    /// allowing a margin of one tile so partially visible entities still count.
+    pub fn is_in_view(&self, pos: Vec2) -> bool {
+        let half = self.half_world_size() + Vec2::splat(TILE_SIZE);
+        pos.x.abs() <= half.x && pos.y.abs() <= half.y
+    }
+
+    /// Converts a position in map coordinates (tiles, possibly fractional) to world space.
+    pub fn map_to_world(
+        &self,
+        map_pos: Vec2,
+        map_offset: &MapOffset,
+        tile_offset: &TileOffset,
+    ) -> Vec2 {
+        (map_pos - map_offset.0.as_vec2() - self.half_extent()) * TILE_SIZE + tile_offset.0
+    }
+
+    /// Converts a world-space position back to map coordinates (tiles, fractional).
+    pub fn world_to_map(
+        &self,
+        world_pos: Vec2,
+        map_offset: &MapOffset,
+        tile_offset: &TileOffset,
+    ) -> Vec2 {
+        (world_pos - tile_offset.0) / TILE_SIZE + map_offset.0.as_vec2() + self.half_extent()
+    }
+
+    /// The map position at the center of the view.
+    pub fn view_center(&self, map_offset: &MapOffset, tile_offset: &TileOffset) -> Vec2 {
+        self.world_to_map(Vec2::ZERO, map_offset, tile_offset)
+    }
+
+    /// The largest map offset that keeps the view on the map.
+    pub fn max_offset(&self, map_data: &MapData) -> IVec2 {
+        (IVec2::new(map_data.width as i32, map_data.height as i32) - self.size()).max(IVec2::ZERO)
+    }
+}
+
+/// The world position of the centre of map tile `grid_pos`, where anything standing on it is
+/// drawn. The inverse of `grid_from_world`.
+pub fn world_from_grid(
+    grid_pos: IVec2,
+    viewport: &ViewportTiles,
+    map_offset: &MapOffset,
+    tile_offset: &TileOffset,
+) -> Vec2 {
+    viewport.map_to_world(grid_pos.as_vec2(), map_offset, tile_offset)
+}
+
+/// The map tile a world position falls in, which may be off the map.
+pub fn grid_from_world(
+    world_pos: Vec2,
+    viewport: &ViewportTiles,
+    map_offset: &MapOffset,
+    tile_offset: &TileOffset,
+) -> IVec2 {
+    viewport
+        .world_to_map(world_pos, map_offset, tile_offset)
+        .round()
+        .as_ivec2()
+}
+
+/// The view's placement on the map, for systems that convert between map and world positions.
+/// Positioned features should go through this rather than redoing the arithmetic, which is
+/// where half-tile misalignments come from.
+///
+/// Map positions count y up from the bottom, the same way as world space, so nothing here
+/// flips y; the only flip is in how `MapData::index` stores rows.
+#[derive(SystemParam)]
+pub struct GridSpace<'w> {
+    pub viewport: Res<'w, ViewportTiles>,
+    pub map_offset: Res<'w, MapOffset>,
+    pub tile_offset: Res<'w, TileOffset>,
+}
+
+impl GridSpace<'_> {
+    /// The world position of a map position, which may be fractional: a tile's centre for a
+    /// whole position, or part-way along a move.
+    pub fn grid_to_world(&self, grid: Vec2) -> Vec2 {
+        self.viewport
+            .map_to_world(grid, &self.map_offset, &self.tile_offset)
+    }
+
+    /// The map position of a world position, fractional; the inverse of `grid_to_world`.
+    pub fn world_to_grid(&self, world: Vec2) -> Vec2 {
+        self.viewport
+            .world_to_map(world, &self.map_offset, &self.tile_offset)
+    }
+
+    /// The world position of the centre of map tile `tile`.
+    pub fn tile_to_world(&self, tile: IVec2) -> Vec2 {
+        world_from_grid(tile, &self.viewport, &self.map_offset, &self.tile_offset)
+    }
+
+    /// The map tile a world position falls in.
+    pub fn world_to_tile(&self, world: Vec2) -> IVec2 {
+        grid_from_world(world, &self.viewport, &self.map_offset, &self.tile_offset)
+    }
+
+    /// The map tiles the tilemap is drawing. Both corners are inside, as `IRect::contains`
+    /// expects, so `max` is the last tile in view rather than one past it.
+    pub fn viewport_rect_in_map(&self) -> IRect {
+        let min = self.map_offset.0;
+        IRect::from_corners(min, min + self.viewport.size() - IVec2::ONE)
+    }
+}
+
+#[derive(Resource, PartialEq)]
 pub struct MapOffset(pub IVec2);
 
 #[derive(Resource)]
 pub struct TileOffset(pub Vec2);
 
-/// A resource to hold the two darkened, randomized colors for the floor pattern.
+/// The darkened, randomized color pairs for the floor's checkerboard, and which map region
+/// uses which pair.
 #[derive(Resource)]
 pub struct FloorPalette {
-    pub color_a: Color,
-    pub color_b: Color,
+    pub pairs: Vec<(Color, Color)>,
+    /// Each tile's region, indexed like `MapData::is_wall`.
+    pub regions: Vec<u8>,
+    /// The two colors lava alternates between.
+    pub lava: [Color; 2],
+}
+
+impl FloorPalette {
+    /// The checkerboard pair for the region `map_pos` is in.
+    fn pair_at(&self, map_data: &MapData, map_pos: IVec2) -> (Color, Color) {
+        let region = map_data
+            .index(map_pos)
+            .and_then(|idx| self.regions.get(idx))
+            .map_or(0, |&region| region as usize);
+        self.pairs
+            .get(region % self.pairs.len().max(1))
+            .copied()
+            .unwrap_or((Color::BLACK, Color::BLACK))
+    }
 }
 
+/// One mesh drawing a block of the view's tiles, starting at view position `origin`.
 #[derive(Component)]
-pub struct Tile {
-    pub grid_pos: IVec2,
+pub struct TileChunk {
+    index: usize,
+    origin: IVec2,
+    size: IVec2,
 }
 
 #[derive(Component)]
 pub struct BasePosition(pub Vec2);
 
+/// What a rendered tile shows: its color, for a wall which of its neighbours are walls, and
+/// whether it's lava in sight, which `animate_lava_tiles` keeps pulsing.
+#[derive(Clone, Copy, PartialEq)]
+struct TileLook {
+    color: Color,
+    wall_mask: Option<u8>,
+    animated: bool,
+}
+
+impl TileLook {
+    const EMPTY: TileLook = TileLook {
+        color: Color::NONE,
+        wall_mask: None,
+        animated: false,
+    };
+
+    fn at(
+        map_pos: IVec2,
+        game_assets: &GameAssets,
+        map_data: &MapData,
+        floor_palette: &FloorPalette,
+        fog: &FogView,
+        pulse: &LavaPulse,
+    ) -> Self {
+        let Some(idx) = map_data.index(map_pos) else {
+            return TileLook::EMPTY;
+        };
+        let color = get_tile_color(map_pos, game_assets, map_data, floor_palette, pulse);
+        let wall_mask = map_data.is_wall[idx].then(|| wall_mask(map_data, map_pos));
+        match fog.state(map_data, map_pos) {
+            FogState::Visible => TileLook {
+                color,
+                wall_mask,
+                animated: map_data.is_lava(map_pos),
+            },
+            // Remembered lava is drawn still.
+            FogState::Remembered => TileLook {
+                color: darken(color, REMEMBERED_SHADE),
+                wall_mask,
+                animated: false,
+            },
+            FogState::Unseen => TileLook {
+                color: Color::BLACK,
+                wall_mask: None,
+                animated: false,
+            },
+        }
+    }
+}
+
+/// The looks currently painted on the tiles, indexed by view position, and the map offset
+/// they were painted for. A scroll reuses the looks that are still in view and only
+/// recomputes the rows and columns that scrolled in, and only the chunks with a changed look
+/// have their meshes rebuilt.
+#[derive(Resource, Default)]
+pub struct PaintedTiles {
+    offset: IVec2,
+    /// The view size the chunks were spawned for.
+    size: ViewportTiles,
+    looks: Vec<TileLook>,
+    /// The view indices of the tiles showing animated looks, so the animation only touches
+    /// those.
+    animated: Vec<usize>,
+    /// Which chunks have looks their meshes don't show yet, indexed like `TileChunk::index`.
+    dirty: Vec<bool>,
+}
+
+impl PaintedTiles {
+    /// Forgets what was painted, ready for a full repaint at `offset`.
+    fn clear(&mut self, offset: IVec2) {
+        self.offset = offset;
+        self.looks = vec![TileLook::EMPTY; self.size.width * self.size.height];
+        self.animated.clear();
+        let chunks = self.size.chunks();
+        self.dirty = vec![true; (chunks.x * chunks.y) as usize];
+    }
+
+    /// Records `look` for the tile at view `index`, marking its chunk for a rebuild if the
+    /// look changed.
+    fn set(&mut self, index: usize, look: TileLook) {
+        if self.looks[index] != look {
+            self.looks[index] = look;
+            let chunk = self.size.chunk_of(index);
+            self.dirty[chunk] = true;
+        }
+        if look.animated {
+            self.animated.push(index);
+        }
+    }
+
+    /// The look at view position `view_pos`, or `TileLook::EMPTY` outside the view.
+    fn look(&self, view_pos: IVec2) -> TileLook {
+        self.size
+            .index(view_pos)
+            .and_then(|index| self.looks.get(index))
+            .copied()
+            .unwrap_or(TileLook::EMPTY)
+    }
+}
+
 pub struct TilemapPlugin;
 
 impl Plugin for TilemapPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(MapOffset(IVec2::ZERO))
             .insert_resource(TileOffset(Vec2::ZERO))
+            .init_resource::<PaintedTiles>()
+            .init_resource::<ViewportTiles>()
             .add_systems(
                 OnEnter(GameState::Playing),
                 (
+                    update_viewport_tiles,
                     setup_initial_offset,
                     setup_floor_palette, // Create the random palette
                     spawn_tilemap,
                 )
                     .chain()
-                    .after(generate_map),
+                    .after(generate_map)
+                    // A carried-over arena keeps its palette and tiles from the last round.
+                    .run_if(not(carry_over_arena)),
             )
             .add_systems(
                 Update,
-                ((update_tile_positions, update_tile_colors)
-                    .run_if(resource_changed::<MapOffset>.or(resource_changed::<TileOffset>)),)
+                update_viewport_tiles.run_if(
+                    resource_exists_and_changed::<Resolution>
+                        .or(resource_exists_and_changed::<MapData>),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Runs before anything else reads the tiles, so they're never out of step
+                    // with the viewport.
+                    (despawn_tilemap, recenter_view, spawn_tilemap)
+                        .chain()
+                        .run_if(viewport_resized),
+                    update_tile_positions
+                        .run_if(resource_changed::<MapOffset>.or(resource_changed::<TileOffset>)),
+                    // Only whole-tile scrolls change the colors; the sub-tile lerp doesn't.
+                    scroll_tile_colors.run_if(resource_changed::<MapOffset>),
+                    // Picks up walls added or removed at runtime, and the fog moving.
+                    update_tile_colors.in_set(TileRepaint).run_if(
+                        resource_exists_and_changed::<MapData>
+                            .or(resource_changed::<TileVisibility>)
+                            .or(resource_changed::<FogOfWar>),
+                    ),
+                    animate_lava_tiles.run_if(resource_changed::<LavaPulse>),
+                    // Also after the palette's repaint below.
+                    rebuild_dirty_chunks.run_if(chunks_dirty).after(TileRepaint),
+                )
                     .chain()
+                    .after(update_viewport_tiles)
                     .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    refresh_floor_palette,
+                    update_tile_colors.in_set(TileRepaint),
+                )
+                    .chain()
+                    .after(recolor_entities)
+                    .run_if(resource_exists::<FloorPalette>.and(on_event::<PaletteChanged>)),
             );
     }
 }
 
-/// A new system that runs once to create and store the floor palette.
-/// It picks two random colors, excluding the wall color, darkens them, and inserts them as a resource.
-fn setup_floor_palette(
+/// Runs once per map to split it into regions and roll their floor colors.
+pub fn setup_floor_palette(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
+    map_data: Res<MapData>,
+    round: Res<CurrentRound>,
+    mut rng: GlobalEntropy<WyRand>,
+) {
+    let regions = map_data.roll_regions(&mut rng, FLOOR_REGIONS);
+    let pairs = roll_floor_pairs(&mut rng, &game_assets.palette, round.0);
+    commands.insert_resource(FloorPalette {
+        pairs,
+        regions,
+        lava: lava_colors(&game_assets.palette),
+    });
+}
+
+/// Re-rolls the floor colors from the new palette when it changes mid-round, keeping the
+/// regions.
+fn refresh_floor_palette(
+    mut floor_palette: ResMut<FloorPalette>,
+    game_assets: Res<GameAssets>,
+    round: Res<CurrentRound>,
     mut rng: GlobalEntropy<WyRand>,
 ) {
-    // Get the wall color to exclude
-    let wall_color = game_assets.palette.colors[WALL_COLOUR_INDEX];
+    floor_palette.pairs = roll_floor_pairs(&mut rng, &game_assets.palette, round.0);
+    floor_palette.lava = lava_colors(&game_assets.palette);
+}
 
-    // Pick two random different colors, excluding the wall color
-    let mut color_a = random_colour_except(&mut rng, &game_assets, wall_color);
-    let mut color_b = random_colour_except(&mut rng, &game_assets, wall_color);
-    while color_a == color_b {
-        color_b = random_colour_except(&mut rng, &game_assets, wall_color);
+/// The palette's two brightest colors other than the wall's, for lava to alternate between.
+fn lava_colors(palette: &Palette) -> [Color; 2] {
+    let wall_color = palette.wall_color();
+    let mut colors: Vec<Color> = palette
+        .colors
+        .iter()
+        .copied()
+        .filter(|&color| color != wall_color)
+        .collect();
+    colors.sort_by(|a, b| b.luminance().total_cmp(&a.luminance()));
+    match colors[..] {
+        [first, second, ..] => [first, second],
+        [only] => [only, wall_color],
+        [] => [wall_color, wall_color],
     }
+}
 
-    // Darken them
-    let darken_factor = 0.25;
-    color_a = darken(color_a, darken_factor);
-    color_b = darken(color_b, darken_factor);
+fn roll_floor_pairs(
+    rng: &mut Entropy<WyRand>,
+    palette: &Palette,
+    round: u32,
+) -> Vec<(Color, Color)> {
+    let wall_color = palette.wall_color();
+    let colors = biome_colors(palette, round);
+    (0..FLOOR_PAIRS)
+        .map(|_| roll_floor_pair(rng, &colors, wall_color))
+        .collect()
+}
 
-    // Insert them into a resource
-    commands.insert_resource(FloorPalette { color_a, color_b });
+/// The palette's colors other than the wall's, split into `BIOMES` runs of similar hue. Round
+/// 1 draws from the first run, round 2 from the next, and so on.
+fn biome_colors(palette: &Palette, round: u32) -> Vec<Color> {
+    let wall_color = palette.wall_color();
+    let mut colors: Vec<Color> = palette
+        .colors
+        .iter()
+        .copied()
+        .filter(|&color| color != wall_color)
+        .collect();
+    colors.sort_by(|a, b| a.hue().total_cmp(&b.hue()));
+    let biome = round.saturating_sub(1) as usize % BIOMES;
+    let family = colors
+        .chunks(colors.len().div_ceil(BIOMES).max(1))
+        .nth(biome)
+        .map(<[Color]>::to_vec)
+        .unwrap_or_default();
+    // Too few to make a pair from, so use them all.
+    if family.len() < 2 {
+        colors
+    } else {
+        family
+    }
+}
+
+/// Picks two different colors from `colors` and darkens them, rerolling if either is too
+/// close in brightness to the walls. Palettes always have enough colors for a pair.
+fn roll_floor_pair(
+    rng: &mut Entropy<WyRand>,
+    colors: &[Color],
+    wall_color: Color,
+) -> (Color, Color) {
+    let contrast = |floor: Color| (wall_color.luminance() - floor.luminance()).abs();
+    let mut best = None;
+    let mut best_contrast = f32::MIN;
+    for _ in 0..FLOOR_REROLLS {
+        let first = random_index(rng, colors.len());
+        let second = (first + 1 + random_index(rng, colors.len() - 1)) % colors.len();
+        let color_a = darken(colors[first], FLOOR_DARKEN);
+        let color_b = darken(colors[second], FLOOR_DARKEN);
+        let worst = contrast(color_a).min(contrast(color_b));
+        if worst >= MIN_FLOOR_CONTRAST {
+            return (color_a, color_b);
+        }
+        if worst > best_contrast {
+            best_contrast = worst;
+            best = Some((color_a, color_b));
+        }
+    }
+    best.unwrap_or_else(|| {
+        let color = darken(*random_pick(rng, colors), FLOOR_DARKEN);
+        (color, color)
+    })
 }
 
 fn darken(c: Color, darken_factor: f32) -> Color {
@@ -103,55 +575,200 @@ fn darken(c: Color, darken_factor: f32) -> Color {
     }
 }
 
+/// Sizes the view for the window and zoom, no bigger than the map once there is one. Without
+/// a window (in the headless simulation) the default size is used.
+fn update_viewport_tiles(
+    resolution: Option<Res<Resolution>>,
+    map_data: Option<Res<MapData>>,
+    mut viewport: ResMut<ViewportTiles>,
+) {
+    let fitted = resolution.map_or_else(ViewportTiles::default, |resolution| {
+        ViewportTiles::fit(resolution.visible_world_size())
+    });
+    let fitted = match map_data {
+        Some(map_data) => fitted.clamped_to(&map_data),
+        None => fitted,
+    };
+    viewport.set_if_neq(fitted);
+}
+
+/// Whether the chunks were spawned for a different view size than the current one.
+fn viewport_resized(viewport: Res<ViewportTiles>, painted: Res<PaintedTiles>) -> bool {
+    painted.size != *viewport
+}
+
+/// Clears away the chunks so they can be spawned again at the new view size.
+fn despawn_tilemap(mut commands: Commands, chunks: Query<Entity, With<TileChunk>>) {
+    for entity in &chunks {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Puts the player back in the middle of a resized view.
+fn recenter_view(
+    viewport: Res<ViewportTiles>,
+    map_data: Res<MapData>,
+    player: Query<&GridMover, With<Player>>,
+    mut map_offset: ResMut<MapOffset>,
+    mut tile_offset: ResMut<TileOffset>,
+) {
+    if let Ok(mover) = player.single() {
+        center_view_on(
+            mover.grid_pos,
+            &viewport,
+            &map_data,
+            &mut map_offset,
+            &mut tile_offset,
+        );
+    }
+}
+
 // Center map in viewport
-fn setup_initial_offset(map_data: Res<MapData>, mut map_offset: ResMut<MapOffset>) {
-    let view_w = RENDERED_WIDTH as i32;
-    let view_h = RENDERED_HEIGHT as i32;
-    let map_w = map_data.width as i32;
-    let map_h = map_data.height as i32;
-    map_offset.0.x = ((map_w - view_w) / 2).max(0);
-    map_offset.0.y = ((map_h - view_h) / 2).max(0);
+fn setup_initial_offset(
+    map_data: Res<MapData>,
+    viewport: Res<ViewportTiles>,
+    mut map_offset: ResMut<MapOffset>,
+) {
+    map_offset.0 = viewport.max_offset(&map_data) / 2;
 }
 
-// Spawns the viewable section of the tilemap, with each visible tile being an individual sprite entity
-fn spawn_tilemap(
+/// Spawns the viewable section of the tilemap as a grid of chunk meshes, each drawing its
+/// tiles as textured quads tinted by vertex color.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_tilemap(
     mut commands: Commands,
     game_assets: Res<GameAssets>,
     map_data: Res<MapData>,
     map_offset: Res<MapOffset>,
     floor_palette: Res<FloorPalette>, // Get the newly created floor palette
+    atlas: Option<Res<GameAtlas>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    fog: FogView,
+    pulse: Res<LavaPulse>,
+    viewport: Res<ViewportTiles>,
+    tile_offset: Res<TileOffset>,
+    mut painted: ResMut<PaintedTiles>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    let wall_texture = game_assets.wall_texture.clone();
-
-    for gx in 0..RENDERED_WIDTH {
-        for gy in 0..RENDERED_HEIGHT {
-            let base_x = (gx as f32 - HALF_WIDTH) * TILE_SIZE;
-            let base_y = (gy as f32 - HALF_HEIGHT) * TILE_SIZE;
-            let base_pos = Vec2::new(base_x, base_y);
+    let textures = TileTextures::new(atlas.as_deref(), &layouts, &game_assets);
+    painted.size = *viewport;
+    painted.clear(map_offset.0);
 
-            let grid_pos = IVec2::new(gx as i32, gy as i32);
-            let map_pos = grid_pos + map_offset.0;
+    for gy in 0..viewport.height {
+        for gx in 0..viewport.width {
+            let map_pos = IVec2::new(gx as i32, gy as i32) + map_offset.0;
             // Pass the palette to the color logic function
-            let color = get_tile_color(map_pos, &game_assets, &map_data, &floor_palette);
+            let look = TileLook::at(
+                map_pos,
+                &game_assets,
+                &map_data,
+                &floor_palette,
+                &fog,
+                &pulse,
+            );
+            painted.set(gy * viewport.width + gx, look);
+        }
+    }
 
+    let material = materials.add(ColorMaterial {
+        texture: Some(textures.image.clone()),
+        alpha_mode: AlphaMode2d::Blend,
+        ..default()
+    });
+    let chunks = viewport.chunks();
+    for cy in 0..chunks.y {
+        for cx in 0..chunks.x {
+            let origin = IVec2::new(cx * CHUNK_WIDTH as i32, cy * CHUNK_HEIGHT as i32);
+            let chunk = TileChunk {
+                index: (cy * chunks.x + cx) as usize,
+                origin,
+                size: IVec2::new(CHUNK_WIDTH as i32, CHUNK_HEIGHT as i32)
+                    .min(viewport.size() - origin),
+            };
+            // The chunk sits where its first tile would, and its quads are laid out from there.
+            let base_pos = (origin.as_vec2() - viewport.half_extent()) * TILE_SIZE;
             commands.spawn((
-                Sprite {
-                    image: wall_texture.clone(),
-                    color,
-                    ..Default::default()
-                },
-                Transform::from_xyz(base_x, base_y, 0.0),
-                Tile { grid_pos },
+                Mesh2d(meshes.add(chunk_mesh(&chunk, &painted, &textures))),
+                MeshMaterial2d(material.clone()),
+                Transform::from_translation((base_pos + tile_offset.0).extend(0.0)),
+                chunk,
                 BasePosition(base_pos),
+                ArenaEntity,
                 GameEntity,
             ));
         }
     }
+    painted.dirty.fill(false);
+    commands.insert_resource(textures);
 }
 
+/// Corners of a tile's quad in half tiles, counter-clockwise from the bottom left.
+const QUAD_CORNERS: [Vec2; 4] = [
+    Vec2::new(-1.0, -1.0),
+    Vec2::new(1.0, -1.0),
+    Vec2::new(1.0, 1.0),
+    Vec2::new(-1.0, 1.0),
+];
+
+/// Builds the mesh for `chunk`, one quad per tile.
+fn chunk_mesh(chunk: &TileChunk, painted: &PaintedTiles, textures: &TileTextures) -> Mesh {
+    let tiles = (chunk.size.x * chunk.size.y) as usize;
+    let mut positions = Vec::with_capacity(tiles * 4);
+    let mut indices = Vec::with_capacity(tiles * 6);
+    for y in 0..chunk.size.y {
+        for x in 0..chunk.size.x {
+            let center = IVec2::new(x, y).as_vec2() * TILE_SIZE;
+            let first = positions.len() as u32;
+            for corner in QUAD_CORNERS {
+                positions.push((center + corner * TILE_SIZE / 2.0).extend(0.0).to_array());
+            }
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+    }
+    let mut mesh = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_indices(Indices::U32(indices));
+    write_chunk_looks(&mut mesh, chunk, painted, textures);
+    mesh
+}
+
+/// Writes the painted looks of `chunk`'s tiles into its mesh: the color into every corner,
+/// and the wall image for the tile's wall mask as texture coordinates.
+fn write_chunk_looks(
+    mesh: &mut Mesh,
+    chunk: &TileChunk,
+    painted: &PaintedTiles,
+    textures: &TileTextures,
+) {
+    let tiles = (chunk.size.x * chunk.size.y) as usize;
+    let mut colors = Vec::with_capacity(tiles * 4);
+    let mut uvs = Vec::with_capacity(tiles * 4);
+    for y in 0..chunk.size.y {
+        for x in 0..chunk.size.x {
+            let look = painted.look(chunk.origin + IVec2::new(x, y));
+            colors.extend([look.color.to_linear().to_f32_array(); 4]);
+            // Image rows count down from the top, so the bottom corners take the larger v.
+            let rect = textures.get(look.wall_mask);
+            uvs.extend([
+                [rect.min.x, rect.max.y],
+                [rect.max.x, rect.max.y],
+                [rect.max.x, rect.min.y],
+                [rect.min.x, rect.min.y],
+            ]);
+        }
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+}
+
+/// Moves the chunks for sub-tile scrolling; their meshes stay as they are.
 fn update_tile_positions(
     tile_offset: Res<TileOffset>,
-    mut query: Query<(&BasePosition, &mut Transform), With<Tile>>,
+    mut query: Query<(&BasePosition, &mut Transform), With<TileChunk>>,
 ) {
     for (base_pos, mut transform) in query.iter_mut() {
         transform.translation = Vec3::new(
@@ -168,6 +785,7 @@ fn get_tile_color(
     game_assets: &GameAssets,
     map_data: &MapData,
     floor_palette: &FloorPalette,
+    pulse: &LavaPulse,
 ) -> Color {
     // First, check if the position is within the map's boundaries.
     // If not, return a transparent color to avoid drawing outside the map area.
@@ -188,32 +806,233 @@ fn get_tile_color(
 
     if is_wall {
         // It's a wall, so calculate its color based on its position.
-        let index = WALL_COLOUR_INDEX; // uncomment if you want walls to use entire palette -> ((map_pos.x.abs() + map_pos.y.abs()) as usize) % game_assets.palette.colors.len();
+        let index = game_assets.palette.wall_index; // uncomment if you want walls to use entire palette -> ((map_pos.x.abs() + map_pos.y.abs()) as usize) % game_assets.palette.colors.len();
         game_assets.palette.colors[index]
+    } else if map_data.is_lava.get(idx).copied().unwrap_or(false) {
+        floor_palette.lava[pulse.phase % 2]
     } else {
         // It's a floor tile, so apply the checkerboard pattern.
         // Use Euclidean division to handle potential negative coordinates gracefully.
         let checker_x = map_pos.x.div_euclid(CHECKER_SIZE as i32);
         let checker_y = map_pos.y.div_euclid(CHECKER_SIZE as i32);
+        let (color_a, color_b) = floor_palette.pair_at(map_data, map_pos);
         if (checker_x + checker_y) % 2 == 0 {
-            floor_palette.color_a
+            color_a
         } else {
-            floor_palette.color_b
+            color_b
         }
     }
 }
 
-/// Updated to pass the FloorPalette resource to the color logic.
-fn update_tile_colors(
+/// Both of the places `update_tile_colors` runs from, for ordering against it: it is added once
+/// for the map and fog changing and once for the palette changing.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct TileRepaint;
+
+/// Repaints every tile, for when the map, the fog or the palette changes. Only the chunks
+/// where a look actually changed are rebuilt.
+#[allow(clippy::too_many_arguments)]
+pub fn update_tile_colors(
     map_offset: Res<MapOffset>,
     game_assets: Res<GameAssets>,
     map_data: Res<MapData>,
     floor_palette: Res<FloorPalette>, // Get the floor palette
-    mut query: Query<(&Tile, &mut Sprite)>,
+    fog: FogView,
+    pulse: Res<LavaPulse>,
+    mut painted: ResMut<PaintedTiles>,
+    timings: Res<SystemTimings>,
+) {
+    let _span = timings.span("update_tile_colors");
+    if painted.offset != map_offset.0 {
+        painted.clear(map_offset.0);
+    } else {
+        painted.animated.clear();
+    }
+    let size = painted.size;
+    for gy in 0..size.height {
+        for gx in 0..size.width {
+            let map_pos = map_offset.0 + IVec2::new(gx as i32, gy as i32);
+            // Pass the palette to the color logic function
+            let look = TileLook::at(
+                map_pos,
+                &game_assets,
+                &map_data,
+                &floor_palette,
+                &fog,
+                &pulse,
+            );
+            painted.set(gy * size.width + gx, look);
+        }
+    }
+}
+
+/// Repaints the tiles after the view scrolls by whole tiles. Looks still in view are shifted
+/// across from the tiles that showed them before, so only the newly exposed rows and columns
+/// are computed.
+#[allow(clippy::too_many_arguments)]
+fn scroll_tile_colors(
+    map_offset: Res<MapOffset>,
+    game_assets: Res<GameAssets>,
+    map_data: Res<MapData>,
+    floor_palette: Res<FloorPalette>,
+    fog: FogView,
+    pulse: Res<LavaPulse>,
+    mut painted: ResMut<PaintedTiles>,
+    timings: Res<SystemTimings>,
+) {
+    let _span = timings.span("scroll_tile_colors");
+    let delta = map_offset.0 - painted.offset;
+    let size = painted.size;
+    if delta == IVec2::ZERO || painted.looks.len() != size.width * size.height {
+        return;
+    }
+    let previous = painted.looks.clone();
+    painted.offset = map_offset.0;
+    painted.animated.clear();
+    for gy in 0..size.height {
+        for gx in 0..size.width {
+            let view_pos = IVec2::new(gx as i32, gy as i32);
+            let look = match size.index(view_pos + delta) {
+                Some(old_index) => previous[old_index],
+                None => TileLook::at(
+                    map_offset.0 + view_pos,
+                    &game_assets,
+                    &map_data,
+                    &floor_palette,
+                    &fog,
+                    &pulse,
+                ),
+            };
+            painted.set(gy * size.width + gx, look);
+        }
+    }
+}
+
+/// Steps the lava in view to the pulse's current color. Only the tiles `PaintedTiles` tracks
+/// as animated are touched, and only their chunks rebuilt.
+fn animate_lava_tiles(
+    pulse: Res<LavaPulse>,
+    floor_palette: Res<FloorPalette>,
+    mut painted: ResMut<PaintedTiles>,
+) {
+    let color = floor_palette.lava[pulse.phase % 2];
+    let PaintedTiles {
+        size,
+        looks,
+        animated,
+        dirty,
+        ..
+    } = &mut *painted;
+    for &index in animated.iter() {
+        if let Some(look) = looks.get_mut(index) {
+            look.color = color;
+            dirty[size.chunk_of(index)] = true;
+        }
+    }
+}
+
+/// Whether any chunk's mesh is behind its looks.
+fn chunks_dirty(painted: Res<PaintedTiles>) -> bool {
+    painted.dirty.contains(&true)
+}
+
+/// Rewrites the colors and texture coordinates of the chunks whose looks changed.
+fn rebuild_dirty_chunks(
+    textures: Res<TileTextures>,
+    mut painted: ResMut<PaintedTiles>,
+    chunks: Query<(&TileChunk, &Mesh2d)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    timings: Res<SystemTimings>,
 ) {
-    for (tile, mut sprite) in query.iter_mut() {
-        let map_pos = map_offset.0 + tile.grid_pos;
-        // Pass the palette to the color logic function
-        sprite.color = get_tile_color(map_pos, &game_assets, &map_data, &floor_palette);
+    let _span = timings.span("rebuild_dirty_chunks");
+    for (chunk, mesh) in &chunks {
+        if !painted.dirty.get(chunk.index).copied().unwrap_or(false) {
+            continue;
+        }
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            write_chunk_looks(mesh, chunk, &painted, &textures);
+        }
+    }
+    painted.dirty.fill(false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// A 5 by 3 view scrolled to (10, 20) on the map and shifted a little mid-scroll.
+    fn scrolled_world() -> World {
+        let mut world = World::new();
+        world.insert_resource(ViewportTiles {
+            width: 5,
+            height: 3,
+        });
+        world.insert_resource(MapOffset(IVec2::new(10, 20)));
+        world.insert_resource(TileOffset(Vec2::new(8.0, -4.0)));
+        world
+    }
+
+    #[test]
+    fn tile_centres_are_pinned() {
+        let mut world = scrolled_world();
+        let check = |space: GridSpace| {
+            // The view's middle tile sits on the world origin, shifted by the tile offset.
+            assert_eq!(
+                space.tile_to_world(IVec2::new(12, 21)),
+                Vec2::new(8.0, -4.0)
+            );
+            assert_eq!(
+                space.tile_to_world(IVec2::new(13, 20)),
+                Vec2::new(72.0, -68.0)
+            );
+            assert_eq!(
+                space.tile_to_world(IVec2::new(10, 20)),
+                Vec2::new(-120.0, -68.0)
+            );
+            assert_eq!(
+                space.grid_to_world(Vec2::new(12.5, 21.0)),
+                Vec2::new(8.0 + TILE_SIZE / 2.0, -4.0)
+            );
+        };
+        world.run_system_once(check).unwrap();
+    }
+
+    #[test]
+    fn world_to_grid_inverts_grid_to_world() {
+        let mut world = scrolled_world();
+        let check = |space: GridSpace| {
+            for grid in [
+                Vec2::new(12.0, 21.0),
+                Vec2::new(10.25, 22.5),
+                Vec2::new(-3.0, 40.0),
+            ] {
+                let back = space.world_to_grid(space.grid_to_world(grid));
+                assert!(
+                    (back - grid).length() < 1e-4,
+                    "{} came back as {}",
+                    grid,
+                    back
+                );
+            }
+            // Anywhere within half a tile of a centre belongs to that tile.
+            let centre = space.tile_to_world(IVec2::new(13, 20));
+            let nudge = Vec2::new(0.45, -0.45) * TILE_SIZE;
+            assert_eq!(space.world_to_tile(centre + nudge), IVec2::new(13, 20));
+            assert_eq!(space.world_to_tile(centre - nudge), IVec2::new(13, 20));
+        };
+        world.run_system_once(check).unwrap();
+    }
+
+    #[test]
+    fn viewport_rect_includes_both_corners() {
+        let mut world = scrolled_world();
+        let rect = world
+            .run_system_once(|space: GridSpace| space.viewport_rect_in_map())
+            .unwrap();
+        assert_eq!(rect.min, IVec2::new(10, 20));
+        assert_eq!(rect.max, IVec2::new(14, 22));
+        assert!(rect.contains(IVec2::new(14, 22)));
+        assert!(!rect.contains(IVec2::new(15, 22)));
     }
 }
@@ -0,0 +1,361 @@
+// time_attack.rs
+
+//! Time attack: a short list of fixed courses, each a single round on a known seed, raced
+//! against a countdown for gold, silver and bronze medals.
+//!
+//! The courses and their par times come from `assets/courses.ron`, compiled into the game,
+//! unless a `courses.ron` in the working directory replaces them. Every attempt at a course,
+//! whether started from the title screen, a restart or a retry, uses the course's seed and so
+//! gets the same map. The countdown is the round timer subtracted from the course's time
+//! limit, and when it runs out the run is over. Clearing the course shows its result here in
+//! place of the victory banner and the bigger next round, and the clear time is kept in the
+//! save file.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::assets::GameAssets;
+use crate::components::{EnemyGroupSize, GameMode, GameState};
+use crate::config::load_ron;
+use crate::explosion::PlayerIsDead;
+use crate::input::{key_label, InputMap};
+use crate::palette::Palette;
+use crate::quicksave::PendingRestore;
+use crate::round_intro::round_in_progress;
+use crate::round_timer::{format_time, tick_round_timer, RoundTimer};
+use crate::save::SaveData;
+use crate::seed::{seed_round, MapSeed};
+use crate::title::MenuInput;
+
+/// Where a replacement course list can be put, relative to the working directory.
+pub const COURSES_PATH: &str = "courses.ron";
+
+/// The course list shipped with the game.
+const BUILT_IN_COURSES: &str = include_str!("../assets/courses.ron");
+
+/// Seconds before the course result accepts input, so a held fire key doesn't skip it.
+const RESULT_INPUT_DELAY: f32 = 0.5;
+
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Courses::load())
+            .init_resource::<CurrentCourse>()
+            .add_systems(OnEnter(GameState::Playing), start_course.before(seed_round))
+            .add_systems(
+                OnEnter(GameState::Victory),
+                (record_course_time, spawn_course_result)
+                    .chain()
+                    .run_if(resource_exists::<ActiveCourse>),
+            )
+            .add_systems(OnExit(GameState::Victory), despawn_course_result)
+            .add_systems(
+                Update,
+                (
+                    end_on_time_up
+                        .after(tick_round_timer)
+                        .run_if(round_in_progress)
+                        .run_if(not(resource_exists::<PlayerIsDead>))
+                        .run_if(resource_exists::<ActiveCourse>)
+                        .run_if(in_state(GameState::Playing)),
+                    handle_course_result_input
+                        .run_if(resource_exists::<CourseResult>)
+                        .run_if(in_state(GameState::Victory)),
+                ),
+            );
+    }
+}
+
+/// One time attack course: a fixed map and the times to beat on it.
+#[derive(Deserialize, Clone, Debug)]
+pub struct Course {
+    pub name: String,
+    pub seed: u64,
+    /// Number of enemies of each type the round starts with, before difficulty.
+    pub group_size: u32,
+    /// Seconds allowed before time runs out.
+    pub time_limit: f32,
+    pub par: Par,
+}
+
+impl Course {
+    /// Seconds left on the countdown after `elapsed` seconds of the round.
+    pub fn time_left(&self, elapsed: f32) -> f32 {
+        (self.time_limit - elapsed).max(0.0)
+    }
+}
+
+/// The clear times, in seconds, needed for each medal.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Par {
+    pub gold: f32,
+    pub silver: f32,
+    pub bronze: f32,
+}
+
+impl Par {
+    /// The medal earned by clearing the course in `seconds`, if any.
+    pub fn medal(&self, seconds: f32) -> Option<Medal> {
+        if seconds <= self.gold {
+            Some(Medal::Gold)
+        } else if seconds <= self.silver {
+            Some(Medal::Silver)
+        } else if seconds <= self.bronze {
+            Some(Medal::Bronze)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Medal {
+    Gold,
+    Silver,
+    Bronze,
+}
+
+impl Medal {
+    pub fn label(self) -> &'static str {
+        match self {
+            Medal::Gold => "GOLD",
+            Medal::Silver => "SILVER",
+            Medal::Bronze => "BRONZE",
+        }
+    }
+
+    pub fn color(self, palette: &Palette) -> Color {
+        match self {
+            Medal::Gold => palette.colors[4],
+            Medal::Silver => palette.colors[13],
+            Medal::Bronze => palette.colors[3],
+        }
+    }
+}
+
+/// The color for a time that earns `medal`, or for one too slow to earn any.
+pub fn medal_color(medal: Option<Medal>, palette: &Palette) -> Color {
+    medal.map_or(palette.colors[2], |medal| medal.color(palette))
+}
+
+/// Every course, in the order they are listed and played.
+#[derive(Resource, Deserialize, Debug)]
+#[serde(transparent)]
+pub struct Courses(pub Vec<Course>);
+
+impl Courses {
+    /// Loads the replacement course list if there is a usable one, otherwise the built-in one.
+    fn load() -> Self {
+        if let Some(courses) = load_ron::<Courses>(COURSES_PATH).filter(|c| !c.0.is_empty()) {
+            return courses;
+        }
+        ron::from_str(BUILT_IN_COURSES).expect("assets/courses.ron is valid")
+    }
+}
+
+/// Index of the course chosen on the title screen, or moved on to from a course result.
+#[derive(Resource, Default)]
+pub struct CurrentCourse(pub usize);
+
+/// The course being played, which only exists during a time attack run.
+#[derive(Resource, Clone, Debug)]
+pub struct ActiveCourse(pub Course);
+
+/// The outcome of the course just cleared, shown until the player moves on.
+#[derive(Resource)]
+struct CourseResult {
+    seconds: f32,
+    medal: Option<Medal>,
+    /// Whether the time beats the previous best for the course.
+    best: bool,
+    input_delay: Timer,
+}
+
+#[derive(Component)]
+struct CourseResultText;
+
+/// Sets up the chosen course before its map is generated, or clears the last one away when
+/// the round isn't part of a time attack run. A resumed quicksave never is, since a time attack
+/// run can't be saved.
+pub fn start_course(
+    mut commands: Commands,
+    mode: Res<GameMode>,
+    pending: Option<Res<PendingRestore>>,
+    courses: Res<Courses>,
+    current: Res<CurrentCourse>,
+    mut seed: ResMut<MapSeed>,
+    mut group_size: ResMut<EnemyGroupSize>,
+) {
+    if *mode != GameMode::TimeAttack || pending.is_some() {
+        commands.remove_resource::<ActiveCourse>();
+        return;
+    }
+    let Some(course) = courses.0.get(current.0) else {
+        warn!("No time attack course {}", current.0);
+        return;
+    };
+    info!("Starting course {}", course.name);
+    seed.next = Some(course.seed);
+    group_size.0 = course.group_size;
+    commands.insert_resource(ActiveCourse(course.clone()));
+}
+
+/// Ends the run once the countdown reaches zero.
+fn end_on_time_up(
+    timer: Res<RoundTimer>,
+    course: Res<ActiveCourse>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if course.0.time_left(timer.round) <= 0.0 {
+        info!("Time up on course {}", course.0.name);
+        next_state.set(GameState::GameOver);
+    }
+}
+
+/// Works out the medal and keeps the time if it is the course's best.
+fn record_course_time(
+    mut commands: Commands,
+    timer: Res<RoundTimer>,
+    course: Res<ActiveCourse>,
+    mut save: ResMut<SaveData>,
+) {
+    let seconds = timer.round;
+    let best = save.record_course_time(course.0.seed, seconds);
+    if best {
+        save.save();
+    }
+    commands.insert_resource(CourseResult {
+        seconds,
+        medal: course.0.par.medal(seconds),
+        best,
+        input_delay: Timer::from_seconds(RESULT_INPUT_DELAY, TimerMode::Once),
+    });
+}
+
+fn spawn_course_result(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    course: Res<ActiveCourse>,
+    courses: Res<Courses>,
+    current: Res<CurrentCourse>,
+    result: Res<CourseResult>,
+    input_map: Res<InputMap>,
+) {
+    let palette = &game_assets.palette;
+    let text_line = |text: String, font_size: f32, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(color),
+            TextLayout::new_with_justify(JustifyText::Center),
+        )
+    };
+    let best = if result.best { "  BEST!" } else { "" };
+    let medal = result
+        .medal
+        .map_or("NO MEDAL".to_string(), |m| format!("{} MEDAL", m.label()));
+    let par = course.0.par;
+    let next = if current.0 + 1 < courses.0.len() {
+        "ENTER FOR NEXT COURSE"
+    } else {
+        "ENTER FOR TITLE"
+    };
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            CourseResultText,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text_line(course.0.name.clone(), 16.0, palette.colors[3]));
+            parent.spawn(text_line(
+                "COURSE CLEAR".to_string(),
+                40.0,
+                palette.colors[12],
+            ));
+            parent.spawn(text_line(
+                format!("time: {}{}", format_time(result.seconds), best),
+                16.0,
+                palette.colors[4],
+            ));
+            parent.spawn(text_line(medal, 20.0, medal_color(result.medal, palette)));
+            parent.spawn(text_line(
+                format!(
+                    "gold {}  silver {}  bronze {}",
+                    format_time(par.gold),
+                    format_time(par.silver),
+                    format_time(par.bronze)
+                ),
+                8.0,
+                palette.colors[13],
+            ));
+            parent.spawn((
+                text_line(
+                    format!(
+                        "{}  -  {} TO RETRY  -  ESC TO QUIT",
+                        next,
+                        key_label(input_map.restart)
+                    ),
+                    8.0,
+                    palette.colors[13],
+                ),
+                Node {
+                    margin: UiRect::top(Val::Px(16.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_course_result(mut commands: Commands, query: Query<Entity, With<CourseResultText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<CourseResult>();
+}
+
+/// Retries the course on the restart key, moves on to the next course on confirm (or back to
+/// the title after the last one), and quits to the title on back.
+#[allow(clippy::too_many_arguments)]
+fn handle_course_result_input(
+    input: MenuInput,
+    keys: Res<ButtonInput<KeyCode>>,
+    input_map: Res<InputMap>,
+    time: Res<Time>,
+    mut result: ResMut<CourseResult>,
+    courses: Res<Courses>,
+    mut current: ResMut<CurrentCourse>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !result.input_delay.tick(time.delta()).finished() {
+        return;
+    }
+    // Both go through `Restarting`, so the attempt starts from a clean score and clock.
+    if keys.just_pressed(input_map.restart) {
+        next_state.set(GameState::Restarting);
+    } else if input.back() {
+        next_state.set(GameState::Title);
+    } else if input.confirm() {
+        if current.0 + 1 < courses.0.len() {
+            current.0 += 1;
+            next_state.set(GameState::Restarting);
+        } else {
+            next_state.set(GameState::Title);
+        }
+    }
+}
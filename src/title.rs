@@ -1,6 +1,20 @@
 // title.rs
+use crate::achievements::Achievement;
 use crate::assets::GameAssets;
-use crate::components::{EnemyGroupSize, GameEntity, GameState};
+use crate::components::{CurrentRound, EnemyGroupSize, GameMode, GameState};
+use crate::difficulty::Difficulty;
+use crate::highscore::HighScores;
+use crate::input::{key_label, InputMap};
+use crate::palette::Palettes;
+use crate::quicksave::{LoadQuickSave, QuickSaveExists};
+use crate::round_timer::format_time;
+use crate::save::{SaveData, Unlock};
+use crate::seed::{format_seed, parse_seed, MapSeed, MAX_SEED_DIGITS};
+use crate::settings::{Settings, SettingsEntry};
+use crate::time_attack::{medal_color, Courses, CurrentCourse};
+use bevy::app::AppExit;
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
 use bevy::prelude::*;
 use bevy::state::app::AppExtStates;
 
@@ -10,23 +24,244 @@ impl Plugin for TitlePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>()
             .insert_resource(EnemyGroupSize(1))
+            .insert_resource(CurrentRound(1))
+            .init_resource::<TitlePage>()
+            .init_resource::<MenuSelection>()
+            .init_resource::<SeedEntry>()
             .add_systems(
                 OnEnter(GameState::Title),
-                (spawn_title, cleanup_game, reset_enemy_count),
+                ((lock_difficulty, open_main_page).chain(), reset_enemy_count),
             )
+            .add_systems(OnEnter(GameState::Restarting), reset_enemy_count)
             .add_systems(OnExit(GameState::Title), despawn_title)
             .add_systems(
                 Update,
-                handle_title_input.run_if(in_state(GameState::Title)),
+                (
+                    (
+                        handle_main_menu_input.run_if(resource_equals(TitlePage::Main)),
+                        handle_settings_input.run_if(resource_equals(TitlePage::Settings)),
+                        handle_info_page_input.run_if(
+                            resource_equals(TitlePage::HowToPlay)
+                                .or(resource_equals(TitlePage::Achievements)),
+                        ),
+                        handle_seed_input.run_if(resource_equals(TitlePage::EnterSeed)),
+                        handle_courses_input.run_if(resource_equals(TitlePage::Courses)),
+                    ),
+                    spawn_title_page.run_if(resource_changed::<TitlePage>),
+                    update_menu_highlight,
+                    animate_bounce_example.run_if(resource_equals(TitlePage::HowToPlay)),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Title)),
             );
     }
 }
 
+/// The page of the title screen currently shown.
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TitlePage {
+    #[default]
+    Main,
+    Settings,
+    HowToPlay,
+    Achievements,
+    EnterSeed,
+    Courses,
+}
+
+/// The entries of the main menu, top to bottom.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuAction {
+    Start,
+    Continue,
+    Endless,
+    TimeAttack,
+    ReplayLastMap,
+    EnterSeed,
+    Settings,
+    HowToPlay,
+    Achievements,
+    Quit,
+}
+
+impl MenuAction {
+    const ALL: [MenuAction; 10] = [
+        MenuAction::Start,
+        MenuAction::Continue,
+        MenuAction::Endless,
+        MenuAction::TimeAttack,
+        MenuAction::ReplayLastMap,
+        MenuAction::EnterSeed,
+        MenuAction::Settings,
+        MenuAction::HowToPlay,
+        MenuAction::Achievements,
+        MenuAction::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MenuAction::Start => "START GAME",
+            MenuAction::Continue => "CONTINUE",
+            MenuAction::Endless => "ENDLESS MODE",
+            MenuAction::TimeAttack => "TIME ATTACK",
+            MenuAction::ReplayLastMap => "REPLAY LAST MAP",
+            MenuAction::EnterSeed => "ENTER SEED",
+            MenuAction::Settings => "SETTINGS",
+            MenuAction::HowToPlay => "HOW TO PLAY",
+            MenuAction::Achievements => "ACHIEVEMENTS",
+            MenuAction::Quit => "QUIT",
+        }
+    }
+
+    /// The game mode whose high scores are shown while this entry is selected. Time attack has
+    /// no table, so none is shown; its best times are listed on the course page.
+    fn table_mode(self) -> GameMode {
+        match self {
+            MenuAction::Endless => GameMode::Endless,
+            MenuAction::TimeAttack => GameMode::TimeAttack,
+            _ => GameMode::Classic,
+        }
+    }
+}
+
+/// Index of the highlighted entry on the current page; reset whenever the page changes.
+#[derive(Resource, Default)]
+struct MenuSelection(usize);
+
+/// The hex digits typed so far on the seed entry page.
+#[derive(Resource, Default)]
+struct SeedEntry(String);
+
+impl MenuSelection {
+    fn action(&self) -> MenuAction {
+        MenuAction::ALL[self.0]
+    }
+
+    fn settings_entry(&self) -> SettingsEntry {
+        SettingsEntry::ALL[self.0]
+    }
+
+    /// Moves the highlight up or down through `count` entries, wrapping at either end.
+    fn navigate(&mut self, input: &MenuInput, count: usize) {
+        if input.up() {
+            self.0 = (self.0 + count - 1) % count;
+        } else if input.down() {
+            self.0 = (self.0 + 1) % count;
+        }
+    }
+}
+
+/// Speed of the selected entry's pulse, in radians per second.
+const MENU_PULSE_SPEED: f32 = 6.0;
+
+/// Width of the ricochet example on the How to Play page, in pixels.
+const BOUNCE_STRIP_WIDTH: f32 = 96.0;
+
+/// On-screen size of the sprites in the ricochet example, in pixels.
+const EXAMPLE_SPRITE_SIZE: f32 = 8.0;
+
+/// Seconds the example shot takes to reach the wall, and again to come back.
+const BOUNCE_LEG_TIME: f32 = 0.8;
+
+/// Seconds the player blinks after being hit, before the example loops.
+const BOUNCE_HIT_TIME: f32 = 0.8;
+
+/// How many times per second the hit player blinks in the example.
+const EXAMPLE_BLINK_RATE: f32 = 10.0;
+
+/// Keyboard, mouse and gamepad input for navigating menus.
+#[derive(SystemParam)]
+pub struct MenuInput<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl MenuInput<'_, '_> {
+    fn pressed(&self, keys: [KeyCode; 2], button: GamepadButton) -> bool {
+        self.keys.any_just_pressed(keys) || self.gamepads.iter().any(|g| g.just_pressed(button))
+    }
+
+    pub fn up(&self) -> bool {
+        self.pressed([KeyCode::KeyW, KeyCode::ArrowUp], GamepadButton::DPadUp)
+    }
+
+    pub fn down(&self) -> bool {
+        self.pressed([KeyCode::KeyS, KeyCode::ArrowDown], GamepadButton::DPadDown)
+    }
+
+    pub fn left(&self) -> bool {
+        self.pressed([KeyCode::KeyA, KeyCode::ArrowLeft], GamepadButton::DPadLeft)
+    }
+
+    pub fn right(&self) -> bool {
+        self.pressed(
+            [KeyCode::KeyD, KeyCode::ArrowRight],
+            GamepadButton::DPadRight,
+        )
+    }
+
+    pub fn confirm(&self) -> bool {
+        self.pressed([KeyCode::Enter, KeyCode::Space], GamepadButton::South)
+            || self.mouse.just_pressed(MouseButton::Left)
+    }
+
+    pub fn back(&self) -> bool {
+        self.pressed([KeyCode::Escape, KeyCode::Backspace], GamepadButton::East)
+    }
+
+    /// Like `back`, but without Backspace, for pages where it edits text.
+    pub fn cancel(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Escape)
+            || self
+                .gamepads
+                .iter()
+                .any(|g| g.just_pressed(GamepadButton::East))
+    }
+}
+
 #[derive(Component)]
 struct TitleText;
 
-fn spawn_title(mut commands: Commands, game_assets: Res<GameAssets>) {
-    let root = commands
+/// A selectable entry on the current page, by index.
+#[derive(Component)]
+struct MenuItem(usize);
+
+/// The text of a settings entry, refreshed when its value changes.
+#[derive(Component)]
+struct SettingsText(SettingsEntry);
+
+/// The seed being typed on the seed entry page.
+#[derive(Component)]
+struct SeedText;
+
+/// The shot in the How to Play ricochet example, timed by its own looping timer.
+#[derive(Component)]
+struct BounceExample(Timer);
+
+/// The player in the How to Play ricochet example.
+#[derive(Component)]
+struct BounceExampleShooter;
+
+/// The high score table for one game mode, shown while that mode's entry is selected.
+#[derive(Component)]
+struct ScoreTable(GameMode);
+
+/// Falls back to Normal if a saved Hard selection hasn't been unlocked.
+fn lock_difficulty(mut settings: ResMut<Settings>, save: Res<SaveData>) {
+    if settings.difficulty == Difficulty::Hard && !save.is_unlocked(Unlock::HardMode) {
+        settings.difficulty = Difficulty::Normal;
+    }
+}
+
+fn open_main_page(mut page: ResMut<TitlePage>, mut selection: ResMut<MenuSelection>) {
+    // Always marks the page as changed, so it is (re)built on entering the title screen.
+    *page = TitlePage::Main;
+    selection.0 = 0;
+}
+
+fn title_root(commands: &mut Commands) -> Entity {
+    commands
         .spawn((
             Node {
                 position_type: PositionType::Absolute,
@@ -40,7 +275,44 @@ fn spawn_title(mut commands: Commands, game_assets: Res<GameAssets>) {
             BackgroundColor(Color::NONE),
             TitleText,
         ))
-        .id();
+        .id()
+}
+
+/// Replaces the title screen contents with the current page.
+#[allow(clippy::too_many_arguments)]
+fn spawn_title_page(
+    mut commands: Commands,
+    page: Res<TitlePage>,
+    game_assets: Res<GameAssets>,
+    high_scores: Res<HighScores>,
+    settings: Res<Settings>,
+    save: Res<SaveData>,
+    input_map: Res<InputMap>,
+    seed: Res<MapSeed>,
+    seed_entry: Res<SeedEntry>,
+    courses: Res<Courses>,
+    existing: Query<Entity, With<TitleText>>,
+) {
+    for entity in &existing {
+        commands.entity(entity).despawn();
+    }
+    match *page {
+        TitlePage::Main => spawn_main_page(&mut commands, &game_assets, &high_scores, &seed),
+        TitlePage::Settings => spawn_settings_page(&mut commands, &game_assets, &settings, &save),
+        TitlePage::HowToPlay => spawn_how_to_play_page(&mut commands, &game_assets, &input_map),
+        TitlePage::Achievements => spawn_achievements_page(&mut commands, &game_assets, &save),
+        TitlePage::EnterSeed => spawn_seed_page(&mut commands, &game_assets, &seed_entry),
+        TitlePage::Courses => spawn_courses_page(&mut commands, &game_assets, &courses, &save),
+    }
+}
+
+fn spawn_main_page(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    high_scores: &HighScores,
+    seed: &MapSeed,
+) {
+    let root = title_root(commands);
 
     commands.entity(root).with_children(|parent| {
         parent
@@ -73,16 +345,542 @@ fn spawn_title(mut commands: Commands, game_assets: Res<GameAssets>) {
                 ));
             });
 
+        // Menu entries; the highlight is applied by `update_menu_highlight`.
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::top(Val::Px(24.0)),
+                row_gap: Val::Px(10.0),
+                ..default()
+            })
+            .with_children(|menu| {
+                for (i, action) in MenuAction::ALL.into_iter().enumerate() {
+                    menu.spawn((
+                        Text::new(action.label()),
+                        TextFont {
+                            font: game_assets.font.clone(),
+                            font_size: 16.0,
+                            ..default()
+                        },
+                        TextColor(game_assets.palette.colors[13]),
+                        TextLayout::new_with_justify(JustifyText::Center),
+                        MenuItem(i),
+                    ));
+                }
+            });
+
+        if let Some(last) = seed.last_played {
+            parent.spawn((
+                Text::new(format!("last seed: {}", format_seed(last))),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 8.0,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[13]),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    margin: UiRect::top(Val::Px(12.0)),
+                    ..default()
+                },
+            ));
+        }
+
+        // High score tables, with this run's entry (if any) highlighted.
+        parent
+            .spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::top(Val::Px(30.0)),
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ScoreTable(GameMode::Classic),
+            ))
+            .with_children(|table| {
+                for (i, entry) in high_scores.entries.iter().enumerate() {
+                    let color = if high_scores.newest == Some(i) {
+                        game_assets.palette.colors[5]
+                    } else {
+                        game_assets.palette.colors[13]
+                    };
+                    table.spawn((
+                        Text::new(format!(
+                            "{:>2}. {:>8}  R{:<2} {}",
+                            i + 1,
+                            entry.score,
+                            entry.round,
+                            entry.date
+                        )),
+                        TextFont {
+                            font: game_assets.font.clone(),
+                            font_size: 8.0,
+                            ..default()
+                        },
+                        TextColor(color),
+                    ));
+                }
+            });
+
+        parent
+            .spawn((
+                Node {
+                    display: Display::None,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::top(Val::Px(30.0)),
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ScoreTable(GameMode::Endless),
+            ))
+            .with_children(|table| {
+                for (i, entry) in high_scores.endless.iter().enumerate() {
+                    let color = if high_scores.newest_endless == Some(i) {
+                        game_assets.palette.colors[5]
+                    } else {
+                        game_assets.palette.colors[13]
+                    };
+                    table.spawn((
+                        Text::new(format!(
+                            "{:>2}. {}  {:>8} {}",
+                            i + 1,
+                            format_time(entry.seconds),
+                            entry.score,
+                            entry.date
+                        )),
+                        TextFont {
+                            font: game_assets.font.clone(),
+                            font_size: 8.0,
+                            ..default()
+                        },
+                        TextColor(color),
+                    ));
+                }
+            });
+    });
+}
+
+fn spawn_settings_page(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    settings: &Settings,
+    save: &SaveData,
+) {
+    let root = title_root(commands);
+    let hint = if save.is_unlocked(Unlock::HardMode) {
+        "ESC TO GO BACK"
+    } else {
+        "WIN ROUND 3 TO UNLOCK HARD  -  ESC TO GO BACK"
+    };
+
+    commands.entity(root).with_children(|parent| {
         parent.spawn((
-            Text::new("FIRE TO PLAY"),
+            Text::new("SETTINGS"),
             TextFont {
                 font: game_assets.font.clone(),
                 font_size: 20.0,
                 ..default()
             },
-            TextColor(game_assets.palette.colors[4]),
+            TextColor(game_assets.palette.colors[3]),
             TextLayout::new_with_justify(JustifyText::Center),
         ));
+
+        // Settings entries; the highlight is applied by `update_menu_highlight`.
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::top(Val::Px(24.0)),
+                row_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|menu| {
+                for (i, entry) in SettingsEntry::ALL.into_iter().enumerate() {
+                    menu.spawn((
+                        Text::new(entry.label(settings)),
+                        TextFont {
+                            font: game_assets.font.clone(),
+                            font_size: 12.0,
+                            ..default()
+                        },
+                        TextColor(game_assets.palette.colors[13]),
+                        TextLayout::new_with_justify(JustifyText::Center),
+                        MenuItem(i),
+                        SettingsText(entry),
+                    ));
+                }
+            });
+
+        parent.spawn((
+            Text::new(hint),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 8.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[13]),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                margin: UiRect::top(Val::Px(24.0)),
+                ..default()
+            },
+        ));
+    });
+}
+
+fn spawn_how_to_play_page(commands: &mut Commands, game_assets: &GameAssets, input_map: &InputMap) {
+    let root = title_root(commands);
+    let controls = [
+        format!("{} - MOVE", input_map.movement_label()),
+        format!("{} / CLICK - FIRE", input_map.fire_label()),
+        format!("{} - RESTART", key_label(input_map.restart)),
+        format!("{} - MUTE", key_label(input_map.mute)),
+        format!(
+            "{} {} / WHEEL - ZOOM",
+            key_label(input_map.zoom_out),
+            key_label(input_map.zoom_in)
+        ),
+        format!(
+            "{} - WATCH ENEMIES AFTER DYING",
+            key_label(input_map.spectate_next)
+        ),
+    ];
+    let rules = [
+        "CLEAR EVERY ENEMY TO WIN THE ROUND.",
+        "SHOTS BOUNCE OFF WALLS, AND",
+        "A BOUNCED SHOT CAN HIT YOU TOO:",
+    ];
+
+    let text_line = |text: String, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 8.0,
+                ..default()
+            },
+            TextColor(color),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                margin: UiRect::bottom(Val::Px(6.0)),
+                ..default()
+            },
+        )
+    };
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new("HOW TO PLAY"),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[3]),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                margin: UiRect::bottom(Val::Px(24.0)),
+                ..default()
+            },
+        ));
+
+        for line in controls {
+            parent.spawn(text_line(line, game_assets.palette.colors[12]));
+        }
+        parent.spawn(Node {
+            height: Val::Px(12.0),
+            ..default()
+        });
+        for line in rules {
+            parent.spawn(text_line(line.to_string(), game_assets.palette.colors[4]));
+        }
+
+        // A looping ricochet example built from the game's own sprites, animated by
+        // `animate_bounce_example`: a shot leaves the player, bounces off the wall and comes back.
+        parent
+            .spawn(Node {
+                width: Val::Px(BOUNCE_STRIP_WIDTH),
+                height: Val::Px(EXAMPLE_SPRITE_SIZE),
+                margin: UiRect::vertical(Val::Px(12.0)),
+                ..default()
+            })
+            .with_children(|strip| {
+                strip.spawn((
+                    ImageNode::new(game_assets.player_texture.clone()),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(0.0),
+                        width: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        height: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        ..default()
+                    },
+                    BounceExampleShooter,
+                ));
+                strip.spawn((
+                    ImageNode::new(game_assets.player_texture.clone())
+                        .with_color(game_assets.palette.colors[5]),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        width: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        height: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        ..default()
+                    },
+                    BounceExample(Timer::from_seconds(
+                        BOUNCE_LEG_TIME * 2.0 + BOUNCE_HIT_TIME,
+                        TimerMode::Repeating,
+                    )),
+                ));
+                strip.spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(0.0),
+                        width: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        height: Val::Px(EXAMPLE_SPRITE_SIZE),
+                        ..default()
+                    },
+                    BackgroundColor(game_assets.palette.wall_color()),
+                ));
+            });
+
+        parent.spawn(text_line(
+            "ESC OR ENTER TO GO BACK".to_string(),
+            game_assets.palette.colors[13],
+        ));
+    });
+}
+
+/// Plays the ricochet example: the shot flies to the wall and back, then the player blinks.
+fn animate_bounce_example(
+    time: Res<Time>,
+    mut shots: Query<(&mut BounceExample, &mut Node, &mut Visibility)>,
+    mut shooters: Query<&mut Visibility, (With<BounceExampleShooter>, Without<BounceExample>)>,
+) {
+    // Distance from the shot's starting cell to the cell in front of the wall.
+    let travel = BOUNCE_STRIP_WIDTH - EXAMPLE_SPRITE_SIZE * 3.0;
+    for (mut example, mut node, mut visibility) in &mut shots {
+        example.0.tick(time.delta());
+        let leg = example.0.elapsed_secs() / BOUNCE_LEG_TIME;
+        let hit = leg >= 2.0;
+        let offset = if leg < 1.0 { leg } else { (2.0 - leg).max(0.0) };
+        node.left = Val::Px(EXAMPLE_SPRITE_SIZE + offset * travel);
+        *visibility = if hit {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+
+        let blink_on = ((example.0.elapsed_secs() * EXAMPLE_BLINK_RATE) as u32).is_multiple_of(2);
+        for mut shooter in &mut shooters {
+            *shooter = if hit && !blink_on {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+    }
+}
+
+fn spawn_seed_page(commands: &mut Commands, game_assets: &GameAssets, seed_entry: &SeedEntry) {
+    let root = title_root(commands);
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            Text::new("ENTER SEED"),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[3]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+
+        parent.spawn((
+            Text::new(seed_field(&seed_entry.0)),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[12]),
+            TextLayout::new_with_justify(JustifyText::Center),
+            Node {
+                margin: UiRect::vertical(Val::Px(24.0)),
+                ..default()
+            },
+            SeedText,
+        ));
+
+        parent.spawn((
+            Text::new("0-9 A-F  -  ENTER TO PLAY  -  ESC TO GO BACK"),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 8.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[13]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+    });
+}
+
+/// Lists the time attack courses, each with its best time and the medal that time earned.
+fn spawn_courses_page(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    courses: &Courses,
+    save: &SaveData,
+) {
+    let root = title_root(commands);
+
+    let text_line = |text: String, font_size: f32, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(color),
+            TextLayout::new_with_justify(JustifyText::Center),
+        )
+    };
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn(text_line(
+            "TIME ATTACK".to_string(),
+            20.0,
+            game_assets.palette.colors[3],
+        ));
+
+        // Course entries; the highlight is applied by `update_menu_highlight`.
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::vertical(Val::Px(24.0)),
+                row_gap: Val::Px(4.0),
+                ..default()
+            })
+            .with_children(|menu| {
+                for (i, course) in courses.0.iter().enumerate() {
+                    menu.spawn((
+                        text_line(
+                            format!("{}. {}", i + 1, course.name),
+                            16.0,
+                            game_assets.palette.colors[13],
+                        ),
+                        MenuItem(i),
+                    ));
+                    let best = save.course_times.get(&course.seed).copied();
+                    let (record, color) = match best {
+                        Some(seconds) => {
+                            let medal = course.par.medal(seconds);
+                            let label = medal.map_or("NO MEDAL", |m| m.label());
+                            (
+                                format!("best {}  {}", format_time(seconds), label),
+                                medal_color(medal, &game_assets.palette),
+                            )
+                        }
+                        None => (
+                            format!("gold {}", format_time(course.par.gold)),
+                            game_assets.palette.colors[13],
+                        ),
+                    };
+                    menu.spawn((
+                        text_line(record, 8.0, color),
+                        Node {
+                            margin: UiRect::bottom(Val::Px(8.0)),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+
+        parent.spawn(text_line(
+            "ENTER TO PLAY  -  ESC TO GO BACK".to_string(),
+            8.0,
+            game_assets.palette.colors[13],
+        ));
+    });
+}
+
+fn spawn_achievements_page(commands: &mut Commands, game_assets: &GameAssets, save: &SaveData) {
+    let root = title_root(commands);
+    let earned = Achievement::ALL
+        .into_iter()
+        .filter(|&achievement| save.has_achievement(achievement))
+        .count();
+
+    let text_line = |text: String, font_size: f32, color: Color| {
+        (
+            Text::new(text),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(color),
+            TextLayout::new_with_justify(JustifyText::Center),
+        )
+    };
+
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            text_line(
+                "ACHIEVEMENTS".to_string(),
+                20.0,
+                game_assets.palette.colors[3],
+            ),
+            Node {
+                margin: UiRect::bottom(Val::Px(8.0)),
+                ..default()
+            },
+        ));
+        parent.spawn((
+            text_line(
+                format!("{}/{} EARNED", earned, Achievement::ALL.len()),
+                8.0,
+                game_assets.palette.colors[13],
+            ),
+            Node {
+                margin: UiRect::bottom(Val::Px(16.0)),
+                ..default()
+            },
+        ));
+
+        // Locked achievements are dimmed, but still say how to earn them.
+        for achievement in Achievement::ALL {
+            let (name_color, description_color) = if save.has_achievement(achievement) {
+                (
+                    game_assets.palette.colors[4],
+                    game_assets.palette.colors[12],
+                )
+            } else {
+                (
+                    game_assets.palette.colors[13].with_alpha(0.6),
+                    game_assets.palette.colors[13].with_alpha(0.6),
+                )
+            };
+            parent.spawn(text_line(achievement.name().to_string(), 12.0, name_color));
+            parent.spawn((
+                text_line(achievement.description(), 8.0, description_color),
+                Node {
+                    margin: UiRect::bottom(Val::Px(8.0)),
+                    ..default()
+                },
+            ));
+        }
+
+        parent.spawn(text_line(
+            "ESC OR ENTER TO GO BACK".to_string(),
+            8.0,
+            game_assets.palette.colors[13],
+        ));
     });
 }
 
@@ -92,24 +890,255 @@ fn despawn_title(mut commands: Commands, query: Query<Entity, With<TitleText>>)
     }
 }
 
-/// Only runs if displaying title screen
-fn handle_title_input(
+#[allow(clippy::too_many_arguments)]
+fn handle_main_menu_input(
+    input: MenuInput,
+    mut selection: ResMut<MenuSelection>,
+    mut page: ResMut<TitlePage>,
+    mut mode: ResMut<GameMode>,
+    mut seed: ResMut<MapSeed>,
+    mut seed_entry: ResMut<SeedEntry>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+    quicksave: Res<QuickSaveExists>,
+    mut load_events: EventWriter<LoadQuickSave>,
+) {
+    selection.navigate(&input, MenuAction::ALL.len());
+
+    if !input.confirm() {
+        return;
+    }
+    match selection.action() {
+        MenuAction::Continue => {
+            // Does nothing without a quicksave; the entry is dimmed then.
+            if quicksave.0 {
+                load_events.write(LoadQuickSave);
+            }
+        }
+        MenuAction::Start => {
+            *mode = GameMode::Classic;
+            next_state.set(GameState::Playing);
+        }
+        MenuAction::Endless => {
+            *mode = GameMode::Endless;
+            next_state.set(GameState::Playing);
+        }
+        MenuAction::TimeAttack => {
+            *page = TitlePage::Courses;
+            selection.0 = 0;
+        }
+        MenuAction::ReplayLastMap => {
+            // Replays in the same mode as last time; does nothing before the first run.
+            if let Some(last) = seed.last_played {
+                seed.next = Some(last);
+                next_state.set(GameState::Playing);
+            }
+        }
+        MenuAction::EnterSeed => {
+            seed_entry.0.clear();
+            *page = TitlePage::EnterSeed;
+            selection.0 = 0;
+        }
+        MenuAction::Settings => {
+            *page = TitlePage::Settings;
+            selection.0 = 0;
+        }
+        MenuAction::HowToPlay => {
+            *page = TitlePage::HowToPlay;
+            selection.0 = 0;
+        }
+        MenuAction::Achievements => {
+            *page = TitlePage::Achievements;
+            selection.0 = 0;
+        }
+        MenuAction::Quit => {
+            exit.write(AppExit::Success);
+        }
+    }
+}
+
+fn handle_settings_input(
+    input: MenuInput,
+    mut selection: ResMut<MenuSelection>,
+    mut page: ResMut<TitlePage>,
+    mut settings: ResMut<Settings>,
+    save: Res<SaveData>,
+    mut labels: Query<(&mut Text, &SettingsText)>,
+    palettes: Res<Palettes>,
+) {
+    if input.back() {
+        *page = TitlePage::Main;
+        selection.0 = 0;
+        return;
+    }
+    selection.navigate(&input, SettingsEntry::ALL.len());
+
+    let entry = selection.settings_entry();
+    let step = if input.right() {
+        1
+    } else if input.left() {
+        -1
+    } else {
+        0
+    };
+    if input.confirm() {
+        match entry {
+            SettingsEntry::ResetToDefaults => {
+                settings.set_if_neq(Settings::default());
+            }
+            SettingsEntry::Back => {
+                *page = TitlePage::Main;
+                selection.0 = 0;
+                return;
+            }
+            _ => {}
+        }
+    } else if step != 0 {
+        let mut next = settings.clone();
+        entry.adjust(&mut next, &palettes, step);
+        if next.difficulty == Difficulty::Hard && !save.is_unlocked(Unlock::HardMode) {
+            return;
+        }
+        settings.set_if_neq(next);
+    }
+
+    if settings.is_changed() {
+        for (mut text, label) in &mut labels {
+            text.0 = label.0.label(&settings);
+        }
+    }
+}
+
+/// Reads typed hex digits (and Backspace) into the seed entry, and starts a run on confirm.
+#[allow(clippy::too_many_arguments)]
+fn handle_seed_input(
+    input: MenuInput,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut page: ResMut<TitlePage>,
+    mut seed_entry: ResMut<SeedEntry>,
+    mut seed: ResMut<MapSeed>,
+    mut mode: ResMut<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut seed_text: Query<&mut Text, With<SeedText>>,
+) {
+    if input.cancel() {
+        *page = TitlePage::Main;
+        return;
+    }
+
+    for event in keyboard_events.read() {
+        if !event.state.is_pressed() {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(text) => {
+                for c in text.chars().filter(char::is_ascii_hexdigit) {
+                    if seed_entry.0.len() < MAX_SEED_DIGITS {
+                        seed_entry.0.push(c.to_ascii_uppercase());
+                    }
+                }
+            }
+            Key::Backspace => {
+                seed_entry.0.pop();
+            }
+            _ => {}
+        }
+    }
+
+    if input.confirm() {
+        if let Some(value) = parse_seed(&seed_entry.0) {
+            seed.next = Some(value);
+            *mode = GameMode::Classic;
+            next_state.set(GameState::Playing);
+        }
+    }
+
+    if seed_entry.is_changed() {
+        if let Ok(mut text) = seed_text.single_mut() {
+            text.0 = seed_field(&seed_entry.0);
+        }
+    }
+}
+
+/// The seed entry text field: the digits typed so far, padded with underscores.
+fn seed_field(entry: &str) -> String {
+    format!("{:_<width$}", entry, width = MAX_SEED_DIGITS)
+}
+
+/// Starts a time attack run on the selected course.
+fn handle_courses_input(
+    input: MenuInput,
+    mut selection: ResMut<MenuSelection>,
+    mut page: ResMut<TitlePage>,
+    mut mode: ResMut<GameMode>,
+    courses: Res<Courses>,
+    mut current: ResMut<CurrentCourse>,
     mut next_state: ResMut<NextState<GameState>>,
-    keys: Res<ButtonInput<KeyCode>>,
-    mouse: Res<ButtonInput<MouseButton>>,
 ) {
-    if keys.just_pressed(KeyCode::Space) || mouse.just_pressed(MouseButton::Left) {
+    if input.back() {
+        *page = TitlePage::Main;
+        selection.0 = 0;
+        return;
+    }
+    selection.navigate(&input, courses.0.len());
+
+    if input.confirm() {
+        current.0 = selection.0;
+        *mode = GameMode::TimeAttack;
         next_state.set(GameState::Playing);
     }
 }
 
-fn cleanup_game(mut commands: Commands, query: Query<Entity, With<GameEntity>>) {
-    info!("Cleaning up game entities");
-    for entity in query.iter() {
-        commands.entity(entity).despawn();
+/// The How to Play and Achievements pages have nothing to select, only a way back.
+fn handle_info_page_input(input: MenuInput, mut page: ResMut<TitlePage>) {
+    if input.back() || input.confirm() {
+        *page = TitlePage::Main;
+    }
+}
+
+/// Highlights and pulses the selected entry, and on the main page shows the matching high
+/// score table.
+fn update_menu_highlight(
+    selection: Res<MenuSelection>,
+    page: Res<TitlePage>,
+    game_assets: Res<GameAssets>,
+    time: Res<Time>,
+    quicksave: Res<QuickSaveExists>,
+    mut items: Query<(&MenuItem, &mut TextColor)>,
+    mut tables: Query<(&mut Node, &ScoreTable)>,
+) {
+    let pulse = 0.8 + 0.2 * (time.elapsed_secs() * MENU_PULSE_SPEED).sin();
+    for (item, mut color) in &mut items {
+        color.0 = if item.0 == selection.0 {
+            game_assets.palette.colors[5].with_alpha(pulse)
+        } else {
+            game_assets.palette.colors[13]
+        };
+        // Only the main page's items are actions; the other pages reuse the indices.
+        let unavailable = *page == TitlePage::Main
+            && MenuAction::ALL.get(item.0) == Some(&MenuAction::Continue)
+            && !quicksave.0;
+        if unavailable {
+            color.0 = color.0.with_alpha(0.4);
+        }
+    }
+
+    if selection.is_changed() && *page == TitlePage::Main {
+        let mode = selection.action().table_mode();
+        for (mut node, table) in &mut tables {
+            node.display = if table.0 == mode {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
     }
 }
 
-fn reset_enemy_count(mut enemy_group_size: ResMut<EnemyGroupSize>) {
+fn reset_enemy_count(
+    mut enemy_group_size: ResMut<EnemyGroupSize>,
+    mut current_round: ResMut<CurrentRound>,
+) {
     enemy_group_size.0 = 1;
+    current_round.0 = 1;
 }
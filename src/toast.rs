@@ -0,0 +1,101 @@
+// toast.rs
+
+//! Short notifications (e.g. "HARD MODE UNLOCKED") that slide in near the top of the screen
+//! and fade away. They are independent of game state, so they survive state transitions.
+
+use bevy::prelude::*;
+
+use crate::assets::GameAssets;
+
+/// Seconds a toast stays on screen.
+const TOAST_LIFETIME: f32 = 3.0;
+
+/// Seconds at the end of a toast's life over which it fades out.
+const TOAST_FADE_TIME: f32 = 0.5;
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ShowToast>()
+            .add_systems(Startup, spawn_toast_container)
+            .add_systems(
+                Update,
+                (spawn_toasts, update_toasts)
+                    .chain()
+                    .run_if(resource_exists::<GameAssets>),
+            );
+    }
+}
+
+/// Request to show a toast with the given message.
+#[derive(Event)]
+pub struct ShowToast(pub String);
+
+/// The column toasts are stacked in.
+#[derive(Component)]
+struct ToastContainer;
+
+#[derive(Component)]
+struct Toast {
+    age: f32,
+}
+
+fn spawn_toast_container(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(80.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(6.0),
+            ..default()
+        },
+        GlobalZIndex(20),
+        ToastContainer,
+    ));
+}
+
+fn spawn_toasts(
+    mut commands: Commands,
+    mut events: EventReader<ShowToast>,
+    game_assets: Res<GameAssets>,
+    container: Query<Entity, With<ToastContainer>>,
+) {
+    let Ok(container) = container.single() else {
+        return;
+    };
+    for ShowToast(message) in events.read() {
+        info!("Toast: {}", message);
+        commands.entity(container).with_children(|parent| {
+            parent.spawn((
+                Text::new(message.clone()),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[5]),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Toast { age: 0.0 },
+            ));
+        });
+    }
+}
+
+fn update_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut query: Query<(Entity, &mut Toast, &mut TextColor)>,
+) {
+    for (entity, mut toast, mut color) in &mut query {
+        toast.age += time.delta_secs();
+        if toast.age >= TOAST_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let alpha = ((TOAST_LIFETIME - toast.age) / TOAST_FADE_TIME).min(1.0);
+        color.0.set_alpha(alpha);
+    }
+}
@@ -1,23 +1,51 @@
 use bevy::prelude::*;
 
+use crate::arena::{carry_over_arena, ArenaEntity};
 use crate::assets::GameAssets;
-use crate::components::{EnemyGroupSize, GameEntity, GameState};
-use crate::enemy::Enemy;
+use crate::components::{CurrentRound, Dying, EnemyGroupSize, GameEntity, GameMode, GameState};
+use crate::frame_step::simulation_running;
+use crate::hit_stop::trigger_hit_stop;
 use crate::player::Player;
+use crate::round_timer::{format_time, record_round_time, RoundTimer};
+use crate::score::{reconcile_enemy_count, EnemyCount, RunStats};
+use crate::spawner::Spawner;
 
 pub struct VictoryPlugin;
 
 impl Plugin for VictoryPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Victory), spawn_victory)
-            .add_systems(OnExit(GameState::Victory), (despawn_victory, cleanup_game))
-            .add_systems(
-                Update,
-                (
-                    check_for_victory.run_if(in_state(GameState::Playing)),
-                    handle_victory_timer.run_if(in_state(GameState::Victory)),
-                ),
-            );
+        // Time attack shows its own course result instead of the banner and the next round.
+        app.add_systems(
+            OnEnter(GameState::Victory),
+            spawn_victory
+                .after(record_round_time)
+                .run_if(resource_equals(GameMode::Classic)),
+        )
+        .add_systems(
+            OnExit(GameState::Victory),
+            (
+                despawn_victory,
+                cleanup_game.run_if(not(carry_over_arena)),
+                cleanup_round.run_if(carry_over_arena),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                // Endless mode has no victory; the run lasts until the player dies. Waits out
+                // the last kill's hit-stop, so the freeze is seen before the banner.
+                check_for_victory
+                    .after(reconcile_enemy_count)
+                    .after(trigger_hit_stop)
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and(not(resource_equals(GameMode::Endless)))
+                            .and(simulation_running),
+                    ),
+                handle_victory_timer
+                    .run_if(in_state(GameState::Victory).and(resource_equals(GameMode::Classic))),
+            ),
+        );
     }
 }
 
@@ -27,7 +55,12 @@ struct VictoryTimer(Timer);
 #[derive(Component)]
 struct VictoryText;
 
-fn spawn_victory(mut commands: Commands, game_assets: Res<GameAssets>) {
+fn spawn_victory(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    round_timer: Res<RoundTimer>,
+    stats: Res<RunStats>,
+) {
     let root = commands
         .spawn((
             Node {
@@ -55,6 +88,35 @@ fn spawn_victory(mut commands: Commands, game_assets: Res<GameAssets>) {
             TextColor(game_assets.palette.colors[12]),
             TextLayout::new_with_justify(JustifyText::Center),
         ));
+
+        let best = if round_timer.last_was_best {
+            "  BEST!"
+        } else {
+            ""
+        };
+        parent.spawn((
+            Text::new(format!("time: {}{}", format_time(round_timer.round), best)),
+            TextFont {
+                font: game_assets.font.clone(),
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(game_assets.palette.colors[4]),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+
+        for line in stats.kill_breakdown() {
+            parent.spawn((
+                Text::new(line),
+                TextFont {
+                    font: game_assets.font.clone(),
+                    font_size: 8.0,
+                    ..default()
+                },
+                TextColor(game_assets.palette.colors[13]),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        }
     });
 
     // Insert the timer resource
@@ -72,21 +134,25 @@ fn handle_victory_timer(
     time: Res<Time>,
     mut next_state: ResMut<NextState<GameState>>,
     mut enemy_group_size: ResMut<EnemyGroupSize>,
+    mut current_round: ResMut<CurrentRound>,
 ) {
     timer.0.tick(time.delta());
     if timer.0.finished() {
         const MAX_PER_TYPE: u32 = 2048;
         enemy_group_size.0 = (enemy_group_size.0 * 2).min(MAX_PER_TYPE);
+        current_round.0 += 1;
         next_state.set(GameState::Playing);
     }
 }
 
+/// A round is won once every enemy is dead and every spawner destroyed.
 fn check_for_victory(
-    enemy_query: Query<(), With<Enemy>>,
+    enemy_count: Res<EnemyCount>,
     player_query: Query<(), With<Player>>,
+    spawner_query: Query<(), (With<Spawner>, Without<Dying>)>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if enemy_query.is_empty() && !player_query.is_empty() {
+    if enemy_count.value == 0 && spawner_query.is_empty() && !player_query.is_empty() {
         next_state.set(GameState::Victory);
     }
 }
@@ -97,3 +163,14 @@ fn cleanup_game(mut commands: Commands, query: Query<Entity, With<GameEntity>>)
         commands.entity(entity).despawn();
     }
 }
+
+/// Like `cleanup_game`, but leaves the map in place for the next round of a persistent arena.
+fn cleanup_round(
+    mut commands: Commands,
+    query: Query<Entity, (With<GameEntity>, Without<ArenaEntity>)>,
+) {
+    info!("Cleaning up round entities, keeping the arena");
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}